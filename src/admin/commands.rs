@@ -1,13 +1,27 @@
 use crate::world::position::Position;
+use crate::world::state::ScriptReloadTarget;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum AdminCommand {
+    Flags { filter: Option<String> },
     Kick { target: Option<String> },
+    Mute {
+        target: String,
+        minutes: u32,
+        reason: Option<String>,
+    },
+    Unmute { target: String },
     Online,
+    Monsters,
     MoveUseAudit,
+    Raids,
+    Raid { name: String },
+    Reload { target: ScriptReloadTarget },
     Restart,
     Shutdown,
     Teleport { position: Position },
+    Tickets { filter: Option<String> },
+    Ticket { id: u32 },
     Where,
     Unknown(String),
 }
@@ -24,11 +38,54 @@ pub fn parse_admin_command(message: &str) -> Result<Option<AdminCommand>, String
         .ok_or_else(|| "admin command missing name".to_string())?;
     let command = command.to_ascii_lowercase();
     let parsed = match command.as_str() {
+        "flags" | "detection" => AdminCommand::Flags {
+            filter: parts.next().map(str::to_string),
+        },
         "kick" => AdminCommand::Kick {
             target: parts.next().map(str::to_string),
         },
+        "mute" => {
+            let target = parts
+                .next()
+                .ok_or_else(|| "admin command missing player name".to_string())?
+                .to_string();
+            let value = parts
+                .next()
+                .ok_or_else(|| "admin command missing mute minutes".to_string())?;
+            let minutes = value
+                .parse::<u32>()
+                .map_err(|_| format!("admin command expected minutes, got '{value}'"))?;
+            let reason = parts.collect::<Vec<_>>().join(" ");
+            AdminCommand::Mute {
+                target,
+                minutes,
+                reason: if reason.is_empty() { None } else { Some(reason) },
+            }
+        }
+        "unmute" => AdminCommand::Unmute {
+            target: parts
+                .next()
+                .ok_or_else(|| "admin command missing player name".to_string())?
+                .to_string(),
+        },
         "online" => AdminCommand::Online,
+        "monsters" => AdminCommand::Monsters,
         "moveuseaudit" | "muaudit" => AdminCommand::MoveUseAudit,
+        "raids" => AdminCommand::Raids,
+        "raid" => AdminCommand::Raid {
+            name: parts
+                .next()
+                .ok_or_else(|| "admin command missing raid name".to_string())?
+                .to_string(),
+        },
+        "reload" => {
+            let target = match parts.next() {
+                Some(name) => ScriptReloadTarget::from_name(name)
+                    .ok_or_else(|| format!("admin reload unknown target '{name}'"))?,
+                None => ScriptReloadTarget::All,
+            };
+            AdminCommand::Reload { target }
+        }
         "restart" => AdminCommand::Restart,
         "shutdown" => AdminCommand::Shutdown,
         "teleport" | "tp" => {
@@ -39,6 +96,19 @@ pub fn parse_admin_command(message: &str) -> Result<Option<AdminCommand>, String
                 position: Position { x, y, z },
             }
         }
+        "tickets" => AdminCommand::Tickets {
+            filter: parts.next().map(str::to_string),
+        },
+        "ticket" => {
+            let value = parts
+                .next()
+                .ok_or_else(|| "admin command missing ticket id".to_string())?;
+            let id = value
+                .trim_start_matches('#')
+                .parse::<u32>()
+                .map_err(|_| format!("admin command expected ticket id, got '{value}'"))?;
+            AdminCommand::Ticket { id }
+        }
         "where" | "pos" => AdminCommand::Where,
         _ => AdminCommand::Unknown(command),
     };
@@ -112,6 +182,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_admin_command_parses_reload_target() {
+        assert_eq!(
+            parse_admin_command("!reload npc").unwrap(),
+            Some(AdminCommand::Reload {
+                target: ScriptReloadTarget::Npcs
+            })
+        );
+        assert_eq!(
+            parse_admin_command("!reload scripts").unwrap(),
+            Some(AdminCommand::Reload {
+                target: ScriptReloadTarget::ContentScripts
+            })
+        );
+        assert_eq!(
+            parse_admin_command("!reload").unwrap(),
+            Some(AdminCommand::Reload {
+                target: ScriptReloadTarget::All
+            })
+        );
+        assert!(parse_admin_command("!reload houses").is_err());
+    }
+
     #[test]
     fn parse_admin_command_parses_moveuse_audit() {
         assert_eq!(
@@ -120,6 +213,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_admin_command_parses_ticket_queries() {
+        assert_eq!(
+            parse_admin_command("!tickets open").unwrap(),
+            Some(AdminCommand::Tickets {
+                filter: Some("open".to_string())
+            })
+        );
+        assert_eq!(
+            parse_admin_command("!ticket #12").unwrap(),
+            Some(AdminCommand::Ticket { id: 12 })
+        );
+        assert!(parse_admin_command("!ticket abc").is_err());
+    }
+
+    #[test]
+    fn parse_admin_command_parses_mute() {
+        assert_eq!(
+            parse_admin_command("!mute Bob 15 spamming trade").unwrap(),
+            Some(AdminCommand::Mute {
+                target: "Bob".to_string(),
+                minutes: 15,
+                reason: Some("spamming trade".to_string()),
+            })
+        );
+        assert!(parse_admin_command("!mute Bob").is_err());
+    }
+
+    #[test]
+    fn parse_admin_command_parses_flags() {
+        assert_eq!(
+            parse_admin_command("!flags").unwrap(),
+            Some(AdminCommand::Flags { filter: None })
+        );
+        assert_eq!(
+            parse_admin_command("!detection Bob").unwrap(),
+            Some(AdminCommand::Flags {
+                filter: Some("Bob".to_string())
+            })
+        );
+    }
+
+    #[test]
+    fn parse_admin_command_parses_raids() {
+        assert_eq!(
+            parse_admin_command("!raids").unwrap(),
+            Some(AdminCommand::Raids)
+        );
+        assert_eq!(
+            parse_admin_command("!raid orcs").unwrap(),
+            Some(AdminCommand::Raid {
+                name: "orcs".to_string()
+            })
+        );
+        assert!(parse_admin_command("!raid").is_err());
+    }
+
+    #[test]
+    fn parse_admin_command_parses_monsters() {
+        assert_eq!(
+            parse_admin_command("!monsters").unwrap(),
+            Some(AdminCommand::Monsters)
+        );
+    }
+
     #[test]
     fn parse_admin_command_parses_where() {
         assert_eq!(
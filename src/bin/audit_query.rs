@@ -0,0 +1,52 @@
+use std::path::PathBuf;
+use tibia::telemetry::audit::{audit_path, read_records, AuditQuery};
+
+const USAGE: &str = "usage: audit_query [--root DIR | --file PATH] [--player NAME] [--item TYPE_ID] [--since UNIX] [--until UNIX] [--limit N]";
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{flag} requires a value"))?;
+    value
+        .parse::<T>()
+        .map_err(|_| format!("{flag} expected a number, got '{value}'"))
+}
+
+fn main() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let mut path: Option<PathBuf> = None;
+    let mut root = PathBuf::from(".");
+    let mut query = AuditQuery::default();
+    let mut limit: Option<usize> = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root" => {
+                root = PathBuf::from(args.next().ok_or_else(|| "--root requires a value".to_string())?);
+            }
+            "--file" => {
+                path = Some(PathBuf::from(
+                    args.next().ok_or_else(|| "--file requires a value".to_string())?,
+                ));
+            }
+            "--player" => {
+                query.player = Some(args.next().ok_or_else(|| "--player requires a value".to_string())?);
+            }
+            "--item" => query.item_type = Some(parse_number("--item", args.next())?),
+            "--since" => query.since = Some(parse_number("--since", args.next())?),
+            "--until" => query.until = Some(parse_number("--until", args.next())?),
+            "--limit" => limit = Some(parse_number("--limit", args.next())?),
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            other => return Err(format!("unknown argument '{other}'\n{USAGE}")),
+        }
+    }
+
+    let path = path.unwrap_or_else(|| audit_path(&root));
+    let (records, skipped) = read_records(&path, &query)?;
+    let start = limit.map_or(0, |limit| records.len().saturating_sub(limit));
+    for record in &records[start..] {
+        println!("{}", record.to_line()?);
+    }
+    eprintln!("{} matching records ({} unreadable lines skipped)", records.len(), skipped);
+    Ok(())
+}
@@ -0,0 +1,363 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tibia::combat::damage::DamageType;
+use tibia::entities::creature::CreatureId;
+use tibia::entities::inventory::InventorySlot;
+use tibia::entities::item::{ItemStack, ItemTypeId};
+use tibia::entities::player::{PlayerId, PlayerState};
+use tibia::entities::spells::register_builtin_spells;
+use tibia::entities::stats::Stats;
+use tibia::world::item_types::build_item_types;
+use tibia::world::map::Tile;
+use tibia::world::monsters::load_monsters;
+use tibia::world::object_types::load_object_types;
+use tibia::world::position::Position;
+use tibia::world::state::WorldState;
+use tibia::world::time::GameClock;
+
+const USAGE: &str = "usage: combat_sim --race NUMBER|NAME [--root DIR] [--fights N] [--seed N] \
+[--level N] [--profession N] [--health N] [--mana N] [--skill N] [--magic N] \
+[--equip SLOT=TYPE_ID[:COUNT]]... [--heal-spell WORDS] [--heal-at PERCENT] \
+[--potions N] [--potion-heal N] [--max-seconds N]";
+
+const PLAYER_ID: PlayerId = PlayerId(1);
+const ARENA_CENTER: Position = Position { x: 100, y: 100, z: 7 };
+const ARENA_RADIUS: u16 = 7;
+/// Potions are a flat `--potion-heal` health restore; mana potions are not
+/// simulated.
+const POTION_COOLDOWN: Duration = Duration::from_secs(1);
+
+struct Loadout {
+    level: u16,
+    profession: u8,
+    health: Option<u32>,
+    mana: Option<u32>,
+    skill: Option<u16>,
+    magic: Option<u16>,
+    equipment: Vec<(InventorySlot, ItemStack)>,
+    heal_spell: Option<String>,
+    heal_at: u32,
+    potions: u32,
+    potion_heal: u32,
+}
+
+#[derive(Default)]
+struct FightResult {
+    killed: bool,
+    died: bool,
+    seconds: f64,
+    damage_taken: BTreeMap<&'static str, u64>,
+    mana_spent: u64,
+    heals_cast: u32,
+    potions_used: u32,
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{flag} requires a value"))?;
+    value
+        .parse::<T>()
+        .map_err(|_| format!("{flag} expected a number, got '{value}'"))
+}
+
+fn parse_slot(name: &str) -> Result<InventorySlot, String> {
+    match name.to_ascii_lowercase().as_str() {
+        "head" => Ok(InventorySlot::Head),
+        "necklace" | "amulet" => Ok(InventorySlot::Necklace),
+        "backpack" => Ok(InventorySlot::Backpack),
+        "armor" | "body" => Ok(InventorySlot::Armor),
+        "right" | "weapon" => Ok(InventorySlot::RightHand),
+        "left" | "shield" => Ok(InventorySlot::LeftHand),
+        "legs" => Ok(InventorySlot::Legs),
+        "feet" => Ok(InventorySlot::Feet),
+        "ring" => Ok(InventorySlot::Ring),
+        "ammo" => Ok(InventorySlot::Ammo),
+        other => Err(format!("unknown equipment slot '{other}'")),
+    }
+}
+
+fn parse_equip(value: Option<String>) -> Result<(InventorySlot, ItemStack), String> {
+    let value = value.ok_or_else(|| "--equip requires a value".to_string())?;
+    let (slot, item) = value
+        .split_once('=')
+        .ok_or_else(|| format!("--equip expected SLOT=TYPE_ID, got '{value}'"))?;
+    let (type_id, count) = match item.split_once(':') {
+        Some((type_id, count)) => (type_id, count),
+        None => (item, "1"),
+    };
+    let type_id = parse_number::<u16>("--equip", Some(type_id.to_string()))?;
+    let count = parse_number::<u16>("--equip", Some(count.to_string()))?;
+    Ok((parse_slot(slot)?, ItemStack::new(ItemTypeId(type_id), count.max(1))))
+}
+
+fn build_arena(world: &mut WorldState) {
+    world.map.tiles.clear();
+    let min_x = ARENA_CENTER.x - ARENA_RADIUS;
+    let min_y = ARENA_CENTER.y - ARENA_RADIUS;
+    for x in min_x..=ARENA_CENTER.x + ARENA_RADIUS {
+        for y in min_y..=ARENA_CENTER.y + ARENA_RADIUS {
+            let position = Position { x, y, z: ARENA_CENTER.z };
+            world.map.tiles.insert(
+                position,
+                Tile {
+                    position,
+                    items: Vec::new(),
+                    item_details: Vec::new(),
+                    refresh: false,
+                    protection_zone: false,
+                    no_logout: false,
+                    annotations: Vec::new(),
+                    tags: Vec::new(),
+                },
+            );
+        }
+    }
+}
+
+fn spawn_player(world: &mut WorldState, loadout: &Loadout) -> Result<(), String> {
+    let mut player = PlayerState::new(PLAYER_ID, "Simulant".to_string(), ARENA_CENTER);
+    player.level = loadout.level;
+    player.profession = loadout.profession;
+    player.stats = Stats::for_level(loadout.profession, loadout.level);
+    if let Some(health) = loadout.health {
+        player.stats.max_health = health;
+        player.stats.health = health;
+    }
+    if let Some(mana) = loadout.mana {
+        player.stats.max_mana = mana;
+        player.stats.mana = mana;
+    }
+    if let Some(skill) = loadout.skill {
+        for level in [
+            &mut player.skills.fist.level,
+            &mut player.skills.club.level,
+            &mut player.skills.sword.level,
+            &mut player.skills.axe.level,
+            &mut player.skills.distance.level,
+            &mut player.skills.shielding.level,
+        ] {
+            *level = skill;
+        }
+    }
+    if let Some(magic) = loadout.magic {
+        player.skills.magic.level = magic;
+    }
+    for (slot, item) in &loadout.equipment {
+        player.inventory.set_slot(*slot, Some(item.clone()));
+    }
+    if let Some(words) = loadout.heal_spell.as_deref() {
+        let spell = world
+            .spellbook
+            .get_by_words(words)
+            .ok_or_else(|| format!("unknown healing spell '{words}'"))?;
+        player.learn_spell(spell.id);
+    }
+    world.players.insert(PLAYER_ID, player);
+    Ok(())
+}
+
+fn run_fight(
+    world: &mut WorldState,
+    loadout: &Loadout,
+    race_number: i64,
+    seed: u64,
+    max_ticks: u64,
+) -> Result<FightResult, String> {
+    world.players.clear();
+    world.monsters.clear();
+    build_arena(world);
+    world.set_rng_seeds(seed, seed.wrapping_add(1), seed.wrapping_add(2));
+    spawn_player(world, loadout)?;
+    let monster_position = Position {
+        x: ARENA_CENTER.x + 1,
+        ..ARENA_CENTER
+    };
+    let monster_id: CreatureId = world.spawn_monster_by_race(race_number, monster_position)?;
+    world.set_player_attack_target(PLAYER_ID, Some(monster_id));
+
+    let mut clock = GameClock::new(Duration::from_millis(100));
+    let potion_ticks = clock.ticks_from_duration_round_up(POTION_COOLDOWN);
+    let mut potions_left = loadout.potions;
+    let mut next_potion_tick = 0;
+    let mut result = FightResult::default();
+    for _ in 0..max_ticks {
+        clock.advance(1);
+        world.tick_conditions(clock.now());
+        world.tick_status_effects(clock.now());
+        world.tick_skill_timers(&clock);
+        world.tick_monsters(&clock);
+        world.tick_player_attack(PLAYER_ID, &clock);
+
+        let Some(player) = world.players.get_mut(&PLAYER_ID) else {
+            return Err("simulated player vanished".to_string());
+        };
+        for entry in player.damage_ledger.entries() {
+            *result
+                .damage_taken
+                .entry(entry.damage_type.name())
+                .or_default() += u64::from(entry.amount);
+        }
+        player.damage_ledger.clear();
+        if player.stats.health == 0 {
+            result.died = true;
+            break;
+        }
+        if !world.monsters.contains_key(&monster_id) {
+            result.killed = true;
+            break;
+        }
+        let health_percent = player.stats.health * 100 / player.stats.max_health.max(1);
+        if health_percent >= loadout.heal_at {
+            continue;
+        }
+        let mana_before = player.stats.mana;
+        if let Some(words) = loadout.heal_spell.as_deref() {
+            if world
+                .cast_spell_words(PLAYER_ID, words, None, None, &clock)
+                .is_ok()
+            {
+                result.heals_cast += 1;
+                if let Some(player) = world.players.get(&PLAYER_ID) {
+                    result.mana_spent += u64::from(mana_before.saturating_sub(player.stats.mana));
+                }
+                continue;
+            }
+        }
+        if potions_left > 0 && clock.now().0 >= next_potion_tick {
+            if let Some(player) = world.players.get_mut(&PLAYER_ID) {
+                player.stats.apply_heal(loadout.potion_heal);
+            }
+            potions_left -= 1;
+            result.potions_used += 1;
+            next_potion_tick = clock.now().0 + potion_ticks;
+        }
+    }
+    result.seconds = clock.now().0 as f64 * clock.tick_length().as_secs_f64();
+    Ok(result)
+}
+
+fn main() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let mut root = PathBuf::from(".");
+    let mut race: Option<String> = None;
+    let mut fights: u32 = 100;
+    let mut seed: u64 = 1;
+    let mut max_seconds: u64 = 600;
+    let mut loadout = Loadout {
+        level: 8,
+        profession: 0,
+        health: None,
+        mana: None,
+        skill: None,
+        magic: None,
+        equipment: Vec::new(),
+        heal_spell: None,
+        heal_at: 50,
+        potions: 0,
+        potion_heal: 100,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root" => {
+                root = PathBuf::from(args.next().ok_or_else(|| "--root requires a value".to_string())?);
+            }
+            "--race" => {
+                race = Some(args.next().ok_or_else(|| "--race requires a value".to_string())?);
+            }
+            "--fights" => fights = parse_number("--fights", args.next())?,
+            "--seed" => seed = parse_number("--seed", args.next())?,
+            "--max-seconds" => max_seconds = parse_number("--max-seconds", args.next())?,
+            "--level" => loadout.level = parse_number("--level", args.next())?,
+            "--profession" => loadout.profession = parse_number("--profession", args.next())?,
+            "--health" => loadout.health = Some(parse_number("--health", args.next())?),
+            "--mana" => loadout.mana = Some(parse_number("--mana", args.next())?),
+            "--skill" => loadout.skill = Some(parse_number("--skill", args.next())?),
+            "--magic" => loadout.magic = Some(parse_number("--magic", args.next())?),
+            "--equip" => loadout.equipment.push(parse_equip(args.next())?),
+            "--heal-spell" => {
+                loadout.heal_spell =
+                    Some(args.next().ok_or_else(|| "--heal-spell requires a value".to_string())?);
+            }
+            "--heal-at" => loadout.heal_at = parse_number("--heal-at", args.next())?,
+            "--potions" => loadout.potions = parse_number("--potions", args.next())?,
+            "--potion-heal" => loadout.potion_heal = parse_number("--potion-heal", args.next())?,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            other => return Err(format!("unknown argument '{other}'\n{USAGE}")),
+        }
+    }
+    let race = race.ok_or_else(|| format!("--race is required\n{USAGE}"))?;
+
+    let mut world = WorldState::empty();
+    let monster_index = load_monsters(&root.join("mon"))?;
+    let race_number = match race.parse::<i64>() {
+        Ok(number) => number,
+        Err(_) => monster_index
+            .race_by_name(&race)
+            .ok_or_else(|| format!("unknown monster race '{race}'"))?,
+    };
+    let race_name = monster_index
+        .name_by_race(race_number)
+        .unwrap_or(&race)
+        .to_string();
+    world.monster_index = Some(monster_index);
+    match load_object_types(&root.join("dat").join("objects.srv")) {
+        Ok(object_types) => {
+            world.item_types = Some(build_item_types(&object_types));
+            world.object_types = Some(object_types);
+        }
+        Err(err) => eprintln!("combat_sim: objects.srv read skipped: {err}"),
+    }
+    register_builtin_spells(&mut world.spellbook)?;
+
+    let max_ticks = max_seconds.saturating_mul(10);
+    let mut results = Vec::new();
+    for fight in 0..fights {
+        let fight_seed = seed.wrapping_add(u64::from(fight).wrapping_mul(0x9e37_79b9));
+        results.push(run_fight(&mut world, &loadout, race_number, fight_seed, max_ticks)?);
+    }
+
+    let count = results.len().max(1) as f64;
+    let kills: Vec<&FightResult> = results.iter().filter(|result| result.killed).collect();
+    let deaths = results.iter().filter(|result| result.died).count();
+    let timeouts = results.len() - kills.len() - deaths;
+    println!("combat simulation: {} (race {})", race_name, race_number);
+    println!("- fights: {}", results.len());
+    println!("- kills: {}", kills.len());
+    println!(
+        "- deaths: {} ({:.1}%)",
+        deaths,
+        deaths as f64 * 100.0 / count
+    );
+    println!("- timeouts: {}", timeouts);
+    if !kills.is_empty() {
+        let times: Vec<f64> = kills.iter().map(|result| result.seconds).collect();
+        let average = times.iter().sum::<f64>() / times.len() as f64;
+        let min = times.iter().cloned().fold(f64::MAX, f64::min);
+        let max = times.iter().cloned().fold(0.0, f64::max);
+        println!(
+            "- time to kill: avg {:.1}s, min {:.1}s, max {:.1}s",
+            average, min, max
+        );
+    }
+    println!("damage taken per fight:");
+    for damage_type in DamageType::ALL {
+        let total: u64 = results
+            .iter()
+            .filter_map(|result| result.damage_taken.get(damage_type.name()))
+            .sum();
+        if total > 0 {
+            println!("- {}: {:.1}", damage_type.name(), total as f64 / count);
+        }
+    }
+    let mana: u64 = results.iter().map(|result| result.mana_spent).sum();
+    let heals: u32 = results.iter().map(|result| result.heals_cast).sum();
+    let potions: u32 = results.iter().map(|result| result.potions_used).sum();
+    println!("resources per fight:");
+    println!("- mana spent: {:.1}", mana as f64 / count);
+    println!("- healing spells: {:.2}", f64::from(heals) / count);
+    println!("- health potions: {:.2}", f64::from(potions) / count);
+    Ok(())
+}
@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tibia::entities::item::ItemTypeId;
+use tibia::world::item_types::{build_item_types, ItemTypeIndex};
+use tibia::world::monsters::{build_loot_table, load_monsters, LootRng, MonsterIndex};
+use tibia::world::object_types::{load_object_types, money_value_for_type, ObjectTypeIndex};
+
+const USAGE: &str = "usage: loot_sim [--root DIR] [--race NUMBER|NAME]... [--kills N] [--seed N]";
+
+#[derive(Default)]
+struct ItemStats {
+    chances: Vec<u16>,
+    kills_with_drop: u32,
+    total_count: u64,
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{flag} requires a value"))?;
+    value
+        .parse::<T>()
+        .map_err(|_| format!("{flag} expected a number, got '{value}'"))
+}
+
+fn resolve_race(index: &MonsterIndex, race: &str) -> Result<i64, String> {
+    let race_number = match race.parse::<i64>() {
+        Ok(number) => number,
+        Err(_) => index
+            .race_by_name(race)
+            .ok_or_else(|| format!("unknown monster race '{race}'"))?,
+    };
+    if index.script_by_race(race_number).is_none() {
+        return Err(format!("unknown monster race '{race}'"));
+    }
+    Ok(race_number)
+}
+
+/// Rolls one race's loot `kills` times and prints how often each item
+/// actually dropped next to the per-mille chance its script asks for.
+/// Returns the number of warnings printed.
+fn simulate_race(
+    index: &MonsterIndex,
+    object_types: &ObjectTypeIndex,
+    item_types: &ItemTypeIndex,
+    race_number: i64,
+    kills: u32,
+    seed: u64,
+) -> usize {
+    let name = index.name_by_race(race_number).unwrap_or("?");
+    let Some(script) = index.script_by_race(race_number) else {
+        return 0;
+    };
+    let table = match build_loot_table(script) {
+        Ok(table) => table,
+        Err(err) => {
+            eprintln!("warning: {name} ({race_number}): loot skipped: {err}");
+            return 1;
+        }
+    };
+    let mut warnings = 0;
+    let mut items: BTreeMap<u16, ItemStats> = BTreeMap::new();
+    for (number, entry) in table.entries.iter().enumerate() {
+        if object_types.get(entry.type_id).is_none() {
+            eprintln!(
+                "warning: {name} ({race_number}): loot entry {} references unknown item type {}",
+                number + 1,
+                entry.type_id.0
+            );
+            warnings += 1;
+        }
+        items.entry(entry.type_id.0).or_default().chances.push(entry.chance);
+    }
+
+    let mut rng = LootRng::from_seed(seed);
+    let mut total_gold = 0u64;
+    for _ in 0..kills {
+        let mut dropped: BTreeMap<u16, u64> = BTreeMap::new();
+        for stack in table.roll(&mut rng, Some(item_types)) {
+            *dropped.entry(stack.type_id.0).or_default() += u64::from(stack.count);
+        }
+        for (type_id, count) in dropped {
+            let stats = items.entry(type_id).or_default();
+            stats.kills_with_drop += 1;
+            stats.total_count += count;
+            if let Some(value) = money_value_for_type(object_types, ItemTypeId(type_id)) {
+                total_gold += u64::from(value) * count;
+            }
+        }
+    }
+
+    let kills_f = f64::from(kills.max(1));
+    println!(
+        "{name} ({race_number}): {kills} kills, {} loot entries, ~{:.2} gold/kill",
+        table.entries.len(),
+        total_gold as f64 / kills_f
+    );
+    for (type_id, stats) in &items {
+        let item_name = object_types
+            .get(ItemTypeId(*type_id))
+            .map(|object| object.name.as_str())
+            .unwrap_or("unknown");
+        let chances: Vec<String> = stats
+            .chances
+            .iter()
+            .map(|chance| format!("{chance}/1000"))
+            .collect();
+        println!(
+            "- {} {}: chance {}, dropped {:.2}% of kills, {:.3} per kill",
+            type_id,
+            item_name,
+            chances.join("+"),
+            f64::from(stats.kills_with_drop) * 100.0 / kills_f,
+            stats.total_count as f64 / kills_f
+        );
+    }
+    warnings
+}
+
+fn main() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let mut root = PathBuf::from(".");
+    let mut races: Vec<String> = Vec::new();
+    let mut kills: u32 = 10_000;
+    let mut seed: u64 = 1;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root" => {
+                root = PathBuf::from(args.next().ok_or_else(|| "--root requires a value".to_string())?);
+            }
+            "--race" => {
+                races.push(args.next().ok_or_else(|| "--race requires a value".to_string())?);
+            }
+            "--kills" => kills = parse_number("--kills", args.next())?,
+            "--seed" => seed = parse_number("--seed", args.next())?,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            other => return Err(format!("unknown argument '{other}'\n{USAGE}")),
+        }
+    }
+
+    let index = load_monsters(&root.join("mon"))?;
+    let object_types = load_object_types(&root.join("dat").join("objects.srv"))?;
+    let item_types = build_item_types(&object_types);
+    let race_numbers: Vec<i64> = if races.is_empty() {
+        let mut numbers: Vec<i64> = index.race_index.keys().copied().collect();
+        numbers.sort_unstable();
+        numbers
+    } else {
+        races
+            .iter()
+            .map(|race| resolve_race(&index, race))
+            .collect::<Result<_, _>>()?
+    };
+
+    let mut warnings = 0;
+    for race_number in race_numbers {
+        warnings += simulate_race(&index, &object_types, &item_types, race_number, kills, seed);
+    }
+    if warnings > 0 {
+        eprintln!("loot_sim: {warnings} warning(s)");
+    }
+    Ok(())
+}
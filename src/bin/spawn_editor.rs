@@ -0,0 +1,350 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tibia::world::monster_homes::{load_monster_homes, save_monster_homes, MonsterHome};
+use tibia::world::monsters::{load_monsters, MonsterIndex};
+use tibia::world::position::Position;
+
+const USAGE: &str = "usage: spawn_editor export [--root DIR] [--db FILE] [--out FILE] [--format yaml|json]\n\
+       spawn_editor import --in FILE [--root DIR] [--out FILE] [--format yaml|json]\n\
+       spawn_editor report [--root DIR] [--db FILE] [--region-size SECTORS] [--top N]";
+
+const SECTOR_TILE_SIZE: u16 = 32;
+
+/// Average respawn delay as a fraction of `regen`: home timers roll
+/// uniformly between regen/2 and regen while the server is not crowded.
+const AVERAGE_REGEN_FACTOR: f64 = 0.75;
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SpawnFile {
+    #[serde(default)]
+    spawns: Vec<SpawnEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SpawnEntry {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    race: Option<i64>,
+    x: u16,
+    y: u16,
+    z: u8,
+    #[serde(default)]
+    radius: u16,
+    #[serde(default = "default_amount")]
+    amount: u16,
+    #[serde(default)]
+    regen: u16,
+}
+
+fn default_amount() -> u16 {
+    1
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Yaml,
+    Json,
+}
+
+#[derive(Default)]
+struct AreaStats {
+    homes: usize,
+    monsters: u32,
+    xp_per_hour: f64,
+}
+
+fn parse_number<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{flag} requires a value"))?;
+    value
+        .parse::<T>()
+        .map_err(|_| format!("{flag} expected a number, got '{value}'"))
+}
+
+fn parse_format(value: Option<String>) -> Result<Format, String> {
+    let value = value.ok_or_else(|| "--format requires a value".to_string())?;
+    match value.to_ascii_lowercase().as_str() {
+        "yaml" | "yml" => Ok(Format::Yaml),
+        "json" => Ok(Format::Json),
+        other => Err(format!("unknown format '{other}'")),
+    }
+}
+
+fn format_for_path(path: Option<&Path>) -> Format {
+    match path.and_then(Path::extension).and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("json") => Format::Json,
+        _ => Format::Yaml,
+    }
+}
+
+fn export(homes: &[MonsterHome], index: &MonsterIndex, format: Format) -> Result<String, String> {
+    let file = SpawnFile {
+        spawns: homes
+            .iter()
+            .map(|home| SpawnEntry {
+                name: index.name_by_race(home.race_number).map(str::to_string),
+                race: Some(home.race_number),
+                x: home.position.x,
+                y: home.position.y,
+                z: home.position.z,
+                radius: home.radius,
+                amount: home.amount,
+                regen: home.regen,
+            })
+            .collect(),
+    };
+    match format {
+        Format::Yaml => serde_yaml::to_string(&file)
+            .map_err(|err| format!("spawn export serialize failed: {err}")),
+        Format::Json => serde_json::to_string_pretty(&file)
+            .map(|data| data + "\n")
+            .map_err(|err| format!("spawn export serialize failed: {err}")),
+    }
+}
+
+/// Turns edited entries back into homes. A name wins over the race number so
+/// designers can swap a spawn's monster by editing the name alone.
+fn import(data: &str, index: &MonsterIndex, format: Format) -> Result<Vec<MonsterHome>, String> {
+    let file: SpawnFile = match format {
+        Format::Yaml => serde_yaml::from_str(data)
+            .map_err(|err| format!("spawn import parse failed: {err}"))?,
+        Format::Json => serde_json::from_str(data)
+            .map_err(|err| format!("spawn import parse failed: {err}"))?,
+    };
+    let mut homes = Vec::with_capacity(file.spawns.len());
+    for (number, entry) in file.spawns.into_iter().enumerate() {
+        let number = number + 1;
+        let race_number = match (entry.name.as_deref(), entry.race) {
+            (Some(name), race) => {
+                let resolved = index
+                    .race_by_name(name)
+                    .ok_or_else(|| format!("spawn {number}: unknown monster '{name}'"))?;
+                if let Some(race) = race.filter(|race| *race != resolved) {
+                    eprintln!(
+                        "spawn_editor: spawn {number}: name '{name}' overrides race {race} with {resolved}"
+                    );
+                }
+                resolved
+            }
+            (None, Some(race)) => {
+                if index.name_by_race(race).is_none() {
+                    return Err(format!("spawn {number}: unknown race {race}"));
+                }
+                race
+            }
+            (None, None) => return Err(format!("spawn {number}: needs a name or race")),
+        };
+        homes.push(MonsterHome {
+            race_number,
+            position: Position {
+                x: entry.x,
+                y: entry.y,
+                z: entry.z,
+            },
+            radius: entry.radius,
+            amount: entry.amount,
+            regen: entry.regen,
+            act_monsters: 0,
+            timer: 0,
+        });
+    }
+    Ok(homes)
+}
+
+/// Kills per hour a home can sustain if farmed nonstop: one respawn per
+/// home timer, and nothing at all for homes without regen.
+fn respawns_per_hour(home: &MonsterHome) -> f64 {
+    if home.regen == 0 {
+        return 0.0;
+    }
+    3600.0 / (f64::from(home.regen) * AVERAGE_REGEN_FACTOR)
+}
+
+fn report(homes: &[MonsterHome], index: &MonsterIndex, region_size: u16, top: usize) {
+    let region_tiles = SECTOR_TILE_SIZE.saturating_mul(region_size.max(1));
+    let mut sectors: BTreeMap<(u16, u16, u8), AreaStats> = BTreeMap::new();
+    let mut regions: BTreeMap<(u16, u16, u8), AreaStats> = BTreeMap::new();
+    let mut unknown: BTreeMap<i64, usize> = BTreeMap::new();
+    let mut total_monsters = 0u32;
+    let mut total_xp = 0.0;
+    for home in homes {
+        let experience = match index.script_by_race(home.race_number) {
+            Some(script) => script.experience().unwrap_or(0),
+            None => {
+                *unknown.entry(home.race_number).or_default() += 1;
+                0
+            }
+        };
+        let xp_per_hour = f64::from(experience) * respawns_per_hour(home);
+        let position = home.position;
+        let sector_key = (
+            position.x / SECTOR_TILE_SIZE,
+            position.y / SECTOR_TILE_SIZE,
+            position.z,
+        );
+        let region_key = (position.x / region_tiles, position.y / region_tiles, position.z);
+        for (stats, key) in [(&mut sectors, sector_key), (&mut regions, region_key)] {
+            let entry = stats.entry(key).or_default();
+            entry.homes += 1;
+            entry.monsters += u32::from(home.amount);
+            entry.xp_per_hour += xp_per_hour;
+        }
+        total_monsters += u32::from(home.amount);
+        total_xp += xp_per_hour;
+    }
+
+    println!(
+        "spawn density report: {} homes, {} monsters, ~{:.0} xp/hour",
+        homes.len(),
+        total_monsters,
+        total_xp
+    );
+    let mut sector_list: Vec<_> = sectors.into_iter().collect();
+    sector_list.sort_by(|a, b| b.1.monsters.cmp(&a.1.monsters).then(a.0.cmp(&b.0)));
+    println!(
+        "densest sectors ({}x{} tiles, {} total):",
+        SECTOR_TILE_SIZE,
+        SECTOR_TILE_SIZE,
+        sector_list.len()
+    );
+    for ((sx, sy, z), stats) in sector_list.iter().take(top) {
+        println!(
+            "- sector {},{},{} (x {} y {}): {} monsters in {} homes",
+            sx,
+            sy,
+            z,
+            u32::from(*sx) * u32::from(SECTOR_TILE_SIZE),
+            u32::from(*sy) * u32::from(SECTOR_TILE_SIZE),
+            stats.monsters,
+            stats.homes
+        );
+    }
+    let mut region_list: Vec<_> = regions.into_iter().collect();
+    region_list.sort_by(|a, b| {
+        b.1.xp_per_hour
+            .total_cmp(&a.1.xp_per_hour)
+            .then(a.0.cmp(&b.0))
+    });
+    println!(
+        "regions by xp/hour ({}x{} tiles, {} total):",
+        region_tiles,
+        region_tiles,
+        region_list.len()
+    );
+    for ((rx, ry, z), stats) in region_list.iter().take(top) {
+        let min_x = u32::from(*rx) * u32::from(region_tiles);
+        let min_y = u32::from(*ry) * u32::from(region_tiles);
+        let span = u32::from(region_tiles) - 1;
+        println!(
+            "- x {}-{} y {}-{} z {}: {} monsters in {} homes, ~{:.0} xp/hour",
+            min_x,
+            min_x + span,
+            min_y,
+            min_y + span,
+            z,
+            stats.monsters,
+            stats.homes,
+            stats.xp_per_hour
+        );
+    }
+    if !unknown.is_empty() {
+        println!("unknown races (no xp counted):");
+        for (race, count) in unknown {
+            println!("- race {}: {} homes", race, count);
+        }
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+fn main() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let command = match args.next() {
+        Some(command) if command == "-h" || command == "--help" => {
+            println!("{USAGE}");
+            return Ok(());
+        }
+        Some(command) => command,
+        None => return Err(USAGE.to_string()),
+    };
+    let mut root = PathBuf::from(".");
+    let mut db: Option<PathBuf> = None;
+    let mut input: Option<PathBuf> = None;
+    let mut out: Option<PathBuf> = None;
+    let mut format: Option<Format> = None;
+    let mut region_size: u16 = 8;
+    let mut top: usize = 20;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--root" => {
+                root = PathBuf::from(args.next().ok_or_else(|| "--root requires a value".to_string())?);
+            }
+            "--db" => {
+                db = Some(PathBuf::from(args.next().ok_or_else(|| "--db requires a value".to_string())?));
+            }
+            "--in" => {
+                input = Some(PathBuf::from(args.next().ok_or_else(|| "--in requires a value".to_string())?));
+            }
+            "--out" => {
+                out = Some(PathBuf::from(args.next().ok_or_else(|| "--out requires a value".to_string())?));
+            }
+            "--format" => format = Some(parse_format(args.next())?),
+            "--region-size" => region_size = parse_number("--region-size", args.next())?,
+            "--top" => top = parse_number("--top", args.next())?,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            other => return Err(format!("unknown argument '{other}'\n{USAGE}")),
+        }
+    }
+
+    let index = load_monsters(&root.join("mon"))?;
+    let db = db.unwrap_or_else(|| root.join("dat").join("monster.db"));
+    match command.as_str() {
+        "export" => {
+            let homes = load_monster_homes(&db)?;
+            let format = format.unwrap_or_else(|| format_for_path(out.as_deref()));
+            let data = export(&homes, &index, format)?;
+            match out {
+                Some(path) => {
+                    fs::write(&path, data)
+                        .map_err(|err| format!("failed to write {}: {}", path.display(), err))?;
+                    println!("exported {} homes to {}", homes.len(), path.display());
+                }
+                None => print!("{data}"),
+            }
+        }
+        "import" => {
+            let input = input.ok_or_else(|| format!("import requires --in FILE\n{USAGE}"))?;
+            let data = fs::read_to_string(&input)
+                .map_err(|err| format!("failed to read {}: {}", input.display(), err))?;
+            let format = format.unwrap_or_else(|| format_for_path(Some(&input)));
+            let homes = import(&data, &index, format)?;
+            let out = out.unwrap_or(db);
+            if out.exists() {
+                // The written file keeps no comments from the original, so
+                // keep a copy of whatever gets replaced.
+                let backup = backup_path(&out);
+                fs::copy(&out, &backup).map_err(|err| {
+                    format!("failed to back up {} to {}: {}", out.display(), backup.display(), err)
+                })?;
+                println!("backed up {} to {}", out.display(), backup.display());
+            }
+            save_monster_homes(&out, &homes, |race| index.name_by_race(race))?;
+            println!("imported {} homes into {}", homes.len(), out.display());
+        }
+        "report" => {
+            let homes = load_monster_homes(&db)?;
+            report(&homes, &index, region_size, top);
+        }
+        other => return Err(format!("unknown command '{other}'\n{USAGE}")),
+    }
+    Ok(())
+}
@@ -29,6 +29,20 @@ impl DamageScaleFlags {
 impl DamageType {
     pub const COUNT: usize = 10;
 
+    /// Every concrete damage type, in `index` order.
+    pub const ALL: [DamageType; Self::COUNT] = [
+        Self::Physical,
+        Self::Energy,
+        Self::Earth,
+        Self::Fire,
+        Self::LifeDrain,
+        Self::ManaDrain,
+        Self::Drown,
+        Self::Ice,
+        Self::Holy,
+        Self::Death,
+    ];
+
     pub fn from_mask(mask: u16) -> Self {
         match mask {
             1 => Self::Physical,
@@ -76,6 +90,22 @@ impl DamageType {
             Self::Unknown(_) => None,
         }
     }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Physical => "physical",
+            Self::Energy => "energy",
+            Self::Earth => "earth",
+            Self::Fire => "fire",
+            Self::LifeDrain => "life drain",
+            Self::ManaDrain => "mana drain",
+            Self::Drown => "drown",
+            Self::Ice => "ice",
+            Self::Holy => "holy",
+            Self::Death => "death",
+            Self::Unknown(_) => "unknown",
+        }
+    }
 }
 
 pub fn compute_damage(
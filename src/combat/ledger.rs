@@ -0,0 +1,235 @@
+use crate::combat::conditions::ConditionKind;
+use crate::combat::damage::DamageType;
+use crate::entities::creature::CreatureId;
+use crate::entities::player::PlayerId;
+use crate::world::time::GameTick;
+use std::collections::HashMap;
+
+/// Who dealt a piece of damage. Summon damage is credited to the master.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DamageSource {
+    Player(PlayerId),
+    Monster(CreatureId),
+    Environment,
+}
+
+impl DamageSource {
+    pub fn player_id(self) -> Option<PlayerId> {
+        match self {
+            DamageSource::Player(player_id) => Some(player_id),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DamageEntry {
+    pub source: DamageSource,
+    pub name: String,
+    pub damage_type: DamageType,
+    pub amount: u32,
+    pub last_hit_at: GameTick,
+}
+
+/// Damage one source dealt within the ledger window, summed over all types.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DamageCredit {
+    pub source: DamageSource,
+    pub name: String,
+    pub amount: u32,
+    pub last_hit_at: GameTick,
+}
+
+/// Per-creature record of who hurt it, used for kill credit, experience
+/// splits and loot ownership. Times are game ticks and windows a number of
+/// ticks.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DamageLedger {
+    entries: Vec<DamageEntry>,
+    conditions: HashMap<ConditionKind, (DamageSource, String)>,
+}
+
+impl DamageLedger {
+    pub fn record(
+        &mut self,
+        source: DamageSource,
+        name: &str,
+        damage_type: DamageType,
+        amount: u32,
+        now: GameTick,
+    ) {
+        if amount == 0 {
+            return;
+        }
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.source == source && entry.damage_type == damage_type)
+        {
+            entry.amount = entry.amount.saturating_add(amount);
+            entry.last_hit_at = entry.last_hit_at.max(now);
+            entry.name = name.to_string();
+            return;
+        }
+        self.entries.push(DamageEntry {
+            source,
+            name: name.to_string(),
+            damage_type,
+            amount,
+            last_hit_at: now,
+        });
+    }
+
+    /// Remembers who inflicted a condition so its later ticks are credited.
+    pub fn note_condition(&mut self, kind: ConditionKind, source: DamageSource, name: &str) {
+        self.conditions.insert(kind, (source, name.to_string()));
+    }
+
+    pub fn condition_source(&self, kind: ConditionKind) -> Option<(DamageSource, &str)> {
+        self.conditions
+            .get(&kind)
+            .map(|(source, name)| (*source, name.as_str()))
+    }
+
+    /// Forgets the sources of conditions that are no longer active, so a
+    /// later condition of the same kind is not credited to them.
+    pub fn retain_conditions(&mut self, mut active: impl FnMut(ConditionKind) -> bool) {
+        self.conditions.retain(|kind, _| active(*kind));
+    }
+
+    pub fn entries(&self) -> &[DamageEntry] {
+        &self.entries
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Drops entries whose last hit is older than the window.
+    pub fn prune(&mut self, now: GameTick, window: u64) {
+        let since = now.0.saturating_sub(window);
+        self.entries.retain(|entry| entry.last_hit_at.0 >= since);
+    }
+
+    /// Damage per source within the window, most recent hit first.
+    pub fn credits(&self, now: GameTick, window: u64) -> Vec<DamageCredit> {
+        let since = now.0.saturating_sub(window);
+        let mut credits: Vec<DamageCredit> = Vec::new();
+        for entry in self.entries.iter().filter(|entry| entry.last_hit_at.0 >= since) {
+            match credits
+                .iter_mut()
+                .find(|credit| credit.source == entry.source)
+            {
+                Some(credit) => {
+                    credit.amount = credit.amount.saturating_add(entry.amount);
+                    if entry.last_hit_at >= credit.last_hit_at {
+                        credit.last_hit_at = entry.last_hit_at;
+                        credit.name = entry.name.clone();
+                    }
+                }
+                None => credits.push(DamageCredit {
+                    source: entry.source,
+                    name: entry.name.clone(),
+                    amount: entry.amount,
+                    last_hit_at: entry.last_hit_at,
+                }),
+            }
+        }
+        credits.sort_by(|a, b| {
+            b.last_hit_at
+                .cmp(&a.last_hit_at)
+                .then_with(|| b.amount.cmp(&a.amount))
+        });
+        credits
+    }
+
+    pub fn total(&self, now: GameTick, window: u64) -> u32 {
+        self.credits(now, window)
+            .iter()
+            .fold(0u32, |total, credit| total.saturating_add(credit.amount))
+    }
+
+    pub fn player_damage(&self, now: GameTick, window: u64) -> HashMap<PlayerId, u32> {
+        self.credits(now, window)
+            .into_iter()
+            .filter_map(|credit| credit.source.player_id().map(|id| (id, credit.amount)))
+            .collect()
+    }
+
+    /// The source with the most damage in the window; ties go to the most
+    /// recent hit.
+    pub fn top_damager(&self, now: GameTick, window: u64) -> Option<DamageCredit> {
+        self.credits(now, window)
+            .into_iter()
+            .reduce(|best, credit| if credit.amount > best.amount { credit } else { best })
+    }
+
+    /// The player with the most damage in the window, ignoring monsters and
+    /// the environment.
+    pub fn top_player(&self, now: GameTick, window: u64) -> Option<PlayerId> {
+        self.credits(now, window)
+            .into_iter()
+            .filter(|credit| credit.source.player_id().is_some())
+            .reduce(|best, credit| if credit.amount > best.amount { credit } else { best })
+            .and_then(|credit| credit.source.player_id())
+    }
+
+    pub fn last_hitter(&self, now: GameTick, window: u64) -> Option<DamageCredit> {
+        self.credits(now, window).into_iter().next()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.conditions.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ledger_sums_per_source_and_forgets_old_hits() {
+        let window = 600;
+        let mut ledger = DamageLedger::default();
+        let knight = DamageSource::Player(PlayerId(1));
+        let druid = DamageSource::Player(PlayerId(2));
+        let rat = DamageSource::Monster(CreatureId(7));
+        ledger.record(knight, "Knight", DamageType::Physical, 40, GameTick(1_000));
+        ledger.record(knight, "Knight", DamageType::Fire, 20, GameTick(1_010));
+        ledger.record(druid, "Druid", DamageType::Ice, 30, GameTick(1_020));
+        ledger.record(rat, "a rat", DamageType::Physical, 5, GameTick(390));
+        ledger.record(rat, "a rat", DamageType::Physical, 0, GameTick(1_030));
+
+        let credits = ledger.credits(GameTick(1_030), window);
+        assert_eq!(credits.len(), 2);
+        assert_eq!(credits[0].source, druid);
+        assert_eq!(credits[1].amount, 60);
+        assert_eq!(ledger.top_damager(GameTick(1_030), window).unwrap().source, knight);
+        assert_eq!(ledger.top_player(GameTick(1_030), window), Some(PlayerId(1)));
+        assert_eq!(ledger.last_hitter(GameTick(1_030), window).unwrap().name, "Druid");
+        assert_eq!(ledger.total(GameTick(1_030), window), 90);
+        assert_eq!(ledger.player_damage(GameTick(1_030), window).get(&PlayerId(1)), Some(&60));
+
+        ledger.prune(GameTick(1_615), window);
+        assert_eq!(ledger.entries().len(), 1);
+        assert_eq!(ledger.entries()[0].source, druid);
+    }
+
+    #[test]
+    fn ledger_remembers_condition_sources_until_cleared() {
+        let mut ledger = DamageLedger::default();
+        let source = DamageSource::Player(PlayerId(3));
+        ledger.note_condition(ConditionKind::Poison, source, "Sorcerer");
+        assert_eq!(
+            ledger.condition_source(ConditionKind::Poison),
+            Some((source, "Sorcerer"))
+        );
+        assert_eq!(ledger.condition_source(ConditionKind::Fire), None);
+        ledger.note_condition(ConditionKind::Fire, source, "Sorcerer");
+        ledger.retain_conditions(|kind| kind == ConditionKind::Poison);
+        assert_eq!(ledger.condition_source(ConditionKind::Fire), None);
+        ledger.clear();
+        assert!(ledger.condition_source(ConditionKind::Poison).is_none());
+    }
+}
@@ -1,4 +1,7 @@
 pub mod damage;
+pub mod distance;
 pub mod conditions;
+pub mod ledger;
 pub mod rules;
 pub mod spells;
+pub mod threat;
@@ -1,18 +1,175 @@
 use std::time::Duration;
 
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Number of unjustified kills tolerated within the last day, week and
+/// month before a limit is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MurderThresholds {
+    pub day: usize,
+    pub week: usize,
+    pub month: usize,
+}
+
+impl MurderThresholds {
+    pub fn reached(&self, murders: &[u64], now: u64) -> bool {
+        let within = |days: u64| {
+            let since = now.saturating_sub(days * SECONDS_PER_DAY);
+            murders.iter().filter(|at| **at > since).count()
+        };
+        within(1) >= self.day || within(7) >= self.week || within(30) >= self.month
+    }
+}
+
+/// How players may fight each other on this world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldType {
+    /// Optional PvP: players cannot damage each other.
+    NoPvp,
+    /// Open PvP with skulls for unjustified attacks and kills.
+    Pvp,
+    /// Everyone may fight without skulls and player kills give experience.
+    PvpEnforced,
+}
+
+impl WorldType {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "no-pvp" | "nopvp" | "non-pvp" | "optional" | "optional-pvp" => Ok(WorldType::NoPvp),
+            "pvp" | "open" | "open-pvp" => Ok(WorldType::Pvp),
+            "pvp-enforced" | "enforced" => Ok(WorldType::PvpEnforced),
+            other => Err(format!("unknown world type '{}'", other)),
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            WorldType::NoPvp => "no-pvp",
+            WorldType::Pvp => "pvp",
+            WorldType::PvpEnforced => "pvp-enforced",
+        }
+    }
+}
+
+/// Experience for killing a player on a PvP-enforced world: 5% of the
+/// victim's experience, shrinking as the killer's level approaches the
+/// victim's and vanishing once the killer is more than ~11% higher.
+pub fn pvp_kill_experience(killer_level: u16, victim_level: u16, victim_experience: u64) -> u64 {
+    if victim_level == 0 {
+        return 0;
+    }
+    let reduced_killer_level = u64::from(killer_level) * 9 / 10;
+    let victim_level = u64::from(victim_level);
+    if reduced_killer_level >= victim_level {
+        return 0;
+    }
+    let factor_numerator = victim_level - reduced_killer_level;
+    (u128::from(victim_experience) * u128::from(factor_numerator) * 5
+        / (u128::from(victim_level) * 100))
+        .min(u128::from(u64::MAX)) as u64
+}
+
 #[derive(Debug, Clone)]
 pub struct CombatRules {
-    pub pvp_enabled: bool,
+    pub world_type: WorldType,
     pub fight_timer: Duration,
     pub white_skull_timer: Duration,
+    pub red_skull_duration: Duration,
+    pub black_skull_duration: Duration,
+    pub red_skull_thresholds: MurderThresholds,
+    /// Twice the red skull limits by default, as in the original game.
+    pub black_skull_thresholds: MurderThresholds,
+    pub banishment_thresholds: MurderThresholds,
+    pub banishment_duration: Duration,
+    /// Hits older than this no longer count towards kill credit.
+    pub damage_window: Duration,
+    /// How long a monster corpse can only be opened by its killer's side.
+    pub loot_protection: Duration,
+}
+
+impl CombatRules {
+    pub fn players_can_attack(&self) -> bool {
+        self.world_type != WorldType::NoPvp
+    }
+
+    pub fn skulls_apply(&self) -> bool {
+        self.world_type == WorldType::Pvp
+    }
+
+    pub fn player_kills_give_experience(&self) -> bool {
+        self.world_type == WorldType::PvpEnforced
+    }
+
+    /// Murders older than this no longer count towards any threshold.
+    pub fn murder_memory(&self) -> Duration {
+        Duration::from_secs(30 * SECONDS_PER_DAY)
+    }
 }
 
 impl Default for CombatRules {
     fn default() -> Self {
         Self {
-            pvp_enabled: true,
+            world_type: WorldType::Pvp,
             fight_timer: Duration::from_secs(60),
             white_skull_timer: Duration::from_secs(60),
+            red_skull_duration: Duration::from_secs(30 * SECONDS_PER_DAY),
+            black_skull_duration: Duration::from_secs(45 * SECONDS_PER_DAY),
+            red_skull_thresholds: MurderThresholds {
+                day: 3,
+                week: 5,
+                month: 10,
+            },
+            black_skull_thresholds: MurderThresholds {
+                day: 6,
+                week: 10,
+                month: 20,
+            },
+            banishment_thresholds: MurderThresholds {
+                day: 6,
+                week: 10,
+                month: 20,
+            },
+            banishment_duration: Duration::from_secs(7 * SECONDS_PER_DAY),
+            damage_window: Duration::from_secs(60),
+            loot_protection: Duration::from_secs(10),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn murder_thresholds_count_each_window() {
+        let thresholds = MurderThresholds {
+            day: 3,
+            week: 5,
+            month: 10,
+        };
+        let now = 100 * SECONDS_PER_DAY;
+        let today = vec![now - 10, now - 20];
+        assert!(!thresholds.reached(&today, now));
+        let mut busy_day = today.clone();
+        busy_day.push(now - 30);
+        assert!(thresholds.reached(&busy_day, now));
+
+        let spread: Vec<u64> = (1..=5).map(|day| now - day * SECONDS_PER_DAY + 1).collect();
+        assert!(thresholds.reached(&spread, now));
+        let stale: Vec<u64> = (40..60).map(|day| now - day * SECONDS_PER_DAY).collect();
+        assert!(!thresholds.reached(&stale, now));
+    }
+
+    #[test]
+    fn world_type_parses_names_and_enforced_experience_scales_by_level() {
+        assert_eq!(WorldType::parse("No-PvP").unwrap(), WorldType::NoPvp);
+        assert_eq!(WorldType::parse("pvp_enforced").unwrap(), WorldType::PvpEnforced);
+        assert_eq!(WorldType::parse(" open ").unwrap(), WorldType::Pvp);
+        assert!(WorldType::parse("hardcore").is_err());
+
+        assert_eq!(pvp_kill_experience(10, 20, 100_000), 2_750);
+        assert_eq!(pvp_kill_experience(22, 20, 100_000), 250);
+        assert_eq!(pvp_kill_experience(23, 20, 100_000), 0);
+        assert_eq!(pvp_kill_experience(1, 1, 0), 0);
+    }
+}
@@ -0,0 +1,114 @@
+use crate::entities::player::PlayerId;
+use std::collections::HashMap;
+
+/// Share of a heal that counts as threat against every monster fighting
+/// the healed player.
+pub const HEALING_THREAT_PERCENT: u32 = 50;
+
+/// How far a challenger's threat has to exceed the current target's, in
+/// percent, before a monster switches to it.
+pub const TARGET_SWITCH_PERCENT: u32 = 130;
+
+/// How much each player has provoked one monster, from damage dealt to it
+/// and healing done to players it is fighting.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ThreatTable {
+    threat: HashMap<PlayerId, u32>,
+}
+
+impl ThreatTable {
+    pub fn add(&mut self, player_id: PlayerId, amount: u32) {
+        if amount == 0 {
+            return;
+        }
+        let entry = self.threat.entry(player_id).or_default();
+        *entry = entry.saturating_add(amount);
+    }
+
+    pub fn threat(&self, player_id: PlayerId) -> u32 {
+        self.threat.get(&player_id).copied().unwrap_or(0)
+    }
+
+    pub fn contains(&self, player_id: PlayerId) -> bool {
+        self.threat.contains_key(&player_id)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.threat.is_empty()
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(PlayerId) -> bool) {
+        self.threat.retain(|player_id, _| keep(*player_id));
+    }
+
+    /// The candidate to attack, or `None` when nobody in `candidates` has
+    /// threat yet. The current target is kept until a challenger's threat
+    /// exceeds its own by `switch_percent`, so near-equal attackers do not
+    /// make the monster flip back and forth.
+    pub fn choose(
+        &self,
+        current: Option<PlayerId>,
+        candidates: &[PlayerId],
+        switch_percent: u32,
+    ) -> Option<PlayerId> {
+        let top = candidates
+            .iter()
+            .copied()
+            .filter(|player_id| self.threat(*player_id) > 0)
+            .max_by(|a, b| {
+                self.threat(*a)
+                    .cmp(&self.threat(*b))
+                    .then(b.0.cmp(&a.0))
+            })?;
+        let Some(current) = current.filter(|current| candidates.contains(current)) else {
+            return Some(top);
+        };
+        let challenger = u64::from(self.threat(top)) * 100;
+        let holder = u64::from(self.threat(current)) * u64::from(switch_percent);
+        if top != current && challenger > holder {
+            Some(top)
+        } else {
+            Some(current)
+        }
+    }
+}
+
+pub fn healing_threat(amount: u32) -> u32 {
+    (u64::from(amount) * u64::from(HEALING_THREAT_PERCENT) / 100) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn threat_table_switches_targets_only_past_the_margin() {
+        let knight = PlayerId(1);
+        let druid = PlayerId(2);
+        let candidates = [knight, druid];
+        let mut table = ThreatTable::default();
+        assert_eq!(table.choose(None, &candidates, TARGET_SWITCH_PERCENT), None);
+
+        table.add(knight, 100);
+        assert_eq!(table.choose(None, &candidates, TARGET_SWITCH_PERCENT), Some(knight));
+        table.add(druid, healing_threat(240));
+        assert_eq!(table.threat(druid), 120);
+        assert_eq!(
+            table.choose(Some(knight), &candidates, TARGET_SWITCH_PERCENT),
+            Some(knight)
+        );
+        table.add(druid, 20);
+        assert_eq!(
+            table.choose(Some(knight), &candidates, TARGET_SWITCH_PERCENT),
+            Some(druid)
+        );
+        assert_eq!(
+            table.choose(Some(knight), &[knight], TARGET_SWITCH_PERCENT),
+            Some(knight)
+        );
+
+        table.retain(|player_id| player_id != druid);
+        assert!(!table.contains(druid));
+        assert_eq!(table.choose(Some(druid), &candidates, TARGET_SWITCH_PERCENT), Some(knight));
+    }
+}
@@ -97,6 +97,14 @@ impl ItemStack {
         }
     }
     
+    /// Uses left on a wearout item, if it has been worn before.
+    pub fn remaining_uses(&self) -> Option<u16> {
+        self.attributes.iter().find_map(|attr| match attr {
+            ItemAttribute::RemainingUses(value) => Some(*value),
+            _ => None,
+        })
+    }
+    
     /// Set text attribute using a string pool (for books, signs, etc.)
     pub fn set_text(&mut self, pool: &mut StringPool, text: &str) {
         // Remove existing DynamicString attribute
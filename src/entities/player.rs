@@ -1,5 +1,6 @@
 use crate::entities::creature::{CreatureId, Outfit, DEFAULT_OUTFIT};
 use crate::combat::damage::DamageType;
+use crate::combat::ledger::DamageLedger;
 use crate::entities::effects::{
     DrunkenEffect,
     LightEffect,
@@ -13,6 +14,7 @@ use crate::entities::item::{ItemStack, ItemTypeId};
 use crate::entities::skills::{SkillRow, SkillSet};
 use crate::entities::spells::{Spell, SpellGroupId, SpellId};
 use crate::entities::stats::Stats;
+use crate::world::moderation::TalkThrottle;
 use crate::world::position::{Direction, Position};
 use crate::world::time::{Cooldown, GameClock, GameTick};
 use std::collections::{HashMap, HashSet, VecDeque};
@@ -25,6 +27,7 @@ pub struct PlayerId(pub u32);
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SkullState {
     None,
+    Yellow,
     White,
     Red,
     Black,
@@ -65,6 +68,9 @@ impl PvpStatus {
     }
 }
 
+pub const BLESSING_COUNT: u32 = 5;
+const EXPERIENCE_LEVEL_BASE: i32 = 50;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FightModes {
     pub attack_mode: u8,
@@ -120,6 +126,8 @@ pub struct PlayerState {
     pub last_login: u64,
     pub last_logout: u64,
     pub playerkiller_end: u64,
+    pub blackskull_end: u64,
+    pub muted_until: u64,
     pub murders: Vec<u64>,
     pub inventory: Inventory,
     pub inventory_containers: HashMap<InventorySlot, Vec<ItemStack>>,
@@ -151,6 +159,9 @@ pub struct PlayerState {
     pub attack_target: Option<CreatureId>,
     pub follow_target: Option<CreatureId>,
     pub autowalk_steps: VecDeque<Direction>,
+    pub talk_throttle: TalkThrottle,
+    pub blessings: u8,
+    pub damage_ledger: DamageLedger,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -206,6 +217,8 @@ impl PlayerState {
             last_login: 0,
             last_logout: 0,
             playerkiller_end: 0,
+            blackskull_end: 0,
+            muted_until: 0,
             murders: Vec::new(),
             inventory: Inventory::default(),
             inventory_containers: HashMap::new(),
@@ -237,6 +250,9 @@ impl PlayerState {
             attack_target: None,
             follow_target: None,
             autowalk_steps: VecDeque::new(),
+            talk_throttle: TalkThrottle::default(),
+            blessings: 0,
+            damage_ledger: DamageLedger::default(),
         }
     }
 
@@ -293,9 +309,71 @@ impl PlayerState {
         self.viewport = Viewport::from_center(position, ViewportSize::default());
     }
 
-    pub fn add_experience(&mut self, amount: u32) {
+    /// Adds experience and raises the level while the total covers the next
+    /// one, with the vocation's health, mana and capacity gains. Returns the
+    /// level the player had before.
+    pub fn add_experience(&mut self, amount: u32) -> u16 {
+        let old_level = self.level;
         self.experience = self.experience.saturating_add(u64::from(amount));
+        while let Some(required) = exp_for_level(i32::from(self.level) + 1, EXPERIENCE_LEVEL_BASE) {
+            if self.experience < required.max(0) as u64 {
+                break;
+            }
+            self.level += 1;
+        }
+        self.apply_level_gains(old_level);
         self.sync_level_skill_row();
+        old_level
+    }
+
+    /// Removes experience and drops levels until the remaining experience
+    /// covers the current level again. Returns the experience actually lost.
+    pub fn lose_experience(&mut self, amount: u64) -> u64 {
+        let old_level = self.level;
+        let lost = amount.min(self.experience);
+        self.experience -= lost;
+        while self.level > 1 {
+            let required = exp_for_level(i32::from(self.level), EXPERIENCE_LEVEL_BASE).unwrap_or(0);
+            if self.experience >= required.max(0) as u64 {
+                break;
+            }
+            self.level -= 1;
+        }
+        self.apply_level_gains(old_level);
+        self.sync_level_skill_row();
+        lost
+    }
+
+    /// Shifts max health, mana and capacity by the per-level gains between
+    /// `old_level` and the current level. Current health and mana move by the
+    /// same amount, within their new maximum.
+    fn apply_level_gains(&mut self, old_level: u16) {
+        if old_level == self.level {
+            return;
+        }
+        let before = Stats::for_level(self.profession, old_level);
+        let after = Stats::for_level(self.profession, self.level);
+        let shift = |value: u32, from: u32, to: u32| {
+            let shifted = i64::from(value) + i64::from(to) - i64::from(from);
+            shifted.clamp(0, i64::from(u32::MAX)) as u32
+        };
+        let stats = &mut self.stats;
+        stats.max_health = shift(stats.max_health, before.max_health, after.max_health);
+        stats.max_mana = shift(stats.max_mana, before.max_mana, after.max_mana);
+        stats.capacity = shift(stats.capacity, before.capacity, after.capacity);
+        stats.health = shift(stats.health, before.max_health, after.max_health).min(stats.max_health);
+        stats.mana = shift(stats.mana, before.max_mana, after.max_mana).min(stats.max_mana);
+    }
+
+    pub fn blessing_count(&self) -> u32 {
+        u32::from(self.blessings).count_ones().min(BLESSING_COUNT)
+    }
+
+    /// Percentage of experience and skill points lost on death: 10%, or 7%
+    /// once promoted, minus one point per blessing.
+    pub fn death_loss_percent(&self) -> u32 {
+        let base = if self.profession >= 10 { 7 } else { 10 };
+        base - self.blessing_count().min(base)
     }
 
     pub fn add_condition(&mut self, condition: ConditionInstance) {
@@ -524,6 +602,9 @@ impl PlayerState {
         if duration.is_zero() {
             return;
         }
+        if matches!(self.pvp.skull, SkullState::Red | SkullState::Black) {
+            return;
+        }
         let ticks = clock.ticks_from_duration_round_up(duration);
         let deadline = GameTick(now.0.saturating_add(ticks));
         let next = match self.pvp.skull_expires_at {
@@ -534,6 +615,39 @@ impl PlayerState {
         self.pvp.skull_expires_at = Some(next);
     }
 
+    /// Applies red and black skulls from their persisted end times (unix
+    /// seconds). Returns true when the skull changed.
+    pub fn refresh_persistent_skull(&mut self, now: u64) -> bool {
+        let skull = if self.blackskull_end > now {
+            SkullState::Black
+        } else if self.playerkiller_end > now {
+            SkullState::Red
+        } else if matches!(self.pvp.skull, SkullState::Red | SkullState::Black) {
+            SkullState::None
+        } else {
+            return false;
+        };
+        if self.pvp.skull == skull {
+            return false;
+        }
+        self.pvp.skull = skull;
+        self.pvp.skull_expires_at = None;
+        true
+    }
+
+    /// Expires timed skulls; returns true when the skull changed.
+    pub fn refresh_skull(&mut self, now: GameTick) -> bool {
+        let before = self.pvp.skull;
+        self.pvp.refresh(now);
+        self.pvp.skull != before
+    }
+
+    pub fn record_murder(&mut self, now: u64, memory: Duration) {
+        let since = now.saturating_sub(memory.as_secs());
+        self.murders.retain(|at| *at > since);
+        self.murders.push(now);
+    }
+
     pub fn in_combat(&mut self, clock: &GameClock) -> bool {
         let now = clock.now();
         self.pvp.refresh(now);
@@ -1,5 +1,7 @@
 use crate::combat::damage::DamageType;
 
+const VOCATION_LEVEL: u32 = 8;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Stats {
     pub health: u32,
@@ -32,6 +34,34 @@ impl Stats {
         }
     }
 
+    /// Hit points, mana and capacity gained per level. Every character
+    /// levels as a vocationless player up to level 8 and with its vocation's
+    /// gains from there on.
+    pub fn level_gains(profession: u8) -> (u32, u32, u32) {
+        match profession {
+            1 | 11 => (15, 5, 25),
+            2 | 12 => (10, 15, 20),
+            3 | 4 | 13 | 14 => (5, 30, 10),
+            _ => (5, 5, 10),
+        }
+    }
+
+    /// Full-health stats of a character of `profession` at `level`.
+    pub fn for_level(profession: u8, level: u16) -> Self {
+        let mut stats = Self::base_for_profession(profession);
+        let level = u32::from(level.max(1));
+        let early = level.min(VOCATION_LEVEL) - 1;
+        let late = level.saturating_sub(VOCATION_LEVEL);
+        let (early_health, early_mana, early_capacity) = Self::level_gains(0);
+        let (health, mana, capacity) = Self::level_gains(profession);
+        stats.max_health += early * early_health + late * health;
+        stats.max_mana += early * early_mana + late * mana;
+        stats.capacity += early * early_capacity + late * capacity;
+        stats.health = stats.max_health;
+        stats.mana = stats.max_mana;
+        stats
+    }
+
     pub fn apply_damage(&mut self, damage_type: DamageType, amount: u32) -> u32 {
         let adjusted = self.resistances.apply(damage_type, amount);
         self.apply_raw_damage(adjusted)
@@ -69,6 +99,17 @@ impl DamageResistances {
         self.percents
     }
 
+    pub fn percent(&self, damage_type: DamageType) -> i16 {
+        damage_type
+            .index()
+            .map(|index| self.percents[index])
+            .unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.percents.iter().all(|percent| *percent == 0)
+    }
+
     pub fn apply(&self, damage_type: DamageType, amount: u32) -> u32 {
         let Some(index) = damage_type.index() else {
             return amount;
@@ -105,6 +146,17 @@ mod tests {
         DamageResistances::from_array(values)
     }
 
+    #[test]
+    fn level_stats_follow_vocation_gains_after_level_eight() {
+        let rookie = Stats::for_level(0, 8);
+        assert_eq!((rookie.max_health, rookie.max_mana, rookie.capacity), (185, 35, 470));
+        let knight = Stats::for_level(1, 20);
+        assert_eq!((knight.max_health, knight.max_mana, knight.capacity), (365, 95, 770));
+        let sorcerer = Stats::for_level(13, 20);
+        assert_eq!((sorcerer.max_health, sorcerer.max_mana), (245, 395));
+        assert_eq!(sorcerer.health, sorcerer.max_health);
+    }
+
     #[test]
     fn damage_resistance_reduces_damage() {
         let res = resistances_with_physical(50);
@@ -28,10 +28,33 @@ pub fn run(args: &[String]) -> Result<(), String> {
     loop {
         let config = config::AppConfig::from_args(args)?;
         telemetry::logging::init(&config.root)?;
+        telemetry::audit::init(&config.root)?;
         let summary = assets::scan(&config.root)?;
-        let world = std::sync::Arc::new(std::sync::Mutex::new(world::state::WorldState::load(
-            &config.root,
-        )?));
+        let mut world_state = world::state::WorldState::load(&config.root)?;
+        world_state.combat_rules.world_type = match std::env::var("TIBIA_WORLD_TYPE") {
+            Ok(value) => match combat::rules::WorldType::parse(&value) {
+                Ok(world_type) => world_type,
+                Err(err) => {
+                    eprintln!("tibia: invalid TIBIA_WORLD_TYPE ({}), using pvp", err);
+                    combat::rules::WorldType::Pvp
+                }
+            },
+            Err(_) => combat::rules::WorldType::Pvp,
+        };
+        if let Ok(value) = std::env::var("TIBIA_LOOT_PROTECTION_SECS") {
+            match value.trim().parse::<u64>() {
+                Ok(seconds) => {
+                    world_state.combat_rules.loot_protection =
+                        std::time::Duration::from_secs(seconds);
+                }
+                Err(_) => eprintln!(
+                    "tibia: invalid TIBIA_LOOT_PROTECTION_SECS '{}', using {}s",
+                    value,
+                    world_state.combat_rules.loot_protection.as_secs()
+                ),
+            }
+        }
+        let world = std::sync::Arc::new(std::sync::Mutex::new(world_state));
         let login_registry = std::sync::Arc::new(net::server::LoginRegistry::new());
 
         {
@@ -57,6 +80,7 @@ pub fn run(args: &[String]) -> Result<(), String> {
             println!("- npc files: {}", summary.npc_files);
             println!("- mon files: {}", summary.mon_files);
             println!("- save files: {}", summary.save_files);
+            println!("- world type: {}", world.combat_rules.world_type.as_str());
             if save_report.missing_dir {
                 println!("- save players: missing save/players directory");
             } else {
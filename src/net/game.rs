@@ -867,7 +867,8 @@ fn collect_creatures(
     let mut map: HashMap<Position, Vec<CreatureSnapshot>> = HashMap::new();
     for player in world.players.values() {
         let party_mark = world.party_mark_for_viewer(viewer_id, player.id);
-        let snapshot = snapshot_player(player, party_mark);
+        let skull = world.skull_for_viewer(viewer_id, player.id);
+        let snapshot = snapshot_player(player, party_mark, skull);
         map.entry(player.position).or_default().push(snapshot);
     }
     for npc in world.npcs.values() {
@@ -887,6 +888,7 @@ fn collect_creatures(
 fn snapshot_player(
     player: &crate::entities::player::PlayerState,
     party_mark: u8,
+    skull: SkullState,
 ) -> CreatureSnapshot {
     let mut outfit = player.current_outfit;
     if outfit.look_type == 0 {
@@ -908,7 +910,7 @@ fn snapshot_player(
             .speed_effect
             .map(|effect| effect.speed)
             .unwrap_or(DEFAULT_SPEED),
-        skull: skull_to_u8(player.pvp.skull),
+        skull: skull_to_u8(skull),
         party_mark,
         known: false,
         removed_id: 0,
@@ -1154,12 +1156,13 @@ fn health_percent(health: u32, max_health: u32) -> u8 {
     (health.saturating_mul(100) / max).min(100) as u8
 }
 
-fn skull_to_u8(skull: SkullState) -> u8 {
+pub(crate) fn skull_to_u8(skull: SkullState) -> u8 {
+    // The 7.72 client has no black skull sprite, so black shows as red.
     match skull {
         SkullState::None => 0,
-        SkullState::White => 1,
-        SkullState::Red => 2,
-        SkullState::Black => 3,
+        SkullState::Yellow => 1,
+        SkullState::White => 3,
+        SkullState::Red | SkullState::Black => 4,
     }
 }
 
@@ -5,10 +5,13 @@ use crate::entities::item::{ItemKind, ItemTypeId};
 use crate::entities::creature::{CreatureId, Outfit};
 use crate::entities::player::{FightModes, PlayerId};
 use crate::net::packet::PacketReader;
+use crate::telemetry::audit::{self, AuditEvent, TransferKind};
+use crate::telemetry::detection::InputKind;
 use crate::telemetry::logging;
 use crate::world::position::{Direction, Position};
 use crate::world::state::{
-    ContainerSource, ContainerUpdate, LogoutBlockReason, MoveUseOutcome, UseObjectSource, WorldState,
+    ContainerOpenError, ContainerSource, ContainerUpdate, LogoutBlockReason, MoveUseOutcome,
+    TalkKind, TalkModeration, UseObjectError, UseObjectSource, WorldState,
 };
 use crate::world::time::GameClock;
 
@@ -88,6 +91,8 @@ const MAX_TALK_MESSAGE_LEN: usize = 512;
 const MAX_TALK_RECIPIENT_LEN: usize = 64;
 const MAX_CHANNEL_NAME_LEN: usize = 64;
 const MAX_REQUEST_NAME_LEN: usize = 0x1e;
+const ADMIN_TICKET_LIST_LIMIT: usize = 20;
+const ADMIN_FLAG_LIST_LIMIT: usize = 20;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CTalkMessage {
@@ -341,13 +346,25 @@ pub fn handle_client_packet(
         }
         OPCODE_CTALK => {
             let talk = parse_ctalk_packet(data)?;
-            if let Some(outcome) = handle_admin_talk(world, caster_id, &talk)? {
+            if let Some(outcome) = handle_admin_talk(world, caster_id, &talk, clock)? {
                 return Ok(ClientPacketOutcome::Admin(outcome));
             }
-            let report = try_cast_spell_from_talk(world, caster_id, &talk, clock)?;
-            Ok(report
-                .map(ClientPacketOutcome::SpellCast)
-                .unwrap_or(ClientPacketOutcome::Talk(talk)))
+            let public = talk.channel_id.is_none() && talk.recipient.is_none();
+            let kind = world.talk_kind(caster_id, &talk.message, public);
+            let mut talk = talk;
+            match world.moderate_talk(caster_id, &talk.message, kind, clock)? {
+                TalkModeration::Allowed(message) => talk.message = message,
+                TalkModeration::Blocked(reason) => {
+                    world.queue_player_message(caster_id, 0x14, reason);
+                    return Ok(ClientPacketOutcome::Ignored);
+                }
+            }
+            if kind == TalkKind::Spell {
+                if let Some(report) = try_cast_spell_from_talk(world, caster_id, &talk, clock)? {
+                    return Ok(ClientPacketOutcome::SpellCast(report));
+                }
+            }
+            Ok(ClientPacketOutcome::Talk(talk))
         }
         OPCODE_LOOK => {
             let mut reader = PacketReader::new(data);
@@ -408,6 +425,34 @@ pub fn handle_client_packet(
                 }
             }
             let mut container_updates = Vec::new();
+            let audit_move = match (&request.from, &request.to) {
+                (ItemLocation::Position(_), ItemLocation::Position(_)) => None,
+                (ItemLocation::Position(position), _) => Some((TransferKind::Pickup, *position)),
+                (_, ItemLocation::Position(position)) => Some((TransferKind::Drop, *position)),
+                _ => None,
+            };
+            let audit_item = audit_move.and_then(|_| match &request.from {
+                ItemLocation::Inventory(slot) => world.players.get(&caster_id).and_then(|player| {
+                    let mut item = player.inventory.slot(*slot)?.clone();
+                    if let Some(contents) = player.inventory_containers.get(slot) {
+                        item.contents = contents.clone();
+                    }
+                    Some(item)
+                }),
+                ItemLocation::Position(position) => world.map.tile(*position).and_then(|tile| {
+                    tile.items
+                        .get(usize::from(request.from_stack))
+                        .filter(|item| item.type_id == request.item_type)
+                        .or_else(|| tile.items.iter().find(|item| item.type_id == request.item_type))
+                        .cloned()
+                }),
+                ItemLocation::Container { container_id, slot } => world
+                    .players
+                    .get(&caster_id)
+                    .and_then(|player| player.open_containers.get(container_id))
+                    .and_then(|container| container.items.get(usize::from(*slot)))
+                    .cloned(),
+            });
             let refresh_map = match (request.from, request.to) {
                 (ItemLocation::Inventory(from), ItemLocation::Inventory(to)) => {
                     container_updates =
@@ -499,6 +544,9 @@ pub fn handle_client_packet(
                     false
                 }
             };
+            if let (Some((kind, position)), Some(item)) = (audit_move, audit_item.as_ref()) {
+                world.audit_item_move(caster_id, kind, item, request.count, position);
+            }
             Ok(ClientPacketOutcome::MoveItem {
                 refresh_map,
                 refresh_positions,
@@ -883,21 +931,36 @@ pub fn handle_client_packet(
                                     return Ok(ClientPacketOutcome::CloseContainer(existing));
                                 }
                             }
-                            if let Ok(open) = world.open_container_for_player(
+                            match world.open_container_for_player(
                                 caster_id,
                                 item_type_id,
                                 source,
                                 request.container_id,
                             ) {
-                                return Ok(ClientPacketOutcome::OpenContainer(open));
+                                Ok(open) => return Ok(ClientPacketOutcome::OpenContainer(open)),
+                                Err(ContainerOpenError::NotOwner) => {
+                                    return Ok(ClientPacketOutcome::Ignored);
+                                }
+                                Err(ContainerOpenError::Failed(_)) => {}
                             }
                         }
-                        let outcome = world.use_object_with_clock(
+                        let outcome = match world.use_object_with_clock(
                             caster_id,
                             position,
                             request.item_type,
                             Some(clock),
-                        )?;
+                        ) {
+                            Ok(outcome) => outcome,
+                            Err(UseObjectError::OutOfReach) => {
+                                let _ = world.walk_player_to_use(
+                                    caster_id,
+                                    position,
+                                    request.item_type,
+                                );
+                                return Ok(ClientPacketOutcome::Ignored);
+                            }
+                            Err(UseObjectError::Failed(err)) => return Err(err),
+                        };
                         return Ok(ClientPacketOutcome::MoveUse(outcome));
                     }
                 }
@@ -1455,13 +1518,22 @@ pub fn handle_client_packet(
             if reader.remaining() != 0 {
                 return Err("bug report packet has trailing bytes".to_string());
             }
-            let name = world
-                .players
-                .get(&caster_id)
-                .map(|player| player.name.clone())
-                .unwrap_or_else(|| "Unknown".to_string());
             if !comment.trim().is_empty() {
-                logging::log_error(&format!("bug report from {}: {}", name, comment));
+                let name = world
+                    .players
+                    .get(&caster_id)
+                    .map(|player| player.name.clone())
+                    .unwrap_or_else(|| "Unknown".to_string());
+                match world.submit_bug_report(caster_id, comment.trim()) {
+                    Ok(ticket_id) => logging::log_game(&format!(
+                        "bug report #{} from {}: {}",
+                        ticket_id, name, comment
+                    )),
+                    Err(err) => logging::log_error(&format!(
+                        "bug report from {} not stored ({}): {}",
+                        name, err, comment
+                    )),
+                }
             }
             Ok(ClientPacketOutcome::Ignored)
         }
@@ -1503,11 +1575,18 @@ pub fn handle_client_packet(
             if reader.remaining() != 0 {
                 return Err("violation packet has trailing bytes".to_string());
             }
-            let name = world
+            let (name, is_gm) = world
                 .players
                 .get(&caster_id)
-                .map(|player| player.name.clone())
-                .unwrap_or_else(|| "Unknown".to_string());
+                .map(|player| (player.name.clone(), player.is_gm))
+                .unwrap_or_else(|| ("Unknown".to_string(), false));
+            if is_gm && action != VIOLATION_ACTION_STATEMENT {
+                audit::record(AuditEvent::Ban {
+                    gamemaster: name.clone(),
+                    target: target.clone(),
+                    reason: format!("reason {}, action {}: {}", reason, action, comment.trim()),
+                });
+            }
             let mut details = format!(
                 "violation report from {}: target={}, reason={}, action={}, channel={}, ip_banish={}",
                 name,
@@ -1699,6 +1778,16 @@ fn movement_direction_for_opcode(opcode: u8) -> Option<Direction> {
     }
 }
 
+pub fn input_kind_for_opcode(opcode: u8) -> Option<InputKind> {
+    if movement_direction_for_opcode(opcode).is_some() {
+        return Some(InputKind::Step);
+    }
+    match opcode {
+        OPCODE_ATTACK => Some(InputKind::Attack),
+        _ => None,
+    }
+}
+
 fn turn_direction_for_opcode(opcode: u8) -> Option<Direction> {
     match opcode {
         OPCODE_TURN_NORTH => Some(Direction::North),
@@ -1750,6 +1839,7 @@ fn handle_admin_talk(
     world: &mut WorldState,
     caster_id: PlayerId,
     talk: &CTalkMessage,
+    clock: &GameClock,
 ) -> Result<Option<AdminOutcome>, String> {
     if talk.channel_id.is_some() || talk.recipient.is_some() {
         return Ok(None);
@@ -1767,6 +1857,12 @@ fn handle_admin_talk(
             "You do not have admin rights.".to_string(),
         )));
     }
+    if let Some(player) = world.players.get(&caster_id) {
+        audit::record(AuditEvent::GmCommand {
+            gamemaster: player.name.clone(),
+            command: talk.message.trim().to_string(),
+        });
+    }
 
     let outcome = match command {
         AdminCommand::Online => {
@@ -1780,6 +1876,27 @@ fn handle_admin_talk(
                 Err(err) => AdminOutcome::Log(format!("moveuse audit failed: {}", err)),
             }
         }
+        AdminCommand::Raids => {
+            let raids = world.upcoming_raids();
+            if raids.is_empty() {
+                AdminOutcome::Log("Raids: (none scheduled)".to_string())
+            } else {
+                let now = unix_time_secs();
+                let lines: Vec<String> = raids.iter().map(|raid| raid.summary(now)).collect();
+                AdminOutcome::Log(format!("Raids:\n{}", lines.join("\n")))
+            }
+        }
+        AdminCommand::Monsters => {
+            let activity = world.monster_activity();
+            AdminOutcome::Log(format!(
+                "Monsters: {} active, {} dormant in {} awake sectors",
+                activity.active, activity.dormant, activity.awake_sectors
+            ))
+        }
+        AdminCommand::Raid { name } => match world.start_raid(&name, clock.now()) {
+            Ok(_) => AdminOutcome::Log(format!("raid {} started", name)),
+            Err(err) => AdminOutcome::Log(format!("raid failed: {}", err)),
+        },
         AdminCommand::Kick { target } => {
             if let Some(target_name) = target {
                 let Some(player) = world.players.get(&caster_id) else {
@@ -1794,6 +1911,10 @@ fn handle_admin_talk(
             }
             AdminOutcome::DisconnectSelf
         }
+        AdminCommand::Reload { target } => match world.reload_scripts(target) {
+            Ok(report) => AdminOutcome::Log(report.summary()),
+            Err(err) => AdminOutcome::Log(format!("reload failed: {}", err)),
+        },
         AdminCommand::Shutdown => AdminOutcome::Shutdown,
         AdminCommand::Restart => AdminOutcome::Restart,
         AdminCommand::Teleport { position } => {
@@ -1805,6 +1926,84 @@ fn handle_admin_talk(
                 Err(err) => AdminOutcome::Log(format!("teleport failed: {}", err)),
             }
         }
+        AdminCommand::Tickets { filter } => {
+            let tickets = match filter.as_deref() {
+                None => world.tickets.active(),
+                Some(value) if value.eq_ignore_ascii_case("open") => world.tickets.active(),
+                Some(value) if value.eq_ignore_ascii_case("all") => {
+                    world.tickets.tickets().iter().collect()
+                }
+                Some(name) => world.tickets.for_player_name(name),
+            };
+            if tickets.is_empty() {
+                AdminOutcome::Log("Tickets: (none)".to_string())
+            } else {
+                let lines: Vec<String> = tickets
+                    .iter()
+                    .rev()
+                    .take(ADMIN_TICKET_LIST_LIMIT)
+                    .map(|ticket| ticket.summary())
+                    .collect();
+                AdminOutcome::Log(format!("Tickets:\n{}", lines.join("\n")))
+            }
+        }
+        AdminCommand::Ticket { id } => match world.tickets.get(id) {
+            Some(ticket) => {
+                let mut lines = vec![ticket.summary()];
+                if let Some(position) = ticket.position {
+                    lines.push(format!(
+                        "position ({},{},{})",
+                        position.x, position.y, position.z
+                    ));
+                }
+                for message in &ticket.transcript {
+                    lines.push(format!("[{}] {}: {}", message.at, message.author, message.text));
+                }
+                AdminOutcome::Log(lines.join("\n"))
+            }
+            None => AdminOutcome::Log(format!("ticket #{} not found", id)),
+        },
+        AdminCommand::Flags { filter } => {
+            let flags: Vec<String> = world
+                .detection
+                .flags()
+                .iter()
+                .filter(|flag| match filter.as_deref() {
+                    Some(name) => {
+                        flag.player_name.eq_ignore_ascii_case(name)
+                            || flag.account.eq_ignore_ascii_case(name)
+                    }
+                    None => true,
+                })
+                .rev()
+                .take(ADMIN_FLAG_LIST_LIMIT)
+                .map(|flag| flag.describe())
+                .collect();
+            let report = match world.write_detection_report() {
+                Ok(path) => format!("report written to {}", path.display()),
+                Err(err) => format!("report failed: {}", err),
+            };
+            if flags.is_empty() {
+                AdminOutcome::Log(format!("Flags: (none)\n{}", report))
+            } else {
+                AdminOutcome::Log(format!("Flags:\n{}\n{}", flags.join("\n"), report))
+            }
+        }
+        AdminCommand::Mute {
+            target,
+            minutes,
+            reason,
+        } => {
+            let duration = std::time::Duration::from_secs(u64::from(minutes) * 60);
+            match world.mute_player(caster_id, &target, duration, reason.as_deref()) {
+                Ok(name) => AdminOutcome::Log(format!("{} muted for {} minutes", name, minutes)),
+                Err(err) => AdminOutcome::Log(format!("mute failed: {}", err)),
+            }
+        }
+        AdminCommand::Unmute { target } => match world.unmute_player(caster_id, &target) {
+            Ok(name) => AdminOutcome::Log(format!("{} unmuted", name)),
+            Err(err) => AdminOutcome::Log(format!("unmute failed: {}", err)),
+        },
         AdminCommand::Where => {
             let player = world
                 .players
@@ -2065,6 +2264,13 @@ fn use_source_from_location(location: &ItemLocation) -> UseObjectSource {
     }
 }
 
+fn unix_time_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
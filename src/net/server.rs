@@ -10,6 +10,7 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 use crate::combat::conditions::{ConditionKind, ConditionTick};
+use crate::combat::damage::DamageType;
 use crate::entities::creature::{CreatureId, DEFAULT_OUTFIT};
 use crate::entities::inventory::{InventorySlot, INVENTORY_SLOTS};
 use crate::entities::item::{ItemAttribute, ItemStack, ItemTypeId};
@@ -25,7 +26,7 @@ use crate::entities::skills::{
 };
 use crate::net::game;
 use crate::net::game_client::{
-    handle_client_packet, AdminOutcome, ClientPacketOutcome, CTalkMessage, LogoutRequestOutcome,
+    handle_client_packet, input_kind_for_opcode, AdminOutcome, ClientPacketOutcome, CTalkMessage, LogoutRequestOutcome,
     LookRequest, LookTarget, PartyRequest, ShopRequest, TradeRequest,
 };
 use crate::net::game_login::{parse_game_login, GameLogin};
@@ -40,8 +41,8 @@ use crate::telemetry::logging;
 use crate::world::position::Position;
 use crate::world::state::{
     BuddyAddResult, ChannelExcludeResult, ChannelInviteResult, ContainerUpdate, CreatureStep,
-    CreatureTurnUpdate, LogoutBlockReason, MonsterTickOutcome, MoveUseActor, MoveUseOutcome,
-    PlayerCombatOutcome, TradeUpdate, WorldState,
+    CreatureTurnUpdate, LogoutBlockReason, MonsterActivity, MonsterTickOutcome, MoveUseActor,
+    MoveUseOutcome, PlayerCombatOutcome, TradeUpdate, WorldState,
 };
 use crate::world::time::GameClock;
 use crate::world::item_types::ItemTypeIndex;
@@ -801,7 +802,9 @@ struct StatusSnapshot {
     software_name: String,
     software_version: String,
     client_version: String,
+    world_type: String,
     players: Vec<(String, u16)>,
+    monsters: MonsterActivity,
 }
 
 fn build_status_snapshot(
@@ -812,7 +815,7 @@ fn build_status_snapshot(
 ) -> Result<StatusSnapshot, String> {
     let (host, port) = split_host_port(&config.login_addr, 7171);
     let ip = resolve_world_ipv4(host, peer);
-    let (players, map_name, map_width, map_height) = match world.lock() {
+    let (players, map_name, map_width, map_height, world_type, monsters) = match world.lock() {
         Ok(world) => {
             let mut players: Vec<(String, u16)> = world
                 .players
@@ -821,9 +824,18 @@ fn build_status_snapshot(
                 .collect();
             players.sort_by(|a, b| a.0.cmp(&b.0));
             let (width, height) = map_dimensions(&world);
-            (players, world.map.name.clone(), width, height)
-        }
-        Err(_) => (Vec::new(), "map".to_string(), 0, 0),
+            let world_type = world.combat_rules.world_type.as_str().to_string();
+            let monsters = world.monster_activity();
+            (players, world.map.name.clone(), width, height, world_type, monsters)
+        }
+        Err(_) => (
+            Vec::new(),
+            "map".to_string(),
+            0,
+            0,
+            String::new(),
+            MonsterActivity::default(),
+        ),
     };
     let players_online = players.len() as u32;
     let players_peak = state.record_peak(players_online as usize);
@@ -852,7 +864,9 @@ fn build_status_snapshot(
         software_name: config.software_name.clone(),
         software_version: config.software_version.clone(),
         client_version: config.client_version.clone(),
+        world_type,
         players,
+        monsters,
     })
 }
 
@@ -954,7 +968,7 @@ fn build_status_xml(snapshot: &StatusSnapshot) -> String {
     let _ = write!(xml, "<tsqp version=\"1.0\">");
     let _ = write!(
         xml,
-        "<serverinfo uptime=\"{}\" ip=\"{}\" servername=\"{}\" port=\"{}\" location=\"{}\" url=\"{}\" server=\"{}\" version=\"{}\" client=\"{}\"/>",
+        "<serverinfo uptime=\"{}\" ip=\"{}\" servername=\"{}\" port=\"{}\" location=\"{}\" url=\"{}\" server=\"{}\" version=\"{}\" client=\"{}\" worldtype=\"{}\"/>",
         snapshot.uptime_secs,
         escape_xml(&snapshot.ip),
         escape_xml(&snapshot.server_name),
@@ -963,7 +977,8 @@ fn build_status_xml(snapshot: &StatusSnapshot) -> String {
         escape_xml(&snapshot.url),
         escape_xml(&snapshot.software_name),
         escape_xml(&snapshot.software_version),
-        escape_xml(&snapshot.client_version)
+        escape_xml(&snapshot.client_version),
+        escape_xml(&snapshot.world_type)
     );
     let _ = write!(
         xml,
@@ -976,6 +991,13 @@ fn build_status_xml(snapshot: &StatusSnapshot) -> String {
         "<players online=\"{}\" max=\"{}\" peak=\"{}\"/>",
         snapshot.players_online, snapshot.players_max, snapshot.players_peak
     );
+    let _ = write!(
+        xml,
+        "<monsters total=\"{}\" active=\"{}\" dormant=\"{}\"/>",
+        snapshot.monsters.active + snapshot.monsters.dormant,
+        snapshot.monsters.active,
+        snapshot.monsters.dormant
+    );
     let _ = write!(
         xml,
         "<map name=\"{}\" author=\"{}\" width=\"{}\" height=\"{}\"/>",
@@ -1031,7 +1053,7 @@ fn spawn_autosave_loop(
             let now = Instant::now();
             if state.due(now) {
                 let report = match world.lock() {
-                    Ok(world) => autosave_world(&world, &store, &root),
+                    Ok(mut world) => autosave_world(&mut world, &store, &root),
                     Err(_) => {
                         logging::log_error("autosave failed (world lock poisoned)");
                         eprintln!("tibia: autosave failed (world lock poisoned)");
@@ -1048,6 +1070,10 @@ fn spawn_autosave_loop(
                     logging::log_houses(&format!("autosave house owners error: {}", err));
                     eprintln!("tibia: autosave house owners error: {}", err);
                 }
+                if let Some(err) = report.ticket_error {
+                    logging::log_error(&format!("autosave tickets error: {}", err));
+                    eprintln!("tibia: autosave tickets error: {}", err);
+                }
                 logging::log_game(&format!(
                     "autosave completed (players: {})",
                     report.saved_players
@@ -1087,7 +1113,7 @@ fn spawn_world_tick_loop(
                     status_updates
                         .light_updates
                         .extend(skill_outcome.status_updates.light_updates.clone());
-                    let _ = world_guard.tick_raids(clock.now(), &clock);
+                    let _ = world_guard.tick_raids(clock.now());
                     let _ = world_guard.tick_monster_homes(&clock);
                     world_guard.tick_map_refresh(&clock);
                     world_guard.tick_houses();
@@ -1101,6 +1127,8 @@ fn spawn_world_tick_loop(
                         &mut creature_stacks,
                     );
                     world_guard.tick_cron_system(&clock);
+                    world_guard.tick_player_skulls(&clock);
+                    world_guard.tick_player_deaths();
                     state.store_global_tick_replay(
                         tick,
                         &condition_ticks,
@@ -1639,7 +1667,15 @@ fn handle_login_session<T: PacketTransport>(
                     peer, payload.account
                 );
             }
-            if let Some(bans) = state.bans.as_ref() {
+            // Re-read the ban list so banishments issued by the game world apply
+            // without restarting the login server.
+            let bans = config
+                .root
+                .as_ref()
+                .and_then(|root| BanList::load(root).ok().flatten())
+                .map(Arc::new)
+                .or_else(|| state.bans.clone());
+            if let Some(bans) = bans.as_ref() {
                 if bans.is_banned(&payload.account, std::time::SystemTime::now()) {
                     let response = LoginErrorKind::AccountBanned.to_response();
                     send_response(transport, &response, trace.as_mut())?;
@@ -1891,8 +1927,11 @@ fn handle_game_session<T: PacketTransport>(
             config.root.as_ref(),
         )?;
         world.queue_buddy_status_update(player_id, true);
+        let account = login_info.as_ref().map(|login| login.account.as_str()).unwrap_or("");
+        world.record_session_start(player_id, account, peer_ip);
     }
     let _guard = GamePlayerGuard::new(Arc::clone(world), player_id);
+    let session_started = Instant::now();
 
     let tick_length = state.clock_tick_length();
     transport
@@ -1998,6 +2037,9 @@ fn handle_game_session<T: PacketTransport>(
             return Err("read packet failed: idle timeout".to_string());
         }
 
+        // Input timing is measured on receipt, before waiting for the world lock,
+        // so lock contention does not distort the intervals.
+        let mut received_at_ms = 0;
         let payload = match queued_payload.take() {
             Some(payload) => {
                 last_activity = Instant::now();
@@ -2008,6 +2050,7 @@ fn handle_game_session<T: PacketTransport>(
                 Ok(ReadPacketOutcome::Packet(payload)) => {
                     last_activity = Instant::now();
                     idle_warning_sent = false;
+                    received_at_ms = session_started.elapsed().as_millis() as u64;
                     Some(payload)
                 }
                 Ok(ReadPacketOutcome::Timeout) => None,
@@ -2037,6 +2080,13 @@ fn handle_game_session<T: PacketTransport>(
             let mut world_guard = world
                 .lock()
                 .map_err(|_| "world lock poisoned".to_string())?;
+            if let Some(kind) = payload
+                .as_ref()
+                .and_then(|payload| payload.first().copied())
+                .and_then(input_kind_for_opcode)
+            {
+                world_guard.record_player_input(player_id, kind, received_at_ms);
+            }
             let old_position = world_guard.players.get(&player_id).map(|player| player.position);
             let mut condition_ticks: Vec<(PlayerId, Vec<ConditionTick>)> = Vec::new();
             let mut status_updates = crate::world::state::CreatureStatusUpdates::default();
@@ -2360,6 +2410,11 @@ fn handle_game_session<T: PacketTransport>(
                             }
                         },
                         Ok(ClientPacketOutcome::Talk(talk)) => {
+                            world_guard.record_ticket_talk(
+                                player_id,
+                                talk.recipient.as_deref(),
+                                &talk.message,
+                            );
                             let handled = handle_house_list_command(
                                 &talk,
                                 player_id,
@@ -2617,7 +2672,9 @@ fn handle_game_session<T: PacketTransport>(
                                 let mut writer = PacketWriter::new();
                                 game::write_message(&mut writer, 0x14, REQUEST_WAIT_MESSAGE);
                                 packets.push(writer.into_vec());
-                            } else if let Some(entry) = world_guard.take_request_by_name(&name) {
+                            } else if let Some(entry) =
+                                world_guard.process_request(player_id, &name)
+                            {
                                 if !request_queue_open {
                                     let mut writer = PacketWriter::new();
                                     game::write_open_request_queue(&mut writer);
@@ -2639,7 +2696,7 @@ fn handle_game_session<T: PacketTransport>(
                                 let mut writer = PacketWriter::new();
                                 game::write_message(&mut writer, 0x14, REQUEST_WAIT_MESSAGE);
                                 packets.push(writer.into_vec());
-                            } else if let Some(entry) = world_guard.take_request_by_name(&name) {
+                            } else if let Some(entry) = world_guard.remove_request(&name) {
                                 if !request_queue_open {
                                     let mut writer = PacketWriter::new();
                                     game::write_open_request_queue(&mut writer);
@@ -2662,6 +2719,7 @@ fn handle_game_session<T: PacketTransport>(
                                 game::write_message(&mut writer, 0x14, REQUEST_WAIT_MESSAGE);
                                 packets.push(writer.into_vec());
                             } else if request_queue_open {
+                                world_guard.finish_requests_for_gamemaster(player_id);
                                 let mut writer = PacketWriter::new();
                                 game::write_close_request(&mut writer);
                                 packets.push(writer.into_vec());
@@ -2976,7 +3034,11 @@ fn handle_game_session<T: PacketTransport>(
                 }
             }
             let player_combat = world_guard.tick_player_attack(player_id, &clock);
-            packets.extend(build_player_combat_packets(&player_combat, &world_guard));
+            packets.extend(build_player_combat_packets(
+                &player_combat,
+                &world_guard,
+                player_id,
+            ));
             if player_combat.refresh_map {
                 if let Some(player) = world_guard.players.get(&player_id) {
                     let mut writer = PacketWriter::new();
@@ -2989,6 +3051,24 @@ fn handle_game_session<T: PacketTransport>(
                     packets.push(writer.into_vec());
                 }
             }
+            let missiles = world_guard.take_pending_missile_effects(player_id);
+            if let Some(player) = world_guard.players.get(&player_id) {
+                for missile in missiles {
+                    if !game::position_in_viewport(player.position, missile.from)
+                        && !game::position_in_viewport(player.position, missile.to)
+                    {
+                        continue;
+                    }
+                    let mut writer = PacketWriter::new();
+                    game::write_missile_effect(
+                        &mut writer,
+                        missile.from,
+                        missile.to,
+                        missile.missile_id,
+                    );
+                    packets.push(writer.into_vec());
+                }
+            }
             let decay_positions = world_guard.take_pending_map_refreshes(player_id);
             if !decay_positions.is_empty() {
                 if let Some(player) = world_guard.players.get(&player_id) {
@@ -3181,6 +3261,18 @@ fn handle_game_session<T: PacketTransport>(
                 game::write_message(&mut writer, message.message_type, &message.message);
                 packets.push(writer.into_vec());
             }
+            for update in world_guard.take_pending_skull_updates(player_id) {
+                let mut writer = PacketWriter::new();
+                game::write_creature_skull(
+                    &mut writer,
+                    update.target_id.0,
+                    game::skull_to_u8(update.skull),
+                );
+                packets.push(writer.into_vec());
+            }
+            if world_guard.take_pending_forced_logout(player_id) {
+                disconnect_after_send = true;
+            }
             for update in world_guard.take_pending_buddy_updates(player_id) {
                 let mut writer = PacketWriter::new();
                 game::write_buddy_status(&mut writer, update.buddy_id.0, update.online);
@@ -3416,6 +3508,38 @@ fn weight_line(
     Some(format!("It weighs {}.{:02} oz.", oz, hundredths))
 }
 
+/// Look lines for worn protection: the absorbed damage types and, for items
+/// with charges, how many are left.
+fn protection_lines(
+    object_types: Option<&ObjectTypeIndex>,
+    type_id: ItemTypeId,
+    attributes: &[ItemAttribute],
+) -> Vec<String> {
+    let Some(object_type) = object_types.and_then(|types| types.get(type_id)) else {
+        return Vec::new();
+    };
+    let absorbs = object_type.protection_absorbs();
+    if absorbs.is_empty() {
+        return Vec::new();
+    }
+    let parts = DamageType::ALL
+        .into_iter()
+        .filter(|damage_type| absorbs.percent(*damage_type) != 0)
+        .map(|damage_type| format!("{} {:+}%", damage_type.name(), absorbs.percent(damage_type)))
+        .collect::<Vec<_>>();
+    let mut lines = vec![format!("It grants protection {}.", parts.join(", "))];
+    let remaining_uses = attributes.iter().find_map(|attribute| match attribute {
+        ItemAttribute::RemainingUses(value) => Some(*value),
+        _ => None,
+    });
+    match object_type.remaining_charges(remaining_uses) {
+        Some(1) => lines.push("It has 1 charge left.".to_string()),
+        Some(charges) => lines.push(format!("It has {} charges left.", charges)),
+        None => {}
+    }
+    lines
+}
+
 fn describe_item(
     item: &ItemStack,
     item_types: Option<&ItemTypeIndex>,
@@ -3430,6 +3554,7 @@ fn describe_item(
     if lines.is_empty() {
         lines.push(format!("You see an object ({}).", item.type_id.0));
     }
+    lines.extend(protection_lines(object_types, item.type_id, &item.attributes));
     if let Some(weight) = weight_line(object_types, item.type_id, item.count) {
         lines.push(weight);
     }
@@ -3457,6 +3582,7 @@ fn map_item_description(
     });
 
     if let Some(object_type) = object_types.and_then(|types| types.get(item.type_id)) {
+        lines.extend(protection_lines(object_types, item.type_id, &item.attributes));
         if let Some(weight) = weight_line(object_types, item.type_id, item.count) {
             lines.push(weight);
         }
@@ -3489,6 +3615,10 @@ fn map_item_description(
                     lines.push("Nothing is written on it.".to_string());
                 }
             }
+        } else if object_type.has_flag("Corpse") {
+            if let Some(text) = text {
+                lines.push(text.trim().to_string());
+            }
         }
     } else if let Some(text) = text {
         lines.push(text);
@@ -3524,10 +3654,24 @@ fn describe_creature(
             .and_then(|script| script.article())
             .map(|value| value.trim().to_string())
             .filter(|value| !value.is_empty());
-        if let Some(article) = article {
-            return Some(format!("You see {} {}.", article, monster.name));
+        let mut text = match article {
+            Some(article) => format!("You see {} {}.", article, monster.name),
+            None => format!("You see {}.", monster.name),
+        };
+        if let Some(master_id) = monster.summoner {
+            let master = if master_id == player_id {
+                "you".to_string()
+            } else {
+                world
+                    .players
+                    .get(&master_id)
+                    .map(|master| master.name.clone())
+                    .unwrap_or_else(|| "someone".to_string())
+            };
+            let how = if monster.summoned { "summoned" } else { "convinced" };
+            text.push_str(&format!(" It was {how} by {master}."));
         }
-        return Some(format!("You see {}.", monster.name));
+        return Some(text);
     }
     None
 }
@@ -3618,7 +3762,7 @@ fn handle_help_channel_request(
     if message.is_empty() {
         return Ok(true);
     }
-    let added = world.submit_request(caster_id)?;
+    let added = world.submit_request(caster_id, message)?;
     let response = if added {
         REQUEST_WAIT_MESSAGE
     } else {
@@ -4429,6 +4573,7 @@ fn build_monster_combat_packets(
 fn build_player_combat_packets(
     outcome: &PlayerCombatOutcome,
     world: &WorldState,
+    player_id: PlayerId,
 ) -> Vec<Vec<u8>> {
     let mut packets = Vec::new();
     for effect in &outcome.effects {
@@ -4436,6 +4581,18 @@ fn build_player_combat_packets(
         game::write_graphical_effect(&mut writer, effect.position, effect.effect_id);
         packets.push(writer.into_vec());
     }
+    if let Some(player) = world.players.get(&player_id) {
+        for slot in &outcome.inventory_updates {
+            let mut writer = PacketWriter::new();
+            match player.inventory.slot(*slot) {
+                Some(item) => {
+                    game::write_inventory_set(&mut writer, *slot, item, world.item_types.as_ref())
+                }
+                None => game::write_inventory_reset(&mut writer, *slot),
+            }
+            packets.push(writer.into_vec());
+        }
+    }
     for monster_id in &outcome.monster_updates {
         let Some(monster) = world.monsters.get(monster_id) else {
             continue;
@@ -5000,7 +5157,7 @@ mod tests {
                 flee_threshold: 0,
                 lose_target_distance: 0,
                 target: None,
-                damage_by: HashMap::new(),
+                damage_ledger: crate::combat::ledger::DamageLedger::default(),
                 speed: 220,
                 outfit_effect: None,
                 speed_effect: None,
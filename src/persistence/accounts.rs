@@ -47,6 +47,13 @@ impl AccountRegistry {
             None
         }
     }
+
+    /// The account owning `player_id`, if any.
+    pub fn account_for_player(&self, player_id: PlayerId) -> Option<&AccountRecord> {
+        self.accounts
+            .values()
+            .find(|record| record.player_ids.contains(&player_id))
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -79,6 +86,52 @@ impl BanList {
         Ok(Some(BanList { accounts }))
     }
 
+    /// Adds or replaces a ban for `account` and rewrites save/banlist.txt.
+    pub fn banish(
+        root: &Path,
+        account: &str,
+        expires_at: Option<SystemTime>,
+        reason: &str,
+    ) -> Result<(), String> {
+        let mut list = Self::load(root)?.unwrap_or_default();
+        list.accounts.insert(
+            normalize_account_name(account),
+            BanRecord {
+                account: account.to_string(),
+                expires_at,
+                reason: Some(reason.to_string()),
+            },
+        );
+        let path = root.join("save").join("banlist.txt");
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                format!("banlist dir create failed for {}: {}", parent.display(), err)
+            })?;
+        }
+        fs::write(&path, list.to_text())
+            .map_err(|err| format!("banlist write failed for {}: {}", path.display(), err))
+    }
+
+    fn to_text(&self) -> String {
+        let mut records: Vec<&BanRecord> = self.accounts.values().collect();
+        records.sort_by(|a, b| a.account.cmp(&b.account));
+        let mut out = String::new();
+        for record in records {
+            let expires_at = record
+                .expires_at
+                .and_then(|at| at.duration_since(UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs())
+                .unwrap_or(0);
+            out.push_str(&format!("account = {}\n", escape_string(&record.account)));
+            out.push_str(&format!("expires_at = {}\n", expires_at));
+            if let Some(reason) = record.reason.as_ref() {
+                out.push_str(&format!("reason = {}\n", escape_string(reason)));
+            }
+            out.push('\n');
+        }
+        out
+    }
+
     pub fn is_banned(&self, account: &str, now: SystemTime) -> bool {
         let key = normalize_account_name(account);
         let Some(record) = self.accounts.get(&key) else {
@@ -413,6 +466,22 @@ fn parse_string(value: &str, label: &str, line_no: usize) -> Result<String, Stri
     }
 }
 
+fn escape_string(input: &str) -> String {
+    let mut out = String::from("\"");
+    for ch in input.chars() {
+        match ch {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            other => out.push(other),
+        }
+    }
+    out.push('"');
+    out
+}
+
 fn unescape_string(input: &str) -> Result<String, String> {
     let mut out = String::new();
     let mut chars = input.chars().peekable();
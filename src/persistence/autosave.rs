@@ -47,10 +47,11 @@ pub struct AutosaveReport {
     pub saved_players: usize,
     pub player_errors: Vec<String>,
     pub house_owner_error: Option<String>,
+    pub ticket_error: Option<String>,
 }
 
 pub fn autosave_world(
-    world: &WorldState,
+    world: &mut WorldState,
     store: &SaveStore,
     root: &Path,
 ) -> AutosaveReport {
@@ -65,5 +66,8 @@ pub fn autosave_world(
     if let Err(err) = world.save_house_owners(root) {
         report.house_owner_error = Some(err);
     }
+    if let Err(err) = world.tickets.flush() {
+        report.ticket_error = Some(err);
+    }
     report
 }
@@ -0,0 +1,98 @@
+use crate::world::position::Position;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeathRecord {
+    pub at: u64,
+    pub player_id: u32,
+    pub player_name: String,
+    pub level: u16,
+    pub new_level: u16,
+    pub experience_lost: u64,
+    pub killers: Vec<String>,
+    pub position: Position,
+}
+
+impl DeathRecord {
+    pub fn summary(&self) -> String {
+        let killers = if self.killers.is_empty() {
+            "unknown causes".to_string()
+        } else {
+            self.killers.join(", ")
+        };
+        format!(
+            "[{}] {} died at level {} (now {}), killed by {}",
+            self.at, self.player_name, self.level, self.new_level, killers
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DeathFile {
+    deaths: Vec<DeathRecord>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DeathStore {
+    path: Option<PathBuf>,
+    deaths: Vec<DeathRecord>,
+}
+
+impl DeathStore {
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let path = root.join("save").join("deaths.yaml");
+        let file = match fs::read_to_string(&path) {
+            Ok(data) => serde_yaml::from_str::<DeathFile>(&data).map_err(|err| {
+                format!("death store parse failed for {}: {}", path.display(), err)
+            })?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => DeathFile::default(),
+            Err(err) => {
+                return Err(format!(
+                    "death store read failed for {}: {}",
+                    path.display(),
+                    err
+                ))
+            }
+        };
+        Ok(Self {
+            path: Some(path),
+            deaths: file.deaths,
+        })
+    }
+
+    pub fn deaths(&self) -> &[DeathRecord] {
+        &self.deaths
+    }
+
+    pub fn for_player_name(&self, name: &str) -> Vec<&DeathRecord> {
+        self.deaths
+            .iter()
+            .filter(|death| death.player_name.eq_ignore_ascii_case(name))
+            .collect()
+    }
+
+    pub fn record(&mut self, death: DeathRecord) -> Result<(), String> {
+        self.deaths.push(death);
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                format!("death store dir create failed for {}: {}", parent.display(), err)
+            })?;
+        }
+        let file = DeathFile {
+            deaths: self.deaths.clone(),
+        };
+        let data = serde_yaml::to_string(&file)
+            .map_err(|err| format!("death store serialize failed: {}", err))?;
+        fs::write(path, data)
+            .map_err(|err| format!("death store write failed for {}: {}", path.display(), err))
+    }
+}
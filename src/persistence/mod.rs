@@ -1,3 +1,6 @@
 pub mod autosave;
+pub mod deaths;
+pub mod raids;
 pub mod accounts;
 pub mod store;
+pub mod tickets;
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RaidHistoryFile {
+    last_runs: BTreeMap<String, u64>,
+}
+
+/// When each raid last started (unix seconds), so the raid calendar keeps
+/// its rhythm across restarts.
+#[derive(Debug, Clone, Default)]
+pub struct RaidHistory {
+    path: Option<PathBuf>,
+    last_runs: BTreeMap<String, u64>,
+}
+
+impl RaidHistory {
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let path = root.join("save").join("raids.yaml");
+        let file = match fs::read_to_string(&path) {
+            Ok(data) => serde_yaml::from_str::<RaidHistoryFile>(&data).map_err(|err| {
+                format!("raid history parse failed for {}: {}", path.display(), err)
+            })?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => RaidHistoryFile::default(),
+            Err(err) => {
+                return Err(format!(
+                    "raid history read failed for {}: {}",
+                    path.display(),
+                    err
+                ))
+            }
+        };
+        Ok(Self {
+            path: Some(path),
+            last_runs: file.last_runs,
+        })
+    }
+
+    pub fn last_run(&self, raid: &str) -> Option<u64> {
+        self.last_runs.get(raid).copied()
+    }
+
+    pub fn record(&mut self, raid: &str, at: u64) -> Result<(), String> {
+        self.last_runs.insert(raid.to_string(), at);
+        self.save()
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                format!("raid history dir create failed for {}: {}", parent.display(), err)
+            })?;
+        }
+        let file = RaidHistoryFile {
+            last_runs: self.last_runs.clone(),
+        };
+        let data = serde_yaml::to_string(&file)
+            .map_err(|err| format!("raid history serialize failed: {}", err))?;
+        fs::write(path, data)
+            .map_err(|err| format!("raid history write failed for {}: {}", path.display(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn raid_history_persists_across_reload() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("tibia-raid-history-test-{}", suffix));
+        let mut history = RaidHistory::load(&root).expect("load");
+        assert_eq!(history.last_run("orcs"), None);
+        history.record("orcs", 1_000).expect("record");
+        history.record("orcs", 2_000).expect("record");
+
+        let reloaded = RaidHistory::load(&root).expect("reload");
+        assert_eq!(reloaded.last_run("orcs"), Some(2_000));
+        let _ = fs::remove_dir_all(&root);
+    }
+}
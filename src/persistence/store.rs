@@ -299,6 +299,9 @@ struct PlayerSave {
     last_login: Option<u64>,
     last_logout: Option<u64>,
     playerkiller_end: Option<u64>,
+    blackskull_end: Option<u64>,
+    muted_until: Option<u64>,
+    blessings: Option<u8>,
     stats: Option<Stats>,
     skills: Option<SkillSet>,
     raw_skills: Vec<SkillRow>,
@@ -324,6 +327,9 @@ const KEY_LAST_LOGOUT: &str = "LastLogout      = ";
 const KEY_START_POSITION: &str = "StartPosition   = ";
 const KEY_CURRENT_POSITION: &str = "CurrentPosition = ";
 const KEY_PLAYERKILLER_END: &str = "PlayerkillerEnd = ";
+const KEY_BLACKSKULL_END: &str = "BlackskullEnd   = ";
+const KEY_MUTED_UNTIL: &str = "MutedUntil      = ";
+const KEY_BLESSINGS: &str = "Blessings       = ";
 const KEY_SKILL: &str = "Skill = (";
 const KEY_SPELLS: &str = "Spells      = {";
 const KEY_QUEST_VALUES: &str = "QuestValues = {";
@@ -376,6 +382,9 @@ impl PlayerSave {
             last_login: Some(player.last_login),
             last_logout: Some(player.last_logout),
             playerkiller_end: Some(player.playerkiller_end),
+            blackskull_end: Some(player.blackskull_end),
+            muted_until: Some(player.muted_until),
+            blessings: Some(player.blessings),
             stats: Some(player.stats),
             skills: Some(player.skills.clone()),
             raw_skills,
@@ -464,6 +473,21 @@ impl PlayerSave {
             current_position.z
         ));
         lines.push(format!("{}{}", KEY_PLAYERKILLER_END, playerkiller_end));
+        if let Some(blackskull_end) = self.blackskull_end {
+            if blackskull_end > 0 {
+                lines.push(format!("{}{}", KEY_BLACKSKULL_END, blackskull_end));
+            }
+        }
+        if let Some(muted_until) = self.muted_until {
+            if muted_until > 0 {
+                lines.push(format!("{}{}", KEY_MUTED_UNTIL, muted_until));
+            }
+        }
+        if let Some(blessings) = self.blessings {
+            if blessings > 0 {
+                lines.push(format!("{}{}", KEY_BLESSINGS, blessings));
+            }
+        }
         lines.push(String::new());
 
         let skill_rows = self.skill_rows_for_save();
@@ -705,6 +729,15 @@ impl PlayerSave {
                     "playerkillerend" => {
                         save.playerkiller_end = Some(parse_u64(value, "PlayerkillerEnd")?);
                     }
+                    "blackskullend" => {
+                        save.blackskull_end = Some(parse_u64(value, "BlackskullEnd")?);
+                    }
+                    "muteduntil" => {
+                        save.muted_until = Some(parse_u64(value, "MutedUntil")?);
+                    }
+                    "blessings" => {
+                        save.blessings = Some(parse_u8(value, "Blessings")?);
+                    }
                     "skill" => {
                         save.raw_skills.push(parse_skill_row(value, line_number + 1)?);
                     }
@@ -964,6 +997,15 @@ impl PlayerSave {
         if let Some(playerkiller_end) = self.playerkiller_end {
             player.playerkiller_end = playerkiller_end;
         }
+        if let Some(blackskull_end) = self.blackskull_end {
+            player.blackskull_end = blackskull_end;
+        }
+        if let Some(muted_until) = self.muted_until {
+            player.muted_until = muted_until;
+        }
+        if let Some(blessings) = self.blessings {
+            player.blessings = blessings;
+        }
         player.raw_skills = self.raw_skills;
         player.murders = self.murders;
 
@@ -0,0 +1,356 @@
+use crate::entities::player::PlayerId;
+use crate::world::position::Position;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TicketKind {
+    Help,
+    BugReport,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TicketStatus {
+    Open,
+    InProgress,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TicketOutcome {
+    Answered,
+    Removed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketMessage {
+    pub at: u64,
+    pub author: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupportTicket {
+    pub id: u32,
+    pub kind: TicketKind,
+    pub status: TicketStatus,
+    pub player_id: u32,
+    pub player_name: String,
+    pub position: Option<Position>,
+    pub assigned_to: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+    pub closed_at: Option<u64>,
+    pub outcome: Option<TicketOutcome>,
+    pub transcript: Vec<TicketMessage>,
+}
+
+impl SupportTicket {
+    pub fn is_active(&self) -> bool {
+        self.status != TicketStatus::Closed
+    }
+
+    pub fn summary(&self) -> String {
+        let assigned = self.assigned_to.as_deref().unwrap_or("-");
+        let outcome = match self.outcome {
+            Some(outcome) => format!(" {:?}", outcome),
+            None => String::new(),
+        };
+        format!(
+            "#{} {:?} {:?}{} {} (gm {}, {} msgs)",
+            self.id,
+            self.kind,
+            self.status,
+            outcome,
+            self.player_name,
+            assigned,
+            self.transcript.len()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TicketFile {
+    next_id: u32,
+    tickets: Vec<SupportTicket>,
+}
+
+/// Support tickets kept in `save/tickets.yaml`. Opening, assigning and
+/// closing a ticket write the file right away; transcript lines only mark the
+/// store dirty and are written by `flush` on the world save tick.
+#[derive(Debug, Clone, Default)]
+pub struct TicketStore {
+    path: Option<PathBuf>,
+    next_id: u32,
+    tickets: Vec<SupportTicket>,
+    dirty: bool,
+}
+
+impl TicketStore {
+    pub fn load(root: &Path) -> Result<Self, String> {
+        let path = root.join("save").join("tickets.yaml");
+        let file = match fs::read_to_string(&path) {
+            Ok(data) => serde_yaml::from_str::<TicketFile>(&data).map_err(|err| {
+                format!("ticket store parse failed for {}: {}", path.display(), err)
+            })?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => TicketFile::default(),
+            Err(err) => {
+                return Err(format!(
+                    "ticket store read failed for {}: {}",
+                    path.display(),
+                    err
+                ))
+            }
+        };
+        let max_id = file.tickets.iter().map(|ticket| ticket.id).max().unwrap_or(0);
+        Ok(Self {
+            path: Some(path),
+            next_id: file.next_id.max(max_id.saturating_add(1)).max(1),
+            tickets: file.tickets,
+            dirty: false,
+        })
+    }
+
+    /// Closes help requests left open by the previous run. The request queue
+    /// they belonged to lives in memory, so no gamemaster can pick them up
+    /// again. Bug reports stay open for review.
+    pub fn cancel_stale_requests(&mut self, now: u64) -> Result<usize, String> {
+        let mut cancelled = 0;
+        for ticket in &mut self.tickets {
+            if ticket.kind == TicketKind::Help && ticket.is_active() {
+                ticket.status = TicketStatus::Closed;
+                ticket.outcome = Some(TicketOutcome::Cancelled);
+                ticket.closed_at = Some(now);
+                ticket.updated_at = now;
+                cancelled += 1;
+            }
+        }
+        if cancelled > 0 {
+            self.dirty = true;
+            self.flush()?;
+        }
+        Ok(cancelled)
+    }
+
+    pub fn tickets(&self) -> &[SupportTicket] {
+        &self.tickets
+    }
+
+    pub fn get(&self, id: u32) -> Option<&SupportTicket> {
+        self.tickets.iter().find(|ticket| ticket.id == id)
+    }
+
+    pub fn active_for_player(&self, player_id: PlayerId, kind: TicketKind) -> Option<&SupportTicket> {
+        self.tickets
+            .iter()
+            .find(|ticket| ticket.player_id == player_id.0 && ticket.kind == kind && ticket.is_active())
+    }
+
+    pub fn for_player_name(&self, name: &str) -> Vec<&SupportTicket> {
+        self.tickets
+            .iter()
+            .filter(|ticket| ticket.player_name.eq_ignore_ascii_case(name))
+            .collect()
+    }
+
+    pub fn active(&self) -> Vec<&SupportTicket> {
+        self.tickets.iter().filter(|ticket| ticket.is_active()).collect()
+    }
+
+    pub fn open(
+        &mut self,
+        kind: TicketKind,
+        player_id: PlayerId,
+        player_name: &str,
+        position: Option<Position>,
+        text: &str,
+        now: u64,
+    ) -> Result<u32, String> {
+        let id = self.next_id.max(1);
+        let ticket = SupportTicket {
+            id,
+            kind,
+            status: TicketStatus::Open,
+            player_id: player_id.0,
+            player_name: player_name.to_string(),
+            position,
+            assigned_to: None,
+            created_at: now,
+            updated_at: now,
+            closed_at: None,
+            outcome: None,
+            transcript: vec![TicketMessage {
+                at: now,
+                author: player_name.to_string(),
+                text: text.to_string(),
+            }],
+        };
+        // Written before it is kept, so a failed write leaves no ticket that
+        // only exists in memory.
+        let mut tickets = self.tickets.clone();
+        tickets.push(ticket);
+        let next_id = id.saturating_add(1);
+        self.write(next_id, &tickets)?;
+        self.tickets = tickets;
+        self.next_id = next_id;
+        self.dirty = false;
+        Ok(id)
+    }
+
+    pub fn append_message(&mut self, id: u32, author: &str, text: &str, now: u64) -> Result<(), String> {
+        let ticket = self.active_mut(id)?;
+        ticket.transcript.push(TicketMessage {
+            at: now,
+            author: author.to_string(),
+            text: text.to_string(),
+        });
+        ticket.updated_at = now;
+        self.dirty = true;
+        Ok(())
+    }
+
+    pub fn assign(&mut self, id: u32, gamemaster: &str, now: u64) -> Result<(), String> {
+        let ticket = self.active_mut(id)?;
+        ticket.status = TicketStatus::InProgress;
+        ticket.assigned_to = Some(gamemaster.to_string());
+        ticket.updated_at = now;
+        self.dirty = true;
+        self.flush()
+    }
+
+    pub fn close(&mut self, id: u32, outcome: TicketOutcome, now: u64) -> Result<(), String> {
+        let ticket = self.active_mut(id)?;
+        ticket.status = TicketStatus::Closed;
+        ticket.outcome = Some(outcome);
+        ticket.closed_at = Some(now);
+        ticket.updated_at = now;
+        self.dirty = true;
+        self.flush()
+    }
+
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
+    }
+
+    /// Writes pending changes; a failed write keeps them pending for the
+    /// next flush.
+    pub fn flush(&mut self) -> Result<(), String> {
+        if !self.dirty {
+            return Ok(());
+        }
+        self.write(self.next_id, &self.tickets)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn active_mut(&mut self, id: u32) -> Result<&mut SupportTicket, String> {
+        self.tickets
+            .iter_mut()
+            .find(|ticket| ticket.id == id && ticket.is_active())
+            .ok_or_else(|| format!("no active ticket #{}", id))
+    }
+
+    fn write(&self, next_id: u32, tickets: &[SupportTicket]) -> Result<(), String> {
+        let Some(path) = self.path.as_ref() else {
+            return Ok(());
+        };
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| {
+                format!("ticket store dir create failed for {}: {}", parent.display(), err)
+            })?;
+        }
+        let file = TicketFile {
+            next_id,
+            tickets: tickets.to_vec(),
+        };
+        let data = serde_yaml::to_string(&file)
+            .map_err(|err| format!("ticket store serialize failed: {}", err))?;
+        fs::write(path, data)
+            .map_err(|err| format!("ticket store write failed for {}: {}", path.display(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_root() -> PathBuf {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        std::env::temp_dir().join(format!("tibia-ticket-test-{}", suffix))
+    }
+
+    #[test]
+    fn ticket_lifecycle_persists_across_reload() {
+        let root = temp_root();
+        let mut store = TicketStore::load(&root).expect("load");
+        let id = store
+            .open(TicketKind::Help, PlayerId(7), "Alice", None, "stuck in a hole", 100)
+            .expect("open");
+        store.assign(id, "GM Bob", 110).expect("assign");
+        store.append_message(id, "GM Bob", "on my way", 120).expect("append");
+        assert!(store.is_dirty());
+        let saved = TicketStore::load(&root).expect("reload");
+        assert_eq!(saved.get(id).expect("ticket").transcript.len(), 1);
+        store.close(id, TicketOutcome::Answered, 130).expect("close");
+        assert!(!store.is_dirty());
+        assert!(store.append_message(id, "Alice", "thanks", 140).is_err());
+
+        let reloaded = TicketStore::load(&root).expect("reload");
+        let ticket = reloaded.get(id).expect("ticket");
+        assert_eq!(ticket.status, TicketStatus::Closed);
+        assert_eq!(ticket.outcome, Some(TicketOutcome::Answered));
+        assert_eq!(ticket.assigned_to.as_deref(), Some("GM Bob"));
+        assert_eq!(ticket.transcript.len(), 2);
+        assert_eq!(ticket.closed_at, Some(130));
+
+        let mut reloaded = reloaded;
+        let next = reloaded
+            .open(TicketKind::BugReport, PlayerId(7), "Alice", None, "typo", 200)
+            .expect("open");
+        assert!(next > id);
+
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn stale_requests_are_cancelled_and_failed_opens_leave_no_ticket() {
+        let root = temp_root();
+        let mut store = TicketStore::load(&root).expect("load");
+        let help = store
+            .open(TicketKind::Help, PlayerId(7), "Alice", None, "help", 100)
+            .expect("open");
+        let bug = store
+            .open(TicketKind::BugReport, PlayerId(7), "Alice", None, "typo", 100)
+            .expect("open");
+
+        let mut reloaded = TicketStore::load(&root).expect("reload");
+        assert_eq!(reloaded.cancel_stale_requests(200), Ok(1));
+        let ticket = reloaded.get(help).expect("help ticket");
+        assert_eq!(ticket.outcome, Some(TicketOutcome::Cancelled));
+        assert!(reloaded.get(bug).expect("bug report").is_active());
+        let saved = TicketStore::load(&root).expect("reload");
+        assert!(saved.active_for_player(PlayerId(7), TicketKind::Help).is_none());
+
+        // A file where the save directory should be makes every write fail.
+        let blocked = temp_root();
+        fs::create_dir_all(&blocked).expect("create root");
+        fs::write(blocked.join("save"), "").expect("block save dir");
+        let mut store = TicketStore {
+            path: Some(blocked.join("save").join("tickets.yaml")),
+            ..TicketStore::default()
+        };
+        assert!(store
+            .open(TicketKind::Help, PlayerId(8), "Bob", None, "help", 100)
+            .is_err());
+        assert!(store.tickets().is_empty());
+
+        let _ = fs::remove_dir_all(root);
+        let _ = fs::remove_dir_all(blocked);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod monster;
 pub mod npc;
 pub mod raid;
+pub mod runtime;
 pub mod value;
@@ -69,6 +69,42 @@ pub enum MonsterSpellEffect {
     Unknown { name: String, args: Vec<ScriptValue> },
 }
 
+/// Boss extension to the monster format. `Phase = <percent>` opens a phase
+/// that starts once hit points drop to that share of the maximum; the
+/// `Phase*` fields after it describe what changes. `Enrage` applies to the
+/// whole fight.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MonsterBossScript {
+    pub phases: Vec<MonsterPhase>,
+    pub enrage: Option<MonsterEnrage>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MonsterPhase {
+    pub health_percent: u8,
+    /// Replaces the spell list (`PhaseSpells`).
+    pub spells: Option<Vec<MonsterSpell>>,
+    /// `PhaseOutfit`.
+    pub outfit: Option<Outfit>,
+    /// New base speed (`PhaseSpeed`).
+    pub speed: Option<u16>,
+    /// Race numbers and counts to summon on entry (`PhaseSummon`).
+    pub summons: Vec<(i64, u8)>,
+    /// Said once on entry (`PhaseYell`).
+    pub yell: Option<String>,
+    /// Seconds the boss takes no damage after entry (`PhaseImmunity`).
+    pub immunity_seconds: u32,
+}
+
+/// `Enrage = (seconds, percent)`: after that long in combat attack and
+/// speed rise by `percent`; `EnrageYell` is said once when it happens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonsterEnrage {
+    pub after_seconds: u32,
+    pub percent: u16,
+    pub yell: Option<String>,
+}
+
 impl MonsterDefinition {
     pub fn field_value(&self, key: &str) -> Option<&ScriptValue> {
         self.fields
@@ -214,22 +250,95 @@ impl MonsterDefinition {
     }
 
     pub fn spells(&self) -> Result<Vec<MonsterSpell>, String> {
-        let value = match self.field_value("Spells") {
-            Some(value) => value,
-            None => return Ok(Vec::new()),
-        };
-        let ScriptValue::List(entries) = value else {
-            return Err("Spells expects a list".to_string());
-        };
-        let mut spells = Vec::with_capacity(entries.len());
-        for entry in entries {
-            let raw = match entry {
-                ScriptValue::Ident(value) | ScriptValue::String(value) => value,
-                _ => return Err("Spells entry expects string".to_string()),
+        match self.field_value("Spells") {
+            Some(value) => parse_spell_list("Spells", value),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Boss phases and enrage timer, or `None` for ordinary monsters.
+    pub fn boss(&self) -> Result<Option<MonsterBossScript>, String> {
+        let mut boss = MonsterBossScript::default();
+        let mut is_boss = false;
+        for (key, value) in &self.fields {
+            if key.eq_ignore_ascii_case("Phase") {
+                let percent = parse_script_u16(value)
+                    .ok()
+                    .filter(|percent| (1..100).contains(percent))
+                    .ok_or_else(|| "Phase expects a hit point percent from 1 to 99".to_string())?;
+                if boss
+                    .phases
+                    .last()
+                    .is_some_and(|previous| u16::from(previous.health_percent) <= percent)
+                {
+                    return Err(format!(
+                        "Phase {} must start below the previous phase",
+                        percent
+                    ));
+                }
+                boss.phases.push(MonsterPhase {
+                    health_percent: percent as u8,
+                    ..MonsterPhase::default()
+                });
+                is_boss = true;
+                continue;
+            }
+            if key.eq_ignore_ascii_case("Enrage") {
+                let parts = match value {
+                    ScriptValue::Tuple(parts) | ScriptValue::List(parts) if parts.len() == 2 => {
+                        parts
+                    }
+                    _ => return Err("Enrage expects (seconds, percent)".to_string()),
+                };
+                let yell = boss.enrage.take().and_then(|enrage| enrage.yell);
+                boss.enrage = Some(MonsterEnrage {
+                    after_seconds: parse_script_u32(&parts[0])?,
+                    percent: parse_script_u16(&parts[1])?,
+                    yell,
+                });
+                is_boss = true;
+                continue;
+            }
+            if key.eq_ignore_ascii_case("EnrageYell") {
+                let yell = parse_script_string(value)
+                    .ok_or_else(|| "EnrageYell expects a string".to_string())?;
+                match boss.enrage.as_mut() {
+                    Some(enrage) => enrage.yell = Some(yell),
+                    None => return Err("EnrageYell before Enrage".to_string()),
+                }
+                continue;
+            }
+            let Some(field) = key
+                .get(..5)
+                .filter(|prefix| prefix.eq_ignore_ascii_case("Phase"))
+                .map(|_| &key[5..])
+            else {
+                continue;
             };
-            spells.push(parse_spell_entry(raw)?);
+            let phase = boss
+                .phases
+                .last_mut()
+                .ok_or_else(|| format!("{} before Phase", key))?;
+            if field.eq_ignore_ascii_case("Spells") {
+                phase.spells = Some(parse_spell_list(key, value)?);
+            } else if field.eq_ignore_ascii_case("Outfit") {
+                phase.outfit = Some(parse_outfit(value)?);
+            } else if field.eq_ignore_ascii_case("Speed") {
+                phase.speed = Some(parse_script_u16(value)?);
+            } else if field.eq_ignore_ascii_case("Summon") {
+                phase.summons = parse_summon_list(value)?;
+            } else if field.eq_ignore_ascii_case("Yell") {
+                phase.yell = Some(
+                    parse_script_string(value)
+                        .ok_or_else(|| "PhaseYell expects a string".to_string())?,
+                );
+            } else if field.eq_ignore_ascii_case("Immunity") {
+                phase.immunity_seconds = parse_script_u32(value)?;
+            } else {
+                return Err(format!("unknown boss field {}", key));
+            }
         }
-        Ok(spells)
+        Ok(is_boss.then_some(boss))
     }
 
     pub fn outfit(&self) -> Option<Outfit> {
@@ -563,6 +672,42 @@ fn parse_u16_list_from_text(raw: &str) -> Result<Vec<u16>, String> {
     Ok(values)
 }
 
+fn parse_spell_list(key: &str, value: &ScriptValue) -> Result<Vec<MonsterSpell>, String> {
+    let ScriptValue::List(entries) = value else {
+        return Err(format!("{} expects a list", key));
+    };
+    let mut spells = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let raw = match entry {
+            ScriptValue::Ident(value) | ScriptValue::String(value) => value,
+            _ => return Err(format!("{} entry expects string", key)),
+        };
+        spells.push(parse_spell_entry(raw)?);
+    }
+    Ok(spells)
+}
+
+fn parse_summon_list(value: &ScriptValue) -> Result<Vec<(i64, u8)>, String> {
+    let entries = match value {
+        ScriptValue::List(entries) => entries.as_slice(),
+        ScriptValue::Tuple(_) => std::slice::from_ref(value),
+        _ => return Err("PhaseSummon expects a list of (race, count)".to_string()),
+    };
+    let mut summons = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let ScriptValue::Tuple(parts) = entry else {
+            return Err("PhaseSummon entry expects (race, count)".to_string());
+        };
+        if parts.len() != 2 {
+            return Err("PhaseSummon entry expects (race, count)".to_string());
+        }
+        let race = parse_script_i64(&parts[0])?;
+        let count = parse_script_u16(&parts[1])?.min(u16::from(u8::MAX)) as u8;
+        summons.push((race, count));
+    }
+    Ok(summons)
+}
+
 fn parse_spell_entry(entry: &str) -> Result<MonsterSpell, String> {
     let entry = entry.trim();
     let (target_part, effect_part) = entry
@@ -827,4 +972,50 @@ Spells = {Victim (7, 9, 0) -> Damage (1, 25, 5) : 10}
             other => panic!("unexpected effect {:?}", other),
         }
     }
+
+    #[test]
+    fn parse_monster_script_reads_boss_phases_and_enrage() {
+        let input = r#"
+Name = "Orshabaal"
+Spells = {Victim (7, 9, 0) -> Damage (1, 25, 5) : 10}
+Enrage = (300, 50)
+EnrageYell = "ENOUGH!"
+Phase = 60
+PhaseSpells = {Actor (13) -> Healing (100, 50) : 5}
+PhaseOutfit = (35, 0-0-0-0)
+PhaseSpeed = 320
+PhaseSummon = {(35, 2)}
+PhaseYell = "You will pay!"
+Phase = 25
+PhaseImmunity = 5
+"#;
+        let script = parse_monster_script(input).expect("parse");
+        assert_eq!(script.spells().expect("spells").len(), 1);
+        let boss = script.boss().expect("boss").expect("is boss");
+        assert_eq!(
+            boss.enrage,
+            Some(MonsterEnrage {
+                after_seconds: 300,
+                percent: 50,
+                yell: Some("ENOUGH!".to_string()),
+            })
+        );
+        assert_eq!(boss.phases.len(), 2);
+        let first = &boss.phases[0];
+        assert_eq!(first.health_percent, 60);
+        assert_eq!(first.spells.as_ref().map(Vec::len), Some(1));
+        assert_eq!(first.outfit.map(|outfit| outfit.look_type), Some(35));
+        assert_eq!(first.speed, Some(320));
+        assert_eq!(first.summons, vec![(35, 2)]);
+        assert_eq!(first.yell.as_deref(), Some("You will pay!"));
+        assert_eq!(boss.phases[1].immunity_seconds, 5);
+        assert_eq!(boss.phases[1].spells, None);
+
+        let plain = parse_monster_script("Name = \"rat\"\n").expect("parse");
+        assert_eq!(plain.boss().expect("boss"), None);
+        let orphan = parse_monster_script("PhaseYell = \"hi\"\n").expect("parse");
+        assert!(orphan.boss().is_err());
+        let rising = parse_monster_script("Phase = 30\nPhase = 50\n").expect("parse");
+        assert!(rising.boss().is_err());
+    }
 }
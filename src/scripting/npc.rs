@@ -67,7 +67,7 @@ pub struct NpcQuestRequirement {
     pub value: i64,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct NpcScript {
     pub name: Option<String>,
     pub fields: Vec<(String, ScriptValue)>,
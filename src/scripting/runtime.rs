@@ -0,0 +1,542 @@
+use crate::world::position::Position;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, EvalAltResult, Scope, AST, INT};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// Budget for one script call; a runaway loop fails the call instead of
+/// stalling the world tick.
+const SCRIPT_MAX_OPERATIONS: u64 = 50_000;
+const SCRIPT_MAX_CALL_LEVELS: usize = 32;
+const SCRIPT_MAX_STRING_SIZE: usize = 4_096;
+const SCRIPT_MAX_COLLECTION_SIZE: usize = 1_024;
+
+/// A change a script asked for. Scripts never touch the world directly; the
+/// world applies these after the call returns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptCommand {
+    GiveItem {
+        player_id: u32,
+        type_id: u16,
+        count: u16,
+    },
+    Teleport {
+        player_id: u32,
+        position: Position,
+    },
+    SetQuestValue {
+        player_id: u32,
+        quest_id: u16,
+        value: i32,
+    },
+    SpawnMonster {
+        race_number: i64,
+        position: Position,
+    },
+    SendMessage {
+        player_id: u32,
+        message: String,
+    },
+}
+
+/// What a script can read about the player who triggered it.
+#[derive(Debug, Clone, Default)]
+pub struct ScriptPlayer {
+    pub id: u32,
+    pub name: String,
+    pub level: u16,
+    pub profession: u8,
+    pub position: Position,
+    pub quest_values: HashMap<u16, i32>,
+    pub item_counts: HashMap<u16, u32>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScriptItem {
+    pub type_id: u16,
+    pub position: Position,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptHook {
+    Login,
+    Logout,
+    LevelUp { old_level: u16, new_level: u16 },
+    Kill { race_number: i64 },
+    Step,
+    Use(u16),
+}
+
+impl ScriptHook {
+    fn function_name(self) -> &'static str {
+        match self {
+            ScriptHook::Login => "on_login",
+            ScriptHook::Logout => "on_logout",
+            ScriptHook::LevelUp { .. } => "on_level_up",
+            ScriptHook::Kill { .. } => "on_kill",
+            ScriptHook::Step => "on_step",
+            ScriptHook::Use(_) => "on_use",
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ScriptRun {
+    /// Some script returned `true`, e.g. to replace the `moveuse.dat` rule
+    /// for a used item.
+    pub handled: bool,
+    pub commands: Vec<ScriptCommand>,
+    pub errors: Vec<String>,
+}
+
+struct LoadedScript {
+    name: String,
+    ast: AST,
+    hooks: Vec<String>,
+    use_items: Vec<u16>,
+    step_positions: Vec<Position>,
+}
+
+impl LoadedScript {
+    fn listens_to(&self, hook: ScriptHook, position: Position) -> bool {
+        if !self.hooks.iter().any(|name| name == hook.function_name()) {
+            return false;
+        }
+        match hook {
+            ScriptHook::Use(type_id) => self.use_items.contains(&type_id),
+            ScriptHook::Step => self.step_positions.contains(&position),
+            _ => true,
+        }
+    }
+}
+
+/// Content scripts loaded from `<root>/scripts/*.rhai`. A script defines any
+/// of `on_login(player)`, `on_logout(player)`,
+/// `on_level_up(player, old_level, new_level)`, `on_kill(player, race)`,
+/// `on_step(player)` and `on_use(player, item)`;
+/// `on_use` runs for the item types in its `USE_ITEMS` constant when they are
+/// used on the map, not from the inventory or a container, and `on_step` only
+/// on the `[x, y, z]` positions in `STEP_POSITIONS`.
+pub struct ScriptRuntime {
+    engine: Engine,
+    scripts: Vec<LoadedScript>,
+    commands: Arc<Mutex<Vec<ScriptCommand>>>,
+}
+
+impl fmt::Debug for ScriptRuntime {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.scripts.iter().map(|script| script.name.as_str()).collect();
+        f.debug_struct("ScriptRuntime").field("scripts", &names).finish()
+    }
+}
+
+impl ScriptRuntime {
+    pub fn new() -> Self {
+        let commands = Arc::new(Mutex::new(Vec::new()));
+        let engine = build_engine(&commands);
+        Self {
+            engine,
+            scripts: Vec::new(),
+            commands,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.scripts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// Compiles a script and runs its top level once to read `USE_ITEMS` and
+    /// `STEP_POSITIONS`.
+    pub fn add_script(&mut self, name: &str, source: &str) -> Result<(), String> {
+        let ast = self
+            .engine
+            .compile(source)
+            .map_err(|err| format!("script {} failed to compile: {}", name, err))?;
+        let mut scope = Scope::new();
+        let run = self.engine.run_ast_with_scope(&mut scope, &ast);
+        self.take_commands();
+        run.map_err(|err| format!("script {} failed to load: {}", name, err))?;
+        let use_items = match scope.get_value::<Array>("USE_ITEMS") {
+            Some(items) => items
+                .into_iter()
+                .map(|item| {
+                    item.as_int()
+                        .ok()
+                        .and_then(|value| u16::try_from(value).ok())
+                        .ok_or_else(|| format!("script {} USE_ITEMS expects item type ids", name))
+                })
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+        let step_positions = match scope.get_value::<Array>("STEP_POSITIONS") {
+            Some(positions) => positions
+                .into_iter()
+                .map(|position| {
+                    position_from_dynamic(position).ok_or_else(|| {
+                        format!("script {} STEP_POSITIONS expects [x, y, z] entries", name)
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+            None => Vec::new(),
+        };
+        let hooks = ast
+            .iter_functions()
+            .map(|function| function.name.to_string())
+            .collect();
+        self.scripts.push(LoadedScript {
+            name: name.to_string(),
+            ast,
+            hooks,
+            use_items,
+            step_positions,
+        });
+        Ok(())
+    }
+
+    pub fn handles_use(&self, type_id: u16) -> bool {
+        self.scripts
+            .iter()
+            .any(|script| script.use_items.contains(&type_id))
+    }
+
+    pub fn handles_step(&self, position: Position) -> bool {
+        self.scripts
+            .iter()
+            .any(|script| script.step_positions.contains(&position))
+    }
+
+    /// Calls `hook` in every script listening for it, in file name order.
+    /// A failing script is reported in `errors` and skipped; the commands
+    /// of the others still apply.
+    pub fn run_hook(&self, hook: ScriptHook, player: &ScriptPlayer, item: Option<ScriptItem>) -> ScriptRun {
+        let mut run = ScriptRun::default();
+        for script in &self.scripts {
+            if !script.listens_to(hook, player.position) {
+                continue;
+            }
+            let mut scope = Scope::new();
+            let options = CallFnOptions::new().eval_ast(false);
+            let name = hook.function_name();
+            let player = player.clone();
+            let result = match hook {
+                ScriptHook::LevelUp {
+                    old_level,
+                    new_level,
+                } => self.engine.call_fn_with_options::<Dynamic>(
+                    options,
+                    &mut scope,
+                    &script.ast,
+                    name,
+                    (player, INT::from(old_level), INT::from(new_level)),
+                ),
+                ScriptHook::Kill { race_number } => self.engine.call_fn_with_options::<Dynamic>(
+                    options,
+                    &mut scope,
+                    &script.ast,
+                    name,
+                    (player, race_number),
+                ),
+                ScriptHook::Use(_) => self.engine.call_fn_with_options::<Dynamic>(
+                    options,
+                    &mut scope,
+                    &script.ast,
+                    name,
+                    (player, item.unwrap_or_default()),
+                ),
+                _ => self.engine.call_fn_with_options::<Dynamic>(
+                    options,
+                    &mut scope,
+                    &script.ast,
+                    name,
+                    (player,),
+                ),
+            };
+            let commands = self.take_commands();
+            match result {
+                Ok(value) => {
+                    run.handled |= value.as_bool().unwrap_or(false);
+                    run.commands.extend(commands);
+                }
+                Err(err) => run.errors.push(format!("script {} {}: {}", script.name, name, err)),
+            }
+        }
+        run
+    }
+
+    fn take_commands(&self) -> Vec<ScriptCommand> {
+        match self.commands.lock() {
+            Ok(mut commands) => std::mem::take(&mut *commands),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl Default for ScriptRuntime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn load_scripts(dir: &Path) -> Result<ScriptRuntime, String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|err| format!("failed to read scripts dir {}: {}", dir.display(), err))?;
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some("rhai"))
+        .collect();
+    paths.sort();
+    let mut runtime = ScriptRuntime::new();
+    for path in paths {
+        let source = std::fs::read_to_string(&path)
+            .map_err(|err| format!("failed to read script {}: {}", path.display(), err))?;
+        let name = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("script");
+        runtime.add_script(name, &source)?;
+    }
+    Ok(runtime)
+}
+
+type ApiResult<T> = Result<T, Box<EvalAltResult>>;
+
+fn build_engine(commands: &Arc<Mutex<Vec<ScriptCommand>>>) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.set_max_operations(SCRIPT_MAX_OPERATIONS);
+    engine.set_max_call_levels(SCRIPT_MAX_CALL_LEVELS);
+    engine.set_max_string_size(SCRIPT_MAX_STRING_SIZE);
+    engine.set_max_array_size(SCRIPT_MAX_COLLECTION_SIZE);
+    engine.set_max_map_size(SCRIPT_MAX_COLLECTION_SIZE);
+    engine.on_print(|text| crate::telemetry::logging::log_game(&format!("script: {}", text)));
+    engine.on_debug(|text, source, position| {
+        crate::telemetry::logging::log_game(&format!(
+            "script debug {}{:?}: {}",
+            source.unwrap_or(""),
+            position,
+            text
+        ))
+    });
+
+    engine
+        .register_type_with_name::<ScriptPlayer>("Player")
+        .register_get("id", |player: &mut ScriptPlayer| INT::from(player.id))
+        .register_get("name", |player: &mut ScriptPlayer| player.name.clone())
+        .register_get("level", |player: &mut ScriptPlayer| INT::from(player.level))
+        .register_get("vocation", |player: &mut ScriptPlayer| INT::from(player.profession))
+        .register_get("x", |player: &mut ScriptPlayer| INT::from(player.position.x))
+        .register_get("y", |player: &mut ScriptPlayer| INT::from(player.position.y))
+        .register_get("z", |player: &mut ScriptPlayer| INT::from(player.position.z))
+        .register_fn("quest", |player: &mut ScriptPlayer, quest_id: INT| -> ApiResult<INT> {
+            let quest_id = api_u16("quest id", quest_id)?;
+            Ok(INT::from(player.quest_values.get(&quest_id).copied().unwrap_or(0)))
+        })
+        .register_fn("item_count", |player: &mut ScriptPlayer, type_id: INT| -> ApiResult<INT> {
+            let type_id = api_u16("item type", type_id)?;
+            Ok(INT::from(player.item_counts.get(&type_id).copied().unwrap_or(0)))
+        });
+    engine
+        .register_type_with_name::<ScriptItem>("Item")
+        .register_get("type_id", |item: &mut ScriptItem| INT::from(item.type_id))
+        .register_get("x", |item: &mut ScriptItem| INT::from(item.position.x))
+        .register_get("y", |item: &mut ScriptItem| INT::from(item.position.y))
+        .register_get("z", |item: &mut ScriptItem| INT::from(item.position.z));
+
+    let queue = Arc::clone(commands);
+    engine.register_fn(
+        "give_item",
+        move |player: &mut ScriptPlayer, type_id: INT, count: INT| -> ApiResult<()> {
+            let type_id = api_u16("item type", type_id)?;
+            let count = api_u16("item count", count)?;
+            if count == 0 {
+                return Err("give_item expects a count above zero".into());
+            }
+            *player.item_counts.entry(type_id).or_default() += u32::from(count);
+            push_command(&queue, ScriptCommand::GiveItem {
+                player_id: player.id,
+                type_id,
+                count,
+            })
+        },
+    );
+    let queue = Arc::clone(commands);
+    engine.register_fn(
+        "teleport",
+        move |player: &mut ScriptPlayer, x: INT, y: INT, z: INT| -> ApiResult<()> {
+            let position = api_position(x, y, z)?;
+            player.position = position;
+            push_command(&queue, ScriptCommand::Teleport {
+                player_id: player.id,
+                position,
+            })
+        },
+    );
+    let queue = Arc::clone(commands);
+    engine.register_fn(
+        "set_quest",
+        move |player: &mut ScriptPlayer, quest_id: INT, value: INT| -> ApiResult<()> {
+            let quest_id = api_u16("quest id", quest_id)?;
+            let value = i32::try_from(value)
+                .map_err(|_| format!("quest value {} out of range", value))?;
+            player.quest_values.insert(quest_id, value);
+            push_command(&queue, ScriptCommand::SetQuestValue {
+                player_id: player.id,
+                quest_id,
+                value,
+            })
+        },
+    );
+    let queue = Arc::clone(commands);
+    engine.register_fn(
+        "message",
+        move |player: &mut ScriptPlayer, message: &str| -> ApiResult<()> {
+            push_command(&queue, ScriptCommand::SendMessage {
+                player_id: player.id,
+                message: message.to_string(),
+            })
+        },
+    );
+    let queue = Arc::clone(commands);
+    engine.register_fn(
+        "spawn_monster",
+        move |race_number: INT, x: INT, y: INT, z: INT| -> ApiResult<()> {
+            let position = api_position(x, y, z)?;
+            push_command(&queue, ScriptCommand::SpawnMonster {
+                race_number,
+                position,
+            })
+        },
+    );
+    engine
+}
+
+fn push_command(queue: &Mutex<Vec<ScriptCommand>>, command: ScriptCommand) -> ApiResult<()> {
+    let mut commands = queue
+        .lock()
+        .map_err(|_| "script command queue poisoned".to_string())?;
+    commands.push(command);
+    Ok(())
+}
+
+fn api_u16(what: &str, value: INT) -> ApiResult<u16> {
+    u16::try_from(value).map_err(|_| format!("{} {} out of range", what, value).into())
+}
+
+fn api_position(x: INT, y: INT, z: INT) -> ApiResult<Position> {
+    let z = u8::try_from(z).map_err(|_| format!("floor {} out of range", z))?;
+    Ok(Position {
+        x: api_u16("x", x)?,
+        y: api_u16("y", y)?,
+        z,
+    })
+}
+
+fn position_from_dynamic(value: Dynamic) -> Option<Position> {
+    let parts = value.try_cast::<Array>()?;
+    let [x, y, z] = parts.as_slice() else {
+        return None;
+    };
+    Some(Position {
+        x: u16::try_from(x.as_int().ok()?).ok()?,
+        y: u16::try_from(y.as_int().ok()?).ok()?,
+        z: u8::try_from(z.as_int().ok()?).ok()?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scripts_queue_commands_for_their_hooks() {
+        let mut runtime = ScriptRuntime::new();
+        runtime
+            .add_script(
+                "chest",
+                r#"
+const USE_ITEMS = [2480];
+const STEP_POSITIONS = [[50, 61, 7]];
+fn on_use(player, item) {
+    if player.quest(4001) > 0 {
+        player.message("The chest is empty.");
+        return true;
+    }
+    player.give_item(3031, 100);
+    player.set_quest(4001, 1);
+    player.message(`You found 100 gold in the chest at ${item.x}.`);
+    true
+}
+fn on_level_up(player, old_level, new_level) {
+    if new_level >= 20 && old_level < 20 { player.teleport(100, 100, 7); }
+}
+"#,
+            )
+            .expect("load chest");
+        assert!(runtime.handles_use(2480));
+        assert!(!runtime.handles_use(2481));
+        assert!(runtime.handles_step(Position { x: 50, y: 61, z: 7 }));
+        assert!(!runtime.handles_step(Position { x: 50, y: 60, z: 7 }));
+
+        let mut player = ScriptPlayer {
+            id: 7,
+            name: "Hero".to_string(),
+            level: 19,
+            ..ScriptPlayer::default()
+        };
+        let item = ScriptItem {
+            type_id: 2480,
+            position: Position { x: 50, y: 60, z: 7 },
+        };
+        let run = runtime.run_hook(ScriptHook::Use(2480), &player, Some(item));
+        assert!(run.handled);
+        assert!(run.errors.is_empty());
+        assert_eq!(run.commands.len(), 3);
+        assert_eq!(
+            run.commands[2],
+            ScriptCommand::SendMessage {
+                player_id: 7,
+                message: "You found 100 gold in the chest at 50.".to_string(),
+            }
+        );
+
+        player.quest_values.insert(4001, 1);
+        let run = runtime.run_hook(ScriptHook::Use(2480), &player, Some(item));
+        assert_eq!(run.commands.len(), 1);
+
+        let run = runtime.run_hook(
+            ScriptHook::LevelUp {
+                old_level: 19,
+                new_level: 20,
+            },
+            &player,
+            None,
+        );
+        assert!(!run.handled);
+        assert_eq!(
+            run.commands,
+            vec![ScriptCommand::Teleport {
+                player_id: 7,
+                position: Position { x: 100, y: 100, z: 7 },
+            }]
+        );
+    }
+
+    #[test]
+    fn scripts_are_sandboxed() {
+        let mut runtime = ScriptRuntime::new();
+        assert!(runtime.add_script("import", "import \"other\" as other;").is_err());
+        runtime
+            .add_script("loop", "fn on_login(player) { loop { player.give_item(1, 1); } }")
+            .expect("load loop");
+        let run = runtime.run_hook(ScriptHook::Login, &ScriptPlayer::default(), None);
+        assert_eq!(run.errors.len(), 1);
+        assert!(run.commands.is_empty());
+        assert!(runtime.take_commands().is_empty());
+    }
+}
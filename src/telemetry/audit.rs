@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const AUDIT_FILE_NAME: &str = "audit.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferKind {
+    Trade,
+    Drop,
+    Pickup,
+    Mail,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum AuditEvent {
+    Login {
+        player: String,
+        account: String,
+        ip: Option<String>,
+    },
+    Logout {
+        player: String,
+    },
+    ItemTransfer {
+        kind: TransferKind,
+        from: Option<String>,
+        to: Option<String>,
+        item_type: u16,
+        count: u16,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        position: Option<[u16; 3]>,
+    },
+    Kill {
+        killer: String,
+        victim: String,
+    },
+    Death {
+        player: String,
+        killers: Vec<String>,
+    },
+    GmCommand {
+        gamemaster: String,
+        command: String,
+    },
+    Ban {
+        gamemaster: String,
+        target: String,
+        reason: String,
+    },
+}
+
+impl AuditEvent {
+    /// Every player name the event refers to, in no particular order.
+    pub fn players(&self) -> Vec<&str> {
+        match self {
+            AuditEvent::Login { player, .. }
+            | AuditEvent::Logout { player } => vec![player.as_str()],
+            AuditEvent::ItemTransfer { from, to, .. } => {
+                from.iter().chain(to.iter()).map(String::as_str).collect()
+            }
+            AuditEvent::Kill { killer, victim } => vec![killer.as_str(), victim.as_str()],
+            AuditEvent::Death { player, killers } => std::iter::once(player.as_str())
+                .chain(killers.iter().map(String::as_str))
+                .collect(),
+            AuditEvent::GmCommand { gamemaster, .. } => vec![gamemaster.as_str()],
+            AuditEvent::Ban {
+                gamemaster, target, ..
+            } => vec![gamemaster.as_str(), target.as_str()],
+        }
+    }
+
+    pub fn item_type(&self) -> Option<u16> {
+        match self {
+            AuditEvent::ItemTransfer { item_type, .. } => Some(*item_type),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub at: u64,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+impl AuditRecord {
+    pub fn involves_player(&self, name: &str) -> bool {
+        self.event
+            .players()
+            .iter()
+            .any(|player| player.eq_ignore_ascii_case(name))
+    }
+
+    pub fn to_line(&self) -> Result<String, String> {
+        serde_json::to_string(self).map_err(|err| format!("audit serialize failed: {}", err))
+    }
+
+    pub fn from_line(line: &str) -> Result<Self, String> {
+        serde_json::from_str(line).map_err(|err| format!("audit parse failed: {}", err))
+    }
+}
+
+/// Item types whose drops and pickups are always audited, read from
+/// `dat/audit-items.txt`: one type id per line, `#` starts a comment.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditItems {
+    type_ids: HashSet<u16>,
+}
+
+impl AuditItems {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+        Self::parse(&content).map_err(|err| format!("{}: {}", path.display(), err))
+    }
+
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let mut type_ids = HashSet::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let type_id = line
+                .parse::<u16>()
+                .map_err(|_| format!("line {}: expected an item type id, got '{}'", index + 1, line))?;
+            type_ids.insert(type_id);
+        }
+        Ok(Self { type_ids })
+    }
+
+    pub fn contains(&self, type_id: u16) -> bool {
+        self.type_ids.contains(&type_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.type_ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.type_ids.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditQuery {
+    pub player: Option<String>,
+    pub item_type: Option<u16>,
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+impl AuditQuery {
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        if let Some(player) = self.player.as_deref() {
+            if !record.involves_player(player) {
+                return false;
+            }
+        }
+        if let Some(item_type) = self.item_type {
+            if record.event.item_type() != Some(item_type) {
+                return false;
+            }
+        }
+        if self.since.is_some_and(|since| record.at < since) {
+            return false;
+        }
+        if self.until.is_some_and(|until| record.at > until) {
+            return false;
+        }
+        true
+    }
+}
+
+/// Reads an audit file and returns the matching records together with the
+/// number of lines that could not be parsed.
+pub fn read_records(path: &Path, query: &AuditQuery) -> Result<(Vec<AuditRecord>, usize), String> {
+    let file = File::open(path)
+        .map_err(|err| format!("audit open failed for {}: {}", path.display(), err))?;
+    let mut records = Vec::new();
+    let mut skipped = 0usize;
+    for line in BufReader::new(file).lines() {
+        let line = line.map_err(|err| format!("audit read failed for {}: {}", path.display(), err))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        match AuditRecord::from_line(&line) {
+            Ok(record) if query.matches(&record) => records.push(record),
+            Ok(_) => {}
+            Err(_) => skipped += 1,
+        }
+    }
+    Ok((records, skipped))
+}
+
+struct AuditLog {
+    file: Mutex<File>,
+}
+
+static AUDIT_LOG: OnceLock<AuditLog> = OnceLock::new();
+
+pub fn audit_path(root: &Path) -> PathBuf {
+    root.join("log").join(AUDIT_FILE_NAME)
+}
+
+pub fn init(root: &Path) -> Result<(), String> {
+    if AUDIT_LOG.get().is_some() {
+        return Ok(());
+    }
+    let path = audit_path(root);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| format!("audit directory create failed: {}", err))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|err| format!("open audit log {} failed: {}", path.display(), err))?;
+    AUDIT_LOG
+        .set(AuditLog {
+            file: Mutex::new(file),
+        })
+        .map_err(|_| "audit log already initialized".to_string())?;
+    Ok(())
+}
+
+pub fn record(event: AuditEvent) {
+    let Some(log) = AUDIT_LOG.get() else {
+        return;
+    };
+    let at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    let Ok(line) = (AuditRecord { at, event }).to_line() else {
+        return;
+    };
+    if let Ok(mut file) = log.file.lock() {
+        let _ = writeln!(file, "{line}");
+        let _ = file.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn audit_record_round_trips_and_filters() {
+        let record = AuditRecord {
+            at: 100,
+            event: AuditEvent::ItemTransfer {
+                kind: TransferKind::Trade,
+                from: Some("Alice".to_string()),
+                to: Some("Bob".to_string()),
+                item_type: 3043,
+                count: 5,
+                position: None,
+            },
+        };
+        let line = record.to_line().expect("serialize");
+        assert!(line.contains("\"event\":\"item_transfer\""));
+        assert!(line.contains("\"kind\":\"trade\""));
+        let parsed = AuditRecord::from_line(&line).expect("parse");
+        assert_eq!(parsed, record);
+
+        let by_player = AuditQuery {
+            player: Some("bob".to_string()),
+            ..AuditQuery::default()
+        };
+        assert!(by_player.matches(&parsed));
+        let by_item = AuditQuery {
+            item_type: Some(3031),
+            ..AuditQuery::default()
+        };
+        assert!(!by_item.matches(&parsed));
+        let too_late = AuditQuery {
+            since: Some(101),
+            ..AuditQuery::default()
+        };
+        assert!(!too_late.matches(&parsed));
+    }
+
+    #[test]
+    fn audit_items_parse_ids_and_comments() {
+        let items = AuditItems::parse("# rare gear\n3388\n\n3366 # magic plate armor\n").expect("parse");
+        assert_eq!(items.len(), 2);
+        assert!(items.contains(3366));
+        assert!(!items.contains(3031));
+        assert!(AuditItems::parse("plate armor\n").is_err());
+    }
+}
@@ -0,0 +1,445 @@
+use crate::entities::player::PlayerId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+const CLOSED_SESSION_HISTORY: usize = 512;
+const REPORT_CLOSED_SESSIONS: usize = 50;
+const MAX_FLAGS: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DetectionConfig {
+    /// Concurrent sessions from one IP before every account on it is flagged.
+    pub max_sessions_per_ip: usize,
+    /// Intervals needed before input regularity is judged.
+    pub min_interval_samples: u64,
+    /// Coefficient of variation (stddev / mean) below which input is too regular.
+    pub max_interval_variation: f64,
+    /// Gaps longer than this are pauses and do not count as an interval.
+    pub max_interval_ms: u64,
+    /// Step gaps within this fraction of the server step pace come from a held
+    /// arrow key and do not count as an interval.
+    pub step_pace_tolerance: f64,
+}
+
+impl Default for DetectionConfig {
+    fn default() -> Self {
+        Self {
+            max_sessions_per_ip: 2,
+            min_interval_samples: 200,
+            max_interval_variation: 0.03,
+            max_interval_ms: 2000,
+            step_pace_tolerance: 0.15,
+        }
+    }
+}
+
+impl DetectionConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+        serde_yaml::from_str(&content)
+            .map_err(|err| format!("failed to parse {}: {}", path.display(), err))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputKind {
+    Step,
+    Attack,
+}
+
+impl InputKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            InputKind::Step => "step",
+            InputKind::Attack => "attack",
+        }
+    }
+}
+
+/// Running mean/variance of the gaps between inputs (Welford's algorithm).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct InputTimingStats {
+    pub samples: u64,
+    mean_ms: f64,
+    m2: f64,
+    last_at_ms: Option<u64>,
+}
+
+impl InputTimingStats {
+    fn record(&mut self, at_ms: u64, max_interval_ms: u64, ignored: Option<(u64, u64)>) {
+        let previous = self.last_at_ms.replace(at_ms);
+        let Some(previous) = previous else {
+            return;
+        };
+        let interval = at_ms.saturating_sub(previous);
+        if interval == 0 || interval > max_interval_ms {
+            return;
+        }
+        if ignored.is_some_and(|(low, high)| (low..=high).contains(&interval)) {
+            return;
+        }
+        let interval = interval as f64;
+        self.samples += 1;
+        let delta = interval - self.mean_ms;
+        self.mean_ms += delta / self.samples as f64;
+        self.m2 += delta * (interval - self.mean_ms);
+    }
+
+    pub fn mean_ms(&self) -> f64 {
+        self.mean_ms
+    }
+
+    pub fn variation(&self) -> Option<f64> {
+        if self.samples < 2 || self.mean_ms <= 0.0 {
+            return None;
+        }
+        let variance = self.m2 / (self.samples - 1) as f64;
+        Some(variance.sqrt() / self.mean_ms)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionRecord {
+    pub player_id: PlayerId,
+    pub player_name: String,
+    pub account: String,
+    pub ip: Option<IpAddr>,
+    pub login_at: u64,
+    pub logout_at: Option<u64>,
+    pub steps: InputTimingStats,
+    pub attacks: InputTimingStats,
+    flagged_inputs: Vec<InputKind>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DetectionReason {
+    MultiClient { ip: IpAddr, characters: Vec<String> },
+    RegularInput { kind: InputKind, samples: u64, mean_ms: f64, variation: f64 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DetectionFlag {
+    pub at: u64,
+    pub account: String,
+    pub player_name: String,
+    pub reason: DetectionReason,
+}
+
+impl DetectionFlag {
+    pub fn describe(&self) -> String {
+        let reason = match &self.reason {
+            DetectionReason::MultiClient { ip, characters } => {
+                format!("multi-client from {} ({})", ip, characters.join(", "))
+            }
+            DetectionReason::RegularInput {
+                kind,
+                samples,
+                mean_ms,
+                variation,
+            } => format!(
+                "regular {} input: {} samples, mean {:.0}ms, variation {:.3}",
+                kind.as_str(),
+                samples,
+                mean_ms,
+                variation
+            ),
+        };
+        format!("[{}] {} ({}): {}", self.at, self.player_name, self.account, reason)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DetectionMonitor {
+    config: DetectionConfig,
+    sessions: HashMap<PlayerId, SessionRecord>,
+    history: Vec<SessionRecord>,
+    flags: Vec<DetectionFlag>,
+    multi_client_flagged: HashSet<(String, IpAddr)>,
+}
+
+impl DetectionMonitor {
+    pub fn new(config: DetectionConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn flags(&self) -> &[DetectionFlag] {
+        &self.flags
+    }
+
+    pub fn session(&self, player_id: PlayerId) -> Option<&SessionRecord> {
+        self.sessions.get(&player_id)
+    }
+
+    pub fn session_started(
+        &mut self,
+        player_id: PlayerId,
+        player_name: &str,
+        account: &str,
+        ip: Option<IpAddr>,
+        now: u64,
+    ) -> Vec<DetectionFlag> {
+        self.sessions.insert(
+            player_id,
+            SessionRecord {
+                player_id,
+                player_name: player_name.to_string(),
+                account: account.to_string(),
+                ip,
+                login_at: now,
+                logout_at: None,
+                steps: InputTimingStats::default(),
+                attacks: InputTimingStats::default(),
+                flagged_inputs: Vec::new(),
+            },
+        );
+        let Some(ip) = ip else {
+            return Vec::new();
+        };
+        let same_ip: Vec<&SessionRecord> = self
+            .sessions
+            .values()
+            .filter(|session| session.ip == Some(ip))
+            .collect();
+        if same_ip.len() <= self.config.max_sessions_per_ip {
+            return Vec::new();
+        }
+        let mut characters: Vec<String> = same_ip
+            .iter()
+            .map(|session| session.player_name.clone())
+            .collect();
+        characters.sort();
+        let new_flags: Vec<DetectionFlag> = same_ip
+            .iter()
+            .filter(|session| {
+                !self
+                    .multi_client_flagged
+                    .contains(&(session.account.clone(), ip))
+            })
+            .map(|session| DetectionFlag {
+                at: now,
+                account: session.account.clone(),
+                player_name: session.player_name.clone(),
+                reason: DetectionReason::MultiClient {
+                    ip,
+                    characters: characters.clone(),
+                },
+            })
+            .collect();
+        for flag in &new_flags {
+            self.multi_client_flagged.insert((flag.account.clone(), ip));
+            self.push_flag(flag.clone());
+        }
+        new_flags
+    }
+
+    fn push_flag(&mut self, flag: DetectionFlag) {
+        self.flags.push(flag);
+        if self.flags.len() > MAX_FLAGS {
+            let excess = self.flags.len() - MAX_FLAGS;
+            self.flags.drain(..excess);
+        }
+    }
+
+    pub fn session_ended(&mut self, player_id: PlayerId, now: u64) {
+        let Some(mut session) = self.sessions.remove(&player_id) else {
+            return;
+        };
+        session.logout_at = Some(now);
+        self.history.push(session);
+        if self.history.len() > CLOSED_SESSION_HISTORY {
+            let excess = self.history.len() - CLOSED_SESSION_HISTORY;
+            self.history.drain(..excess);
+        }
+    }
+
+    /// Records an input received `at_ms` into the session. `step_pace_ms` is
+    /// the server step delay for the player, if known; intervals close to it
+    /// are walking with a held key and are left out of the statistics.
+    pub fn record_input(
+        &mut self,
+        player_id: PlayerId,
+        kind: InputKind,
+        at_ms: u64,
+        step_pace_ms: Option<u64>,
+        now: u64,
+    ) -> Option<DetectionFlag> {
+        let session = self.sessions.get_mut(&player_id)?;
+        let stats = match kind {
+            InputKind::Step => &mut session.steps,
+            InputKind::Attack => &mut session.attacks,
+        };
+        let ignored = step_pace_ms.map(|pace| {
+            let slack = (pace as f64 * self.config.step_pace_tolerance) as u64;
+            (pace.saturating_sub(slack), pace.saturating_add(slack))
+        });
+        stats.record(at_ms, self.config.max_interval_ms, ignored);
+        if stats.samples < self.config.min_interval_samples
+            || session.flagged_inputs.contains(&kind)
+        {
+            return None;
+        }
+        let variation = stats.variation()?;
+        if variation > self.config.max_interval_variation {
+            return None;
+        }
+        let flag = DetectionFlag {
+            at: now,
+            account: session.account.clone(),
+            player_name: session.player_name.clone(),
+            reason: DetectionReason::RegularInput {
+                kind,
+                samples: stats.samples,
+                mean_ms: stats.mean_ms(),
+                variation,
+            },
+        };
+        session.flagged_inputs.push(kind);
+        self.push_flag(flag.clone());
+        Some(flag)
+    }
+
+    pub fn report(&self) -> String {
+        let mut lines = Vec::new();
+        lines.push(format!("flags: {}", self.flags.len()));
+        for flag in &self.flags {
+            lines.push(format!("  {}", flag.describe()));
+        }
+        let mut sessions: Vec<&SessionRecord> = self.sessions.values().collect();
+        sessions.sort_by_key(|session| session.login_at);
+        lines.push(format!("active sessions: {}", sessions.len()));
+        let recent = self.history.iter().rev().take(REPORT_CLOSED_SESSIONS);
+        for session in sessions.into_iter().chain(recent) {
+            let ip = session
+                .ip
+                .map(|ip| ip.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let logout = session
+                .logout_at
+                .map(|at| at.to_string())
+                .unwrap_or_else(|| "online".to_string());
+            lines.push(format!(
+                "  {} ({}) ip={} login={} logout={} steps={}/{:.0}ms attacks={}/{:.0}ms",
+                session.player_name,
+                session.account,
+                ip,
+                session.login_at,
+                logout,
+                session.steps.samples,
+                session.steps.mean_ms(),
+                session.attacks.samples,
+                session.attacks.mean_ms()
+            ));
+        }
+        lines.join("\n")
+    }
+
+    pub fn write_report(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|err| format!("failed to create {}: {}", parent.display(), err))?;
+        }
+        fs::write(path, self.report() + "\n")
+            .map_err(|err| format!("failed to write {}: {}", path.display(), err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::Ipv4Addr;
+
+    #[test]
+    fn flags_all_characters_over_ip_limit() {
+        let mut monitor = DetectionMonitor::new(DetectionConfig {
+            max_sessions_per_ip: 1,
+            ..DetectionConfig::default()
+        });
+        let ip = Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)));
+        assert!(monitor.session_started(PlayerId(1), "Alice", "a", ip, 10).is_empty());
+        let flags = monitor.session_started(PlayerId(2), "Bob", "b", ip, 20);
+        assert_eq!(flags.len(), 2);
+        monitor.session_ended(PlayerId(2), 30);
+        assert!(monitor.session_started(PlayerId(3), "Carol", "c", None, 40).is_empty());
+        assert_eq!(monitor.flags().len(), 2);
+    }
+
+    #[test]
+    fn flags_machine_regular_steps_once() {
+        let mut monitor = DetectionMonitor::new(DetectionConfig {
+            min_interval_samples: 20,
+            ..DetectionConfig::default()
+        });
+        monitor.session_started(PlayerId(1), "Bot", "bot", None, 0);
+        let mut flagged = 0;
+        for step in 0..50u64 {
+            if monitor
+                .record_input(PlayerId(1), InputKind::Step, step * 200, None, 0)
+                .is_some()
+            {
+                flagged += 1;
+            }
+        }
+        assert_eq!(flagged, 1);
+
+        monitor.session_started(PlayerId(2), "Human", "human", None, 0);
+        let mut at = 0;
+        for step in 0..50u64 {
+            at += 150 + (step * 37) % 200;
+            assert!(monitor
+                .record_input(PlayerId(2), InputKind::Step, at, None, 0)
+                .is_none());
+        }
+    }
+
+    #[test]
+    fn held_arrow_keys_at_step_pace_are_not_flagged() {
+        let mut monitor = DetectionMonitor::new(DetectionConfig {
+            min_interval_samples: 20,
+            ..DetectionConfig::default()
+        });
+        monitor.session_started(PlayerId(1), "Walker", "walker", None, 0);
+        let mut at = 0;
+        for step in 0..100u64 {
+            at += 222 + step % 3;
+            assert!(monitor
+                .record_input(PlayerId(1), InputKind::Step, at, Some(222), 0)
+                .is_none());
+        }
+        assert_eq!(monitor.session(PlayerId(1)).unwrap().steps.samples, 0);
+
+        monitor.session_started(PlayerId(2), "Bot", "bot", None, 0);
+        let flagged = (0..50u64)
+            .filter(|step| {
+                monitor
+                    .record_input(PlayerId(2), InputKind::Step, step * 400, Some(222), 0)
+                    .is_some()
+            })
+            .count();
+        assert_eq!(flagged, 1);
+    }
+
+    #[test]
+    fn multi_client_flags_once_per_account_and_ip() {
+        let mut monitor = DetectionMonitor::new(DetectionConfig {
+            max_sessions_per_ip: 1,
+            ..DetectionConfig::default()
+        });
+        let ip = Some(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)));
+        monitor.session_started(PlayerId(1), "Alice", "a", ip, 10);
+        assert_eq!(monitor.session_started(PlayerId(2), "Bob", "b", ip, 20).len(), 2);
+        monitor.session_ended(PlayerId(2), 30);
+        assert!(monitor.session_started(PlayerId(2), "Bob", "b", ip, 40).is_empty());
+        let flags = monitor.session_started(PlayerId(3), "Carol", "c", ip, 50);
+        assert_eq!(flags.len(), 1);
+        assert_eq!(flags[0].account, "c");
+        assert_eq!(monitor.flags().len(), 3);
+    }
+}
@@ -19,6 +19,7 @@ enum LogFile {
     Houses,
     Lag,
     Load,
+    Moderation,
     Netload,
 }
 
@@ -52,6 +53,7 @@ pub fn init(root: &Path) -> Result<(), String> {
         (LogFile::Houses, "houses.log", true),
         (LogFile::Lag, "lag.log", false),
         (LogFile::Load, "load.log", false),
+        (LogFile::Moderation, "moderation.log", true),
         (LogFile::Netload, "netload.log", true),
     ] {
         let path = log_dir.join(name);
@@ -94,6 +96,10 @@ pub fn log_banish(message: &str) {
     log_timestamped(LogFile::Banish, message);
 }
 
+pub fn log_moderation(message: &str) {
+    log_timestamped(LogFile::Moderation, message);
+}
+
 pub fn log_netload(message: &str) {
     log_timestamped(LogFile::Netload, message);
 }
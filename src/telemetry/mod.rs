@@ -1,2 +1,4 @@
+pub mod audit;
+pub mod detection;
 pub mod logging;
 pub mod metrics;
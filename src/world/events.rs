@@ -0,0 +1,403 @@
+use crate::entities::creature::CreatureId;
+use crate::entities::player::PlayerId;
+use crate::world::moveuse::{parse_moveuse, MoveUseExpr, MoveUseSection};
+use crate::world::position::Position;
+use crate::world::state::{parse_position_arg, WorldState};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+
+/// Events a single publish may cascade into (handlers publishing further
+/// events) before the rest is dropped, so a script loop cannot hang a tick.
+/// The world's built-in handlers run on publish and still see dropped events.
+pub const MAX_CHAINED_EVENTS: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WorldEventKind {
+    CreatureKilled,
+    PlayerLogin,
+    PlayerLogout,
+    LevelUp,
+    StepOn,
+    MonsterSpawned,
+    RaidStarted,
+}
+
+impl WorldEventKind {
+    /// The event name used in `events.dat`.
+    pub fn name(self) -> &'static str {
+        match self {
+            WorldEventKind::CreatureKilled => "Kill",
+            WorldEventKind::PlayerLogin => "Login",
+            WorldEventKind::PlayerLogout => "Logout",
+            WorldEventKind::LevelUp => "LevelUp",
+            WorldEventKind::StepOn => "Step",
+            WorldEventKind::MonsterSpawned => "Spawn",
+            WorldEventKind::RaidStarted => "Raid",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "kill" => Some(WorldEventKind::CreatureKilled),
+            "login" => Some(WorldEventKind::PlayerLogin),
+            "logout" => Some(WorldEventKind::PlayerLogout),
+            "levelup" => Some(WorldEventKind::LevelUp),
+            "step" => Some(WorldEventKind::StepOn),
+            "spawn" => Some(WorldEventKind::MonsterSpawned),
+            "raid" => Some(WorldEventKind::RaidStarted),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KilledCreature {
+    Monster {
+        id: CreatureId,
+        race_number: i64,
+        home_id: Option<usize>,
+    },
+    Player(PlayerId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorldEvent {
+    CreatureKilled {
+        victim: KilledCreature,
+        position: Position,
+        killer: Option<PlayerId>,
+    },
+    PlayerLogin {
+        player_id: PlayerId,
+        position: Position,
+    },
+    PlayerLogout {
+        player_id: PlayerId,
+        position: Position,
+    },
+    LevelUp {
+        player_id: PlayerId,
+        old_level: u16,
+        new_level: u16,
+    },
+    StepOn {
+        player_id: PlayerId,
+        position: Position,
+    },
+    MonsterSpawned {
+        monster_id: CreatureId,
+        race_number: i64,
+        position: Position,
+        home_id: Option<usize>,
+    },
+    RaidStarted {
+        name: String,
+    },
+}
+
+impl WorldEvent {
+    pub fn kind(&self) -> WorldEventKind {
+        match self {
+            WorldEvent::CreatureKilled { .. } => WorldEventKind::CreatureKilled,
+            WorldEvent::PlayerLogin { .. } => WorldEventKind::PlayerLogin,
+            WorldEvent::PlayerLogout { .. } => WorldEventKind::PlayerLogout,
+            WorldEvent::LevelUp { .. } => WorldEventKind::LevelUp,
+            WorldEvent::StepOn { .. } => WorldEventKind::StepOn,
+            WorldEvent::MonsterSpawned { .. } => WorldEventKind::MonsterSpawned,
+            WorldEvent::RaidStarted { .. } => WorldEventKind::RaidStarted,
+        }
+    }
+
+    /// The player an event happened to, or the killer for kills. Script
+    /// `Message` and `SetQuestValue` actions apply to this player.
+    pub fn player_id(&self) -> Option<PlayerId> {
+        match self {
+            WorldEvent::CreatureKilled { killer, .. } => *killer,
+            WorldEvent::PlayerLogin { player_id, .. }
+            | WorldEvent::PlayerLogout { player_id, .. }
+            | WorldEvent::LevelUp { player_id, .. }
+            | WorldEvent::StepOn { player_id, .. } => Some(*player_id),
+            WorldEvent::MonsterSpawned { .. } | WorldEvent::RaidStarted { .. } => None,
+        }
+    }
+
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            WorldEvent::CreatureKilled { position, .. }
+            | WorldEvent::PlayerLogin { position, .. }
+            | WorldEvent::PlayerLogout { position, .. }
+            | WorldEvent::StepOn { position, .. }
+            | WorldEvent::MonsterSpawned { position, .. } => Some(*position),
+            WorldEvent::LevelUp { .. } | WorldEvent::RaidStarted { .. } => None,
+        }
+    }
+
+    fn race_number(&self) -> Option<i64> {
+        match self {
+            WorldEvent::CreatureKilled {
+                victim: KilledCreature::Monster { race_number, .. },
+                ..
+            }
+            | WorldEvent::MonsterSpawned { race_number, .. } => Some(*race_number),
+            _ => None,
+        }
+    }
+}
+
+pub type WorldEventHandler = fn(&mut WorldState, &WorldEvent);
+
+/// Typed world events and the subsystems listening for them. Events are
+/// queued and handed to every handler of their kind in publish order; an
+/// event published from a handler runs after the current one finishes.
+#[derive(Debug, Default)]
+pub struct EventBus {
+    handlers: HashMap<WorldEventKind, Vec<WorldEventHandler>>,
+    queue: VecDeque<WorldEvent>,
+    dispatching: bool,
+}
+
+impl EventBus {
+    pub fn subscribe(&mut self, kind: WorldEventKind, handler: WorldEventHandler) {
+        self.handlers.entry(kind).or_default().push(handler);
+    }
+
+    pub fn handlers(&self, kind: WorldEventKind) -> Vec<WorldEventHandler> {
+        self.handlers.get(&kind).cloned().unwrap_or_default()
+    }
+
+    pub(crate) fn push(&mut self, event: WorldEvent) {
+        self.queue.push_back(event);
+    }
+
+    /// Claims the dispatch loop, or returns false when an outer publish is
+    /// already draining the queue.
+    pub(crate) fn begin_dispatch(&mut self) -> bool {
+        if self.dispatching {
+            return false;
+        }
+        self.dispatching = true;
+        true
+    }
+
+    pub(crate) fn next_event(&mut self) -> Option<WorldEvent> {
+        self.queue.pop_front()
+    }
+
+    pub(crate) fn end_dispatch(&mut self) -> usize {
+        let dropped = self.queue.len();
+        self.queue.clear();
+        self.dispatching = false;
+        dropped
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventCondition {
+    Race(i64),
+    Level(u16),
+    Position(Position),
+    Name(String),
+    Player,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventAction {
+    Broadcast(String),
+    Message(String),
+    MonsterOnMap { position: Position, race_number: i64 },
+    SetQuestValue { quest_id: u16, value: i32 },
+}
+
+/// One `events.dat` rule: `Event, Condition... -> Action, ...`, written in
+/// the same syntax as `moveuse.dat`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventScript {
+    pub kind: WorldEventKind,
+    pub conditions: Vec<EventCondition>,
+    pub actions: Vec<EventAction>,
+    pub line_no: usize,
+}
+
+impl EventScript {
+    pub fn matches(&self, event: &WorldEvent) -> bool {
+        event.kind() == self.kind
+            && self
+                .conditions
+                .iter()
+                .all(|condition| condition_holds(condition, event))
+    }
+}
+
+fn condition_holds(condition: &EventCondition, event: &WorldEvent) -> bool {
+    match condition {
+        EventCondition::Race(race) => event.race_number() == Some(*race),
+        EventCondition::Level(level) => matches!(
+            event,
+            WorldEvent::LevelUp { old_level, new_level, .. }
+                if old_level < level && new_level >= level
+        ),
+        EventCondition::Position(position) => event.position() == Some(*position),
+        EventCondition::Name(name) => matches!(
+            event,
+            WorldEvent::RaidStarted { name: raid } if raid.eq_ignore_ascii_case(name)
+        ),
+        EventCondition::Player => matches!(
+            event,
+            WorldEvent::CreatureKilled {
+                victim: KilledCreature::Player(_),
+                ..
+            }
+        ),
+    }
+}
+
+pub fn load_event_scripts(path: &Path) -> Result<Vec<EventScript>, String> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|err| format!("failed to read events.dat {}: {}", path.display(), err))?;
+    parse_event_scripts(&content)
+}
+
+pub fn parse_event_scripts(content: &str) -> Result<Vec<EventScript>, String> {
+    let database = parse_moveuse(content)
+        .map_err(|err| err.replace("moveuse.dat", "events.dat"))?;
+    let mut scripts = Vec::new();
+    collect_scripts(&database.sections, &mut scripts)?;
+    Ok(scripts)
+}
+
+fn collect_scripts(
+    sections: &[MoveUseSection],
+    scripts: &mut Vec<EventScript>,
+) -> Result<(), String> {
+    for section in sections {
+        for rule in &section.rules {
+            let line_no = rule.line_no;
+            let at_line = |err: String| format!("events.dat {} at line {}", err, line_no);
+            let kind = WorldEventKind::from_name(&rule.event.name)
+                .ok_or_else(|| at_line(format!("unknown event '{}'", rule.event.name)))?;
+            let conditions = rule
+                .conditions
+                .iter()
+                .map(|expr| parse_condition(expr).map_err(at_line))
+                .collect::<Result<_, _>>()?;
+            let actions = rule
+                .actions
+                .iter()
+                .map(|expr| parse_action(expr).map_err(at_line))
+                .collect::<Result<_, _>>()?;
+            scripts.push(EventScript {
+                kind,
+                conditions,
+                actions,
+                line_no,
+            });
+        }
+        collect_scripts(&section.children, scripts)?;
+    }
+    Ok(())
+}
+
+fn parse_condition(expr: &MoveUseExpr) -> Result<EventCondition, String> {
+    let arg = |index: usize| {
+        expr.args
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("{} expects {} args", expr.name, index + 1))
+    };
+    match expr.name.as_str() {
+        "Race" => Ok(EventCondition::Race(parse_number(&expr.name, arg(0)?)?)),
+        "Level" => Ok(EventCondition::Level(parse_number(&expr.name, arg(0)?)?)),
+        "Position" => Ok(EventCondition::Position(parse_position_arg(arg(0)?)?)),
+        "Name" => Ok(EventCondition::Name(parse_text(arg(0)?))),
+        "Player" => Ok(EventCondition::Player),
+        other => Err(format!("unknown condition '{other}'")),
+    }
+}
+
+fn parse_action(expr: &MoveUseExpr) -> Result<EventAction, String> {
+    let arg = |index: usize| {
+        expr.args
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| format!("{} expects {} args", expr.name, index + 1))
+    };
+    match expr.name.as_str() {
+        "Broadcast" => Ok(EventAction::Broadcast(parse_text(arg(0)?))),
+        "Message" => Ok(EventAction::Message(parse_text(arg(0)?))),
+        "MonsterOnMap" => Ok(EventAction::MonsterOnMap {
+            position: parse_position_arg(arg(0)?)?,
+            race_number: parse_number(&expr.name, arg(1)?)?,
+        }),
+        "SetQuestValue" => Ok(EventAction::SetQuestValue {
+            quest_id: parse_number(&expr.name, arg(0)?)?,
+            value: parse_number(&expr.name, arg(1)?)?,
+        }),
+        other => Err(format!("unknown action '{other}'")),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, raw: &str) -> Result<T, String> {
+    raw.trim()
+        .parse::<T>()
+        .map_err(|_| format!("{name} expected a number, got '{}'", raw.trim()))
+}
+
+fn parse_text(raw: &str) -> String {
+    let raw = raw.trim();
+    raw.strip_prefix('"')
+        .and_then(|inner| inner.strip_suffix('"'))
+        .unwrap_or(raw)
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_event_scripts_reads_rules_and_matches_events() {
+        let content = "BEGIN \"Events\"\n\
+            Kill, Race(27) -> Broadcast(\"A wolf fell.\")\n\
+            LevelUp, Level(20) -> Message(\"Welcome to level 20, %N.\"), SetQuestValue(100, 1)\n\
+            Step, Position([100,100,7]) -> MonsterOnMap([101,100,7], 27)\n\
+            Raid, Name(\"orcs\") -> Broadcast(\"Orcs!\")\n\
+            END\n";
+        let scripts = parse_event_scripts(content).expect("parse");
+        assert_eq!(scripts.len(), 4);
+        assert_eq!(scripts[0].kind, WorldEventKind::CreatureKilled);
+        assert_eq!(
+            scripts[1].actions,
+            vec![
+                EventAction::Message("Welcome to level 20, %N.".to_string()),
+                EventAction::SetQuestValue {
+                    quest_id: 100,
+                    value: 1
+                },
+            ]
+        );
+
+        let wolf = WorldEvent::CreatureKilled {
+            victim: KilledCreature::Monster {
+                id: CreatureId(1),
+                race_number: 27,
+                home_id: None,
+            },
+            position: Position { x: 1, y: 1, z: 7 },
+            killer: None,
+        };
+        assert!(scripts[0].matches(&wolf));
+        let level_up = |old_level, new_level| WorldEvent::LevelUp {
+            player_id: PlayerId(1),
+            old_level,
+            new_level,
+        };
+        assert!(scripts[1].matches(&level_up(19, 21)));
+        assert!(!scripts[1].matches(&level_up(20, 21)));
+        assert!(scripts[3].matches(&WorldEvent::RaidStarted {
+            name: "Orcs".to_string()
+        }));
+
+        assert!(parse_event_scripts("BEGIN \"E\"\nDance -> Broadcast(\"x\")\nEND\n").is_err());
+        assert!(parse_event_scripts("BEGIN \"E\"\nLogin -> Teleport([1,1,7])\nEND\n").is_err());
+    }
+}
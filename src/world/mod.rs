@@ -1,20 +1,25 @@
 pub mod area;
 pub mod circles;
 pub mod cron;
+pub mod events;
 pub mod housing;
 pub mod item_types;
 pub mod map;
 pub mod map_dat;
 pub mod map_patching;
 pub mod mem_dat;
+pub mod moderation;
 pub mod monster_homes;
 pub mod monsters;
 pub mod moveuse;
 pub mod npc;
 pub mod object_types;
+pub mod pathfinding;
 pub mod position;
 pub mod premium;
+pub mod raids;
 pub mod sector_cache;
+pub mod sight;
 pub mod state;
 pub mod time;
 pub mod viewport;
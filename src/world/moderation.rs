@@ -0,0 +1,208 @@
+use crate::world::time::{GameClock, GameTick};
+use std::collections::VecDeque;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+const TALK_BURST_LIMIT: usize = 5;
+/// NPC conversations go back and forth quickly, so they get more room.
+const NPC_DIALOG_BURST_LIMIT: usize = 10;
+const TALK_BURST_WINDOW: Duration = Duration::from_secs(4);
+const MUTE_BASE_SECS: u64 = 10;
+const MUTE_MAX_SECS: u64 = 600;
+const MUTE_STRIKE_DECAY: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TalkCheck {
+    Allowed,
+    Muted { remaining_secs: u64 },
+    Throttled { mute_secs: u64 },
+}
+
+/// Per-player spam state. Each time the burst limit is exceeded the player is
+/// muted, and the mute doubles for every strike until the strikes decay.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TalkThrottle {
+    recent: VecDeque<GameTick>,
+    strikes: u32,
+    muted_until: Option<GameTick>,
+    last_strike_at: Option<GameTick>,
+}
+
+impl TalkThrottle {
+    /// Seconds left on a spam mute, without counting a message.
+    pub fn muted_for(&mut self, clock: &GameClock) -> Option<u64> {
+        let now = clock.now();
+        let until = self.muted_until?;
+        if now < until {
+            let remaining = clock.duration_for_ticks(until.0 - now.0);
+            return Some(remaining.as_secs().max(1));
+        }
+        self.muted_until = None;
+        None
+    }
+
+    pub fn check(&mut self, clock: &GameClock) -> TalkCheck {
+        self.check_with_limit(clock, TALK_BURST_LIMIT)
+    }
+
+    /// Like `check`, with the looser burst limit of NPC dialog.
+    pub fn check_npc_dialog(&mut self, clock: &GameClock) -> TalkCheck {
+        self.check_with_limit(clock, NPC_DIALOG_BURST_LIMIT)
+    }
+
+    fn check_with_limit(&mut self, clock: &GameClock, burst_limit: usize) -> TalkCheck {
+        let now = clock.now();
+        if let Some(remaining_secs) = self.muted_for(clock) {
+            return TalkCheck::Muted { remaining_secs };
+        }
+        if let Some(last_strike) = self.last_strike_at {
+            let decay = clock.ticks_from_duration_round_up(MUTE_STRIKE_DECAY);
+            if now.0.saturating_sub(last_strike.0) >= decay {
+                self.strikes = 0;
+                self.last_strike_at = None;
+            }
+        }
+        let window = clock.ticks_from_duration_round_up(TALK_BURST_WINDOW);
+        while let Some(front) = self.recent.front() {
+            if now.0.saturating_sub(front.0) < window {
+                break;
+            }
+            self.recent.pop_front();
+        }
+        self.recent.push_back(now);
+        if self.recent.len() <= burst_limit {
+            return TalkCheck::Allowed;
+        }
+        self.recent.clear();
+        let mute_secs = MUTE_BASE_SECS
+            .saturating_mul(1u64 << self.strikes.min(16))
+            .min(MUTE_MAX_SECS);
+        self.strikes = self.strikes.saturating_add(1);
+        self.last_strike_at = Some(now);
+        let ticks = clock.ticks_from_duration_round_up(Duration::from_secs(mute_secs));
+        self.muted_until = Some(GameTick(now.0.saturating_add(ticks)));
+        TalkCheck::Throttled { mute_secs }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WordFilter {
+    words: Vec<String>,
+}
+
+impl WordFilter {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read word filter {}: {}", path.display(), err))?;
+        Ok(Self::from_words(content.lines()))
+    }
+
+    pub fn from_words<'a>(words: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut words: Vec<String> = words
+            .into_iter()
+            .map(str::trim)
+            .filter(|word| !word.is_empty() && !word.starts_with('#'))
+            .map(str::to_ascii_lowercase)
+            .collect();
+        words.sort_by_key(|word| std::cmp::Reverse(word.len()));
+        words.dedup();
+        Self { words }
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Returns the message with every banned word replaced by asterisks, or
+    /// `None` when the message is clean. Only whole words match, so a banned
+    /// word inside a longer one is left alone.
+    pub fn censor(&self, message: &str) -> Option<String> {
+        if self.words.is_empty() {
+            return None;
+        }
+        let lowered = message.to_ascii_lowercase();
+        let bytes = lowered.as_bytes();
+        let mut masked = vec![false; message.len()];
+        let mut hit = false;
+        for word in &self.words {
+            let mut start = 0;
+            while let Some(offset) = lowered[start..].find(word.as_str()) {
+                let begin = start + offset;
+                let end = begin + word.len();
+                let starts_word = begin == 0 || !is_word_byte(bytes[begin - 1]);
+                let ends_word = end == bytes.len() || !is_word_byte(bytes[end]);
+                if starts_word && ends_word {
+                    masked[begin..end].iter_mut().for_each(|flag| *flag = true);
+                    hit = true;
+                    start = end;
+                } else {
+                    start = begin + lowered[begin..].chars().next().map_or(1, char::len_utf8);
+                }
+            }
+        }
+        if !hit {
+            return None;
+        }
+        let censored = message
+            .char_indices()
+            .map(|(index, ch)| if masked[index] { '*' } else { ch })
+            .collect();
+        Some(censored)
+    }
+}
+
+/// Letters, digits and every byte of a non-ASCII character count as part of
+/// a word.
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || !byte.is_ascii()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn word_filter_masks_banned_words_case_insensitively() {
+        let filter = WordFilter::from_words(["# comment", "noob", "", "Scam"]);
+        assert_eq!(filter.len(), 2);
+        assert_eq!(
+            filter.censor("NOOB, this is no scam"),
+            Some("****, this is no ****".to_string())
+        );
+        assert_eq!(filter.censor("hello there"), None);
+        assert_eq!(filter.censor("the scamp noobs about"), None);
+        assert_eq!(filter.censor("scam!scam"), Some("****!****".to_string()));
+    }
+
+    #[test]
+    fn talk_throttle_escalates_mutes() {
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        let mut throttle = TalkThrottle::default();
+        for _ in 0..TALK_BURST_LIMIT {
+            assert_eq!(throttle.check(&clock), TalkCheck::Allowed);
+        }
+        assert_eq!(
+            throttle.check(&clock),
+            TalkCheck::Throttled {
+                mute_secs: MUTE_BASE_SECS
+            }
+        );
+        assert!(matches!(throttle.check(&clock), TalkCheck::Muted { .. }));
+
+        clock.advance_duration(Duration::from_secs(MUTE_BASE_SECS));
+        for _ in 0..TALK_BURST_LIMIT {
+            assert_eq!(throttle.check(&clock), TalkCheck::Allowed);
+        }
+        assert_eq!(
+            throttle.check(&clock),
+            TalkCheck::Throttled {
+                mute_secs: MUTE_BASE_SECS * 2
+            }
+        );
+    }
+}
@@ -1,4 +1,5 @@
 use crate::world::position::Position;
+use std::fmt::Write as _;
 use std::fs;
 use std::path::Path;
 
@@ -51,6 +52,45 @@ pub fn load_monster_homes(path: &Path) -> Result<Vec<MonsterHome>, String> {
     Ok(homes)
 }
 
+/// Renders homes back into `monster.db` lines (`race x y z radius amount regen`),
+/// with the race name as a trailing comment when `name_of` knows it.
+pub fn format_monster_homes<'a>(
+    homes: &[MonsterHome],
+    name_of: impl Fn(i64) -> Option<&'a str>,
+) -> String {
+    let mut out = String::from("# race x y z radius amount regen\n");
+    for home in homes {
+        let _ = write!(
+            out,
+            "{} {} {} {} {} {} {}",
+            home.race_number,
+            home.position.x,
+            home.position.y,
+            home.position.z,
+            home.radius,
+            home.amount,
+            home.regen
+        );
+        match name_of(home.race_number) {
+            Some(name) => {
+                let _ = writeln!(out, "  # {}", name);
+            }
+            None => out.push('\n'),
+        }
+    }
+    out.push_str("0\n");
+    out
+}
+
+pub fn save_monster_homes<'a>(
+    path: &Path,
+    homes: &[MonsterHome],
+    name_of: impl Fn(i64) -> Option<&'a str>,
+) -> Result<(), String> {
+    fs::write(path, format_monster_homes(homes, name_of))
+        .map_err(|err| format!("failed to write {}: {}", path.display(), err))
+}
+
 fn strip_comment(line: &str) -> &str {
     if let Some(idx) = line.find('#') {
         &line[..idx]
@@ -99,3 +139,46 @@ fn parse_u8(value: Option<&str>, label: &str, line_no: usize) -> Result<u8, Stri
         .parse::<u8>()
         .map_err(|_| format!("monster.db line {} invalid {}", line_no, label))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn monster_homes_round_trip_through_monster_db_format() {
+        let homes = vec![
+            MonsterHome {
+                race_number: 5,
+                position: Position { x: 32000, y: 31000, z: 7 },
+                radius: 3,
+                amount: 4,
+                regen: 600,
+                act_monsters: 0,
+                timer: 0,
+            },
+            MonsterHome {
+                race_number: 21,
+                position: Position { x: 32010, y: 31005, z: 8 },
+                radius: 1,
+                amount: 1,
+                regen: 0,
+                act_monsters: 0,
+                timer: 0,
+            },
+        ];
+        let text = format_monster_homes(&homes, |race| (race == 5).then_some("Orc"));
+        assert!(text.contains("5 32000 31000 7 3 4 600  # Orc\n"));
+        assert!(text.ends_with("21 32010 31005 8 1 1 0\n0\n"));
+
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let path = std::env::temp_dir().join(format!("tibia-monster-db-test-{}.db", suffix));
+        save_monster_homes(&path, &homes, |_| None).expect("save");
+        let reloaded = load_monster_homes(&path).expect("load");
+        let _ = fs::remove_file(&path);
+        assert_eq!(reloaded, homes);
+    }
+}
@@ -5,7 +5,8 @@ use crate::scripting::monster::{load_monster_script, MonsterLootEntry, MonsterSc
 use crate::scripting::raid::{load_raid_script, RaidCount, RaidPosition, RaidScript, RaidSpawn};
 use crate::world::item_types::ItemTypeIndex;
 use crate::world::position::{Position, PositionDelta};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 use std::fs;
 use std::path::Path;
 
@@ -22,7 +23,34 @@ pub struct MonsterValidationReport {
     pub raid_files: usize,
     pub parsed_monsters: usize,
     pub parsed_raids: usize,
-    pub errors: Vec<String>,
+    pub errors: Vec<MonsterValidationError>,
+}
+
+/// Which files a validation error came from, so reloading monsters alone
+/// can ignore a broken raid script and the other way round.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonsterScriptKind {
+    Directory,
+    Monster,
+    Raid,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonsterValidationError {
+    pub kind: MonsterScriptKind,
+    pub message: String,
+}
+
+impl MonsterValidationError {
+    fn new(kind: MonsterScriptKind, message: String) -> Self {
+        Self { kind, message }
+    }
+}
+
+impl fmt::Display for MonsterValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -287,6 +315,16 @@ fn resolve_spawn(
     rng: &mut RaidRng,
 ) -> Result<RaidSpawnPlan, String> {
     let delay = spawn.delay.unwrap_or(0);
+    // Announcement entries carry only a message and spawn nothing.
+    if spawn.position.is_none() && spawn.race.is_none() && spawn.message.is_some() {
+        return Ok(RaidSpawnPlan {
+            delay,
+            race_number: 0,
+            race_name: None,
+            positions: Vec::new(),
+            message: spawn.message.clone(),
+        });
+    }
     let position = spawn
         .position
         .as_ref()
@@ -459,13 +497,14 @@ pub fn load_monsters(dir: &Path) -> Result<MonsterIndex, String> {
 
 pub fn validate_monsters(dir: &Path) -> MonsterValidationReport {
     let mut report = MonsterValidationReport::default();
+    let mut races = HashSet::new();
+    let mut boss_summons = Vec::new();
     let entries = match fs::read_dir(dir) {
         Ok(entries) => entries,
         Err(err) => {
-            report.errors.push(format!(
-                "failed to read monster dir {}: {}",
-                dir.display(),
-                err
+            report.errors.push(MonsterValidationError::new(
+                MonsterScriptKind::Directory,
+                format!("failed to read monster dir {}: {}", dir.display(), err),
             ));
             return report;
         }
@@ -475,9 +514,10 @@ pub fn validate_monsters(dir: &Path) -> MonsterValidationReport {
         let entry = match entry {
             Ok(entry) => entry,
             Err(err) => {
-                report
-                    .errors
-                    .push(format!("failed to read monster dir entry: {}", err));
+                report.errors.push(MonsterValidationError::new(
+                    MonsterScriptKind::Directory,
+                    format!("failed to read monster dir entry: {}", err),
+                ));
                 continue;
             }
         };
@@ -497,25 +537,57 @@ pub fn validate_monsters(dir: &Path) -> MonsterValidationReport {
             report.raid_files += 1;
             match load_raid_script(&path) {
                 Ok(_) => report.parsed_raids += 1,
-                Err(err) => report.errors.push(format!(
-                    "raid script {}: {}",
-                    path.display(),
-                    err
+                Err(err) => report.errors.push(MonsterValidationError::new(
+                    MonsterScriptKind::Raid,
+                    format!("raid script {}: {}", path.display(), err),
                 )),
             }
         } else {
             report.monster_files += 1;
-            match load_monster_script(&path) {
-                Ok(_) => report.parsed_monsters += 1,
-                Err(err) => report.errors.push(format!(
-                    "monster script {}: {}",
-                    path.display(),
-                    err
+            let script = match load_monster_script(&path) {
+                Ok(script) => script,
+                Err(err) => {
+                    report.errors.push(MonsterValidationError::new(
+                        MonsterScriptKind::Monster,
+                        format!("monster script {}: {}", path.display(), err),
+                    ));
+                    continue;
+                }
+            };
+            report.parsed_monsters += 1;
+            if let Some(race) = script.race_number() {
+                races.insert(race);
+            }
+            match script.boss() {
+                Ok(Some(boss)) => {
+                    for phase in &boss.phases {
+                        for (race, _) in &phase.summons {
+                            boss_summons.push((path.clone(), *race));
+                        }
+                    }
+                }
+                Ok(None) => {}
+                Err(err) => report.errors.push(MonsterValidationError::new(
+                    MonsterScriptKind::Monster,
+                    format!("monster script {}: {}", path.display(), err),
                 )),
             }
         }
     }
 
+    for (path, race) in boss_summons {
+        if !races.contains(&race) {
+            report.errors.push(MonsterValidationError::new(
+                MonsterScriptKind::Monster,
+                format!(
+                    "monster script {}: PhaseSummon names unknown race {}",
+                    path.display(),
+                    race
+                ),
+            ));
+        }
+    }
+
     report
 }
 
@@ -584,6 +656,24 @@ mod tests {
         };
         let err = resolve_raid_spawns(&index, &raid, 1).expect_err("resolve");
         assert!(err.contains("missing Position"));
+
+        let announcement = RaidScript {
+            raid_type: None,
+            interval: None,
+            spawns: vec![RaidSpawn {
+                delay: Some(0),
+                position: None,
+                spread: None,
+                race: None,
+                count: None,
+                message: Some("Orcs are approaching!".to_string()),
+                fields: Vec::new(),
+            }],
+            fields: Vec::new(),
+        };
+        let plans = resolve_raid_spawns(&index, &announcement, 1).expect("message only");
+        assert!(plans[0].positions.is_empty());
+        assert_eq!(plans[0].message.as_deref(), Some("Orcs are approaching!"));
     }
 
     #[test]
@@ -32,7 +32,7 @@ pub fn load_moveuse(path: &Path) -> Result<MoveUseDatabase, String> {
     parse_moveuse(&content)
 }
 
-fn parse_moveuse(content: &str) -> Result<MoveUseDatabase, String> {
+pub(crate) fn parse_moveuse(content: &str) -> Result<MoveUseDatabase, String> {
     let mut sections = Vec::new();
     let mut stack: Vec<usize> = Vec::new();
 
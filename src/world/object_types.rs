@@ -1,4 +1,6 @@
+use crate::combat::damage::DamageType;
 use crate::entities::item::ItemTypeId;
+use crate::entities::stats::DamageResistances;
 use std::collections::HashMap;
 use std::path::Path;
 
@@ -50,6 +52,10 @@ impl ObjectType {
         self.has_flag("Unpass")
     }
 
+    pub fn blocks_projectile(&self) -> bool {
+        self.has_flag("Unthrow")
+    }
+
     pub fn is_movable(&self) -> bool {
         self.has_flag("Take")
     }
@@ -63,6 +69,73 @@ impl ObjectType {
     pub fn floor_change_hint(&self) -> Option<FloorChange> {
         None
     }
+
+    /// Percent of each damage type a worn `Protection` item absorbs. The
+    /// classic `ProtectionDamageTypes` mask grants `DamageReduction` to every
+    /// listed type; `Absorb<Type>` attributes (e.g. `AbsorbIce`) override a
+    /// single type and may be negative.
+    pub fn protection_absorbs(&self) -> DamageResistances {
+        let mut percents = [0i16; DamageType::COUNT];
+        if !self.has_flag("Protection") {
+            return DamageResistances::from_array(percents);
+        }
+        let mask = self.attribute_u16("ProtectionDamageTypes").unwrap_or(0);
+        let reduction = self.attribute_u16("DamageReduction").unwrap_or(0).min(100) as i16;
+        for (index, damage_type) in DamageType::ALL.into_iter().enumerate() {
+            if mask & legacy_protection_mask(damage_type) != 0 {
+                percents[index] = reduction;
+            }
+            let key = format!("Absorb{}", damage_type.name().replace(' ', ""));
+            if let Some(value) = self
+                .attribute(&key)
+                .and_then(|value| value.trim().parse::<i16>().ok())
+            {
+                percents[index] = value.clamp(-100, 100);
+            }
+        }
+        DamageResistances::from_array(percents)
+    }
+
+    /// Charges left on a protection item with `TotalUses`; items that were
+    /// never worn have all of them.
+    pub fn remaining_charges(&self, remaining_uses: Option<u16>) -> Option<u16> {
+        let total_uses = self.attribute_u16("TotalUses")?;
+        Some(remaining_uses.filter(|uses| *uses > 0).unwrap_or(total_uses))
+    }
+}
+
+/// Bits of the `ProtectionDamageTypes` mask, which predates the damage type
+/// numbering used elsewhere.
+fn legacy_protection_mask(damage_type: DamageType) -> u16 {
+    match damage_type {
+        DamageType::Physical => 1,
+        DamageType::Earth => 2,
+        DamageType::Fire => 4,
+        DamageType::Energy => 8,
+        DamageType::LifeDrain => 0x100,
+        DamageType::ManaDrain => 0x200,
+        _ => 0,
+    }
+}
+
+/// Gold pieces one item of this type is worth, for types whose `Meaning`
+/// marks them as coins.
+pub fn money_value_for_type(
+    object_types: &ObjectTypeIndex,
+    type_id: ItemTypeId,
+) -> Option<u32> {
+    let object = object_types.get(type_id)?;
+    money_value_from_object(object)
+}
+
+pub fn money_value_from_object(object: &ObjectType) -> Option<u32> {
+    let meaning = object.attribute_u16("Meaning")?;
+    match meaning {
+        1 => Some(1),
+        2 => Some(100),
+        3 => Some(10_000),
+        _ => None,
+    }
 }
 
 #[derive(Debug, Default, Clone)]
@@ -296,4 +369,17 @@ mod tests {
         }
         let _ = parse_object_types(&content);
     }
+
+    #[test]
+    fn protection_absorbs_merge_mask_and_per_type_attributes() {
+        let content = "TypeID = 300\nName = \"ring\"\nFlags = {Protection,Clothes}\n\
+            Attributes = {ProtectionDamageTypes=7,DamageReduction=30,AbsorbFire=-10,AbsorbIce=15}\n";
+        let index = parse_object_types(content).expect("parse");
+        let absorbs = index.get(ItemTypeId(300)).expect("ring").protection_absorbs();
+        assert_eq!(absorbs.percent(DamageType::Physical), 30);
+        assert_eq!(absorbs.percent(DamageType::Earth), 30);
+        assert_eq!(absorbs.percent(DamageType::Fire), -10);
+        assert_eq!(absorbs.percent(DamageType::Ice), 15);
+        assert_eq!(absorbs.percent(DamageType::Energy), 0);
+    }
 }
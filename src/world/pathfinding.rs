@@ -0,0 +1,215 @@
+use crate::world::position::{Direction, Position};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+const PATH_DIRECTIONS: [Direction; 8] = [
+    Direction::North,
+    Direction::East,
+    Direction::South,
+    Direction::West,
+    Direction::Northeast,
+    Direction::Northwest,
+    Direction::Southeast,
+    Direction::Southwest,
+];
+
+/// Diagonal steps take three times as long as straight ones, so paths
+/// prefer them only when they save real walking time.
+const DIAGONAL_COST_FACTOR: u32 = 3;
+
+/// Bounds for one search: how many tiles may be expanded and how far from
+/// the start the path may stray (in tiles, Chebyshev distance).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PathLimits {
+    pub max_nodes: usize,
+    pub max_radius: u16,
+    /// Cost of the cheapest tile, used to keep the heuristic admissible.
+    pub min_step_cost: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathSearch {
+    pub steps: Option<Vec<Direction>>,
+    pub expanded: usize,
+}
+
+/// Bounded A* on a single floor. `step_cost` returns the cost of entering a
+/// tile or `None` when it cannot be entered; the search stops at the first
+/// tile accepted by `is_goal`. `target` only steers the heuristic, so a goal
+/// of "next to the target" works for occupied targets.
+pub fn find_path(
+    from: Position,
+    target: Position,
+    limits: PathLimits,
+    is_goal: impl Fn(Position) -> bool,
+    mut step_cost: impl FnMut(Position) -> Option<u32>,
+) -> PathSearch {
+    let mut search = PathSearch {
+        steps: None,
+        expanded: 0,
+    };
+    if from.z != target.z {
+        return search;
+    }
+    if is_goal(from) {
+        search.steps = Some(Vec::new());
+        return search;
+    }
+    let heuristic = |position: Position| {
+        let dx = u32::from(position.x.abs_diff(target.x));
+        let dy = u32::from(position.y.abs_diff(target.y));
+        dx.max(dy).saturating_sub(1) * limits.min_step_cost
+    };
+    let mut open = BinaryHeap::new();
+    let mut best_cost: HashMap<Position, u32> = HashMap::new();
+    let mut came_from: HashMap<Position, (Position, Direction)> = HashMap::new();
+    let mut closed: HashSet<Position> = HashSet::new();
+    let mut sequence = 0u32;
+    best_cost.insert(from, 0);
+    open.push(Reverse((heuristic(from), sequence, from.x, from.y)));
+    while let Some(Reverse((_, _, x, y))) = open.pop() {
+        let position = Position { x, y, z: from.z };
+        if !closed.insert(position) {
+            continue;
+        }
+        if is_goal(position) {
+            search.steps = Some(rebuild_path(&came_from, from, position));
+            return search;
+        }
+        if search.expanded >= limits.max_nodes {
+            break;
+        }
+        search.expanded += 1;
+        let cost = best_cost.get(&position).copied().unwrap_or(u32::MAX);
+        for direction in PATH_DIRECTIONS {
+            let Some(next) = position.step(direction) else {
+                continue;
+            };
+            if next.x.abs_diff(from.x) > limits.max_radius
+                || next.y.abs_diff(from.y) > limits.max_radius
+            {
+                continue;
+            }
+            let Some(tile_cost) = step_cost(next) else {
+                continue;
+            };
+            let tile_cost = if direction.is_diagonal() {
+                tile_cost.saturating_mul(DIAGONAL_COST_FACTOR)
+            } else {
+                tile_cost
+            };
+            let next_cost = cost.saturating_add(tile_cost.max(1));
+            if best_cost
+                .get(&next)
+                .is_some_and(|known| *known <= next_cost)
+            {
+                continue;
+            }
+            best_cost.insert(next, next_cost);
+            came_from.insert(next, (position, direction));
+            sequence = sequence.wrapping_add(1);
+            open.push(Reverse((
+                next_cost.saturating_add(heuristic(next)),
+                sequence,
+                next.x,
+                next.y,
+            )));
+        }
+    }
+    search
+}
+
+fn rebuild_path(
+    came_from: &HashMap<Position, (Position, Direction)>,
+    from: Position,
+    goal: Position,
+) -> Vec<Direction> {
+    let mut steps = Vec::new();
+    let mut position = goal;
+    while position != from {
+        let Some((previous, direction)) = came_from.get(&position) else {
+            break;
+        };
+        steps.push(*direction);
+        position = *previous;
+    }
+    steps.reverse();
+    steps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(x: u16, y: u16) -> Position {
+        Position { x, y, z: 7 }
+    }
+
+    fn limits() -> PathLimits {
+        PathLimits {
+            max_nodes: 500,
+            max_radius: 10,
+            min_step_cost: 100,
+        }
+    }
+
+    fn walk(from: Position, steps: &[Direction]) -> Position {
+        steps
+            .iter()
+            .fold(from, |position, direction| position.step(*direction).unwrap())
+    }
+
+    #[test]
+    fn path_goes_around_a_wall() {
+        let walls: HashSet<Position> = (8..=12).map(|y| pos(12, y)).collect();
+        let from = pos(10, 10);
+        let target = pos(14, 10);
+        let search = find_path(
+            from,
+            target,
+            limits(),
+            |position| position == target,
+            |position| (!walls.contains(&position)).then_some(100),
+        );
+        let steps = search.steps.expect("path");
+        assert_eq!(walk(from, &steps), target);
+        let mut position = from;
+        for direction in steps {
+            position = position.step(direction).unwrap();
+            assert!(!walls.contains(&position));
+        }
+    }
+
+    #[test]
+    fn search_respects_node_budget_and_goal_adjacency() {
+        let from = pos(10, 10);
+        let target = pos(10, 14);
+        let adjacent = find_path(
+            from,
+            target,
+            limits(),
+            |position| {
+                position != target && position.x.abs_diff(10) <= 1 && position.y.abs_diff(14) <= 1
+            },
+            |position| (position != target).then_some(100),
+        );
+        assert_eq!(adjacent.steps.map(|steps| steps.len()), Some(3));
+
+        let enclosed: HashSet<Position> = (9..=11)
+            .flat_map(|x| (13..=15).map(move |y| pos(x, y)))
+            .filter(|position| *position != target)
+            .collect();
+        let blocked = find_path(
+            from,
+            target,
+            PathLimits {
+                max_nodes: 50,
+                ..limits()
+            },
+            |position| position == target,
+            |position| (!enclosed.contains(&position)).then_some(100),
+        );
+        assert!(blocked.steps.is_none());
+        assert_eq!(blocked.expanded, 50);
+    }
+}
@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const SECS_PER_MINUTE: u64 = 60;
+const SECS_PER_DAY: u64 = 86_400;
+const SECS_PER_WEEK: u64 = 7 * SECS_PER_DAY;
+/// 1970-01-01 was a Thursday; weekdays count from Monday = 0.
+const EPOCH_WEEKDAY: u64 = 3;
+const WEEKDAY_NAMES: [&str; 7] = [
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+    "sunday",
+];
+
+/// Raid type whose runs never overlap another raid.
+pub const BIG_RAID_TYPE: &str = "BigRaid";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RaidCalendarConfig {
+    /// Offset of the calendar times from UTC, in minutes. The offset is
+    /// fixed: daylight saving time is not applied, so a server that follows
+    /// DST has to change it twice a year.
+    pub utc_offset_minutes: i32,
+    /// Interval raids start somewhere in this share of their interval after
+    /// they become due.
+    pub interval_window_percent: u32,
+    /// Fixed start times; raids listed here ignore their script `Interval`.
+    pub raids: Vec<RaidCalendarEntry>,
+}
+
+impl Default for RaidCalendarConfig {
+    fn default() -> Self {
+        Self {
+            utc_offset_minutes: 0,
+            interval_window_percent: 25,
+            raids: Vec::new(),
+        }
+    }
+}
+
+impl RaidCalendarConfig {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+        let config: Self = serde_yaml::from_str(&content)
+            .map_err(|err| format!("failed to parse {}: {}", path.display(), err))?;
+        for entry in &config.raids {
+            parse_raid_time(&entry.at)
+                .map_err(|err| format!("{}: raid {}: {}", path.display(), entry.raid, err))?;
+        }
+        Ok(config)
+    }
+
+    /// Calendar entries whose raid is not one of the loaded raid scripts.
+    pub fn unknown_raids(&self, known: impl Fn(&str) -> bool) -> Vec<&str> {
+        let mut unknown: Vec<&str> = Vec::new();
+        for entry in &self.raids {
+            let raid = entry.raid.as_str();
+            if !known(raid) && !unknown.contains(&raid) {
+                unknown.push(raid);
+            }
+        }
+        unknown
+    }
+
+    /// Start rules for one raid: its calendar entries, or its script
+    /// interval when the calendar does not mention it.
+    pub fn rules_for(&self, raid: &str, interval_secs: Option<i64>) -> Vec<RaidRule> {
+        let calendar: Vec<RaidRule> = self
+            .raids
+            .iter()
+            .filter(|entry| entry.raid == raid)
+            .filter_map(|entry| {
+                let time = parse_raid_time(&entry.at).ok()?;
+                Some(RaidRule::Calendar {
+                    time,
+                    window_secs: entry.window_minutes.saturating_mul(SECS_PER_MINUTE),
+                })
+            })
+            .collect();
+        if !calendar.is_empty() {
+            return calendar;
+        }
+        match interval_secs {
+            Some(seconds) if seconds > 0 => {
+                let seconds = seconds as u64;
+                vec![RaidRule::Interval {
+                    seconds,
+                    window_secs: seconds
+                        .saturating_mul(u64::from(self.interval_window_percent))
+                        / 100,
+                }]
+            }
+            _ => Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RaidCalendarEntry {
+    /// Raid script name (the `.evt` file stem).
+    pub raid: String,
+    /// "Saturday 20:00", "sat 20:00", "daily 18:30" or just "18:30".
+    pub at: String,
+    /// The raid starts at a random point up to this many minutes late.
+    #[serde(default)]
+    pub window_minutes: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RaidTime {
+    /// Monday = 0; `None` repeats every day.
+    pub weekday: Option<u8>,
+    pub minute_of_day: u32,
+}
+
+impl RaidTime {
+    /// First occurrence strictly after `after` (unix seconds).
+    pub fn next_after(self, after: u64, utc_offset_minutes: i32) -> u64 {
+        let offset = i64::from(utc_offset_minutes) * SECS_PER_MINUTE as i64;
+        let local = (after as i64).saturating_add(offset).max(0) as u64;
+        let day_start = local - local % SECS_PER_DAY;
+        let mut candidate = day_start + u64::from(self.minute_of_day) * SECS_PER_MINUTE;
+        if let Some(weekday) = self.weekday {
+            let today = (local / SECS_PER_DAY + EPOCH_WEEKDAY) % 7;
+            let days_ahead = (u64::from(weekday) + 7 - today) % 7;
+            candidate += days_ahead * SECS_PER_DAY;
+        }
+        let period = if self.weekday.is_some() {
+            SECS_PER_WEEK
+        } else {
+            SECS_PER_DAY
+        };
+        if candidate <= local {
+            candidate += period;
+        }
+        (candidate as i64 - offset).max(0) as u64
+    }
+
+    pub fn describe(self) -> String {
+        let day = match self.weekday {
+            Some(weekday) => {
+                let name = WEEKDAY_NAMES[usize::from(weekday)];
+                let mut chars = name.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            }
+            None => "daily".to_string(),
+        };
+        format!(
+            "{} {:02}:{:02}",
+            day,
+            self.minute_of_day / 60,
+            self.minute_of_day % 60
+        )
+    }
+}
+
+pub fn parse_raid_time(text: &str) -> Result<RaidTime, String> {
+    let mut parts: Vec<&str> = text.split_whitespace().collect();
+    if parts.first().is_some_and(|word| word.eq_ignore_ascii_case("every")) {
+        parts.remove(0);
+    }
+    let (day, clock) = match parts.as_slice() {
+        [clock] => (None, *clock),
+        [day, clock] => (Some(*day), *clock),
+        _ => return Err(format!("invalid raid time '{}'", text)),
+    };
+    let weekday = match day {
+        None => None,
+        Some(day) if day.eq_ignore_ascii_case("daily") => None,
+        Some(day) => {
+            let day = day.to_ascii_lowercase();
+            let index = WEEKDAY_NAMES
+                .iter()
+                .position(|name| day.len() >= 3 && name.starts_with(day.as_str()))
+                .ok_or_else(|| format!("invalid raid weekday '{}'", day))?;
+            Some(index as u8)
+        }
+    };
+    let (hours, minutes) = clock
+        .split_once(':')
+        .ok_or_else(|| format!("invalid raid time of day '{}'", clock))?;
+    let hours: u32 = hours
+        .parse()
+        .ok()
+        .filter(|hours| *hours < 24)
+        .ok_or_else(|| format!("invalid raid hour '{}'", hours))?;
+    let minutes: u32 = minutes
+        .parse()
+        .ok()
+        .filter(|minutes| *minutes < 60)
+        .ok_or_else(|| format!("invalid raid minute '{}'", minutes))?;
+    Ok(RaidTime {
+        weekday,
+        minute_of_day: hours * 60 + minutes,
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaidRule {
+    Interval { seconds: u64, window_secs: u64 },
+    Calendar { time: RaidTime, window_secs: u64 },
+}
+
+impl RaidRule {
+    /// Next start after a run at `last_run` (or a fresh start at `now`).
+    /// `roll(n)` picks the delay into the window, in `0..=n`. Runs missed
+    /// while the server was down start right away, unless their window has
+    /// already closed.
+    pub fn next_start(
+        self,
+        now: u64,
+        last_run: Option<u64>,
+        utc_offset_minutes: i32,
+        mut roll: impl FnMut(u64) -> u64,
+    ) -> u64 {
+        match self {
+            RaidRule::Interval {
+                seconds,
+                window_secs,
+            } => {
+                let due = last_run.unwrap_or(now).saturating_add(seconds).max(now);
+                due.saturating_add(roll(window_secs))
+            }
+            RaidRule::Calendar { time, window_secs } => {
+                let from = last_run.map_or(now, |last| last.max(now.saturating_sub(window_secs)));
+                let mut start = time.next_after(from, utc_offset_minutes);
+                if start.saturating_add(window_secs) < now {
+                    start = time.next_after(now, utc_offset_minutes);
+                }
+                start.saturating_add(roll(window_secs)).max(now)
+            }
+        }
+    }
+
+    pub fn describe(self) -> String {
+        match self {
+            RaidRule::Interval { seconds, .. } => format!("every {}", format_duration(seconds)),
+            RaidRule::Calendar { time, .. } => time.describe(),
+        }
+    }
+}
+
+/// A scheduled raid as shown by `!raids`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpcomingRaid {
+    pub name: String,
+    pub at: u64,
+    pub rules: Vec<RaidRule>,
+    pub last_run: Option<u64>,
+}
+
+impl UpcomingRaid {
+    pub fn summary(&self, now: u64) -> String {
+        let rules: Vec<String> = self.rules.iter().map(|rule| rule.describe()).collect();
+        let last = match self.last_run {
+            Some(last) => format!("{} ago", format_duration(now.saturating_sub(last))),
+            None => "never".to_string(),
+        };
+        format!(
+            "{} in {} ({}; last run {})",
+            self.name,
+            format_duration(self.at.saturating_sub(now)),
+            rules.join(", "),
+            last
+        )
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    let days = seconds / SECS_PER_DAY;
+    let hours = seconds % SECS_PER_DAY / 3600;
+    let minutes = seconds % 3600 / SECS_PER_MINUTE;
+    if days > 0 {
+        format!("{}d{}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h{}m", hours, minutes)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Saturday 2024-01-06 12:00 UTC.
+    const SATURDAY_NOON: u64 = 1_704_542_400;
+
+    #[test]
+    fn parse_raid_time_accepts_weekdays_and_daily_times() {
+        assert_eq!(
+            parse_raid_time("every Saturday 20:00").unwrap(),
+            RaidTime {
+                weekday: Some(5),
+                minute_of_day: 20 * 60
+            }
+        );
+        assert_eq!(
+            parse_raid_time("mon 07:30").unwrap(),
+            RaidTime {
+                weekday: Some(0),
+                minute_of_day: 7 * 60 + 30
+            }
+        );
+        assert_eq!(parse_raid_time("18:05").unwrap().weekday, None);
+        assert!(parse_raid_time("someday 10:00").is_err());
+        assert!(parse_raid_time("sat 24:00").is_err());
+    }
+
+    #[test]
+    fn calendar_times_roll_to_the_next_occurrence() {
+        let saturday_evening = parse_raid_time("Saturday 20:00").unwrap();
+        assert_eq!(
+            saturday_evening.next_after(SATURDAY_NOON, 0),
+            SATURDAY_NOON + 8 * 3600
+        );
+        assert_eq!(
+            saturday_evening.next_after(SATURDAY_NOON + 8 * 3600, 0),
+            SATURDAY_NOON + 8 * 3600 + SECS_PER_WEEK
+        );
+        // 20:00 at UTC+2 is 18:00 UTC.
+        assert_eq!(
+            saturday_evening.next_after(SATURDAY_NOON, 120),
+            SATURDAY_NOON + 6 * 3600
+        );
+        let morning = parse_raid_time("daily 09:00").unwrap();
+        assert_eq!(
+            morning.next_after(SATURDAY_NOON, 0),
+            SATURDAY_NOON + 21 * 3600
+        );
+    }
+
+    #[test]
+    fn rules_catch_up_missed_runs_inside_their_window() {
+        let rule = RaidRule::Calendar {
+            time: parse_raid_time("Saturday 11:50").unwrap(),
+            window_secs: 30 * 60,
+        };
+        let last_week = SATURDAY_NOON - SECS_PER_WEEK;
+        assert_eq!(rule.next_start(SATURDAY_NOON, Some(last_week), 0, |_| 0), SATURDAY_NOON);
+        let late = SATURDAY_NOON + 3600;
+        assert_eq!(
+            rule.next_start(late, Some(last_week), 0, |_| 0),
+            SATURDAY_NOON - 600 + SECS_PER_WEEK
+        );
+
+        let interval = RaidRule::Interval {
+            seconds: 3600,
+            window_secs: 900,
+        };
+        assert_eq!(interval.next_start(SATURDAY_NOON, None, 0, |window| window), SATURDAY_NOON + 4500);
+        assert_eq!(
+            interval.next_start(SATURDAY_NOON, Some(SATURDAY_NOON - 1800), 0, |_| 0),
+            SATURDAY_NOON + 1800
+        );
+    }
+
+    #[test]
+    fn config_prefers_calendar_entries_over_script_intervals() {
+        let config: RaidCalendarConfig = serde_yaml::from_str(
+            "raids:\n  - raid: orcs\n    at: Saturday 20:00\n    window_minutes: 30\n",
+        )
+        .unwrap();
+        assert_eq!(config.interval_window_percent, 25);
+        assert_eq!(
+            config.rules_for("orcs", Some(600)),
+            vec![RaidRule::Calendar {
+                time: parse_raid_time("sat 20:00").unwrap(),
+                window_secs: 1800,
+            }]
+        );
+        assert_eq!(
+            config.rules_for("rats", Some(600)),
+            vec![RaidRule::Interval {
+                seconds: 600,
+                window_secs: 150,
+            }]
+        );
+        assert!(config.rules_for("rats", None).is_empty());
+        assert_eq!(config.unknown_raids(|raid| raid == "orcs"), Vec::<&str>::new());
+        assert_eq!(config.unknown_raids(|raid| raid == "rats"), vec!["orcs"]);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::world::position::Position;
+
+/// Tiles a projectile passes from `from` to `to` on one floor, walked with
+/// Bresenham's algorithm. The origin is excluded and the destination is the
+/// last entry.
+pub fn sight_line(from: Position, to: Position) -> Vec<Position> {
+    let mut positions = Vec::new();
+    if from.z != to.z {
+        return positions;
+    }
+    let (mut x, mut y) = (i32::from(from.x), i32::from(from.y));
+    let (dest_x, dest_y) = (i32::from(to.x), i32::from(to.y));
+    let dx = (dest_x - x).abs();
+    let dy = -(dest_y - y).abs();
+    let step_x = if x < dest_x { 1 } else { -1 };
+    let step_y = if y < dest_y { 1 } else { -1 };
+    let mut error = dx + dy;
+    while x != dest_x || y != dest_y {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+        positions.push(Position {
+            x: x as u16,
+            y: y as u16,
+            z: from.z,
+        });
+    }
+    positions
+}
+
+/// Whether a projectile can fly from `from` to `to`: both must share a floor
+/// and no tile strictly between them may block projectiles. A blocked line
+/// is retried from the other end so sight is symmetric.
+pub fn has_line_of_sight(
+    from: Position,
+    to: Position,
+    blocks_projectile: impl Fn(Position) -> bool,
+) -> bool {
+    if from.z != to.z {
+        return false;
+    }
+    let clear = |a: Position, b: Position| {
+        sight_line(a, b)
+            .into_iter()
+            .filter(|position| *position != b)
+            .all(|position| !blocks_projectile(position))
+    };
+    clear(from, to) || clear(to, from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(x: u16, y: u16) -> Position {
+        Position { x, y, z: 7 }
+    }
+
+    #[test]
+    fn sight_line_walks_every_tile_to_the_target() {
+        assert_eq!(
+            sight_line(pos(10, 10), pos(13, 11)),
+            vec![pos(11, 10), pos(12, 11), pos(13, 11)]
+        );
+        assert_eq!(sight_line(pos(10, 10), pos(10, 8)), vec![pos(10, 9), pos(10, 8)]);
+        assert!(sight_line(pos(10, 10), pos(10, 10)).is_empty());
+        assert!(sight_line(pos(10, 10), Position { x: 11, y: 10, z: 6 }).is_empty());
+    }
+
+    #[test]
+    fn walls_between_block_sight_but_not_at_the_ends() {
+        let wall = pos(12, 10);
+        let blocks = |position: Position| position == wall;
+        assert!(!has_line_of_sight(pos(10, 10), pos(14, 10), blocks));
+        assert!(has_line_of_sight(pos(10, 10), pos(12, 10), blocks));
+        assert!(has_line_of_sight(pos(10, 10), pos(12, 13), blocks));
+        assert!(!has_line_of_sight(
+            pos(10, 10),
+            Position { x: 10, y: 10, z: 6 },
+            |_| false
+        ));
+    }
+}
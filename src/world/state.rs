@@ -1,21 +1,34 @@
 use crate::world::map::{Map, MapItem, SectorBounds, SectorCoord, Tile};
+use crate::world::events::{
+    EventAction, EventBus, EventScript, KilledCreature, WorldEvent, WorldEventHandler,
+    WorldEventKind, MAX_CHAINED_EVENTS,
+};
 use crate::world::map_dat::MapDat;
 use crate::world::mem_dat::MemDat;
 use crate::world::monster_homes::{load_monster_homes, MonsterHome};
 use crate::world::monsters::{
-    LootRng, MonsterFlags, MonsterIndex, MonsterLootTable, MonsterSkills, RaidSpawnPlan,
+    LootRng, MonsterFlags, MonsterIndex, MonsterLootTable, MonsterScriptKind, MonsterSkills,
+    RaidSpawnPlan,
 };
+use crate::world::moderation::{TalkCheck, WordFilter};
 use crate::world::moveuse::{MoveUseDatabase, MoveUseExpr, MoveUseRule, MoveUseSection};
-use crate::world::npc::NpcIndex;
+use crate::world::npc::{NpcDefinition, NpcIndex};
 use crate::scripting::npc::{
     NpcAction, NpcBehaviourRule, NpcCompareOp, NpcCondition, NpcScript, NpcTradeEntry,
 };
-use crate::world::object_types::{FloorChange, ObjectType, ObjectTypeIndex};
+use crate::world::object_types::{
+    money_value_for_type, money_value_from_object, FloorChange, ObjectType, ObjectTypeIndex,
+};
+use crate::telemetry::audit::{self, AuditEvent, AuditItems, TransferKind};
+use crate::telemetry::detection::{DetectionConfig, DetectionFlag, DetectionMonitor, InputKind};
 use crate::telemetry::logging;
 use crate::world::item_types::ItemTypeIndex;
 use crate::world::circles::Circles;
 use crate::world::housing::{House, HouseArea, HouseOwner};
 use crate::world::area::{circle_positions, cone_positions, line_positions};
+use crate::world::pathfinding::{find_path, PathLimits, PathSearch};
+use crate::world::raids::{RaidCalendarConfig, RaidRule, UpcomingRaid, BIG_RAID_TYPE};
+use crate::world::sight::has_line_of_sight;
 use crate::world::position::{Direction, Position, PositionDelta};
 use crate::entities::inventory::{Inventory, InventorySlot, INVENTORY_SLOTS};
 use crate::entities::item::{ItemAttribute, ItemId, ItemKind, ItemStack, ItemTypeId};
@@ -28,7 +41,9 @@ use crate::entities::effects::{
     SpeedEffect,
     StrengthEffect,
 };
-use crate::entities::player::{ActiveDepot, OpenContainer, PlayerId, PlayerState};
+use crate::entities::player::{
+    ActiveDepot, OpenContainer, PlayerId, PlayerState, SkullState, BLESSING_COUNT,
+};
 use crate::entities::spells::{
     spell_word_tokens,
     Spell,
@@ -73,7 +88,10 @@ use crate::entities::skills::{
 use crate::entities::stats::Stats;
 use crate::combat::conditions::{ConditionKind, ConditionTick};
 use crate::combat::damage::{compute_damage, DamageScaleFlags, DamageType};
-use crate::combat::rules::CombatRules;
+use crate::combat::distance::distance_hit_chance;
+use crate::combat::ledger::{DamageCredit, DamageLedger, DamageSource};
+use crate::combat::rules::{pvp_kill_experience, CombatRules};
+use crate::combat::threat::{healing_threat, ThreatTable, TARGET_SWITCH_PERCENT};
 use crate::combat::spells::{
     SpellCastReport,
     SpellCastMessage,
@@ -84,11 +102,22 @@ use crate::combat::spells::{
     SpellTargetId,
     SpellTextEffect,
 };
-use crate::scripting::monster::{MonsterSpell, MonsterSpellEffect, MonsterSpellTarget};
+use crate::scripting::monster::{
+    MonsterBossScript, MonsterEnrage, MonsterPhase, MonsterSpell, MonsterSpellEffect,
+    MonsterSpellTarget,
+};
+use crate::scripting::runtime::{
+    ScriptCommand, ScriptHook, ScriptItem, ScriptPlayer, ScriptRun, ScriptRuntime,
+};
 use crate::scripting::value::{split_top_level, ScriptValue};
 use crate::persistence::store::{SaveStore, skill_rows_from_player};
+use crate::persistence::accounts::{AccountRegistry, BanList};
+use crate::persistence::deaths::{DeathRecord, DeathStore};
+use crate::persistence::raids::RaidHistory;
+use crate::persistence::tickets::{TicketKind, TicketOutcome, TicketStore};
 use std::path::{Path, PathBuf};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use crate::world::time::{Cooldown, GameClock, GameTick};
 
@@ -110,6 +139,10 @@ pub struct WorldState {
     next_status_effect_tick: Option<GameTick>,
     pub raid_events: Vec<RaidSpawnEvent>,
     raid_schedules: HashMap<String, RaidSchedule>,
+    /// Set once `raid_schedules` was built from the loaded raid scripts.
+    raid_schedules_built: bool,
+    pub raid_calendar: RaidCalendarConfig,
+    raid_history: RaidHistory,
     pub house_areas: Option<Vec<HouseArea>>,
     pub houses: Option<Vec<House>>,
     pub house_owners: Option<Vec<HouseOwner>>,
@@ -129,14 +162,54 @@ pub struct WorldState {
     pending_turn_updates: Vec<PendingTurnUpdate>,
     pending_outfit_updates: Vec<PendingOutfitUpdate>,
     pending_map_refreshes: Vec<PendingMapRefresh>,
+    pending_missile_effects: Vec<PendingMissileEffect>,
     pending_buddy_updates: Vec<PendingBuddyUpdate>,
     pending_party_updates: Vec<PendingPartyUpdate>,
+    pending_skull_updates: Vec<PendingSkullUpdate>,
     pending_trade_updates: Vec<PendingTradeUpdate>,
     pending_container_closes: HashMap<PlayerId, Vec<u8>>,
     pending_container_refresh: HashSet<PlayerId>,
     shop_sessions: HashMap<PlayerId, ShopSession>,
     request_queue: Vec<RequestQueueEntry>,
     request_queue_players: HashSet<PlayerId>,
+    pub tickets: TicketStore,
+    pub deaths: DeathStore,
+    pending_forced_logouts: HashSet<PlayerId>,
+    /// Monster corpses that only their killer or the killer's party may open
+    /// until the protection runs out.
+    corpse_owners: HashMap<ItemId, CorpseOwner>,
+    /// Magic fields laid by a player or monster, so damage they deal is
+    /// credited to whoever laid them.
+    field_owners: HashMap<ItemId, FieldOwner>,
+    /// The latest clock the world was ticked with. Damage ledgers are timed
+    /// against it, including on paths that do not carry a clock.
+    combat_clock: Option<GameClock>,
+    /// Unjustified attacks as (attacker, victim), kept until the white skull
+    /// timer of that attack runs out.
+    pvp_aggressions: HashMap<(PlayerId, PlayerId), GameTick>,
+    /// Unwalked remainder of each creature's last path search.
+    creature_paths: HashMap<CreatureId, CachedPath>,
+    /// Phase and enrage progress of spawned bosses.
+    boss_states: HashMap<CreatureId, BossState>,
+    /// Who each monster is angry at, and how much.
+    monster_threat: HashMap<CreatureId, ThreatTable>,
+    /// Monsters walking back to their spawn home after being pulled out of
+    /// its area; they ignore players until they arrive.
+    monster_leashing: HashSet<CreatureId>,
+    /// Sectors with a player nearby, and when one was last seen there.
+    /// Monsters outside them are dormant and skip their AI.
+    awake_sectors: HashMap<SectorCoord, GameTick>,
+    event_bus: EventBus,
+    event_scripts: Vec<EventScript>,
+    scripts: Option<ScriptRuntime>,
+    /// Points NPCs were sent to walk to, outside their usual wandering.
+    npc_walk_targets: HashMap<CreatureId, Position>,
+    /// Map uses re-issued once a walk-to started for an out-of-reach object
+    /// arrives.
+    walk_to_uses: HashMap<PlayerId, (Position, ItemTypeId)>,
+    pub word_filter: WordFilter,
+    pub audit_items: AuditItems,
+    pub detection: DetectionMonitor,
     private_channels: HashMap<u16, PrivateChannel>,
     private_channel_owners: HashMap<PlayerId, u16>,
     next_private_channel_id: u16,
@@ -210,6 +283,8 @@ struct PrivateChannel {
     invited: HashSet<PlayerId>,
 }
 
+/// Money (in gold) a single drop or pickup must be worth to be audited.
+const AUDIT_MONEY_THRESHOLD: u32 = 1_000;
 const PARTY_SHIELD_NONE: u8 = 0;
 const PARTY_SHIELD_WHITE_YELLOW: u8 = 1;
 const PARTY_SHIELD_WHITE_BLUE: u8 = 2;
@@ -257,6 +332,18 @@ struct PendingPartyUpdate {
     update: PartyMarkUpdate,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkullUpdate {
+    pub target_id: PlayerId,
+    pub skull: SkullState,
+}
+
+#[derive(Debug, Clone)]
+struct PendingSkullUpdate {
+    player_id: PlayerId,
+    update: SkullUpdate,
+}
+
 #[derive(Debug, Clone)]
 pub enum TradeUpdate {
     Offer {
@@ -277,6 +364,9 @@ struct PendingTradeUpdate {
 pub(crate) struct RequestQueueEntry {
     pub(crate) player_id: PlayerId,
     pub(crate) name: String,
+    /// `None` when the ticket could not be written; the request is still
+    /// queued for the gamemasters.
+    pub(crate) ticket_id: Option<u32>,
 }
 
 #[derive(Debug, Clone)]
@@ -360,6 +450,91 @@ struct ListEditSession {
     max_len: usize,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BossState {
+    script: MonsterBossScript,
+    /// Index of the next phase to enter.
+    next_phase: usize,
+    engaged_at: Option<GameTick>,
+    enraged: bool,
+    immune_until: Option<GameTick>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CachedPath {
+    goal: Position,
+    steps: VecDeque<Direction>,
+}
+
+/// Who a path is planned for; monsters keep out of protection zones and
+/// away from fields they avoid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PathWalker {
+    Monster,
+    Npc,
+    Player,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CorpseOwner {
+    player_id: PlayerId,
+    party_id: Option<u32>,
+    expires_at: u64,
+}
+
+/// Why a container could not be opened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ContainerOpenError {
+    /// The container is a corpse still reserved for its killer's side; the
+    /// player has already been told.
+    NotOwner,
+    Failed(String),
+}
+
+impl fmt::Display for ContainerOpenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerOpenError::NotOwner => f.write_str(LOOT_NOT_OWNER_MESSAGE),
+            ContainerOpenError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for ContainerOpenError {
+    fn from(message: String) -> Self {
+        ContainerOpenError::Failed(message)
+    }
+}
+
+/// Why using a map object failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UseObjectError {
+    /// The object is not next to the player; the caller may walk there first.
+    OutOfReach,
+    Failed(String),
+}
+
+impl fmt::Display for UseObjectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UseObjectError::OutOfReach => f.write_str(OBJECT_OUT_OF_REACH),
+            UseObjectError::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for UseObjectError {
+    fn from(message: String) -> Self {
+        UseObjectError::Failed(message)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FieldOwner {
+    source: DamageSource,
+    positions: Vec<Position>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MonsterInstance {
     pub id: CreatureId,
@@ -388,7 +563,7 @@ pub struct MonsterInstance {
     pub flee_threshold: u32,
     pub lose_target_distance: u16,
     pub target: Option<PlayerId>,
-    pub damage_by: HashMap<PlayerId, u32>,
+    pub damage_ledger: DamageLedger,
     pub speed: u16,
     pub outfit_effect: Option<OutfitEffect>,
     pub speed_effect: Option<SpeedEffect>,
@@ -414,6 +589,19 @@ struct AttackSelection {
     range: u16,
     skill: SkillType,
     damage_type: DamageType,
+    ranged: Option<RangedAttack>,
+}
+
+/// A distance attack that uses up one item from `slot` per shot: ammunition
+/// for bows and crossbows, or the thrown weapon itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RangedAttack {
+    slot: InventorySlot,
+    missile_id: u8,
+    fires_ammo: bool,
+    /// Chance in percent that the shot item is destroyed instead of landing
+    /// on the target's tile.
+    fragility: u32,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -557,7 +745,7 @@ pub struct CombatVisualEffect {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct MonsterMissileEffect {
+pub struct MissileEffect {
     pub from: Position,
     pub to: Position,
     pub missile_id: u8,
@@ -611,12 +799,21 @@ pub struct CreatureStep {
     pub to: Position,
 }
 
+/// How many monsters are currently running their AI and how many sleep in
+/// sectors no player is near.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MonsterActivity {
+    pub active: usize,
+    pub dormant: usize,
+    pub awake_sectors: usize,
+}
+
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct MonsterTickOutcome {
     pub moved: usize,
     pub moves: Vec<CreatureStep>,
     pub effects: Vec<MonsterVisualEffect>,
-    pub missiles: Vec<MonsterMissileEffect>,
+    pub missiles: Vec<MissileEffect>,
     pub talks: Vec<MonsterTalk>,
     pub player_hits: Vec<PlayerId>,
     pub player_hit_marks: Vec<PlayerHitMarker>,
@@ -629,6 +826,7 @@ pub struct MonsterTickOutcome {
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct PlayerCombatOutcome {
     pub effects: Vec<CombatVisualEffect>,
+    pub inventory_updates: Vec<InventorySlot>,
     pub monster_updates: Vec<CreatureId>,
     pub player_updates: Vec<PlayerId>,
     pub refresh_map: bool,
@@ -672,6 +870,12 @@ struct PendingMapRefresh {
     position: Position,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PendingMissileEffect {
+    player_id: PlayerId,
+    missile: MissileEffect,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct PendingBuddyUpdate {
     player_id: PlayerId,
@@ -702,14 +906,99 @@ enum MonsterSpellTargetMeta {
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RaidSpawnEvent {
+    pub raid: String,
     pub at: GameTick,
     pub plan: RaidSpawnPlan,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 struct RaidSchedule {
-    interval_ticks: u64,
-    next_at: GameTick,
+    rules: Vec<RaidRule>,
+    /// Unix seconds of the next start.
+    next_at: u64,
+}
+
+/// What a spoken line is for, which decides how much moderation applies to
+/// it. Anything spoken respects mutes; chat and NPC dialog also feed the spam
+/// throttle, dialog with a looser burst limit, and the word filter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TalkKind {
+    Chat,
+    /// Dialog with an NPC that is talking to the player.
+    NpcDialog,
+    /// Words of a spell the player knows; exhaustion already limits these and
+    /// their parameters must reach the spell unchanged.
+    Spell,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TalkModeration {
+    Allowed(String),
+    Blocked(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptReloadTarget {
+    Npcs,
+    Monsters,
+    Raids,
+    MoveUse,
+    ContentScripts,
+    All,
+}
+
+impl ScriptReloadTarget {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "npc" | "npcs" => Some(ScriptReloadTarget::Npcs),
+            "monster" | "monsters" | "mon" => Some(ScriptReloadTarget::Monsters),
+            "raid" | "raids" => Some(ScriptReloadTarget::Raids),
+            "moveuse" => Some(ScriptReloadTarget::MoveUse),
+            "scripts" | "rhai" => Some(ScriptReloadTarget::ContentScripts),
+            "all" => Some(ScriptReloadTarget::All),
+            _ => None,
+        }
+    }
+
+    fn includes(self, other: ScriptReloadTarget) -> bool {
+        self == ScriptReloadTarget::All || self == other
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScriptReloadReport {
+    pub npc_scripts: Option<usize>,
+    pub npcs_respawned: usize,
+    pub npcs_removed: usize,
+    pub monster_scripts: Option<usize>,
+    pub raid_scripts: Option<usize>,
+    pub moveuse_sections: Option<usize>,
+    pub content_scripts: Option<usize>,
+}
+
+impl ScriptReloadReport {
+    pub fn summary(&self) -> String {
+        let mut parts = Vec::new();
+        if let Some(count) = self.npc_scripts {
+            parts.push(format!(
+                "npc={} (respawned={}, removed={})",
+                count, self.npcs_respawned, self.npcs_removed
+            ));
+        }
+        if let Some(count) = self.monster_scripts {
+            parts.push(format!("mon={}", count));
+        }
+        if let Some(count) = self.raid_scripts {
+            parts.push(format!("raids={}", count));
+        }
+        if let Some(count) = self.moveuse_sections {
+            parts.push(format!("moveuse sections={}", count));
+        }
+        if let Some(count) = self.content_scripts {
+            parts.push(format!("scripts={}", count));
+        }
+        format!("reloaded {}", parts.join(", "))
+    }
 }
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum SpellCostMode {
@@ -740,6 +1029,34 @@ const MONSTER_ACQUIRE_RANGE: u16 = 8;
 const MONSTER_ACTIVE_RANGE: u16 = 12;
 const MONSTER_COMBAT_INTERVAL_TICKS: u64 = 20;
 const MONSTER_MELEE_RANGE: u16 = 1;
+const MONSTER_PATH_BUDGET_PER_TICK: usize = 4000;
+const MONSTER_PATH_MAX_NODES: usize = 400;
+const MONSTER_PATH_RADIUS: u16 = 12;
+/// Distance-fighting monsters try to stay this many tiles from their target.
+const MONSTER_DISTANCE_RANGE: u16 = 4;
+/// How far beyond its spawn radius a monster follows a target before it
+/// gives up and walks home.
+const MONSTER_LEASH_RANGE: u16 = 10;
+/// How long a sector stays awake after the last player walked out of range.
+const MONSTER_DORMANT_AFTER: Duration = Duration::from_secs(30);
+
+/// World bookkeeping that reacts to world events. These run as soon as an
+/// event is published, so the chained event limit never drops them, and must
+/// not publish events themselves.
+const BUILTIN_EVENT_HANDLERS: &[(WorldEventKind, WorldEventHandler)] = &[(
+    WorldEventKind::CreatureKilled,
+    WorldState::release_monster_home_slot,
+)];
+/// A summon further than this from its master jumps back to its side.
+const FOLLOWER_CATCH_UP_RANGE: u16 = 10;
+const NPC_PATH_BUDGET_PER_TICK: usize = 2000;
+const NPC_PATH_MAX_NODES: usize = 400;
+const NPC_PATH_RADIUS: u16 = 16;
+const PLAYER_PATH_MAX_NODES: usize = 2000;
+const PLAYER_PATH_RADIUS: u16 = 20;
+/// Lower bound on a tile's ground speed, keeping the path heuristic from
+/// overestimating on fast ground.
+const PATH_MIN_STEP_COST: u32 = 50;
 const PLAYER_SUMMON_LIMIT: usize = 2;
 const MONSTER_FIELD_TICK_DAMAGE: u32 = 5;
 const MONSTER_TALK_MIN_TICKS: u64 = 100;
@@ -751,7 +1068,21 @@ const PLAYER_DISTANCE_RANGE: u16 = 3;
 const PLAYER_ATTACK_EFFECT_BLOOD_ID: u16 = 1;
 const PLAYER_ATTACK_EFFECT_BLOCK_ID: u16 = 4;
 const SKILL_TRAINING_POINTS: u8 = 30;
+const TALK_TYPE_MONSTER_SAY: u8 = 0x10;
+const TALK_TYPE_MONSTER_YELL: u8 = 0x11;
+/// How long a raid waits when a big raid is in the way.
+const RAID_POSTPONE_SECS: u64 = 60;
 const MESSAGE_EVENT: u8 = 0x14;
+const MESSAGE_WARNING: u8 = 0x12;
+const MESSAGE_STATUS_SMALL: u8 = 0x17;
+const PLAYER_NO_WAY_MESSAGE: &str = "There is no way.";
+const OBJECT_OUT_OF_REACH: &str = "object is out of reach";
+pub(crate) const LOOT_NOT_OWNER_MESSAGE: &str = "You are not the owner.";
+const PLAYER_CORPSE_MALE_TYPE_ID: ItemTypeId = ItemTypeId(4240);
+const PLAYER_CORPSE_FEMALE_TYPE_ID: ItemTypeId = ItemTypeId(4247);
+const AMULET_OF_LOSS_TYPE_ID: ItemTypeId = ItemTypeId(3057);
+const DEATH_ITEM_LOSS_PERCENT: u32 = 10;
+const BLACK_SKULL_RESPAWN_HEALTH: u32 = 40;
 const DEFEND_COOLDOWN_MS: u64 = 2000;
 const DRUNKEN_CHANCE_PER_LEVEL: u32 = 10;
 const FIRE_FIELD_TYPE_ID: u16 = 2118;
@@ -925,6 +1256,30 @@ impl WorldState {
                 None
             }
         };
+        let events_path = root.join("dat").join("events.dat");
+        let event_scripts = if events_path.exists() {
+            match crate::world::events::load_event_scripts(&events_path) {
+                Ok(scripts) => scripts,
+                Err(err) => {
+                    eprintln!("tibia: events.dat read skipped: {}", err);
+                    Vec::new()
+                }
+            }
+        } else {
+            Vec::new()
+        };
+        let scripts_dir = root.join("scripts");
+        let scripts = if scripts_dir.is_dir() {
+            match crate::scripting::runtime::load_scripts(&scripts_dir) {
+                Ok(scripts) => Some(scripts),
+                Err(err) => {
+                    eprintln!("tibia: scripts load skipped: {}", err);
+                    None
+                }
+            }
+        } else {
+            None
+        };
         let objects_path = root.join("dat").join("objects.srv");
         let object_types = match crate::world::object_types::load_object_types(&objects_path) {
             Ok(object_types) => Some(object_types),
@@ -936,6 +1291,87 @@ impl WorldState {
         let item_types = object_types
             .as_ref()
             .map(crate::world::item_types::build_item_types);
+        let tickets = match TicketStore::load(root) {
+            Ok(mut tickets) => {
+                match tickets.cancel_stale_requests(unix_time_now()) {
+                    Ok(0) => {}
+                    Ok(count) => {
+                        println!("tibia: cancelled {} help requests from the last run", count)
+                    }
+                    Err(err) => eprintln!("tibia: support tickets write failed: {}", err),
+                }
+                tickets
+            }
+            Err(err) => {
+                eprintln!("tibia: support tickets read skipped: {}", err);
+                TicketStore::default()
+            }
+        };
+        let deaths = match DeathStore::load(root) {
+            Ok(deaths) => deaths,
+            Err(err) => {
+                eprintln!("tibia: death records read skipped: {}", err);
+                DeathStore::default()
+            }
+        };
+        let word_filter_path = root.join("dat").join("badwords.txt");
+        let word_filter = if word_filter_path.exists() {
+            match WordFilter::load(&word_filter_path) {
+                Ok(filter) => filter,
+                Err(err) => {
+                    eprintln!("tibia: badwords.txt read skipped: {}", err);
+                    WordFilter::default()
+                }
+            }
+        } else {
+            WordFilter::default()
+        };
+        let audit_items_path = root.join("dat").join("audit-items.txt");
+        let audit_items = if audit_items_path.exists() {
+            match AuditItems::load(&audit_items_path) {
+                Ok(items) => items,
+                Err(err) => {
+                    eprintln!("tibia: audit-items.txt read skipped: {}", err);
+                    AuditItems::default()
+                }
+            }
+        } else {
+            AuditItems::default()
+        };
+        let detection_path = root.join("dat").join("detection.yaml");
+        let detection_config = if detection_path.exists() {
+            match DetectionConfig::load(&detection_path) {
+                Ok(config) => config,
+                Err(err) => {
+                    eprintln!("tibia: detection.yaml read skipped: {}", err);
+                    DetectionConfig::default()
+                }
+            }
+        } else {
+            DetectionConfig::default()
+        };
+        let raid_calendar_path = root.join("dat").join("raids.yaml");
+        let raid_calendar = if raid_calendar_path.exists() {
+            match RaidCalendarConfig::load(&raid_calendar_path) {
+                Ok(config) => config,
+                Err(err) => {
+                    eprintln!("tibia: raids.yaml read skipped: {}", err);
+                    RaidCalendarConfig::default()
+                }
+            }
+        } else {
+            RaidCalendarConfig::default()
+        };
+        if let Some(index) = monster_index.as_ref() {
+            report_unknown_calendar_raids(&raid_calendar, index);
+        }
+        let raid_history = match RaidHistory::load(root) {
+            Ok(history) => history,
+            Err(err) => {
+                eprintln!("tibia: raid history read skipped: {}", err);
+                RaidHistory::default()
+            }
+        };
         let refresh_state = init_refresh_state(map_dat.as_ref(), &map);
         let mut world = Self {
             root: Some(root.to_path_buf()),
@@ -954,6 +1390,9 @@ impl WorldState {
             next_status_effect_tick: None,
             raid_events: Vec::new(),
             raid_schedules: HashMap::new(),
+            raid_schedules_built: false,
+            raid_calendar,
+            raid_history,
             house_areas,
             houses,
             house_owners,
@@ -973,14 +1412,36 @@ impl WorldState {
             pending_turn_updates: Vec::new(),
             pending_outfit_updates: Vec::new(),
             pending_map_refreshes: Vec::new(),
+            pending_missile_effects: Vec::new(),
             pending_buddy_updates: Vec::new(),
             pending_party_updates: Vec::new(),
+            pending_skull_updates: Vec::new(),
             pending_trade_updates: Vec::new(),
             pending_container_closes: HashMap::new(),
             pending_container_refresh: HashSet::new(),
             shop_sessions: HashMap::new(),
             request_queue: Vec::new(),
             request_queue_players: HashSet::new(),
+            tickets,
+            deaths,
+            pending_forced_logouts: HashSet::new(),
+            corpse_owners: HashMap::new(),
+            field_owners: HashMap::new(),
+            combat_clock: None,
+            pvp_aggressions: HashMap::new(),
+            creature_paths: HashMap::new(),
+            boss_states: HashMap::new(),
+            monster_threat: HashMap::new(),
+            monster_leashing: HashSet::new(),
+            awake_sectors: HashMap::new(),
+            event_bus: EventBus::default(),
+            event_scripts,
+            scripts,
+            npc_walk_targets: HashMap::new(),
+            walk_to_uses: HashMap::new(),
+            word_filter,
+            audit_items,
+            detection: DetectionMonitor::new(detection_config),
             private_channels: HashMap::new(),
             private_channel_owners: HashMap::new(),
             next_private_channel_id: PRIVATE_CHANNEL_ID_START,
@@ -1022,6 +1483,113 @@ impl WorldState {
         Ok(world)
     }
 
+    /// A world without map, scripts or data files, for tools and tests that
+    /// fill in only the parts they need.
+    pub fn empty() -> Self {
+        Self {
+            root: None,
+            map: Map {
+                name: "headless".to_string(),
+                sector_bounds: None,
+                sectors: Vec::new(),
+                tiles: HashMap::new(),
+            },
+            map_original: None,
+            map_dat: None,
+            mem_dat: None,
+            circles: None,
+            npc_index: None,
+            monster_index: None,
+            monster_homes: Vec::new(),
+            npcs: HashMap::new(),
+            monsters: HashMap::new(),
+            monster_sector_index: HashMap::new(),
+            monster_sector_index_count: 0,
+            next_status_effect_tick: None,
+            raid_events: Vec::new(),
+            raid_schedules: HashMap::new(),
+            raid_schedules_built: false,
+            raid_calendar: RaidCalendarConfig::default(),
+            raid_history: RaidHistory::default(),
+            house_areas: None,
+            houses: None,
+            house_owners: None,
+            house_position_index: None,
+            next_house_rent_check: None,
+            moveuse: None,
+            object_types: None,
+            item_types: None,
+            cron: crate::world::cron::CronSystem::new(),
+            players: HashMap::new(),
+            offline_players: HashMap::new(),
+            spellbook: SpellBook::default(),
+            combat_rules: CombatRules::default(),
+            pending_messages: Vec::new(),
+            pending_skill_updates: Vec::new(),
+            pending_data_updates: Vec::new(),
+            pending_turn_updates: Vec::new(),
+            pending_outfit_updates: Vec::new(),
+            pending_map_refreshes: Vec::new(),
+            pending_missile_effects: Vec::new(),
+            pending_buddy_updates: Vec::new(),
+            pending_party_updates: Vec::new(),
+            pending_skull_updates: Vec::new(),
+            pending_trade_updates: Vec::new(),
+            pending_container_closes: HashMap::new(),
+            pending_container_refresh: HashSet::new(),
+            shop_sessions: HashMap::new(),
+            request_queue: Vec::new(),
+            request_queue_players: HashSet::new(),
+            tickets: TicketStore::default(),
+            deaths: DeathStore::default(),
+            pending_forced_logouts: HashSet::new(),
+            corpse_owners: HashMap::new(),
+            field_owners: HashMap::new(),
+            combat_clock: None,
+            pvp_aggressions: HashMap::new(),
+            creature_paths: HashMap::new(),
+            boss_states: HashMap::new(),
+            monster_threat: HashMap::new(),
+            monster_leashing: HashSet::new(),
+            awake_sectors: HashMap::new(),
+            event_bus: EventBus::default(),
+            event_scripts: Vec::new(),
+            scripts: None,
+            npc_walk_targets: HashMap::new(),
+            walk_to_uses: HashMap::new(),
+            word_filter: WordFilter::default(),
+            audit_items: AuditItems::default(),
+            detection: DetectionMonitor::default(),
+            private_channels: HashMap::new(),
+            private_channel_owners: HashMap::new(),
+            next_private_channel_id: PRIVATE_CHANNEL_ID_START,
+            parties: HashMap::new(),
+            next_party_id: 1,
+            pending_moveuse_outcomes: HashMap::new(),
+            trade_sessions: HashMap::new(),
+            trade_by_player: HashMap::new(),
+            next_trade_id: 1,
+            next_text_edit_id: 1,
+            text_edit_sessions: HashMap::new(),
+            next_list_edit_id: 1,
+            list_edit_sessions: HashMap::new(),
+            moveuse_rng: MoveUseRng::default(),
+            loot_rng: LootRng::default(),
+            monster_rng: MonsterRng::default(),
+            npc_rng: NpcRng::default(),
+            next_npc_id: NPC_ID_BASE,
+            next_monster_id: 1,
+            refresh_state: None,
+            skill_tick_last: None,
+            monster_home_tick_last: None,
+            cron_tick_last: None,
+            cron_tick_accum: 0,
+            cron_round: 0,
+            item_index: HashMap::new(),
+            item_index_dirty: true,
+        }
+    }
+
     fn queue_message(&mut self, message: MoveUseMessage) {
         self.pending_messages.push(message);
     }
@@ -1207,6 +1775,35 @@ impl WorldState {
         ready
     }
 
+    fn queue_missile_effect(&mut self, from: Position, to: Position, missile_id: u8) {
+        let targets: Vec<PlayerId> = self.players.keys().copied().collect();
+        for player_id in targets {
+            self.pending_missile_effects.push(PendingMissileEffect {
+                player_id,
+                missile: MissileEffect {
+                    from,
+                    to,
+                    missile_id,
+                },
+            });
+        }
+    }
+
+    pub(crate) fn take_pending_missile_effects(
+        &mut self,
+        player_id: PlayerId,
+    ) -> Vec<MissileEffect> {
+        if self.pending_missile_effects.is_empty() {
+            return Vec::new();
+        }
+        let (ready, remaining): (Vec<_>, Vec<_>) =
+            std::mem::take(&mut self.pending_missile_effects)
+                .into_iter()
+                .partition(|entry| entry.player_id == player_id);
+        self.pending_missile_effects = remaining;
+        ready.into_iter().map(|entry| entry.missile).collect()
+    }
+
     pub(crate) fn queue_buddy_status_update(&mut self, buddy_id: PlayerId, online: bool) {
         let update = BuddyStatusUpdate { buddy_id, online };
         let targets: Vec<PlayerId> = self
@@ -1255,6 +1852,39 @@ impl WorldState {
             .push(PendingPartyUpdate { player_id, update });
     }
 
+    fn queue_skull_update(&mut self, viewer_id: PlayerId, target_id: PlayerId) {
+        let skull = self.skull_for_viewer(viewer_id, target_id);
+        self.pending_skull_updates.push(PendingSkullUpdate {
+            player_id: viewer_id,
+            update: SkullUpdate { target_id, skull },
+        });
+    }
+
+    fn queue_skull_updates_for_target(&mut self, target_id: PlayerId) {
+        let viewers: Vec<PlayerId> = self.players.keys().copied().collect();
+        for viewer_id in viewers {
+            self.queue_skull_update(viewer_id, target_id);
+        }
+    }
+
+    pub(crate) fn take_pending_skull_updates(&mut self, player_id: PlayerId) -> Vec<SkullUpdate> {
+        if self.pending_skull_updates.is_empty() {
+            return Vec::new();
+        }
+        let mut pending = std::mem::take(&mut self.pending_skull_updates);
+        let mut remaining = Vec::new();
+        let mut ready = Vec::new();
+        for entry in pending.drain(..) {
+            if entry.player_id == player_id {
+                ready.push(entry.update);
+            } else {
+                remaining.push(entry);
+            }
+        }
+        self.pending_skull_updates = remaining;
+        ready
+    }
+
     pub(crate) fn take_pending_party_updates(
         &mut self,
         player_id: PlayerId,
@@ -1520,11 +2150,32 @@ impl WorldState {
         PARTY_SHIELD_NONE
     }
 
-    fn queue_party_marks_for_players(&mut self, players: &[PlayerId]) {
-        let mut unique: Vec<PlayerId> = players
-            .iter()
-            .copied()
-            .filter(|player_id| self.players.contains_key(player_id))
+    /// The skull `viewer_id` sees on `target_id`. Skulls from unjustified
+    /// attacks are visible to everyone, while a yellow skull only shows the
+    /// aggressor which of its victims may fight back.
+    pub(crate) fn skull_for_viewer(&self, viewer_id: PlayerId, target_id: PlayerId) -> SkullState {
+        let Some(target) = self.players.get(&target_id) else {
+            return SkullState::None;
+        };
+        match target.pvp.skull {
+            SkullState::None | SkullState::Yellow => {
+                if viewer_id != target_id
+                    && self.pvp_aggressions.contains_key(&(viewer_id, target_id))
+                {
+                    SkullState::Yellow
+                } else {
+                    SkullState::None
+                }
+            }
+            skull => skull,
+        }
+    }
+
+    fn queue_party_marks_for_players(&mut self, players: &[PlayerId]) {
+        let mut unique: Vec<PlayerId> = players
+            .iter()
+            .copied()
+            .filter(|player_id| self.players.contains_key(player_id))
             .collect();
         unique.sort_by_key(|player_id| player_id.0);
         unique.dedup();
@@ -2418,12 +3069,34 @@ impl WorldState {
             let removed = self.take_trade_item_from_location(session.partner, entry)?;
             partner_items.push(removed);
         }
-        for item in requester_items {
+        let requester_name = self.players.get(&session.requester).map(|player| player.name.clone());
+        let partner_name = self.players.get(&session.partner).map(|player| player.name.clone());
+        for item in requester_items.iter().cloned() {
             self.add_item_stack_to_player(session.partner, item)?;
         }
-        for item in partner_items {
+        for item in partner_items.iter().cloned() {
             self.add_item_stack_to_player(session.requester, item)?;
         }
+        for item in &requester_items {
+            record_item_transfer(
+                TransferKind::Trade,
+                &requester_name,
+                &partner_name,
+                item,
+                item.count,
+                None,
+            );
+        }
+        for item in &partner_items {
+            record_item_transfer(
+                TransferKind::Trade,
+                &partner_name,
+                &requester_name,
+                item,
+                item.count,
+                None,
+            );
+        }
         Ok(())
     }
 
@@ -2713,24 +3386,187 @@ impl WorldState {
         })
     }
 
-    pub(crate) fn submit_request(&mut self, player_id: PlayerId) -> Result<bool, String> {
+    pub(crate) fn submit_request(
+        &mut self,
+        player_id: PlayerId,
+        message: &str,
+    ) -> Result<bool, String> {
         if self.request_queue_players.contains(&player_id) {
+            if let Some(ticket_id) = self.active_request_ticket(player_id) {
+                let name = self.player_name_by_id(player_id).unwrap_or_default();
+                if let Err(err) =
+                    self.tickets
+                        .append_message(ticket_id, &name, message, unix_time_now())
+                {
+                    logging::log_error(&format!("ticket transcript failed: {}", err));
+                }
+            }
             return Ok(false);
         }
         let player = self
             .players
             .get(&player_id)
             .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+        let name = player.name.clone();
+        let ticket_id = match self.tickets.open(
+            TicketKind::Help,
+            player_id,
+            &name,
+            Some(player.position),
+            message,
+            unix_time_now(),
+        ) {
+            Ok(ticket_id) => Some(ticket_id),
+            Err(err) => {
+                logging::log_error(&format!("ticket open failed: {}", err));
+                None
+            }
+        };
         let entry = RequestQueueEntry {
             player_id,
-            name: player.name.clone(),
+            name,
+            ticket_id,
         };
         self.request_queue.push(entry);
         self.request_queue_players.insert(player_id);
         Ok(true)
     }
 
-    pub(crate) fn take_request_by_name(&mut self, name: &str) -> Option<RequestQueueEntry> {
+    pub(crate) fn process_request(
+        &mut self,
+        gamemaster_id: PlayerId,
+        name: &str,
+    ) -> Option<RequestQueueEntry> {
+        let entry = self.take_request_by_name(name)?;
+        let gamemaster = self.player_name_by_id(gamemaster_id).unwrap_or_default();
+        if let Some(ticket_id) = entry.ticket_id {
+            if let Err(err) = self.tickets.assign(ticket_id, &gamemaster, unix_time_now()) {
+                logging::log_error(&format!("ticket assign failed: {}", err));
+            }
+        }
+        Some(entry)
+    }
+
+    pub(crate) fn remove_request(&mut self, name: &str) -> Option<RequestQueueEntry> {
+        let entry = self.take_request_by_name(name)?;
+        if let Some(ticket_id) = entry.ticket_id {
+            self.close_ticket(ticket_id, TicketOutcome::Removed);
+        }
+        Some(entry)
+    }
+
+    pub(crate) fn finish_requests_for_gamemaster(&mut self, gamemaster_id: PlayerId) {
+        let Some(gamemaster) = self.player_name_by_id(gamemaster_id) else {
+            return;
+        };
+        let ticket_ids: Vec<u32> = self
+            .tickets
+            .active()
+            .into_iter()
+            .filter(|ticket| {
+                ticket.kind == TicketKind::Help
+                    && ticket
+                        .assigned_to
+                        .as_deref()
+                        .is_some_and(|assigned| assigned.eq_ignore_ascii_case(&gamemaster))
+            })
+            .map(|ticket| ticket.id)
+            .collect();
+        for ticket_id in ticket_ids {
+            self.close_ticket(ticket_id, TicketOutcome::Answered);
+        }
+    }
+
+    pub(crate) fn record_ticket_talk(
+        &mut self,
+        author_id: PlayerId,
+        recipient: Option<&str>,
+        message: &str,
+    ) {
+        let message = message.trim();
+        if message.is_empty() {
+            return;
+        }
+        let Some(author) = self.players.get(&author_id) else {
+            return;
+        };
+        let author_name = author.name.clone();
+        let ticket_id = if author.is_gm {
+            let Some(recipient) = recipient else {
+                return;
+            };
+            self.tickets
+                .active()
+                .into_iter()
+                .find(|ticket| {
+                    ticket.kind == TicketKind::Help
+                        && ticket.player_name.eq_ignore_ascii_case(recipient)
+                        && ticket
+                            .assigned_to
+                            .as_deref()
+                            .is_some_and(|assigned| assigned.eq_ignore_ascii_case(&author_name))
+                })
+                .map(|ticket| ticket.id)
+        } else {
+            let Some(recipient) = recipient else {
+                return;
+            };
+            self.tickets
+                .active_for_player(author_id, TicketKind::Help)
+                .filter(|ticket| {
+                    ticket
+                        .assigned_to
+                        .as_deref()
+                        .is_some_and(|assigned| assigned.eq_ignore_ascii_case(recipient))
+                })
+                .map(|ticket| ticket.id)
+        };
+        let Some(ticket_id) = ticket_id else {
+            return;
+        };
+        if let Err(err) =
+            self.tickets
+                .append_message(ticket_id, &author_name, message, unix_time_now())
+        {
+            logging::log_error(&format!("ticket transcript failed: {}", err));
+        }
+    }
+
+    pub(crate) fn submit_bug_report(
+        &mut self,
+        player_id: PlayerId,
+        comment: &str,
+    ) -> Result<u32, String> {
+        let player = self
+            .players
+            .get(&player_id)
+            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+        let name = player.name.clone();
+        let position = player.position;
+        self.tickets.open(
+            TicketKind::BugReport,
+            player_id,
+            &name,
+            Some(position),
+            comment,
+            unix_time_now(),
+        )
+    }
+
+    fn active_request_ticket(&self, player_id: PlayerId) -> Option<u32> {
+        self.request_queue
+            .iter()
+            .find(|entry| entry.player_id == player_id)
+            .and_then(|entry| entry.ticket_id)
+    }
+
+    fn close_ticket(&mut self, ticket_id: u32, outcome: TicketOutcome) {
+        if let Err(err) = self.tickets.close(ticket_id, outcome, unix_time_now()) {
+            logging::log_error(&format!("ticket close failed: {}", err));
+        }
+    }
+
+    fn take_request_by_name(&mut self, name: &str) -> Option<RequestQueueEntry> {
         let index = self
             .request_queue
             .iter()
@@ -2750,9 +3586,178 @@ impl WorldState {
             .position(|entry| entry.player_id == player_id)?;
         let entry = self.request_queue.remove(index);
         self.request_queue_players.remove(&entry.player_id);
+        if let Some(ticket_id) = entry.ticket_id {
+            self.close_ticket(ticket_id, TicketOutcome::Cancelled);
+        }
         Some(entry)
     }
 
+    /// Classifies a line said in the default channel; anything sent to a
+    /// channel or a player is chat.
+    pub(crate) fn talk_kind(&self, player_id: PlayerId, message: &str, public: bool) -> TalkKind {
+        if !public {
+            return TalkKind::Chat;
+        }
+        let Some(player) = self.players.get(&player_id) else {
+            return TalkKind::Chat;
+        };
+        let known_spell = self
+            .spellbook
+            .get_by_input(message.trim())
+            .is_some_and(|spell| player.knows_spell(spell.id));
+        if known_spell {
+            return TalkKind::Spell;
+        }
+        let in_dialog = self.npcs.values().any(|npc| {
+            npc_in_range(npc.position, player.position, NPC_TALK_RANGE)
+                && (npc.focused == Some(player_id) || npc.queue.contains(&player_id))
+        });
+        if in_dialog {
+            TalkKind::NpcDialog
+        } else {
+            TalkKind::Chat
+        }
+    }
+
+    pub(crate) fn moderate_talk(
+        &mut self,
+        player_id: PlayerId,
+        message: &str,
+        kind: TalkKind,
+        clock: &GameClock,
+    ) -> Result<TalkModeration, String> {
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+        if player.is_gm {
+            return Ok(TalkModeration::Allowed(message.to_string()));
+        }
+        let now = unix_time_now();
+        if player.muted_until > now {
+            let remaining = player.muted_until - now;
+            return Ok(TalkModeration::Blocked(format!(
+                "You are muted by a gamemaster. You may speak again in {} seconds.",
+                remaining
+            )));
+        }
+        if kind == TalkKind::Spell {
+            if let Some(remaining_secs) = player.talk_throttle.muted_for(clock) {
+                return Ok(TalkModeration::Blocked(format!(
+                    "You may not speak for {} seconds.",
+                    remaining_secs
+                )));
+            }
+            return Ok(TalkModeration::Allowed(message.to_string()));
+        }
+        let check = match kind {
+            TalkKind::NpcDialog => player.talk_throttle.check_npc_dialog(clock),
+            _ => player.talk_throttle.check(clock),
+        };
+        match check {
+            TalkCheck::Allowed => {}
+            TalkCheck::Muted { remaining_secs } => {
+                return Ok(TalkModeration::Blocked(format!(
+                    "You may not speak for {} seconds.",
+                    remaining_secs
+                )));
+            }
+            TalkCheck::Throttled { mute_secs } => {
+                logging::log_moderation(&format!(
+                    "{} muted for {} seconds (spam)",
+                    player.name, mute_secs
+                ));
+                return Ok(TalkModeration::Blocked(format!(
+                    "You may not speak for {} seconds.",
+                    mute_secs
+                )));
+            }
+        }
+        match self.word_filter.censor(message) {
+            Some(censored) => {
+                logging::log_moderation(&format!(
+                    "{} message filtered: {}",
+                    player.name, message
+                ));
+                Ok(TalkModeration::Allowed(censored))
+            }
+            None => Ok(TalkModeration::Allowed(message.to_string())),
+        }
+    }
+
+    pub fn mute_player(
+        &mut self,
+        gamemaster_id: PlayerId,
+        name: &str,
+        duration: Duration,
+        reason: Option<&str>,
+    ) -> Result<String, String> {
+        let until = unix_time_now().saturating_add(duration.as_secs());
+        let target = self.set_player_muted_until(name, until)?;
+        let gamemaster = self
+            .player_name_by_id(gamemaster_id)
+            .unwrap_or_else(|| "unknown".to_string());
+        logging::log_moderation(&format!(
+            "{} muted {} for {} seconds ({})",
+            gamemaster,
+            target,
+            duration.as_secs(),
+            reason.unwrap_or("no reason")
+        ));
+        if let Some(target_id) = self
+            .players
+            .values()
+            .find(|player| player.name == target)
+            .map(|player| player.id)
+        {
+            self.queue_player_message(
+                target_id,
+                MESSAGE_EVENT,
+                format!(
+                    "You have been muted for {} minutes.",
+                    duration.as_secs().div_ceil(60)
+                ),
+            );
+        }
+        Ok(target)
+    }
+
+    pub fn unmute_player(&mut self, gamemaster_id: PlayerId, name: &str) -> Result<String, String> {
+        let target = self.set_player_muted_until(name, 0)?;
+        let gamemaster = self
+            .player_name_by_id(gamemaster_id)
+            .unwrap_or_else(|| "unknown".to_string());
+        logging::log_moderation(&format!("{} unmuted {}", gamemaster, target));
+        Ok(target)
+    }
+
+    fn set_player_muted_until(&mut self, name: &str, until: u64) -> Result<String, String> {
+        let target = name.trim();
+        if let Some(player) = self
+            .players
+            .values_mut()
+            .chain(self.offline_players.values_mut())
+            .find(|player| player.name.eq_ignore_ascii_case(target))
+        {
+            player.muted_until = until;
+            return Ok(player.name.clone());
+        }
+        let player_id = self
+            .find_player_id_by_name_in_saves(target)?
+            .ok_or_else(|| format!("player {} not found", target))?;
+        let root = self
+            .root
+            .as_ref()
+            .ok_or_else(|| "world root missing".to_string())?;
+        let store = SaveStore::from_root(root);
+        let mut player = store
+            .load_player(player_id)?
+            .ok_or_else(|| format!("player {} not found", target))?;
+        player.muted_until = until;
+        store.save_player(&player)?;
+        Ok(player.name)
+    }
+
     pub fn set_rng_seeds(&mut self, moveuse_seed: u64, loot_seed: u64, monster_seed: u64) {
         self.moveuse_rng = MoveUseRng::from_seed(moveuse_seed);
         self.loot_rng = LootRng::from_seed(loot_seed);
@@ -2810,19 +3815,155 @@ impl WorldState {
         Ok(())
     }
 
+    pub fn record_session_start(
+        &mut self,
+        player_id: PlayerId,
+        account: &str,
+        ip: Option<std::net::IpAddr>,
+    ) {
+        let Some(name) = self.players.get(&player_id).map(|player| player.name.clone()) else {
+            return;
+        };
+        audit::record(AuditEvent::Login {
+            player: name.clone(),
+            account: account.to_string(),
+            ip: ip.map(|ip| ip.to_string()),
+        });
+        let flags = self
+            .detection
+            .session_started(player_id, &name, account, ip, unix_time_now());
+        for flag in flags {
+            Self::log_detection_flag(&flag);
+        }
+        if let Some(position) = self.players.get(&player_id).map(|player| player.position) {
+            self.publish_event(WorldEvent::PlayerLogin {
+                player_id,
+                position,
+            });
+        }
+    }
+
+    pub fn record_player_input(&mut self, player_id: PlayerId, kind: InputKind, at_ms: u64) {
+        let step_pace_ms = match kind {
+            InputKind::Step => self.player_step_pace_ms(player_id),
+            InputKind::Attack => None,
+        };
+        if let Some(flag) = self.detection.record_input(
+            player_id,
+            kind,
+            at_ms,
+            step_pace_ms,
+            unix_time_now(),
+        ) {
+            Self::log_detection_flag(&flag);
+        }
+    }
+
+    /// Milliseconds the server makes the player wait between straight steps
+    /// from their current tile, which is the rhythm of a held arrow key.
+    fn player_step_pace_ms(&self, player_id: PlayerId) -> Option<u64> {
+        let player = self.players.get(&player_id)?;
+        let tile_speed = self
+            .map
+            .tile(player.position)
+            .map(|tile| self.tile_ground_speed(tile))
+            .unwrap_or(DEFAULT_GROUND_SPEED);
+        let speed = u64::from(self.player_move_speed(player).max(1));
+        Some(u64::from(tile_speed.max(1)).saturating_mul(1000) / speed)
+    }
+
+    /// Writes the detection report below `log/` and returns its path.
+    pub fn write_detection_report(&self) -> Result<PathBuf, String> {
+        let root = self
+            .root
+            .as_ref()
+            .ok_or_else(|| "world root not set".to_string())?;
+        let path = root.join("log").join("detection-report.txt");
+        self.detection.write_report(&path)?;
+        Ok(path)
+    }
+
+    /// Records a drop or pickup in the audit log when the item is worth
+    /// tracking: listed in `dat/audit-items.txt`, money above
+    /// `AUDIT_MONEY_THRESHOLD`, or a container, whose contents are recorded
+    /// along with it.
+    pub(crate) fn audit_item_move(
+        &self,
+        player_id: PlayerId,
+        kind: TransferKind,
+        item: &ItemStack,
+        count: u16,
+        position: Position,
+    ) {
+        let type_id = item.type_id;
+        let money = self
+            .object_types
+            .as_ref()
+            .and_then(|object_types| money_value_for_type(object_types, type_id))
+            .map(|value| value.saturating_mul(u32::from(count.max(1))))
+            .unwrap_or(0);
+        let valuable = self.audit_items.contains(type_id.0) || money >= AUDIT_MONEY_THRESHOLD;
+        let container = !item.contents.is_empty()
+            || self
+                .item_types
+                .as_ref()
+                .and_then(|types| types.get(type_id))
+                .map(|entry| entry.kind == ItemKind::Container)
+                .unwrap_or(false);
+        if !valuable && !container {
+            return;
+        }
+        let Some(name) = self.players.get(&player_id).map(|player| player.name.clone()) else {
+            return;
+        };
+        let (from, to) = match kind {
+            TransferKind::Pickup => (None, Some(name)),
+            _ => (Some(name), None),
+        };
+        let position = Some([position.x, position.y, u16::from(position.z)]);
+        record_item_transfer(kind, &from, &to, item, count, position);
+    }
+
+    fn log_detection_flag(flag: &DetectionFlag) {
+        logging::log_game(&format!("detection: {}", flag.describe()));
+    }
+
     pub fn handle_disconnect(&mut self, player_id: PlayerId) {
         if !self.players.contains_key(&player_id) {
             return;
         }
+        self.end_player_session(player_id);
+        if self.request_logout(player_id, None).is_ok() {
+            self.move_player_offline(player_id);
+        }
+    }
+
+    fn end_player_session(&mut self, player_id: PlayerId) {
         self.queue_buddy_status_update(player_id, false);
         self.take_request_for_player(player_id);
+        self.detection.session_ended(player_id, unix_time_now());
+        if let Some(player) = self.players.get(&player_id) {
+            audit::record(AuditEvent::Logout {
+                player: player.name.clone(),
+            });
+        }
         let _ = self.trade_close(player_id);
         let _ = self.party_leave(player_id, false);
-        if self.request_logout(player_id, None).is_ok() {
-            if let Some(mut player) = self.players.remove(&player_id) {
-                player.last_logout = unix_time_now();
-                self.offline_players.insert(player_id, player);
-            }
+    }
+
+    /// Takes the player out of the world. Only here does the character
+    /// really leave, so this is where `PlayerLogout` goes out.
+    fn move_player_offline(&mut self, player_id: PlayerId) {
+        if let Some(position) = self.players.get(&player_id).map(|player| player.position) {
+            self.publish_event(WorldEvent::PlayerLogout {
+                player_id,
+                position,
+            });
+        }
+        self.despawn_player_summons(player_id);
+        if let Some(mut player) = self.players.remove(&player_id) {
+            player.last_logout = unix_time_now();
+            self.offline_players.insert(player_id, player);
         }
     }
 
@@ -2841,36 +3982,36 @@ impl WorldState {
         };
         let definitions = index.definitions.clone();
         for definition in definitions {
-            let Some(position) = definition.home else {
-                continue;
-            };
-            if !self.position_in_bounds(position) {
-                continue;
-            }
-            if !self.map.tiles.is_empty() && !self.map.has_tile(position) {
-                continue;
-            }
-            let position = match self.find_login_position(position, 1u8, false) {
-                Some(position) => position,
-                None => continue,
-            };
-            let id = self.next_npc_id();
-            let npc = NpcInstance {
-                id,
-                script_key: definition.script_key,
-                name: definition.name,
-                position,
-                direction: Direction::South,
-                home: position,
-                outfit: definition.outfit,
-                radius: definition.radius.unwrap_or(1),
-                focused: None,
-                focus_expires_at: None,
-                queue: VecDeque::new(),
-                move_cooldown: Cooldown::new(GameTick(0)),
-            };
-            self.npcs.insert(id, npc);
+            self.spawn_npc_from_definition(definition);
+        }
+    }
+
+    fn spawn_npc_from_definition(&mut self, definition: NpcDefinition) -> Option<CreatureId> {
+        let position = definition.home?;
+        if !self.position_in_bounds(position) {
+            return None;
+        }
+        if !self.map.tiles.is_empty() && !self.map.has_tile(position) {
+            return None;
         }
+        let position = self.find_login_position(position, 1u8, false)?;
+        let id = self.next_npc_id();
+        let npc = NpcInstance {
+            id,
+            script_key: definition.script_key,
+            name: definition.name,
+            position,
+            direction: Direction::South,
+            home: position,
+            outfit: definition.outfit,
+            radius: definition.radius.unwrap_or(1),
+            focused: None,
+            focus_expires_at: None,
+            queue: VecDeque::new(),
+            move_cooldown: Cooldown::new(GameTick(0)),
+        };
+        self.npcs.insert(id, npc);
+        Some(id)
     }
 
     fn spawn_monsters_from_homes(&mut self) {
@@ -3035,12 +4176,267 @@ impl WorldState {
         }
     }
 
-    fn player_can_see_floor(player_position: Position, floor_z: u8) -> bool {
-        if player_position.z <= 7 {
-            floor_z <= 7
-        } else {
-            (i32::from(player_position.z) - i32::from(floor_z)).abs() <= 2
-        }
+    /// Frees the home slot of a killed spawn monster so the home can
+    /// respawn it.
+    fn release_monster_home_slot(&mut self, event: &WorldEvent) {
+        if let WorldEvent::CreatureKilled {
+            victim: KilledCreature::Monster {
+                home_id: Some(home_id),
+                ..
+            },
+            ..
+        } = event
+        {
+            self.notify_monster_home_death(*home_id);
+        }
+    }
+
+    /// Forwards login, logout, level, kill and step events to the matching
+    /// hooks of the loaded content scripts.
+    fn run_script_event_hooks(&mut self, event: &WorldEvent) {
+        let (player_id, hook) = match event {
+            WorldEvent::PlayerLogin { player_id, .. } => (*player_id, ScriptHook::Login),
+            WorldEvent::PlayerLogout { player_id, .. } => (*player_id, ScriptHook::Logout),
+            WorldEvent::LevelUp {
+                player_id,
+                old_level,
+                new_level,
+            } => (
+                *player_id,
+                ScriptHook::LevelUp {
+                    old_level: *old_level,
+                    new_level: *new_level,
+                },
+            ),
+            WorldEvent::CreatureKilled {
+                victim: KilledCreature::Monster { race_number, .. },
+                killer: Some(player_id),
+                ..
+            } => (
+                *player_id,
+                ScriptHook::Kill {
+                    race_number: *race_number,
+                },
+            ),
+            WorldEvent::StepOn { player_id, .. } => (*player_id, ScriptHook::Step),
+            _ => return,
+        };
+        self.run_script_hook(player_id, hook, None);
+    }
+
+    /// Runs a content script hook for a player and applies what the scripts
+    /// asked for. Returns whether a script reported the hook as handled.
+    fn run_script_hook(
+        &mut self,
+        player_id: PlayerId,
+        hook: ScriptHook,
+        item: Option<ScriptItem>,
+    ) -> bool {
+        let Some(scripts) = self.scripts.as_ref() else {
+            return false;
+        };
+        if let ScriptHook::Use(type_id) = hook {
+            if !scripts.handles_use(type_id) {
+                return false;
+            }
+        }
+        if hook == ScriptHook::Step {
+            let Some(position) = self.players.get(&player_id).map(|player| player.position)
+            else {
+                return false;
+            };
+            if !scripts.handles_step(position) {
+                return false;
+            }
+        }
+        let Some(player) = self.script_player(player_id) else {
+            return false;
+        };
+        let run = scripts.run_hook(hook, &player, item);
+        self.apply_script_run(run)
+    }
+
+    fn script_player(&self, player_id: PlayerId) -> Option<ScriptPlayer> {
+        let player = self.players.get(&player_id)?;
+        let mut item_counts = HashMap::new();
+        let inventory_items = (0..)
+            .map_while(InventorySlot::from_index)
+            .filter_map(|slot| player.inventory.slot(slot))
+            .chain(player.inventory_containers.values().flatten())
+            .chain(
+                player
+                    .open_containers
+                    .values()
+                    .flat_map(|container| container.items.iter()),
+            );
+        for item in inventory_items {
+            item_counts
+                .entry(item.type_id.0)
+                .or_insert_with(|| npc_count_item(player, item.type_id));
+        }
+        Some(ScriptPlayer {
+            id: player_id.0,
+            name: player.name.clone(),
+            level: player.level,
+            profession: player.profession,
+            position: player.position,
+            quest_values: player.quest_values.clone(),
+            item_counts,
+        })
+    }
+
+    fn apply_script_run(&mut self, run: ScriptRun) -> bool {
+        for err in &run.errors {
+            logging::log_error(err);
+        }
+        for command in run.commands {
+            if let Err(err) = self.apply_script_command(command) {
+                logging::log_error(&format!("script command failed: {}", err));
+            }
+        }
+        run.handled
+    }
+
+    fn apply_script_command(&mut self, command: ScriptCommand) -> Result<(), String> {
+        match command {
+            ScriptCommand::GiveItem {
+                player_id,
+                type_id,
+                count,
+            } => {
+                let player_id = PlayerId(player_id);
+                self.add_item_to_player(player_id, ItemTypeId(type_id), count)?;
+                self.queue_container_refresh(player_id);
+                self.queue_player_data_update(player_id);
+            }
+            ScriptCommand::Teleport {
+                player_id,
+                position,
+            } => {
+                self.teleport_player(PlayerId(player_id), position)?;
+            }
+            ScriptCommand::SetQuestValue {
+                player_id,
+                quest_id,
+                value,
+            } => {
+                let player = self
+                    .players
+                    .get_mut(&PlayerId(player_id))
+                    .ok_or_else(|| format!("unknown player {}", player_id))?;
+                player.quest_values.insert(quest_id, value);
+            }
+            ScriptCommand::SpawnMonster {
+                race_number,
+                position,
+            } => {
+                self.spawn_monster_by_race(race_number, position)?;
+            }
+            ScriptCommand::SendMessage { player_id, message } => {
+                self.queue_player_message(PlayerId(player_id), MESSAGE_EVENT, message);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn subscribe_event(&mut self, kind: WorldEventKind, handler: WorldEventHandler) {
+        self.event_bus.subscribe(kind, handler);
+    }
+
+    /// Runs the built-in handlers for a world event right away, then queues
+    /// it and, unless another publish is already dispatching, runs it and
+    /// every event it triggers through the content script hooks, runtime
+    /// subscribers and `events.dat` scripts.
+    pub fn publish_event(&mut self, event: WorldEvent) {
+        let kind = event.kind();
+        for (handler_kind, handler) in BUILTIN_EVENT_HANDLERS {
+            if *handler_kind == kind {
+                handler(self, &event);
+            }
+        }
+        self.event_bus.push(event);
+        if !self.event_bus.begin_dispatch() {
+            return;
+        }
+        for _ in 0..MAX_CHAINED_EVENTS {
+            let Some(event) = self.event_bus.next_event() else {
+                break;
+            };
+            let kind = event.kind();
+            self.run_script_event_hooks(&event);
+            for handler in self.event_bus.handlers(kind) {
+                handler(self, &event);
+            }
+            self.run_event_scripts(&event);
+        }
+        let dropped = self.event_bus.end_dispatch();
+        if dropped > 0 {
+            logging::log_error(&format!("world events: dropped {} chained events", dropped));
+        }
+    }
+
+    fn run_event_scripts(&mut self, event: &WorldEvent) {
+        let actions: Vec<(usize, EventAction)> = self
+            .event_scripts
+            .iter()
+            .filter(|script| script.matches(event))
+            .flat_map(|script| {
+                script
+                    .actions
+                    .iter()
+                    .map(move |action| (script.line_no, action.clone()))
+            })
+            .collect();
+        for (line_no, action) in actions {
+            if let Err(err) = self.apply_event_action(event, &action) {
+                logging::log_error(&format!("events.dat line {}: {}", line_no, err));
+            }
+        }
+    }
+
+    fn apply_event_action(&mut self, event: &WorldEvent, action: &EventAction) -> Result<(), String> {
+        let player_id = event.player_id();
+        let player_name = player_id
+            .and_then(|id| self.players.get(&id))
+            .map(|player| player.name.clone())
+            .unwrap_or_default();
+        match action {
+            EventAction::Broadcast(text) => {
+                let text = text.replace("%N", &player_name);
+                let player_ids: Vec<PlayerId> = self.players.keys().copied().collect();
+                for id in player_ids {
+                    self.queue_player_message(id, MESSAGE_WARNING, text.clone());
+                }
+            }
+            EventAction::Message(text) => {
+                let id = player_id.ok_or_else(|| "Message needs an event with a player".to_string())?;
+                self.queue_player_message(id, MESSAGE_EVENT, text.replace("%N", &player_name));
+            }
+            EventAction::MonsterOnMap {
+                position,
+                race_number,
+            } => {
+                self.spawn_monster_by_race(*race_number, *position)?;
+            }
+            EventAction::SetQuestValue { quest_id, value } => {
+                let id = player_id
+                    .ok_or_else(|| "SetQuestValue needs an event with a player".to_string())?;
+                let player = self
+                    .players
+                    .get_mut(&id)
+                    .ok_or_else(|| format!("unknown player {:?}", id))?;
+                player.quest_values.insert(*quest_id, *value);
+            }
+        }
+        Ok(())
+    }
+
+    fn player_can_see_floor(player_position: Position, floor_z: u8) -> bool {
+        if player_position.z <= 7 {
+            floor_z <= 7
+        } else {
+            (i32::from(player_position.z) - i32::from(floor_z)).abs() <= 2
+        }
     }
 
     pub fn tick_monster_homes(&mut self, clock: &GameClock) -> usize {
@@ -3550,6 +4946,7 @@ impl WorldState {
         let armor = script.armor().unwrap_or(0);
         let poison = script.poison().unwrap_or(0);
         let spells = script.spells().unwrap_or_default();
+        let boss = script.boss().ok().flatten();
         let talk_lines = script.talk_lines().unwrap_or_default();
         let talk_cooldown = Cooldown::new(GameTick(0));
         let speed = script
@@ -3586,7 +4983,7 @@ impl WorldState {
             flee_threshold,
             lose_target_distance,
             target: None,
-            damage_by: HashMap::new(),
+            damage_ledger: DamageLedger::default(),
             speed,
             outfit_effect: None,
             speed_effect: None,
@@ -3599,6 +4996,24 @@ impl WorldState {
         self.populate_monster_loot(&mut monster);
         self.monsters.insert(id, monster);
         self.add_monster_to_sector_index(id, position);
+        if let Some(script) = boss {
+            self.boss_states.insert(
+                id,
+                BossState {
+                    script,
+                    next_phase: 0,
+                    engaged_at: None,
+                    enraged: false,
+                    immune_until: None,
+                },
+            );
+        }
+        self.publish_event(WorldEvent::MonsterSpawned {
+            monster_id: id,
+            race_number,
+            position,
+            home_id,
+        });
         Ok(id)
     }
 
@@ -3781,8 +5196,10 @@ impl WorldState {
         monster_id: CreatureId,
         damage_type: DamageType,
         mut amount: u32,
-        killer: Option<PlayerId>,
+        source: DamageSource,
     ) -> Result<Option<MonsterReward>, String> {
+        let (source, name) = self.resolve_damage_source(source);
+        let (now, window) = self.damage_window();
         let dead = {
             let monster = self
                 .monsters
@@ -3791,23 +5208,35 @@ impl WorldState {
             if monster.flags.blocks_damage(damage_type) {
                 return Ok(None);
             }
+            if self
+                .boss_states
+                .get(&monster_id)
+                .and_then(|boss| boss.immune_until)
+                .is_some_and(|until| now < until)
+            {
+                return Ok(None);
+            }
             if damage_type == DamageType::Physical {
                 amount = amount
                     .saturating_sub(monster.defend)
                     .saturating_sub(monster.armor);
             }
             let applied = monster.stats.apply_damage(damage_type, amount);
-            if let Some(attacker) = killer {
-                if applied > 0 {
-                    let entry = monster.damage_by.entry(attacker).or_insert(0);
-                    *entry = entry.saturating_add(applied);
-                }
+            monster.damage_ledger.prune(now, window);
+            monster
+                .damage_ledger
+                .record(source, &name, damage_type, applied, now);
+            if let Some(player_id) = source.player_id() {
+                self.monster_threat
+                    .entry(monster_id)
+                    .or_default()
+                    .add(player_id, applied);
             }
             monster.stats.health == 0
         };
 
         if dead {
-            return Ok(Some(self.defeat_monster(monster_id, killer)?));
+            return Ok(Some(self.defeat_monster(monster_id, source.player_id())?));
         }
 
         Ok(None)
@@ -3823,9 +5252,18 @@ impl WorldState {
             .remove(&monster_id)
             .ok_or_else(|| format!("unknown monster {:?}", monster_id))?;
         self.remove_monster_from_sector_index(monster_id, monster.position);
-        if let Some(home_id) = monster.home_id {
-            self.notify_monster_home_death(home_id);
-        }
+        self.boss_states.remove(&monster_id);
+        self.monster_threat.remove(&monster_id);
+        self.monster_leashing.remove(&monster_id);
+        self.publish_event(WorldEvent::CreatureKilled {
+            victim: KilledCreature::Monster {
+                id: monster_id,
+                race_number: monster.race_number,
+                home_id: monster.home_id,
+            },
+            position: monster.position,
+            killer,
+        });
         let drops = monster_inventory_items(&monster);
 
         if let Some(corpse_id) = monster.corpse_ids.first().copied() {
@@ -3835,6 +5273,7 @@ impl WorldState {
                 attributes: Vec::new(),
                 contents: drops.clone(),
             };
+            self.protect_monster_corpse(corpse.id, &monster, killer);
             self.cron_expire_item(&corpse, -1);
             let stackable = self
                 .item_types
@@ -3867,1060 +5306,2156 @@ impl WorldState {
         }
 
         let experience = monster.experience;
-        if let Some(killer_id) = killer {
-            let party_id = self.party_id_for_player(killer_id);
-            if let Some(party_id) = party_id {
-                if let Some(members) = self.party_shared_exp_members(party_id) {
-                    let share = Self::party_shared_exp_share(experience, members.len());
-                    for member_id in members {
-                        if let Some(player) = self.players.get_mut(&member_id) {
-                            player.add_experience(share);
-                            Self::apply_soul_regen_on_experience(player, share);
-                        }
-                    }
-                } else if let Some(player) = self.players.get_mut(&killer_id) {
-                    player.add_experience(experience);
-                    Self::apply_soul_regen_on_experience(player, experience);
-                }
-            } else if let Some(player) = self.players.get_mut(&killer_id) {
-                player.add_experience(experience);
-                Self::apply_soul_regen_on_experience(player, experience);
-            }
-        }
+        self.award_monster_kill_experience(&monster, killer);
 
         Ok(MonsterReward { experience, drops })
     }
 
-    pub fn schedule_raid(
+    /// Reserves a fresh monster corpse for the top damager, falling back to
+    /// the killer, and their party.
+    fn protect_monster_corpse(
         &mut self,
-        raid_name: &str,
-        seed: u64,
-        start: GameTick,
-    ) -> Result<usize, String> {
-        let index = self
-            .monster_index
-            .as_ref()
-            .ok_or_else(|| "monster index missing".to_string())?;
-        let raid = index
-            .raids
-            .get(raid_name)
-            .ok_or_else(|| format!("unknown raid {}", raid_name))?;
-        let plans = crate::world::monsters::resolve_raid_spawns(index, raid, seed)?;
-        for plan in plans {
-            let delay = plan.delay.max(0) as u64;
-            let at = GameTick(start.0.saturating_add(delay));
-            self.raid_events.push(RaidSpawnEvent { at, plan });
+        corpse_id: ItemId,
+        monster: &MonsterInstance,
+        killer: Option<PlayerId>,
+    ) {
+        let now = unix_time_now();
+        let protection = self.combat_rules.loot_protection.as_secs();
+        if protection == 0 {
+            return;
         }
-        Ok(self.raid_events.len())
+        let (tick, window) = self.damage_window();
+        let owner = monster.damage_ledger.top_player(tick, window).or(killer);
+        let Some(player_id) = owner else {
+            return;
+        };
+        let party_id = self.party_id_for_player(player_id);
+        self.corpse_owners.insert(
+            corpse_id,
+            CorpseOwner {
+                player_id,
+                party_id,
+                expires_at: now.saturating_add(protection),
+            },
+        );
     }
 
-    pub fn spawn_due_raids(&mut self, now: GameTick) -> Vec<CreatureId> {
-        if self.raid_events.is_empty() {
-            return Vec::new();
+    fn corpse_open_allowed(&self, player_id: PlayerId, corpse_id: ItemId) -> bool {
+        let Some(owner) = self.corpse_owners.get(&corpse_id) else {
+            return true;
+        };
+        if owner.expires_at <= unix_time_now() || owner.player_id == player_id {
+            return true;
         }
-        let mut spawned = Vec::new();
-        let mut pending = Vec::new();
-        let events = std::mem::take(&mut self.raid_events);
-        for event in events {
-            if event.at <= now {
-                for position in event.plan.positions {
-                    if let Ok(id) = self.spawn_monster_by_race(event.plan.race_number, position) {
-                        spawned.push(id);
-                    }
+        owner.party_id.is_some() && self.party_id_for_player(player_id) == owner.party_id
+    }
+
+    /// Splits a monster's experience between the players who damaged it by
+    /// their share of the player damage; what monsters and the environment
+    /// dealt is left out. Without any player damage the killer gets
+    /// everything.
+    fn award_monster_kill_experience(&mut self, monster: &MonsterInstance, killer: Option<PlayerId>) {
+        let (now, window) = self.damage_window();
+        let mut attackers: Vec<(PlayerId, u32)> = monster
+            .damage_ledger
+            .player_damage(now, window)
+            .into_iter()
+            .collect();
+        attackers.sort_by_key(|(attacker_id, _)| attacker_id.0);
+        let total: u32 = attackers
+            .iter()
+            .fold(0u32, |total, (_, damage)| total.saturating_add(*damage));
+        if total == 0 {
+            if let Some(killer_id) = killer {
+                self.grant_kill_experience(killer_id, monster.experience);
+            }
+            return;
+        }
+        for (attacker_id, damage) in attackers {
+            let share = (u64::from(monster.experience) * u64::from(damage) / u64::from(total))
+                .min(u64::from(u32::MAX)) as u32;
+            if share > 0 {
+                self.grant_kill_experience(attacker_id, share);
+            }
+        }
+    }
+
+    /// Gives kill experience to a player, or shares it across their party
+    /// when shared experience is active.
+    fn grant_kill_experience(&mut self, killer_id: PlayerId, experience: u32) {
+        let party_id = self.party_id_for_player(killer_id);
+        if let Some(party_id) = party_id {
+            if let Some(members) = self.party_shared_exp_members(party_id) {
+                let share = Self::party_shared_exp_share(experience, members.len());
+                for member_id in members {
+                    self.add_player_experience(member_id, share);
                 }
-            } else {
-                pending.push(event);
+                return;
             }
         }
-        self.raid_events = pending;
-        spawned
+        self.add_player_experience(killer_id, experience);
     }
 
-    pub fn tick_raids(&mut self, now: GameTick, clock: &GameClock) -> Vec<CreatureId> {
-        self.ensure_raid_schedules(now, clock);
-        if self.raid_schedules.is_empty() {
-            return Vec::new();
+    fn add_player_experience(&mut self, player_id: PlayerId, amount: u32) {
+        let Some(player) = self.players.get_mut(&player_id) else {
+            return;
+        };
+        let old_level = player.add_experience(amount);
+        Self::apply_soul_regen_on_experience(player, amount);
+        let new_level = player.level;
+        if new_level <= old_level {
+            return;
         }
-        let mut due_raids = Vec::new();
-        for (name, schedule) in self.raid_schedules.iter_mut() {
-            if now >= schedule.next_at {
-                due_raids.push(name.clone());
-                schedule.next_at =
-                    GameTick(schedule.next_at.0.saturating_add(schedule.interval_ticks));
+        self.queue_player_message(
+            player_id,
+            MESSAGE_EVENT,
+            format!("You advanced from Level {} to Level {}.", old_level, new_level),
+        );
+        self.queue_player_skills_update(player_id);
+        self.queue_player_data_update(player_id);
+        self.publish_event(WorldEvent::LevelUp {
+            player_id,
+            old_level,
+            new_level,
+        });
+    }
+
+    /// Resolves every player whose health reached zero: applies the death
+    /// penalties, leaves a corpse and sends the character back to its temple.
+    pub fn tick_player_deaths(&mut self) -> Vec<DeathRecord> {
+        let mut dead: Vec<PlayerId> = self
+            .players
+            .iter()
+            .filter(|(_, player)| player.stats.health == 0 && player.stats.max_health > 0)
+            .map(|(id, _)| *id)
+            .collect();
+        dead.sort_by_key(|id| id.0);
+        let mut deaths = Vec::new();
+        for player_id in dead {
+            match self.handle_player_death(player_id) {
+                Ok(death) => deaths.push(death),
+                Err(err) => logging::log_error(&format!(
+                    "player death failed for {:?}: {}",
+                    player_id, err
+                )),
             }
         }
-        for name in due_raids {
-            let seed = raid_seed(&name, now);
-            if let Err(err) = self.schedule_raid(&name, seed, now) {
-                eprintln!("tibia: raid schedule {} failed: {}", name, err);
+        deaths
+    }
+
+    pub(crate) fn take_pending_forced_logout(&mut self, player_id: PlayerId) -> bool {
+        self.pending_forced_logouts.remove(&player_id)
+    }
+
+    /// Credits summon damage to the summoner and names the source the way
+    /// death messages and corpses show it.
+    fn resolve_damage_source(&self, source: DamageSource) -> (DamageSource, String) {
+        match source {
+            DamageSource::Player(player_id) => {
+                let name = self
+                    .players
+                    .get(&player_id)
+                    .map(|player| player.name.clone())
+                    .unwrap_or_else(|| "someone".to_string());
+                (source, name)
+            }
+            DamageSource::Monster(monster_id) => {
+                let Some(monster) = self.monsters.get(&monster_id) else {
+                    return (source, "something".to_string());
+                };
+                if let Some(summoner) = monster.summoner {
+                    if self.players.contains_key(&summoner) {
+                        return self.resolve_damage_source(DamageSource::Player(summoner));
+                    }
+                }
+                let article = self
+                    .monster_index
+                    .as_ref()
+                    .and_then(|index| index.script_by_race(monster.race_number))
+                    .and_then(|script| script.article())
+                    .map(|value| value.trim().to_string())
+                    .filter(|value| !value.is_empty());
+                let name = match article {
+                    Some(article) => format!("{} {}", article, monster.name),
+                    None => monster.name.clone(),
+                };
+                (source, name)
             }
+            DamageSource::Environment => (source, "the environment".to_string()),
         }
-        self.spawn_due_raids(now)
     }
 
-    pub fn add_spell(&mut self, spell: Spell) -> Result<(), String> {
-        self.spellbook.insert(spell)
+    fn record_player_damage(
+        &mut self,
+        target_id: PlayerId,
+        source: DamageSource,
+        damage_type: DamageType,
+        damage: u32,
+    ) {
+        let (source, name) = self.resolve_damage_source(source);
+        let (now, window) = self.damage_window();
+        if let Some(target) = self.players.get_mut(&target_id) {
+            target.damage_ledger.prune(now, window);
+            target.damage_ledger.record(source, &name, damage_type, damage, now);
+        }
     }
 
-    pub fn teach_spell(&mut self, player_id: PlayerId, spell_id: SpellId) -> Result<(), String> {
-        if self.spellbook.get(spell_id).is_none() {
-            return Err(format!("unknown spell {:?}", spell_id));
+    /// Moves the combat clock up to `clock`; ledger times never go back.
+    fn sync_combat_clock(&mut self, clock: &GameClock) {
+        let behind = self
+            .combat_clock
+            .as_ref()
+            .is_none_or(|current| current.now() <= clock.now());
+        if behind {
+            self.combat_clock = Some(clock.clone());
         }
-        let player = self
-            .players
-            .get_mut(&player_id)
-            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
-        player.learn_spell(spell_id);
-        Ok(())
     }
 
-    pub fn move_player(
-        &mut self,
-        id: PlayerId,
-        direction: Direction,
-        clock: &GameClock,
-    ) -> Result<Position, String> {
-        let (origin, ready, speed, drunken_effect) = {
-            let player = self
-                .players
-                .get(&id)
-                .ok_or_else(|| format!("unknown player {:?}", id))?;
+    /// The current combat tick and the kill credit window in ticks. Before
+    /// the first tick every hit happened at tick zero.
+    fn damage_window(&self) -> (GameTick, u64) {
+        match self.combat_clock.as_ref() {
+            Some(clock) => (
+                clock.now(),
+                clock.ticks_from_duration_round_up(self.combat_rules.damage_window),
+            ),
+            None => (GameTick(0), 0),
+        }
+    }
+
+    fn note_field_owner(&mut self, item_id: ItemId, source: DamageSource, positions: Vec<Position>) {
+        self.field_owners
+            .insert(item_id, FieldOwner { source, positions });
+    }
+
+    /// Who laid a field lying at `position`, if anyone.
+    fn field_owner_at(&self, position: Position) -> Option<DamageSource> {
+        let tile = self.map.tile(position)?;
+        tile.items
+            .iter()
+            .find_map(|item| self.field_owners.get(&item.id))
+            .map(|owner| owner.source)
+    }
+
+    /// Forgets owners of fields that have decayed or been removed from every
+    /// tile they were laid on.
+    fn prune_field_owners(&mut self) {
+        let map = &self.map;
+        self.field_owners.retain(|item_id, owner| {
+            owner.positions.iter().any(|position| {
+                map.tile(*position)
+                    .is_some_and(|tile| tile.items.iter().any(|item| item.id == *item_id))
+            })
+        });
+    }
+
+    /// Remembers who inflicted a damaging condition so its ticks are
+    /// credited to them.
+    fn note_player_condition_source(
+        &mut self,
+        target_id: PlayerId,
+        kind: ConditionKind,
+        source: DamageSource,
+    ) {
+        let (source, name) = self.resolve_damage_source(source);
+        if let Some(target) = self.players.get_mut(&target_id) {
+            target.damage_ledger.note_condition(kind, source, &name);
+        }
+    }
+
+    fn handle_player_death(&mut self, player_id: PlayerId) -> Result<DeathRecord, String> {
+        let now = unix_time_now();
+        let (tick, window) = self.damage_window();
+        let (name, race, level, experience, position, credits, skull) = {
+            let player = self
+                .players
+                .get(&player_id)
+                .ok_or_else(|| format!("unknown player {:?}", player_id))?;
             (
+                player.name.clone(),
+                player.race,
+                player.level,
+                player.experience,
                 player.position,
-                player.move_cooldown.is_ready(clock),
-                self.player_move_speed(player),
-                player.drunken_effect,
+                player.damage_ledger.credits(tick, window),
+                player.pvp.skull,
             )
         };
-        if !ready {
-            return Err("movement blocked: cooldown".to_string());
-        }
-        let mut direction = direction;
-        if let Some(effect) = drunken_effect {
-            if !effect.is_expired(clock.now()) {
-                let chance = u32::from(effect.intensity)
-                    .saturating_mul(DRUNKEN_CHANCE_PER_LEVEL)
-                    .min(100);
-                if chance > 0 && self.monster_rng.roll_percent(chance) {
-                    direction = self.monster_rng.roll_direction();
-                }
-            }
-        }
-        let destination = self.resolve_movement_destination(origin, direction)?;
-        if let Some(house) = self.house_for_position(destination) {
-            let origin_house_id = self.house_for_position(origin).map(|house| house.id);
-            if origin_house_id != Some(house.id) {
-                let player = self
-                    .players
-                    .get(&id)
-                    .ok_or_else(|| format!("unknown player {:?}", id))?;
-                if !self.player_can_enter_house(player, house) {
-                    return Err("movement blocked: house access".to_string());
-                }
-            }
-        }
-        {
+        let lose_everything = matches!(skull, SkullState::Red | SkullState::Black);
+        let experience_lost = {
             let player = self
                 .players
-                .get(&id)
-                .ok_or_else(|| format!("unknown player {:?}", id))?;
-            if !self.door_allows_player(player, destination) {
-                return Err("movement blocked: door access".to_string());
-            }
-        }
-        let tile_speed = self
-            .map
-            .tile(destination)
-            .map(|tile| self.tile_ground_speed(tile))
-            .unwrap_or(DEFAULT_GROUND_SPEED);
-        let cooldown_ticks = self.movement_cooldown_ticks(tile_speed, speed, direction, clock);
-        let player = self
-            .players
-            .get_mut(&id)
-            .ok_or_else(|| format!("unknown player {:?}", id))?;
-        player.move_to(destination, direction);
-        player.move_cooldown.reset_from_now_ticks(clock, cooldown_ticks);
-        let separation =
-            self.trigger_moveuse_tile_event(MoveUseEvent::Separation, id, origin, origin);
-        let collision =
-            self.trigger_moveuse_tile_event(MoveUseEvent::Collision, id, destination, destination);
-        self.queue_moveuse_outcomes(id, separation);
-        self.queue_moveuse_outcomes(id, collision);
-        Ok(destination)
-    }
+                .get_mut(&player_id)
+                .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+            let percent = player.death_loss_percent();
+            let lost = player.lose_experience(
+                player.experience.saturating_mul(u64::from(percent)) / 100,
+            );
+            Self::apply_death_skill_loss(player, percent);
+            lost
+        };
+        let dropped = self.take_death_drops(player_id, lose_everything)?;
+        let killer_names = Self::death_killer_names(&credits);
+        self.place_player_corpse(&name, race, position, &killer_names, dropped);
 
-    pub fn turn_player(&mut self, id: PlayerId, direction: Direction) -> Result<(), String> {
-        let (position, changed) = {
+        self.end_player_session(player_id);
+        let new_level = {
             let player = self
                 .players
-                .get_mut(&id)
-                .ok_or_else(|| format!("unknown player {:?}", id))?;
-            if player.direction == direction {
-                return Ok(());
+                .get_mut(&player_id)
+                .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+            Self::reset_player_after_death(player);
+            if skull == SkullState::Black {
+                player.stats.health = player.stats.max_health.min(BLACK_SKULL_RESPAWN_HEALTH);
+                player.stats.mana = 0;
             }
-            player.direction = direction;
-            (player.position, true)
+            player.level
         };
-        if changed {
-            self.queue_turn_update(CreatureTurnUpdate {
-                id: id.0,
-                position,
-                direction,
+        self.move_player_offline(player_id);
+        self.queue_map_refresh(position);
+        self.queue_player_message(player_id, MESSAGE_WARNING, "You are dead.".to_string());
+        if !killer_names.is_empty() {
+            self.queue_player_message(
+                player_id,
+                MESSAGE_EVENT,
+                format!("You were killed by {}.", killer_names.join(" and ")),
+            );
+        }
+        self.pending_forced_logouts.insert(player_id);
+
+        let death = DeathRecord {
+            at: now,
+            player_id: player_id.0,
+            player_name: name.clone(),
+            level,
+            new_level,
+            experience_lost,
+            killers: killer_names.clone(),
+            position,
+        };
+        if let Err(err) = self.deaths.record(death.clone()) {
+            logging::log_error(&err);
+        }
+        self.judge_player_kill(player_id, &name, skull, &credits);
+        if self.combat_rules.player_kills_give_experience() {
+            self.award_player_kill_experience(player_id, level, experience, &credits);
+        }
+        for killer in credits.iter().filter(|credit| credit.source.player_id().is_some()) {
+            audit::record(AuditEvent::Kill {
+                killer: killer.name.clone(),
+                victim: name.clone(),
             });
         }
-        Ok(())
+        audit::record(AuditEvent::Death {
+            player: name,
+            killers: killer_names,
+        });
+        self.publish_event(WorldEvent::CreatureKilled {
+            victim: KilledCreature::Player(player_id),
+            position,
+            killer: credits.iter().find_map(|credit| credit.source.player_id()),
+        });
+        logging::log_game(&death.summary());
+        Ok(death)
     }
 
-    pub fn set_player_autowalk(&mut self, id: PlayerId, steps: Vec<Direction>) {
-        if let Some(player) = self.players.get_mut(&id) {
-            player.autowalk_steps = steps.into();
+    /// The last hitter, followed by the top damager when that is someone else.
+    fn death_killer_names(credits: &[DamageCredit]) -> Vec<String> {
+        let mut names = Vec::new();
+        if let Some(last) = credits.first() {
+            names.push(last.name.clone());
+        }
+        if let Some(top) = credits
+            .iter()
+            .reduce(|best, credit| if credit.amount > best.amount { credit } else { best })
+        {
+            if credits.first().is_some_and(|last| last.source != top.source) {
+                names.push(top.name.clone());
+            }
         }
+        names
     }
 
-    pub fn clear_player_autowalk(&mut self, id: PlayerId) {
-        if let Some(player) = self.players.get_mut(&id) {
-            player.autowalk_steps.clear();
+    /// Records a player attacking another player. Attacking someone without a
+    /// skull who has not attacked you first is unjustified and earns a white
+    /// skull; the victim then shows a yellow skull to the aggressor.
+    fn record_pvp_attack(&mut self, attacker_id: PlayerId, target_id: PlayerId, clock: &GameClock) {
+        if attacker_id == target_id || !self.combat_rules.skulls_apply() {
+            return;
+        }
+        let justified = self.players.get(&target_id).is_some_and(|target| {
+            matches!(
+                target.pvp.skull,
+                SkullState::White | SkullState::Red | SkullState::Black
+            )
+        }) || self.pvp_aggressions.contains_key(&(target_id, attacker_id));
+        if justified {
+            return;
+        }
+        let skull_timer = self.combat_rules.white_skull_timer;
+        let ticks = clock.ticks_from_duration_round_up(skull_timer);
+        let expires_at = GameTick(clock.now().0.saturating_add(ticks));
+        let first_attack = self
+            .pvp_aggressions
+            .insert((attacker_id, target_id), expires_at)
+            .is_none();
+        let skull_changed = match self.players.get_mut(&attacker_id) {
+            Some(attacker) => {
+                let before = attacker.pvp.skull;
+                attacker.mark_white_skull(clock, skull_timer);
+                attacker.pvp.skull != before
+            }
+            None => false,
+        };
+        if skull_changed {
+            self.queue_skull_updates_for_target(attacker_id);
+        }
+        if first_attack {
+            self.queue_skull_update(attacker_id, target_id);
         }
     }
 
-    pub fn creature_exists(&self, id: CreatureId) -> bool {
-        self.players.contains_key(&PlayerId(id.0))
-            || self.monsters.contains_key(&id)
-            || self.npcs.contains_key(&id)
+    /// Expires white skulls and finished aggressions, and applies red and
+    /// black skulls from their end times.
+    pub fn tick_player_skulls(&mut self, clock: &GameClock) {
+        let now = clock.now();
+        let unix_now = unix_time_now();
+        let mut changed = Vec::new();
+        for (player_id, player) in self.players.iter_mut() {
+            let timed = player.refresh_skull(now);
+            let persistent = player.refresh_persistent_skull(unix_now);
+            if timed || persistent {
+                changed.push(*player_id);
+            }
+        }
+        let expired: Vec<(PlayerId, PlayerId)> = self
+            .pvp_aggressions
+            .iter()
+            .filter(|(_, expires_at)| now >= **expires_at)
+            .map(|(pair, _)| *pair)
+            .collect();
+        for pair in &expired {
+            self.pvp_aggressions.remove(pair);
+        }
+        changed.sort_by_key(|player_id| player_id.0);
+        for player_id in changed {
+            self.queue_skull_updates_for_target(player_id);
+        }
+        for (attacker_id, victim_id) in expired {
+            if self.players.contains_key(&attacker_id) {
+                self.queue_skull_update(attacker_id, victim_id);
+            }
+        }
     }
 
-    pub fn set_player_attack_target(&mut self, id: PlayerId, target: Option<CreatureId>) {
-        if let Some(player) = self.players.get_mut(&id) {
-            player.attack_target = target;
+    /// Decides for every player who took part in a kill whether it was
+    /// justified. A kill is justified when the victim carried a skull or had
+    /// attacked that killer first.
+    fn judge_player_kill(
+        &mut self,
+        victim_id: PlayerId,
+        victim_name: &str,
+        victim_skull: SkullState,
+        killers: &[DamageCredit],
+    ) {
+        if !self.combat_rules.skulls_apply() {
+            self.pvp_aggressions.clear();
+            return;
         }
+        let victim_marked = matches!(
+            victim_skull,
+            SkullState::White | SkullState::Red | SkullState::Black
+        );
+        // Killers who logged out before their victim died still answer for
+        // the murder; punish_unjustified_kill finds them offline or in saves.
+        for killer_id in killers.iter().filter_map(|killer| killer.source.player_id()) {
+            if killer_id == victim_id {
+                continue;
+            }
+            let retaliation = self.pvp_aggressions.contains_key(&(victim_id, killer_id));
+            if !victim_marked && !retaliation {
+                self.punish_unjustified_kill(killer_id, victim_name);
+            }
+        }
+        self.pvp_aggressions
+            .retain(|(attacker, victim), _| *attacker != victim_id && *victim != victim_id);
     }
 
-    pub fn set_player_follow_target(&mut self, id: PlayerId, target: Option<CreatureId>) {
-        if let Some(player) = self.players.get_mut(&id) {
-            player.follow_target = target;
+    /// Splits the PvP-enforced kill experience between every player in the
+    /// victim's damage credits. Each attacker's own level sets the full
+    /// amount, of which they receive their share of the player damage dealt.
+    fn award_player_kill_experience(
+        &mut self,
+        victim_id: PlayerId,
+        victim_level: u16,
+        victim_experience: u64,
+        credits: &[DamageCredit],
+    ) {
+        let mut attackers: Vec<(PlayerId, u32)> = credits
+            .iter()
+            .filter_map(|credit| credit.source.player_id().map(|id| (id, credit.amount)))
+            .filter(|(attacker_id, damage)| *attacker_id != victim_id && *damage > 0)
+            .collect();
+        attackers.sort_by_key(|(attacker_id, _)| attacker_id.0);
+        let total: u64 = attackers.iter().map(|(_, damage)| u64::from(*damage)).sum();
+        if total == 0 {
+            return;
+        }
+        for (attacker_id, damage) in attackers {
+            let Some(level) = self.players.get(&attacker_id).map(|player| player.level) else {
+                continue;
+            };
+            let full = pvp_kill_experience(level, victim_level, victim_experience);
+            let share = (u128::from(full) * u128::from(damage) / u128::from(total))
+                .min(u128::from(u32::MAX)) as u32;
+            if share == 0 {
+                continue;
+            }
+            self.grant_kill_experience(attacker_id, share);
+            self.queue_player_data_update(attacker_id);
         }
     }
 
-    pub fn set_player_outfit(&mut self, id: PlayerId, outfit: Outfit) {
-        let Some(player) = self.players.get_mut(&id) else {
-            return;
+    fn punish_unjustified_kill(&mut self, killer_id: PlayerId, victim_name: &str) {
+        let now = unix_time_now();
+        let rules = self.combat_rules.clone();
+        let record = |killer: &mut PlayerState| {
+            killer.record_murder(now, rules.murder_memory());
+            if rules.black_skull_thresholds.reached(&killer.murders, now) {
+                killer.blackskull_end = now.saturating_add(rules.black_skull_duration.as_secs());
+            } else if rules.red_skull_thresholds.reached(&killer.murders, now) {
+                killer.playerkiller_end = now.saturating_add(rules.red_skull_duration.as_secs());
+            }
+            killer.refresh_persistent_skull(now);
+            (
+                killer.name.clone(),
+                rules.banishment_thresholds.reached(&killer.murders, now),
+            )
         };
-        let outfit = player.clamp_outfit(outfit);
-        let mut update = None;
-        if let Some(effect) = player.outfit_effect.as_mut() {
-            effect.original = outfit;
-            player.original_outfit = outfit;
+        let online = self.players.contains_key(&killer_id);
+        let (killer_name, banish) = if let Some(killer) = self
+            .players
+            .get_mut(&killer_id)
+            .or_else(|| self.offline_players.get_mut(&killer_id))
+        {
+            record(killer)
         } else {
-            let current = player.current_outfit;
-            player.current_outfit = outfit;
-            player.original_outfit = outfit;
-            if current != outfit {
-                update = Some(CreatureOutfitUpdate {
-                    id: id.0,
-                    outfit,
-                });
+            let Some(root) = self.root.as_ref() else {
+                return;
+            };
+            let store = SaveStore::from_root(root);
+            let mut killer = match store.load_player(killer_id) {
+                Ok(Some(killer)) => killer,
+                Ok(None) => return,
+                Err(err) => {
+                    logging::log_error(&format!(
+                        "murder of {} not recorded: {}",
+                        victim_name, err
+                    ));
+                    return;
+                }
+            };
+            let outcome = record(&mut killer);
+            if let Err(err) = store.save_player(&killer) {
+                logging::log_error(&err);
             }
+            outcome
+        };
+        logging::log_game(&format!(
+            "unjustified kill: {} killed {}",
+            killer_name, victim_name
+        ));
+        if online {
+            self.queue_player_message(
+                killer_id,
+                MESSAGE_WARNING,
+                format!("Warning! The murder of {} was not justified.", victim_name),
+            );
+            self.queue_skull_updates_for_target(killer_id);
+        }
+        if banish {
+            self.banish_for_murders(killer_id, &killer_name, now);
+        }
+    }
+
+    fn banish_for_murders(&mut self, player_id: PlayerId, name: &str, now: u64) {
+        let reason = "excessive unjustified player killing".to_string();
+        let account = self
+            .detection
+            .session(player_id)
+            .map(|session| session.account.clone())
+            .or_else(|| {
+                let root = self.root.as_ref()?;
+                let accounts = AccountRegistry::load(root)
+                    .map_err(|err| logging::log_error(&err))
+                    .ok()??;
+                accounts
+                    .account_for_player(player_id)
+                    .map(|record| record.name.clone())
+            });
+        match (self.root.as_ref(), account.as_ref()) {
+            (Some(root), Some(account)) => {
+                let expires_at = UNIX_EPOCH
+                    + Duration::from_secs(now)
+                    + self.combat_rules.banishment_duration;
+                if let Err(err) = BanList::banish(root, account, Some(expires_at), &reason) {
+                    logging::log_error(&err);
+                }
+            }
+            _ => logging::log_error(&format!(
+                "banishment of {} not stored: no account or save root",
+                name
+            )),
+        }
+        logging::log_banish(&format!("{} banished for {}", name, reason));
+        audit::record(AuditEvent::Ban {
+            gamemaster: "server".to_string(),
+            target: name.to_string(),
+            reason: reason.clone(),
+        });
+        if !self.players.contains_key(&player_id) {
+            return;
         }
-        if let Some(update) = update {
-            self.queue_outfit_update(update);
+        self.queue_player_message(
+            player_id,
+            MESSAGE_WARNING,
+            format!("You have been banished for {}.", reason),
+        );
+        self.end_player_session(player_id);
+        self.move_player_offline(player_id);
+        self.pending_forced_logouts.insert(player_id);
+    }
+
+    fn apply_death_skill_loss(player: &mut PlayerState, percent: u32) {
+        if player.raw_skills.is_empty() {
+            player.raw_skills = skill_rows_from_player(player);
+        }
+        for skill in [
+            SkillType::Fist,
+            SkillType::Club,
+            SkillType::Sword,
+            SkillType::Axe,
+            SkillType::Distance,
+            SkillType::Shielding,
+            SkillType::Fishing,
+            SkillType::Magic,
+        ] {
+            let skill_id = skill_id_for_type(skill);
+            let Some(row) = player
+                .raw_skills
+                .iter_mut()
+                .find(|row| row.skill_id == skill_id)
+            else {
+                continue;
+            };
+            let min_level = if skill == SkillType::Magic { 0 } else { 10 };
+            let level = Self::lose_skill_from_row(row, percent, min_level);
+            let progress = skill_progress_from_values(&row.values);
+            Self::set_player_skill_level(player, skill, level.clamp(0, u16::MAX as i32) as u16, progress);
         }
     }
 
-    pub fn tick_player_autowalk(&mut self, id: PlayerId, clock: &GameClock) {
-        let direction = self
-            .players
-            .get(&id)
-            .and_then(|player| player.autowalk_steps.front().copied());
-        let Some(direction) = direction else {
-            return;
-        };
-        match self.move_player(id, direction, clock) {
-            Ok(_) => {
-                if let Some(player) = self.players.get_mut(&id) {
-                    player.autowalk_steps.pop_front();
-                }
-            }
-            Err(err) => {
-                if err != "movement blocked: cooldown" {
-                    if let Some(player) = self.players.get_mut(&id) {
-                        player.autowalk_steps.clear();
-                    }
-                }
-            }
+    /// Removes a percentage of the row's skill points and drops levels that
+    /// are no longer covered, never below `min_level`.
+    fn lose_skill_from_row(row: &mut SkillRow, percent: u32, min_level: i32) -> i32 {
+        let exp_base = row.values[SKILL_FIELD_DELTA];
+        let mut level = row.values[SKILL_FIELD_ACT];
+        if exp_base <= 0 || exp_base == i32::MAX {
+            return level;
+        }
+        let exp_current = i64::from(row.values[SKILL_FIELD_EXP].max(0));
+        let exp_current = exp_current - exp_current * i64::from(percent) / 100;
+        while level > min_level
+            && skill_exp_for_level(level, exp_base).is_some_and(|required| exp_current < required)
+        {
+            level -= 1;
+        }
+        row.values[SKILL_FIELD_ACT] = level;
+        row.values[SKILL_FIELD_EXP] = exp_current.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
+        if let Some(exp_next) = skill_exp_for_level(level.saturating_add(1), exp_base) {
+            row.values[SKILL_FIELD_NEXT_LEVEL] =
+                exp_next.clamp(i32::MIN as i64, i32::MAX as i64) as i32;
         }
+        level
     }
 
-    pub fn tick_player_attack(
+    /// Takes the items a dying player loses. Red and black skulls lose
+    /// everything; otherwise an amulet of loss or full blessings protect the
+    /// inventory, and containers always drop while other items drop rarely.
+    fn take_death_drops(
         &mut self,
         player_id: PlayerId,
-        clock: &GameClock,
-    ) -> PlayerCombatOutcome {
-        let mut outcome = PlayerCombatOutcome::default();
-        let (target_id, attack_ready, attacker_pos, attack_mode, attacker_health) = match self
-            .players
-            .get(&player_id)
-        {
-            Some(player) => (
-                player.attack_target,
-                player.attack_cooldown.is_ready(clock),
-                player.position,
-                player.fight_modes.attack_mode,
-                player.stats.health,
-            ),
-            None => return outcome,
+        lose_everything: bool,
+    ) -> Result<Vec<ItemStack>, String> {
+        if !lose_everything {
+            let (amulet_count, blessed) = {
+                let player = self
+                    .players
+                    .get(&player_id)
+                    .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+                let amulet_count = player
+                    .inventory
+                    .slot(InventorySlot::Necklace)
+                    .filter(|item| item.type_id == AMULET_OF_LOSS_TYPE_ID)
+                    .map(|item| item.count);
+                (amulet_count, player.blessing_count() >= BLESSING_COUNT)
+            };
+            if let Some(count) = amulet_count {
+                self.take_inventory_item_with_contents(player_id, InventorySlot::Necklace, count)?;
+                return Ok(Vec::new());
+            }
+            if blessed {
+                return Ok(Vec::new());
+            }
+        }
+        let mut dropped = Vec::new();
+        for slot in INVENTORY_SLOTS {
+            let Some((type_id, count)) = self
+                .players
+                .get(&player_id)
+                .and_then(|player| player.inventory.slot(slot))
+                .map(|item| (item.type_id, item.count))
+            else {
+                continue;
+            };
+            let is_container = self
+                .item_types
+                .as_ref()
+                .and_then(|types| types.get(type_id))
+                .map(|entry| entry.kind == ItemKind::Container)
+                .unwrap_or(false);
+            let chance = if lose_everything || is_container {
+                100
+            } else {
+                DEATH_ITEM_LOSS_PERCENT
+            };
+            if !self.monster_rng.roll_percent(chance) {
+                continue;
+            }
+            dropped.push(self.take_inventory_item_with_contents(player_id, slot, count)?);
+        }
+        Ok(dropped)
+    }
+
+    fn place_player_corpse(
+        &mut self,
+        name: &str,
+        race: u8,
+        position: Position,
+        killers: &[String],
+        contents: Vec<ItemStack>,
+    ) {
+        let (type_id, pronoun) = if race == 0 {
+            (PLAYER_CORPSE_MALE_TYPE_ID, "He")
+        } else {
+            (PLAYER_CORPSE_FEMALE_TYPE_ID, "She")
         };
-        let Some(target_id) = target_id else {
-            return outcome;
+        let description = if killers.is_empty() {
+            format!("You recognize {}.", name)
+        } else {
+            format!(
+                "You recognize {}. {} was killed by {}.",
+                name,
+                pronoun,
+                killers.join(" and ")
+            )
         };
-        if !attack_ready || attacker_health == 0 {
-            return outcome;
+        let corpse = ItemStack {
+            id: ItemId::next(),
+            type_id,
+            count: 1,
+            attributes: vec![ItemAttribute::String(description)],
+            contents,
+        };
+        self.cron_expire_item(&corpse, -1);
+        for item in &corpse.contents {
+            self.schedule_cron_for_item_tree(item);
         }
-        if self.is_protection_zone(attacker_pos) {
-            return outcome;
+        let stackable = self
+            .item_types
+            .as_ref()
+            .and_then(|index| index.get(type_id))
+            .map(|entry| entry.stackable)
+            .unwrap_or(false);
+        let movable = self.item_is_movable(&corpse);
+        if let Some(tile) = self.map.tile_mut(position) {
+            let _ = place_on_tile_with_dustbin(tile, corpse, stackable, movable);
         }
+    }
 
-        let selection = match self.players.get(&player_id) {
-            Some(player) => self.player_attack_values(player),
-            None => return outcome,
-        };
-        if selection.attack == 0 {
-            return outcome;
+    fn reset_player_after_death(player: &mut PlayerState) {
+        for condition in std::mem::take(&mut player.conditions) {
+            if let Some(skill_id) = Self::condition_skill_id(condition.kind) {
+                if let Some(row) = player
+                    .raw_skills
+                    .iter_mut()
+                    .find(|row| row.skill_id == skill_id)
+                {
+                    Self::clear_skill_timer(row);
+                }
+            }
         }
+        player.stats.health = player.stats.max_health;
+        player.stats.mana = player.stats.max_mana;
+        player.damage_ledger.clear();
+        player.attack_target = None;
+        player.follow_target = None;
+        player.autowalk_steps.clear();
+        player.open_containers.clear();
+        player.pvp.fight_expires_at = None;
+        player.position = player.start_position;
+    }
 
-        let (skill_level, level) = match self.players.get(&player_id) {
-            Some(player) => (player.skills.get(selection.skill).level, player.level),
-            None => return outcome,
-        };
-        let attempted_damage =
-            self.roll_player_attack_damage(selection.attack, attack_mode, skill_level, level);
+    pub fn schedule_raid(
+        &mut self,
+        raid_name: &str,
+        seed: u64,
+        start: GameTick,
+    ) -> Result<usize, String> {
+        let index = self
+            .monster_index
+            .as_ref()
+            .ok_or_else(|| "monster index missing".to_string())?;
+        let raid = index
+            .raids
+            .get(raid_name)
+            .ok_or_else(|| format!("unknown raid {}", raid_name))?;
+        let plans = crate::world::monsters::resolve_raid_spawns(index, raid, seed)?;
+        for plan in plans {
+            let delay = plan.delay.max(0) as u64;
+            let at = GameTick(start.0.saturating_add(delay));
+            self.raid_events.push(RaidSpawnEvent {
+                raid: raid_name.to_string(),
+                at,
+                plan,
+            });
+        }
+        Ok(self.raid_events.len())
+    }
 
-        let mark_attacker;
-        let mark_white_skull;
+    /// Starts a raid right away, as the calendar would, and records the run
+    /// so the next calendar start counts from now.
+    pub fn start_raid(&mut self, raid_name: &str, now: GameTick) -> Result<usize, String> {
+        self.start_raid_at(raid_name, now, unix_time_now())
+    }
 
-        if let Some(monster_pos) = self.monsters.get(&target_id).map(|monster| monster.position) {
-            if monster_pos.z != attacker_pos.z {
-                return outcome;
-            }
-            if Self::monster_tile_distance(attacker_pos, monster_pos) > selection.range {
-                return outcome;
-            }
-            if self.is_protection_zone(monster_pos) {
-                return outcome;
+    fn start_raid_at(
+        &mut self,
+        raid_name: &str,
+        now: GameTick,
+        unix_now: u64,
+    ) -> Result<usize, String> {
+        let seed = raid_seed(raid_name, now);
+        let events = self.schedule_raid(raid_name, seed, now)?;
+        let message = format!("tibia: raid {} started", raid_name);
+        println!("{message}");
+        logging::log_game(&message);
+        if let Err(err) = self.raid_history.record(raid_name, unix_now) {
+            eprintln!("tibia: raid history save failed: {}", err);
+        }
+        let last_run = Some(unix_now);
+        let offset = self.raid_calendar.utc_offset_minutes;
+        if let Some(schedule) = self.raid_schedules.get_mut(raid_name) {
+            schedule.next_at = next_raid_start(raid_name, &schedule.rules, unix_now, last_run, offset);
+        }
+        self.publish_event(WorldEvent::RaidStarted {
+            name: raid_name.to_string(),
+        });
+        Ok(events)
+    }
+
+    /// Raids the calendar will start, soonest first.
+    pub fn upcoming_raids(&mut self) -> Vec<UpcomingRaid> {
+        self.ensure_raid_schedules(unix_time_now());
+        let mut raids: Vec<UpcomingRaid> = self
+            .raid_schedules
+            .iter()
+            .map(|(name, schedule)| UpcomingRaid {
+                name: name.clone(),
+                at: schedule.next_at,
+                rules: schedule.rules.clone(),
+                last_run: self.raid_history.last_run(name),
+            })
+            .collect();
+        raids.sort_by(|a, b| a.at.cmp(&b.at).then_with(|| a.name.cmp(&b.name)));
+        raids
+    }
+
+    pub fn spawn_due_raids(&mut self, now: GameTick) -> Vec<CreatureId> {
+        if self.raid_events.is_empty() {
+            return Vec::new();
+        }
+        let mut spawned = Vec::new();
+        let mut pending = Vec::new();
+        let events = std::mem::take(&mut self.raid_events);
+        for event in events {
+            if event.at <= now {
+                if let Some(message) = event.plan.message.as_ref() {
+                    let player_ids: Vec<PlayerId> = self.players.keys().copied().collect();
+                    for player_id in player_ids {
+                        self.queue_player_message(player_id, MESSAGE_WARNING, message.clone());
+                    }
+                }
+                for position in event.plan.positions {
+                    if let Ok(id) = self.spawn_monster_by_race(event.plan.race_number, position) {
+                        spawned.push(id);
+                    }
+                }
+            } else {
+                pending.push(event);
             }
-            self.train_player_skill(player_id, selection.skill);
-            let before_health = self
-                .monsters
-                .get(&target_id)
-                .map(|monster| monster.stats.health)
-                .unwrap_or(0);
-            if self
-                .apply_damage_to_monster(
-                    target_id,
-                    selection.damage_type,
-                    attempted_damage,
-                    Some(player_id),
-                )
-                .is_err()
-            {
-                return outcome;
+        }
+        self.raid_events = pending;
+        spawned
+    }
+
+    pub fn tick_raids(&mut self, now: GameTick) -> Vec<CreatureId> {
+        self.tick_raids_at(now, unix_time_now())
+    }
+
+    fn tick_raids_at(&mut self, now: GameTick, unix_now: u64) -> Vec<CreatureId> {
+        self.ensure_raid_schedules(unix_now);
+        let mut due_raids: Vec<String> = self
+            .raid_schedules
+            .iter()
+            .filter(|(_, schedule)| unix_now >= schedule.next_at)
+            .map(|(name, _)| name.clone())
+            .collect();
+        due_raids.sort();
+        for name in due_raids {
+            if self.raid_blocked_by_running_raid(&name) {
+                if let Some(schedule) = self.raid_schedules.get_mut(&name) {
+                    schedule.next_at = unix_now.saturating_add(RAID_POSTPONE_SECS);
+                }
+                continue;
             }
-            let after_health = self
-                .monsters
-                .get(&target_id)
-                .map(|monster| monster.stats.health)
-                .unwrap_or(0);
-            let applied = before_health.saturating_sub(after_health);
-            if applied > 0 {
-                if let Some(attacker) = self.players.get_mut(&player_id) {
-                    Self::grant_learning_points(attacker);
+            if let Err(err) = self.start_raid_at(&name, now, unix_now) {
+                eprintln!("tibia: raid schedule {} failed: {}", name, err);
+                if let Some(schedule) = self.raid_schedules.get_mut(&name) {
+                    let offset = self.raid_calendar.utc_offset_minutes;
+                    schedule.next_at =
+                        next_raid_start(&name, &schedule.rules, unix_now, Some(unix_now), offset);
                 }
             }
-            if attempted_damage > 0 {
-                let effect_id = if applied > 0 {
-                    PLAYER_ATTACK_EFFECT_BLOOD_ID
-                } else {
-                    PLAYER_ATTACK_EFFECT_BLOCK_ID
-                };
-                outcome.effects.push(CombatVisualEffect {
-                    position: monster_pos,
-                    effect_id,
-                });
+        }
+        self.spawn_due_raids(now)
+    }
+
+    /// Big raids never overlap: one waits while any other raid still has
+    /// spawns pending, and other raids wait while a big raid runs.
+    fn raid_blocked_by_running_raid(&self, raid_name: &str) -> bool {
+        let is_big = |name: &str| {
+            self.monster_index
+                .as_ref()
+                .and_then(|index| index.raids.get(name))
+                .and_then(|raid| raid.raid_type.as_deref())
+                .is_some_and(|raid_type| raid_type.eq_ignore_ascii_case(BIG_RAID_TYPE))
+        };
+        let big = is_big(raid_name);
+        self.raid_events
+            .iter()
+            .any(|event| event.raid != raid_name && (big || is_big(&event.raid)))
+    }
+
+    pub fn reload_scripts(
+        &mut self,
+        target: ScriptReloadTarget,
+    ) -> Result<ScriptReloadReport, String> {
+        let root = self
+            .root
+            .clone()
+            .ok_or_else(|| "world root missing".to_string())?;
+        let npc_index = if target.includes(ScriptReloadTarget::Npcs) {
+            let npc_dir = root.join("npc");
+            let report = crate::world::npc::validate_npcs(&npc_dir);
+            if !report.errors.is_empty() {
+                return Err(format!(
+                    "npc reload aborted ({} errors): {}",
+                    report.errors.len(),
+                    report.errors.join("; ")
+                ));
             }
-            if self.monsters.contains_key(&target_id) {
-                outcome.monster_updates.push(target_id);
-            } else {
-                outcome.refresh_map = true;
-                if let Some(player) = self.players.get_mut(&player_id) {
-                    if player.attack_target == Some(target_id) {
-                        player.attack_target = None;
-                    }
-                }
+            Some(crate::world::npc::load_npcs(&npc_dir)?)
+        } else {
+            None
+        };
+        let reload_monsters = target.includes(ScriptReloadTarget::Monsters);
+        let reload_raids = target.includes(ScriptReloadTarget::Raids);
+        let monster_index = if reload_monsters || reload_raids {
+            let mon_dir = root.join("mon");
+            let report = crate::world::monsters::validate_monsters(&mon_dir);
+            let errors: Vec<&str> = report
+                .errors
+                .iter()
+                .filter(|err| match err.kind {
+                    MonsterScriptKind::Directory => true,
+                    MonsterScriptKind::Monster => reload_monsters,
+                    MonsterScriptKind::Raid => reload_raids,
+                })
+                .map(|err| err.message.as_str())
+                .collect();
+            if !errors.is_empty() {
+                return Err(format!(
+                    "monster reload aborted ({} errors): {}",
+                    errors.len(),
+                    errors.join("; ")
+                ));
             }
-            mark_attacker = attempted_damage > 0;
-            mark_white_skull = false;
+            Some(crate::world::monsters::load_monsters(&mon_dir)?)
         } else {
-            let target_player_id = PlayerId(target_id.0);
-            if target_player_id == player_id {
-                return outcome;
+            None
+        };
+        let moveuse = if target.includes(ScriptReloadTarget::MoveUse) {
+            let moveuse_path = root.join("dat").join("moveuse.dat");
+            let moveuse = crate::world::moveuse::load_moveuse(&moveuse_path)?;
+            let errors = validate_moveuse(&moveuse);
+            if !errors.is_empty() {
+                return Err(format!(
+                    "moveuse reload aborted ({} errors): {}",
+                    errors.len(),
+                    errors.join("; ")
+                ));
             }
-            let target_pos = match self.players.get(&target_player_id) {
-                Some(target) => target.position,
-                None => {
-                    if self.npcs.contains_key(&target_id) {
-                        if let Some(player) = self.players.get_mut(&player_id) {
-                            player.attack_target = None;
-                        }
-                    } else if let Some(player) = self.players.get_mut(&player_id) {
-                        player.attack_target = None;
-                    }
-                    return outcome;
+            Some(moveuse)
+        } else {
+            None
+        };
+        // `all` skips the scripts directory on worlds that have none.
+        let scripts_dir = root.join("scripts");
+        let scripts = if target == ScriptReloadTarget::ContentScripts
+            || (target == ScriptReloadTarget::All && scripts_dir.is_dir())
+        {
+            Some(crate::scripting::runtime::load_scripts(&scripts_dir)?)
+        } else {
+            None
+        };
+
+        let mut report = ScriptReloadReport::default();
+        if let Some(npc_index) = npc_index {
+            report.npc_scripts = Some(npc_index.scripts.len());
+            self.swap_npc_index(npc_index, &mut report);
+        }
+        if let Some(mut monster_index) = monster_index {
+            // Live monsters keep the stats, spells and loot copied at spawn, so
+            // swapping the index only affects creatures spawned from now on.
+            if let Some(previous) = self.monster_index.take() {
+                if !reload_monsters {
+                    monster_index.scripts = previous.scripts;
+                    monster_index.race_index = previous.race_index;
+                }
+                if !reload_raids {
+                    monster_index.raids = previous.raids;
                 }
-            };
-            if !self.combat_rules.pvp_enabled {
-                return outcome;
-            }
-            if target_pos.z != attacker_pos.z {
-                return outcome;
             }
-            if self.is_protection_zone(target_pos) {
-                return outcome;
+            if reload_monsters {
+                report.monster_scripts = Some(monster_index.scripts.len());
             }
-            if Self::monster_tile_distance(attacker_pos, target_pos) > selection.range {
-                return outcome;
+            if reload_raids {
+                report.raid_scripts = Some(monster_index.raids.len());
+                report_unknown_calendar_raids(&self.raid_calendar, &monster_index);
+                self.raid_schedules.clear();
+                self.raid_schedules_built = false;
             }
+            self.monster_index = Some(monster_index);
+        }
+        if let Some(moveuse) = moveuse {
+            report.moveuse_sections = Some(moveuse.sections.len());
+            self.moveuse = Some(moveuse);
+        }
+        if let Some(scripts) = scripts {
+            report.content_scripts = Some(scripts.len());
+            self.scripts = Some(scripts);
+        }
+        logging::log_game(&format!("script reload: {}", report.summary()));
+        Ok(report)
+    }
 
-            let (defend_selection, defend_ready, target_mode) =
-                match self.players.get(&target_player_id) {
-                    Some(target) => (
-                        self.player_defense_values(target),
-                        target.defend_cooldown.is_ready(clock),
-                        target.fight_modes.attack_mode,
-                    ),
-                    None => return outcome,
-                };
-            let defend = defend_selection.defend;
-            let armor = defend_selection.armor;
-            let defend_roll = if defend_ready {
-                if defend_selection.skill == SkillType::Shielding {
-                    self.train_player_skill(target_player_id, SkillType::Shielding);
-                }
-                let scaled = Self::scale_defend_by_attack_mode(defend, target_mode);
-                if scaled == 0 {
-                    0
-                } else {
-                    let capped = scaled.min(u32::from(u16::MAX));
-                    self.moveuse_rng.roll_range(0, capped)
-                }
-            } else {
-                0
-            };
-            let armor_roll = if armor == 0 {
-                0
-            } else {
-                let capped = armor.min(u32::from(u16::MAX));
-                self.moveuse_rng.roll_range(0, capped)
-            };
-            let mitigated_damage = attempted_damage
-                .saturating_sub(defend_roll)
-                .saturating_sub(armor_roll);
-            let reduced_damage = self.apply_player_protection_reduction(
-                target_player_id,
-                selection.damage_type,
-                mitigated_damage,
-            );
-            let (applied_damage, absorbed_mana) = {
-                let target = match self.players.get_mut(&target_player_id) {
-                    Some(target) => target,
-                    None => return outcome,
+    /// Swaps in a reloaded npc index. NPCs whose definition or behaviour
+    /// script changed are respawned so they drop dialog state tied to the
+    /// old script; removed ones disappear.
+    fn swap_npc_index(&mut self, npc_index: NpcIndex, report: &mut ScriptReloadReport) {
+        let changed: Vec<NpcDefinition> = npc_index
+            .definitions
+            .iter()
+            .filter(|definition| {
+                let Some(previous) = self.npc_index.as_ref() else {
+                    return true;
                 };
-                let (applied, absorbed) = target
-                    .apply_damage_with_magic_shield(selection.damage_type, reduced_damage);
-                if applied > 0 || absorbed > 0 {
-                    target.mark_in_combat(clock, self.combat_rules.fight_timer);
-                }
-                (applied, absorbed)
+                let key = &definition.script_key;
+                !previous.definitions.contains(*definition)
+                    || previous.scripts.get(key) != npc_index.scripts.get(key)
+            })
+            .cloned()
+            .collect();
+        let current_keys: HashSet<&str> = npc_index
+            .definitions
+            .iter()
+            .map(|definition| definition.script_key.as_str())
+            .collect();
+        let changed_keys: HashSet<&str> = changed
+            .iter()
+            .map(|definition| definition.script_key.as_str())
+            .collect();
+        let stale: Vec<CreatureId> = self
+            .npcs
+            .values()
+            .filter(|npc| {
+                !current_keys.contains(npc.script_key.as_str())
+                    || changed_keys.contains(npc.script_key.as_str())
+            })
+            .map(|npc| npc.id)
+            .collect();
+        for npc_id in stale {
+            let Some(npc) = self.npcs.remove(&npc_id) else {
+                continue;
             };
-            if attempted_damage > 0 {
-                let effect_id = if applied_damage > 0 {
-                    PLAYER_ATTACK_EFFECT_BLOOD_ID
-                } else {
-                    PLAYER_ATTACK_EFFECT_BLOCK_ID
-                };
-                outcome.effects.push(CombatVisualEffect {
-                    position: target_pos,
-                    effect_id,
-                });
-                outcome.player_updates.push(target_player_id);
-            }
-            if applied_damage > 0 {
-                if let Some(attacker) = self.players.get_mut(&player_id) {
-                    Self::grant_learning_points(attacker);
-                }
+            if let Some(player_id) = npc.focused {
+                self.close_npc_dialog(player_id);
             }
-            if attempted_damage > 0 && defend_ready {
-                self.apply_defend_wear(target_player_id, defend_selection);
-                let defend_ticks = clock.ticks_from_duration_round_up(Duration::from_millis(
-                    DEFEND_COOLDOWN_MS,
-                ));
-                if let Some(target) = self.players.get_mut(&target_player_id) {
-                    target
-                        .defend_cooldown
-                        .reset_from_now_ticks(clock, defend_ticks);
-                }
+            for player in self.players.values_mut() {
+                player.set_npc_topic(npc_id, 0);
+                player.clear_npc_vars(npc_id);
             }
-            let _ = absorbed_mana;
-            mark_attacker = attempted_damage > 0;
-            mark_white_skull = attempted_damage > 0;
+            self.queue_map_refresh(npc.position);
+            report.npcs_removed += 1;
         }
-
-        if let Some(attacker) = self.players.get_mut(&player_id) {
-            attacker
-                .attack_cooldown
-                .reset_from_now_ticks(clock, PLAYER_COMBAT_INTERVAL_TICKS);
-            if mark_attacker {
-                attacker.mark_in_combat(clock, self.combat_rules.fight_timer);
-                if mark_white_skull {
-                    attacker.mark_white_skull(clock, self.combat_rules.white_skull_timer);
+        self.npc_index = Some(npc_index);
+        for definition in changed {
+            if let Some(npc_id) = self.spawn_npc_from_definition(definition) {
+                if let Some(position) = self.npcs.get(&npc_id).map(|npc| npc.position) {
+                    self.queue_map_refresh(position);
                 }
+                report.npcs_respawned += 1;
             }
         }
-        outcome
     }
 
-    pub fn close_npc_dialog(&mut self, id: PlayerId) {
-        self.shop_close(id);
-        let mut cleared_npcs = Vec::new();
-        for (npc_id, npc) in self.npcs.iter_mut() {
-            let mut cleared = false;
-            if npc.focused == Some(id) {
-                npc.focused = None;
-                npc.focus_expires_at = None;
-                cleared = true;
-            }
-            if npc.queue.iter().any(|queued| *queued == id) {
-                npc.queue.retain(|queued| *queued != id);
-                cleared = true;
-            }
-            if cleared {
-                cleared_npcs.push(*npc_id);
-            }
-        }
-        if let Some(player) = self.players.get_mut(&id) {
-            for npc_id in cleared_npcs {
-                player.set_npc_topic(npc_id, 0);
-                player.clear_npc_vars(npc_id);
-            }
+    pub fn add_spell(&mut self, spell: Spell) -> Result<(), String> {
+        self.spellbook.insert(spell)
+    }
+
+    pub fn teach_spell(&mut self, player_id: PlayerId, spell_id: SpellId) -> Result<(), String> {
+        if self.spellbook.get(spell_id).is_none() {
+            return Err(format!("unknown spell {:?}", spell_id));
         }
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+        player.learn_spell(spell_id);
+        Ok(())
     }
 
-    pub fn move_monster(
+    pub fn move_player(
         &mut self,
-        id: CreatureId,
+        id: PlayerId,
         direction: Direction,
+        clock: &GameClock,
     ) -> Result<Position, String> {
-        let (origin, flags) = {
-            let monster = self
-                .monsters
+        self.sync_combat_clock(clock);
+        let (origin, ready, speed, drunken_effect) = {
+            let player = self
+                .players
                 .get(&id)
-                .ok_or_else(|| format!("unknown monster {:?}", id))?;
-            (monster.position, monster.flags)
+                .ok_or_else(|| format!("unknown player {:?}", id))?;
+            (
+                player.position,
+                player.move_cooldown.is_ready(clock),
+                self.player_move_speed(player),
+                player.drunken_effect,
+            )
         };
-        let destination = self.resolve_monster_movement_destination(origin, direction, flags)?;
-        let monster = self
-            .monsters
+        if !ready {
+            return Err("movement blocked: cooldown".to_string());
+        }
+        let mut direction = direction;
+        if let Some(effect) = drunken_effect {
+            if !effect.is_expired(clock.now()) {
+                let chance = u32::from(effect.intensity)
+                    .saturating_mul(DRUNKEN_CHANCE_PER_LEVEL)
+                    .min(100);
+                if chance > 0 && self.monster_rng.roll_percent(chance) {
+                    direction = self.monster_rng.roll_direction();
+                }
+            }
+        }
+        let destination = self.resolve_movement_destination(origin, direction)?;
+        if let Some(house) = self.house_for_position(destination) {
+            let origin_house_id = self.house_for_position(origin).map(|house| house.id);
+            if origin_house_id != Some(house.id) {
+                let player = self
+                    .players
+                    .get(&id)
+                    .ok_or_else(|| format!("unknown player {:?}", id))?;
+                if !self.player_can_enter_house(player, house) {
+                    return Err("movement blocked: house access".to_string());
+                }
+            }
+        }
+        {
+            let player = self
+                .players
+                .get(&id)
+                .ok_or_else(|| format!("unknown player {:?}", id))?;
+            if !self.door_allows_player(player, destination) {
+                return Err("movement blocked: door access".to_string());
+            }
+        }
+        let tile_speed = self
+            .map
+            .tile(destination)
+            .map(|tile| self.tile_ground_speed(tile))
+            .unwrap_or(DEFAULT_GROUND_SPEED);
+        let cooldown_ticks = self.movement_cooldown_ticks(tile_speed, speed, direction, clock);
+        let player = self
+            .players
             .get_mut(&id)
-            .ok_or_else(|| format!("unknown monster {:?}", id))?;
-        monster.position = destination;
-        monster.direction = direction;
-        self.update_monster_sector_index(id, origin, destination);
+            .ok_or_else(|| format!("unknown player {:?}", id))?;
+        player.move_to(destination, direction);
+        player.move_cooldown.reset_from_now_ticks(clock, cooldown_ticks);
+        let separation =
+            self.trigger_moveuse_tile_event(MoveUseEvent::Separation, id, origin, origin);
+        let collision =
+            self.trigger_moveuse_tile_event(MoveUseEvent::Collision, id, destination, destination);
+        self.queue_moveuse_outcomes(id, separation);
+        self.queue_moveuse_outcomes(id, collision);
+        self.publish_event(WorldEvent::StepOn {
+            player_id: id,
+            position: destination,
+        });
         Ok(destination)
     }
 
-    pub fn move_npc(
-        &mut self,
-        id: CreatureId,
-        direction: Direction,
-    ) -> Result<Position, String> {
-        let origin = self
-            .npcs
-            .get(&id)
-            .ok_or_else(|| format!("unknown npc {:?}", id))?
-            .position;
-        let destination = self.resolve_movement_destination(origin, direction)?;
-        let npc = self
-            .npcs
-            .get_mut(&id)
-            .ok_or_else(|| format!("unknown npc {:?}", id))?;
-        if !npc_within_wander_radius(npc.home, npc.radius, destination) {
-            return Err("movement blocked: npc radius".to_string());
+    pub fn turn_player(&mut self, id: PlayerId, direction: Direction) -> Result<(), String> {
+        let (position, changed) = {
+            let player = self
+                .players
+                .get_mut(&id)
+                .ok_or_else(|| format!("unknown player {:?}", id))?;
+            if player.direction == direction {
+                return Ok(());
+            }
+            player.direction = direction;
+            (player.position, true)
+        };
+        if changed {
+            self.queue_turn_update(CreatureTurnUpdate {
+                id: id.0,
+                position,
+                direction,
+            });
         }
-        npc.position = destination;
-        npc.direction = direction;
-        Ok(destination)
+        Ok(())
     }
 
-    fn player_move_speed(&self, player: &PlayerState) -> u16 {
-        player
-            .speed_effect
-            .map(|effect| effect.speed)
-            .unwrap_or(player.base_speed)
+    pub fn set_player_autowalk(&mut self, id: PlayerId, steps: Vec<Direction>) {
+        self.walk_to_uses.remove(&id);
+        if let Some(player) = self.players.get_mut(&id) {
+            player.autowalk_steps = steps.into();
+        }
     }
 
-    pub fn player_capacity_remaining(&self, player: &PlayerState) -> u32 {
-        let Some(object_types) = self.object_types.as_ref() else {
-            return player.stats.capacity;
+    pub fn clear_player_autowalk(&mut self, id: PlayerId) {
+        self.walk_to_uses.remove(&id);
+        if let Some(player) = self.players.get_mut(&id) {
+            player.autowalk_steps.clear();
+        }
+    }
+
+    pub fn creature_exists(&self, id: CreatureId) -> bool {
+        self.players.contains_key(&PlayerId(id.0))
+            || self.monsters.contains_key(&id)
+            || self.npcs.contains_key(&id)
+    }
+
+    pub fn set_player_attack_target(&mut self, id: PlayerId, target: Option<CreatureId>) {
+        if let Some(player) = self.players.get_mut(&id) {
+            player.attack_target = target;
+        }
+    }
+
+    pub fn set_player_follow_target(&mut self, id: PlayerId, target: Option<CreatureId>) {
+        if let Some(player) = self.players.get_mut(&id) {
+            player.follow_target = target;
+        }
+    }
+
+    pub fn set_player_outfit(&mut self, id: PlayerId, outfit: Outfit) {
+        let Some(player) = self.players.get_mut(&id) else {
+            return;
         };
-        let max_weight = player.stats.capacity.saturating_mul(100);
-        let current_weight = player_total_weight(player, object_types);
-        max_weight.saturating_sub(current_weight) / 100
+        let outfit = player.clamp_outfit(outfit);
+        let mut update = None;
+        if let Some(effect) = player.outfit_effect.as_mut() {
+            effect.original = outfit;
+            player.original_outfit = outfit;
+        } else {
+            let current = player.current_outfit;
+            player.current_outfit = outfit;
+            player.original_outfit = outfit;
+            if current != outfit {
+                update = Some(CreatureOutfitUpdate {
+                    id: id.0,
+                    outfit,
+                });
+            }
+        }
+        if let Some(update) = update {
+            self.queue_outfit_update(update);
+        }
     }
 
-    pub fn open_container_for_player(
-        &mut self,
-        player_id: PlayerId,
-        item_type: ItemTypeId,
-        source: ContainerSource,
-        container_id: Option<u8>,
-    ) -> Result<OpenContainer, String> {
-        let (
-            items,
-            parent_container_id,
-            parent_slot,
-            source_slot,
-            source_position,
-            source_stack,
-            root_position,
-        ) = {
-            let player = self
-                .players
-                .get(&player_id)
-                .ok_or_else(|| format!("unknown player {:?}", player_id))?;
-            match source {
-                ContainerSource::InventorySlot(slot) => {
-                    let mut items = player
-                        .inventory_containers
-                        .get(&slot)
-                        .cloned()
-                        .unwrap_or_default();
-                    if items.is_empty() {
-                        if let Some(item) = player.inventory.slot(slot) {
-                            items = item.contents.clone();
-                        }
+    pub fn tick_player_autowalk(&mut self, id: PlayerId, clock: &GameClock) {
+        let direction = self
+            .players
+            .get(&id)
+            .and_then(|player| player.autowalk_steps.front().copied());
+        let Some(direction) = direction else {
+            return;
+        };
+        match self.move_player(id, direction, clock) {
+            Ok(_) => {
+                let arrived = match self.players.get_mut(&id) {
+                    Some(player) => {
+                        player.autowalk_steps.pop_front();
+                        player.autowalk_steps.is_empty()
                     }
-                    (items, None, None, Some(slot), None, None, None)
-                }
-                ContainerSource::Container { container_id, slot } => {
-                    let parent = player
-                        .open_containers
-                        .get(&container_id)
-                        .ok_or_else(|| "parent container not open".to_string())?;
-                    let item = parent
-                        .items
-                        .get(slot as usize)
-                        .ok_or_else(|| "parent container slot out of range".to_string())?;
-                    (
-                        item.contents.clone(),
-                        Some(container_id),
-                        Some(slot),
-                        None,
-                        None,
-                        None,
-                        container_root_position(player, container_id),
-                    )
+                    None => false,
+                };
+                if !arrived {
+                    return;
                 }
-                ContainerSource::Map { position, stack_pos } => {
-                    self.ensure_player_in_range(player_id, position)?;
-                    let tile = self
-                        .map
-                        .tile(position)
-                        .ok_or_else(|| "map container tile missing".to_string())?;
-                    let index = stack_pos as usize;
-                    let item = tile
-                        .items
-                        .get(index)
-                        .ok_or_else(|| "map container stack missing".to_string())?;
-                    if item.type_id != item_type {
-                        return Err("map container type mismatch".to_string());
+                if let Some((position, type_id)) = self.walk_to_uses.remove(&id) {
+                    if let Ok(outcome) =
+                        self.use_object_with_clock(id, position, type_id, Some(clock))
+                    {
+                        self.queue_moveuse_outcomes(id, vec![outcome]);
                     }
-                    (
-                        item.contents.clone(),
-                        None,
-                        None,
-                        None,
-                        Some(position),
-                        Some(stack_pos),
-                        Some(position),
-                    )
-                }
-            }
-        };
-        let (name, mut capacity) = match self
-            .item_types
-            .as_ref()
-            .and_then(|types| types.get(item_type))
-        {
-            Some(item) => {
-                if item.kind != ItemKind::Container {
-                    return Err("open container failed: item is not a container".to_string());
                 }
-                (
-                    item.name.clone(),
-                    item.container_capacity
-                        .unwrap_or_else(|| items.len().max(1).min(u8::MAX as usize) as u16)
-                        .min(u16::from(u8::MAX)) as u8,
-                )
-            }
-            None => {
-                let fallback_capacity = items.len().max(1).min(u8::MAX as usize) as u8;
-                (format!("Container {}", item_type.0), fallback_capacity)
             }
-        };
-        if item_type == DEPOT_CHEST_TYPE_ID {
-            if let Some(player) = self.players.get(&player_id) {
-                if let (Some(active), Some(root)) = (player.active_depot, root_position) {
-                    if root == active.locker_position {
-                        if let Some(depot_capacity) = active.capacity {
-                            capacity = depot_capacity.min(u32::from(u8::MAX)) as u8;
-                        }
-                    }
+            Err(err) => {
+                if err != "movement blocked: cooldown" {
+                    self.clear_player_autowalk(id);
                 }
             }
         }
-        let player = self
-            .players
-            .get_mut(&player_id)
-            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
-        let container_id = match container_id {
-            Some(container_id) => container_id,
-            None => player
-                .next_container_id()
-                .ok_or_else(|| "open container failed: no free container ids".to_string())?,
-        };
-        player.open_containers.remove(&container_id);
-        let open = OpenContainer {
-            container_id,
-            item_type,
-            name,
-            capacity,
-            has_parent: parent_container_id.is_some(),
-            parent_container_id,
-            parent_slot,
-            source_slot,
-            source_position,
-            source_stack_pos: source_stack,
-            items,
-        };
-        player.open_containers.insert(container_id, open.clone());
-        Ok(open)
     }
 
-    pub fn find_open_container_id_for_player_source(
-        &self,
+    pub fn tick_player_attack(
+        &mut self,
         player_id: PlayerId,
-        source: ContainerSource,
-    ) -> Option<u8> {
-        let player = self.players.get(&player_id)?;
-        match source {
-            ContainerSource::InventorySlot(slot) => find_container_by_slot(player, slot),
-            ContainerSource::Container { container_id, slot } => {
-                find_container_by_parent_slot(player, container_id, slot)
-            }
-            ContainerSource::Map { position, stack_pos } => {
-                find_container_by_map_source(player, position, stack_pos)
-            }
-        }
-    }
-
-    pub fn close_out_of_range_map_containers(&mut self, player_id: PlayerId) -> Vec<u8> {
-        let player_position = match self.players.get(&player_id) {
-            Some(player) => player.position,
-            None => return Vec::new(),
+        clock: &GameClock,
+    ) -> PlayerCombatOutcome {
+        self.sync_combat_clock(clock);
+        let mut outcome = PlayerCombatOutcome::default();
+        let (target_id, attack_ready, attacker_pos, attack_mode, attacker_health) = match self
+            .players
+            .get(&player_id)
+        {
+            Some(player) => (
+                player.attack_target,
+                player.attack_cooldown.is_ready(clock),
+                player.position,
+                player.fight_modes.attack_mode,
+                player.stats.health,
+            ),
+            None => return outcome,
         };
-        let snapshot: Vec<(u8, OpenContainer)> = match self.players.get(&player_id) {
-            Some(player) => player
-                .open_containers
-                .iter()
-                .map(|(id, container)| (*id, container.clone()))
-                .collect(),
-            None => return Vec::new(),
+        let Some(target_id) = target_id else {
+            return outcome;
         };
-        if snapshot.is_empty() {
-            return Vec::new();
+        if !attack_ready || attacker_health == 0 {
+            return outcome;
         }
-        let mut by_id = HashMap::new();
-        for (id, container) in &snapshot {
-            by_id.insert(*id, container);
+        if self.is_protection_zone(attacker_pos) {
+            return outcome;
         }
 
-        let mut root_out_of_range = HashSet::new();
-        for (id, container) in &snapshot {
-            if let Some(position) = container.source_position {
-                let dx = i32::from(player_position.x) - i32::from(position.x);
-                let dy = i32::from(player_position.y) - i32::from(position.y);
-                let in_range = player_position.z == position.z
-                    && dx.unsigned_abs() <= 1
-                    && dy.unsigned_abs() <= 1;
-                if !in_range {
-                    root_out_of_range.insert(*id);
-                }
-            }
-        }
-        if root_out_of_range.is_empty() {
-            return Vec::new();
+        let selection = match self.players.get(&player_id) {
+            Some(player) => self.player_attack_values(player),
+            None => return outcome,
+        };
+        if selection.attack == 0 {
+            return outcome;
         }
 
-        let mut to_close: Vec<(u8, usize)> = Vec::new();
-        for (id, container) in &snapshot {
-            let mut current = *id;
-            let mut depth = 0usize;
-            while let Some(parent_id) = by_id.get(&current).and_then(|entry| entry.parent_container_id)
+        let (skill_level, level) = match self.players.get(&player_id) {
+            Some(player) => (player.skills.get(selection.skill).level, player.level),
+            None => return outcome,
+        };
+        let attempted_damage =
+            self.roll_player_attack_damage(selection.attack, attack_mode, skill_level, level);
+
+        let mark_attacker;
+        let pvp_target;
+
+        if let Some(monster_pos) = self.monsters.get(&target_id).map(|monster| monster.position) {
+            if monster_pos.z != attacker_pos.z {
+                return outcome;
+            }
+            if Self::monster_tile_distance(attacker_pos, monster_pos) > selection.range {
+                return outcome;
+            }
+            if self.is_protection_zone(monster_pos) {
+                return outcome;
+            }
+            let missed = match self.fire_ranged_attack(
+                player_id,
+                selection,
+                attacker_pos,
+                monster_pos,
+                &mut outcome,
+            ) {
+                Some(hit) => !hit,
+                None => return outcome,
+            };
+            let attempted_damage = if missed { 0 } else { attempted_damage };
+            self.train_player_skill(player_id, selection.skill);
+            let before_health = self
+                .monsters
+                .get(&target_id)
+                .map(|monster| monster.stats.health)
+                .unwrap_or(0);
+            if self
+                .apply_damage_to_monster(
+                    target_id,
+                    selection.damage_type,
+                    attempted_damage,
+                    DamageSource::Player(player_id),
+                )
+                .is_err()
             {
-                current = parent_id;
-                depth = depth.saturating_add(1);
+                return outcome;
             }
-            if root_out_of_range.contains(&current) {
-                to_close.push((*id, depth));
+            let after_health = self
+                .monsters
+                .get(&target_id)
+                .map(|monster| monster.stats.health)
+                .unwrap_or(0);
+            let applied = before_health.saturating_sub(after_health);
+            if applied > 0 {
+                if let Some(attacker) = self.players.get_mut(&player_id) {
+                    Self::grant_learning_points(attacker);
+                }
             }
-            if container.source_position.is_some() && root_out_of_range.contains(id) {
-                // Root map containers are already tracked.
+            if attempted_damage > 0 {
+                let effect_id = if applied > 0 {
+                    PLAYER_ATTACK_EFFECT_BLOOD_ID
+                } else {
+                    PLAYER_ATTACK_EFFECT_BLOCK_ID
+                };
+                outcome.effects.push(CombatVisualEffect {
+                    position: monster_pos,
+                    effect_id,
+                });
             }
-        }
-        to_close.sort_by(|a, b| b.1.cmp(&a.1));
-
-        let mut closed = Vec::new();
-        let mut seen = HashSet::new();
-        for (container_id, _) in to_close {
-            if seen.insert(container_id) {
-                if self.close_container_for_player(player_id, container_id).unwrap_or(false) {
-                    closed.push(container_id);
+            if self.monsters.contains_key(&target_id) {
+                outcome.monster_updates.push(target_id);
+            } else {
+                outcome.refresh_map = true;
+                if let Some(player) = self.players.get_mut(&player_id) {
+                    if player.attack_target == Some(target_id) {
+                        player.attack_target = None;
+                    }
                 }
             }
-        }
-        closed
-    }
-
-    fn close_depot_containers(&mut self, player_id: PlayerId, locker_position: Position) {
-        let snapshot: Vec<(u8, OpenContainer)> = match self.players.get(&player_id) {
-            Some(player) => player
-                .open_containers
-                .iter()
-                .map(|(id, container)| (*id, container.clone()))
-                .collect(),
-            None => return,
-        };
-        if snapshot.is_empty() {
-            return;
-        }
-
-        let mut by_id: HashMap<u8, OpenContainer> = HashMap::new();
-        for (id, container) in &snapshot {
-            by_id.insert(*id, container.clone());
-        }
-
-        let mut candidates: Vec<(u8, usize)> = Vec::new();
-        for (id, _container) in &snapshot {
-            let mut current = *id;
-            let mut depth = 0usize;
-            while let Some(parent_id) = by_id.get(&current).and_then(|entry| entry.parent_container_id)
-            {
-                current = parent_id;
-                depth = depth.saturating_add(1);
+            mark_attacker = attempted_damage > 0 || missed;
+            pvp_target = None;
+        } else {
+            let target_player_id = PlayerId(target_id.0);
+            if target_player_id == player_id {
+                return outcome;
             }
-            if let Some(root) = by_id.get(&current).and_then(|entry| entry.source_position) {
-                if root == locker_position {
-                    candidates.push((*id, depth));
+            let target_pos = match self.players.get(&target_player_id) {
+                Some(target) => target.position,
+                None => {
+                    if self.npcs.contains_key(&target_id) {
+                        if let Some(player) = self.players.get_mut(&player_id) {
+                            player.attack_target = None;
+                        }
+                    } else if let Some(player) = self.players.get_mut(&player_id) {
+                        player.attack_target = None;
+                    }
+                    return outcome;
                 }
+            };
+            if !self.combat_rules.players_can_attack() {
+                return outcome;
             }
-        }
-
-        if candidates.is_empty() {
-            return;
-        }
-
-        candidates.sort_by(|a, b| b.1.cmp(&a.1));
-        for (container_id, _) in candidates {
-            if self.close_container_for_player(player_id, container_id).unwrap_or(false) {
-                self.queue_container_close(player_id, container_id);
+            if target_pos.z != attacker_pos.z {
+                return outcome;
             }
-        }
-    }
+            if self.is_protection_zone(target_pos) {
+                return outcome;
+            }
+            if Self::monster_tile_distance(attacker_pos, target_pos) > selection.range {
+                return outcome;
+            }
+            let missed = match self.fire_ranged_attack(
+                player_id,
+                selection,
+                attacker_pos,
+                target_pos,
+                &mut outcome,
+            ) {
+                Some(hit) => !hit,
+                None => return outcome,
+            };
+            let attempted_damage = if missed { 0 } else { attempted_damage };
 
-    fn ensure_depot_capacity_for_insert(
-        &self,
-        player_id: PlayerId,
-        dest_container: u8,
-        source_container: Option<u8>,
-        item: &ItemStack,
-    ) -> Result<(), String> {
-        let player = self
-            .players
-            .get(&player_id)
-            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
-        let Some(active) = container_active_depot(player, dest_container) else {
-            return Ok(());
-        };
-        if let Some(source_container) = source_container {
-            if let Some(source_active) = container_active_depot(player, source_container) {
-                if source_active.locker_position == active.locker_position {
-                    return Ok(());
+            let (defend_selection, defend_ready, target_mode) =
+                match self.players.get(&target_player_id) {
+                    Some(target) => (
+                        self.player_defense_values(target),
+                        target.defend_cooldown.is_ready(clock),
+                        target.fight_modes.attack_mode,
+                    ),
+                    None => return outcome,
+                };
+            let defend = defend_selection.defend;
+            let armor = defend_selection.armor;
+            let defend_roll = if defend_ready {
+                if defend_selection.skill == SkillType::Shielding {
+                    self.train_player_skill(target_player_id, SkillType::Shielding);
+                }
+                let scaled = Self::scale_defend_by_attack_mode(defend, target_mode);
+                if scaled == 0 {
+                    0
+                } else {
+                    let capped = scaled.min(u32::from(u16::MAX));
+                    self.moveuse_rng.roll_range(0, capped)
+                }
+            } else {
+                0
+            };
+            let armor_roll = if armor == 0 {
+                0
+            } else {
+                let capped = armor.min(u32::from(u16::MAX));
+                self.moveuse_rng.roll_range(0, capped)
+            };
+            let mitigated_damage = attempted_damage
+                .saturating_sub(defend_roll)
+                .saturating_sub(armor_roll);
+            let reduced_damage = self.apply_player_protection_reduction(
+                target_player_id,
+                selection.damage_type,
+                mitigated_damage,
+            );
+            let (applied_damage, absorbed_mana) = {
+                let target = match self.players.get_mut(&target_player_id) {
+                    Some(target) => target,
+                    None => return outcome,
+                };
+                let (applied, absorbed) = target
+                    .apply_damage_with_magic_shield(selection.damage_type, reduced_damage);
+                if applied > 0 || absorbed > 0 {
+                    target.mark_in_combat(clock, self.combat_rules.fight_timer);
                 }
+                (applied, absorbed)
+            };
+            if attempted_damage > 0 {
+                let effect_id = if applied_damage > 0 {
+                    PLAYER_ATTACK_EFFECT_BLOOD_ID
+                } else {
+                    PLAYER_ATTACK_EFFECT_BLOCK_ID
+                };
+                outcome.effects.push(CombatVisualEffect {
+                    position: target_pos,
+                    effect_id,
+                });
+                outcome.player_updates.push(target_player_id);
             }
+            if applied_damage > 0 {
+                if let Some(attacker) = self.players.get_mut(&player_id) {
+                    Self::grant_learning_points(attacker);
+                }
+                self.record_player_damage(
+                    target_player_id,
+                    DamageSource::Player(player_id),
+                    selection.damage_type,
+                    applied_damage,
+                );
+            }
+            if attempted_damage > 0 && defend_ready {
+                self.apply_defend_wear(target_player_id, defend_selection);
+                let defend_ticks = clock.ticks_from_duration_round_up(Duration::from_millis(
+                    DEFEND_COOLDOWN_MS,
+                ));
+                if let Some(target) = self.players.get_mut(&target_player_id) {
+                    target
+                        .defend_cooldown
+                        .reset_from_now_ticks(clock, defend_ticks);
+                }
+            }
+            let _ = absorbed_mana;
+            mark_attacker = attempted_damage > 0 || missed;
+            pvp_target = mark_attacker.then_some(target_player_id);
         }
-        let Some(capacity) = active.capacity else {
-            return Ok(());
-        };
-        let tile = self
-            .map
-            .tile(active.locker_position)
-            .ok_or_else(|| "depot locker tile missing".to_string())?;
-        let locker = tile
-            .items
-            .iter()
-            .find(|item| item.type_id == active.locker_type)
-            .ok_or_else(|| "depot locker missing".to_string())?;
-        let current_count = depot_item_count(&locker.contents);
-        let added_count = count_item_with_contents(item);
-        if current_count.saturating_add(added_count) > capacity {
-            return Err("depot capacity exceeded".to_string());
+
+        if let Some(attacker) = self.players.get_mut(&player_id) {
+            attacker
+                .attack_cooldown
+                .reset_from_now_ticks(clock, PLAYER_COMBAT_INTERVAL_TICKS);
+            if mark_attacker {
+                attacker.mark_in_combat(clock, self.combat_rules.fight_timer);
+            }
         }
-        Ok(())
+        if let Some(target_player_id) = pvp_target {
+            self.record_pvp_attack(player_id, target_player_id, clock);
+        }
+        outcome
     }
 
-    pub fn up_container_for_player(
-        &mut self,
-        player_id: PlayerId,
-        container_id: u8,
-    ) -> Result<Option<OpenContainer>, String> {
-        let player = self
-            .players
-            .get_mut(&player_id)
-            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
-        let Some(container) = player.open_containers.get(&container_id).cloned() else {
-            return Ok(None);
-        };
-        let Some(parent_id) = container.parent_container_id else {
-            return Ok(None);
-        };
-        let Some(parent) = player.open_containers.get(&parent_id).cloned() else {
-            return Ok(None);
-        };
-        let mut open = parent;
-        open.container_id = container_id;
-        open.has_parent = open.parent_container_id.is_some();
-        player.open_containers.insert(container_id, open.clone());
-        Ok(Some(open))
+    pub fn close_npc_dialog(&mut self, id: PlayerId) {
+        self.shop_close(id);
+        let mut cleared_npcs = Vec::new();
+        for (npc_id, npc) in self.npcs.iter_mut() {
+            let mut cleared = false;
+            if npc.focused == Some(id) {
+                npc.focused = None;
+                npc.focus_expires_at = None;
+                cleared = true;
+            }
+            if npc.queue.iter().any(|queued| *queued == id) {
+                npc.queue.retain(|queued| *queued != id);
+                cleared = true;
+            }
+            if cleared {
+                cleared_npcs.push(*npc_id);
+            }
+        }
+        if let Some(player) = self.players.get_mut(&id) {
+            for npc_id in cleared_npcs {
+                player.set_npc_topic(npc_id, 0);
+                player.clear_npc_vars(npc_id);
+            }
+        }
     }
 
-    pub fn close_container_for_player(
+    pub fn move_monster(
         &mut self,
-        player_id: PlayerId,
-        container_id: u8,
-    ) -> Result<bool, String> {
-        self.sync_container_contents(player_id, container_id);
-        let player = self
-            .players
-            .get_mut(&player_id)
-            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
-        Ok(player.close_container(container_id))
-    }
-
-    fn is_container_item(&self, item: &ItemStack) -> bool {
-        self.item_types
-            .as_ref()
-            .and_then(|types| types.get(item.type_id))
-            .map(|entry| entry.kind == ItemKind::Container)
-            .unwrap_or(false)
+        id: CreatureId,
+        direction: Direction,
+    ) -> Result<Position, String> {
+        let (origin, flags) = {
+            let monster = self
+                .monsters
+                .get(&id)
+                .ok_or_else(|| format!("unknown monster {:?}", id))?;
+            (monster.position, monster.flags)
+        };
+        let destination = self.resolve_monster_movement_destination(origin, direction, flags)?;
+        let monster = self
+            .monsters
+            .get_mut(&id)
+            .ok_or_else(|| format!("unknown monster {:?}", id))?;
+        monster.position = destination;
+        monster.direction = direction;
+        self.update_monster_sector_index(id, origin, destination);
+        Ok(destination)
     }
 
-    fn close_open_containers_for_item(&mut self, player_id: PlayerId, item: &ItemStack) {
-        let open_ids: Vec<u8> = match self.players.get(&player_id) {
-            Some(player) => player
-                .open_containers
-                .iter()
-                .filter_map(|(id, container)| {
-                    if item_contains_open_container(item, container) {
-                        Some(*id)
-                    } else {
-                        None
-                    }
-                })
-                .collect(),
-            None => Vec::new(),
-        };
-        for container_id in open_ids {
-            if self
-                .close_container_for_player(player_id, container_id)
-                .unwrap_or(false)
-            {
-                self.queue_container_close(player_id, container_id);
-            }
+    pub fn move_npc(
+        &mut self,
+        id: CreatureId,
+        direction: Direction,
+    ) -> Result<Position, String> {
+        let origin = self
+            .npcs
+            .get(&id)
+            .ok_or_else(|| format!("unknown npc {:?}", id))?
+            .position;
+        let destination = self.resolve_movement_destination(origin, direction)?;
+        let npc = self
+            .npcs
+            .get_mut(&id)
+            .ok_or_else(|| format!("unknown npc {:?}", id))?;
+        if !npc_within_wander_radius(npc.home, npc.radius, destination) {
+            return Err("movement blocked: npc radius".to_string());
         }
+        npc.position = destination;
+        npc.direction = direction;
+        Ok(destination)
     }
 
-    fn tile_ground_speed(&self, tile: &Tile) -> u16 {
-        let Some(ground) = tile.items.first() else {
-            return DEFAULT_GROUND_SPEED;
-        };
-        let Some(types) = self.object_types.as_ref() else {
-            return DEFAULT_GROUND_SPEED;
-        };
-        types
-            .get(ground.type_id)
-            .and_then(|object_type| object_type.ground_speed())
-            .unwrap_or(DEFAULT_GROUND_SPEED)
+    fn player_move_speed(&self, player: &PlayerState) -> u16 {
+        player
+            .speed_effect
+            .map(|effect| effect.speed)
+            .unwrap_or(player.base_speed)
     }
 
-    fn movement_cooldown_ticks(
-        &self,
-        tile_speed: u16,
-        creature_speed: u16,
-        direction: Direction,
-        clock: &GameClock,
-    ) -> u64 {
-        let mut tile_speed = u64::from(tile_speed.max(1));
-        if direction.is_diagonal() {
-            tile_speed = tile_speed.saturating_mul(3);
-        }
-        let creature_speed = u64::from(creature_speed.max(1));
-        let raw_ms = tile_speed.saturating_mul(1000) / creature_speed;
-        let duration = Duration::from_millis(raw_ms.max(1));
-        let ticks = clock.ticks_from_duration_round_up(duration);
-        ticks.max(1)
+    pub fn player_capacity_remaining(&self, player: &PlayerState) -> u32 {
+        let Some(object_types) = self.object_types.as_ref() else {
+            return player.stats.capacity;
+        };
+        let max_weight = player.stats.capacity.saturating_mul(100);
+        let current_weight = player_total_weight(player, object_types);
+        max_weight.saturating_sub(current_weight) / 100
     }
 
-    pub fn teleport_player_admin(
+    pub fn open_container_for_player(
         &mut self,
-        id: PlayerId,
-        position: Position,
-    ) -> Result<(), String> {
-        if !self.position_in_bounds(position) {
-            return Err("target out of bounds".to_string());
+        player_id: PlayerId,
+        item_type: ItemTypeId,
+        source: ContainerSource,
+        container_id: Option<u8>,
+    ) -> Result<OpenContainer, ContainerOpenError> {
+        if let ContainerSource::Container { container_id, .. } = source {
+            if self.ensure_container_loot_access(player_id, container_id).is_err() {
+                return Err(ContainerOpenError::NotOwner);
+            }
         }
-        if self
-            .players
-            .get(&id)
-            .map(|player| player.is_test_god)
-            .unwrap_or(false)
-        {
+        let (
+            items,
+            parent_container_id,
+            parent_slot,
+            source_slot,
+            source_position,
+            source_stack,
+            root_position,
+        ) = {
             let player = self
                 .players
-                .get_mut(&id)
-                .ok_or_else(|| format!("unknown player {:?}", id))?;
-            player.move_to(position, player.direction);
-            return Ok(());
-        }
-        self.teleport_player(id, position)
-    }
-
-    pub fn run_moveuse_audit_for_player(&mut self, user_id: PlayerId) -> Result<String, String> {
-        let Some(player) = self.players.get(&user_id) else {
-            return Err(format!("unknown player {:?}", user_id));
-        };
-        if !player.is_test_god {
-            return Err("moveuse audit requires test_god privileges".to_string());
-        }
-        let base = player.position;
-        let Some(moveuse) = self.moveuse.clone() else {
+                .get(&player_id)
+                .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+            match source {
+                ContainerSource::InventorySlot(slot) => {
+                    let mut items = player
+                        .inventory_containers
+                        .get(&slot)
+                        .cloned()
+                        .unwrap_or_default();
+                    if items.is_empty() {
+                        if let Some(item) = player.inventory.slot(slot) {
+                            items = item.contents.clone();
+                        }
+                    }
+                    (items, None, None, Some(slot), None, None, None)
+                }
+                ContainerSource::Container { container_id, slot } => {
+                    let parent = player
+                        .open_containers
+                        .get(&container_id)
+                        .ok_or_else(|| "parent container not open".to_string())?;
+                    let item = parent
+                        .items
+                        .get(slot as usize)
+                        .ok_or_else(|| "parent container slot out of range".to_string())?;
+                    (
+                        item.contents.clone(),
+                        Some(container_id),
+                        Some(slot),
+                        None,
+                        None,
+                        None,
+                        container_root_position(player, container_id),
+                    )
+                }
+                ContainerSource::Map { position, stack_pos } => {
+                    self.ensure_player_in_range(player_id, position)?;
+                    let tile = self
+                        .map
+                        .tile(position)
+                        .ok_or_else(|| "map container tile missing".to_string())?;
+                    let index = stack_pos as usize;
+                    let item = tile
+                        .items
+                        .get(index)
+                        .ok_or_else(|| "map container stack missing".to_string())?;
+                    if item.type_id != item_type {
+                        return Err("map container type mismatch".to_string().into());
+                    }
+                    if !self.corpse_open_allowed(player_id, item.id) {
+                        self.queue_player_message(
+                            player_id,
+                            MESSAGE_STATUS_SMALL,
+                            LOOT_NOT_OWNER_MESSAGE.to_string(),
+                        );
+                        return Err(ContainerOpenError::NotOwner);
+                    }
+                    (
+                        item.contents.clone(),
+                        None,
+                        None,
+                        None,
+                        Some(position),
+                        Some(stack_pos),
+                        Some(position),
+                    )
+                }
+            }
+        };
+        let (name, mut capacity) = match self
+            .item_types
+            .as_ref()
+            .and_then(|types| types.get(item_type))
+        {
+            Some(item) => {
+                if item.kind != ItemKind::Container {
+                    return Err("open container failed: item is not a container"
+                        .to_string()
+                        .into());
+                }
+                (
+                    item.name.clone(),
+                    item.container_capacity
+                        .unwrap_or_else(|| items.len().max(1).min(u8::MAX as usize) as u16)
+                        .min(u16::from(u8::MAX)) as u8,
+                )
+            }
+            None => {
+                let fallback_capacity = items.len().max(1).min(u8::MAX as usize) as u8;
+                (format!("Container {}", item_type.0), fallback_capacity)
+            }
+        };
+        if item_type == DEPOT_CHEST_TYPE_ID {
+            if let Some(player) = self.players.get(&player_id) {
+                if let (Some(active), Some(root)) = (player.active_depot, root_position) {
+                    if root == active.locker_position {
+                        if let Some(depot_capacity) = active.capacity {
+                            capacity = depot_capacity.min(u32::from(u8::MAX)) as u8;
+                        }
+                    }
+                }
+            }
+        }
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+        let container_id = match container_id {
+            Some(container_id) => container_id,
+            None => player
+                .next_container_id()
+                .ok_or_else(|| "open container failed: no free container ids".to_string())?,
+        };
+        player.open_containers.remove(&container_id);
+        let open = OpenContainer {
+            container_id,
+            item_type,
+            name,
+            capacity,
+            has_parent: parent_container_id.is_some(),
+            parent_container_id,
+            parent_slot,
+            source_slot,
+            source_position,
+            source_stack_pos: source_stack,
+            items,
+        };
+        player.open_containers.insert(container_id, open.clone());
+        Ok(open)
+    }
+
+    pub fn find_open_container_id_for_player_source(
+        &self,
+        player_id: PlayerId,
+        source: ContainerSource,
+    ) -> Option<u8> {
+        let player = self.players.get(&player_id)?;
+        match source {
+            ContainerSource::InventorySlot(slot) => find_container_by_slot(player, slot),
+            ContainerSource::Container { container_id, slot } => {
+                find_container_by_parent_slot(player, container_id, slot)
+            }
+            ContainerSource::Map { position, stack_pos } => {
+                find_container_by_map_source(player, position, stack_pos)
+            }
+        }
+    }
+
+    pub fn close_out_of_range_map_containers(&mut self, player_id: PlayerId) -> Vec<u8> {
+        let player_position = match self.players.get(&player_id) {
+            Some(player) => player.position,
+            None => return Vec::new(),
+        };
+        let snapshot: Vec<(u8, OpenContainer)> = match self.players.get(&player_id) {
+            Some(player) => player
+                .open_containers
+                .iter()
+                .map(|(id, container)| (*id, container.clone()))
+                .collect(),
+            None => return Vec::new(),
+        };
+        if snapshot.is_empty() {
+            return Vec::new();
+        }
+        let mut by_id = HashMap::new();
+        for (id, container) in &snapshot {
+            by_id.insert(*id, container);
+        }
+
+        let mut root_out_of_range = HashSet::new();
+        for (id, container) in &snapshot {
+            if let Some(position) = container.source_position {
+                let dx = i32::from(player_position.x) - i32::from(position.x);
+                let dy = i32::from(player_position.y) - i32::from(position.y);
+                let in_range = player_position.z == position.z
+                    && dx.unsigned_abs() <= 1
+                    && dy.unsigned_abs() <= 1;
+                if !in_range {
+                    root_out_of_range.insert(*id);
+                }
+            }
+        }
+        if root_out_of_range.is_empty() {
+            return Vec::new();
+        }
+
+        let mut to_close: Vec<(u8, usize)> = Vec::new();
+        for (id, container) in &snapshot {
+            let mut current = *id;
+            let mut depth = 0usize;
+            while let Some(parent_id) = by_id.get(&current).and_then(|entry| entry.parent_container_id)
+            {
+                current = parent_id;
+                depth = depth.saturating_add(1);
+            }
+            if root_out_of_range.contains(&current) {
+                to_close.push((*id, depth));
+            }
+            if container.source_position.is_some() && root_out_of_range.contains(id) {
+                // Root map containers are already tracked.
+            }
+        }
+        to_close.sort_by(|a, b| b.1.cmp(&a.1));
+
+        let mut closed = Vec::new();
+        let mut seen = HashSet::new();
+        for (container_id, _) in to_close {
+            if seen.insert(container_id) {
+                if self.close_container_for_player(player_id, container_id).unwrap_or(false) {
+                    closed.push(container_id);
+                }
+            }
+        }
+        closed
+    }
+
+    fn close_depot_containers(&mut self, player_id: PlayerId, locker_position: Position) {
+        let snapshot: Vec<(u8, OpenContainer)> = match self.players.get(&player_id) {
+            Some(player) => player
+                .open_containers
+                .iter()
+                .map(|(id, container)| (*id, container.clone()))
+                .collect(),
+            None => return,
+        };
+        if snapshot.is_empty() {
+            return;
+        }
+
+        let mut by_id: HashMap<u8, OpenContainer> = HashMap::new();
+        for (id, container) in &snapshot {
+            by_id.insert(*id, container.clone());
+        }
+
+        let mut candidates: Vec<(u8, usize)> = Vec::new();
+        for (id, _container) in &snapshot {
+            let mut current = *id;
+            let mut depth = 0usize;
+            while let Some(parent_id) = by_id.get(&current).and_then(|entry| entry.parent_container_id)
+            {
+                current = parent_id;
+                depth = depth.saturating_add(1);
+            }
+            if let Some(root) = by_id.get(&current).and_then(|entry| entry.source_position) {
+                if root == locker_position {
+                    candidates.push((*id, depth));
+                }
+            }
+        }
+
+        if candidates.is_empty() {
+            return;
+        }
+
+        candidates.sort_by(|a, b| b.1.cmp(&a.1));
+        for (container_id, _) in candidates {
+            if self.close_container_for_player(player_id, container_id).unwrap_or(false) {
+                self.queue_container_close(player_id, container_id);
+            }
+        }
+    }
+
+    fn ensure_depot_capacity_for_insert(
+        &self,
+        player_id: PlayerId,
+        dest_container: u8,
+        source_container: Option<u8>,
+        item: &ItemStack,
+    ) -> Result<(), String> {
+        let player = self
+            .players
+            .get(&player_id)
+            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+        let Some(active) = container_active_depot(player, dest_container) else {
+            return Ok(());
+        };
+        if let Some(source_container) = source_container {
+            if let Some(source_active) = container_active_depot(player, source_container) {
+                if source_active.locker_position == active.locker_position {
+                    return Ok(());
+                }
+            }
+        }
+        let Some(capacity) = active.capacity else {
+            return Ok(());
+        };
+        let tile = self
+            .map
+            .tile(active.locker_position)
+            .ok_or_else(|| "depot locker tile missing".to_string())?;
+        let locker = tile
+            .items
+            .iter()
+            .find(|item| item.type_id == active.locker_type)
+            .ok_or_else(|| "depot locker missing".to_string())?;
+        let current_count = depot_item_count(&locker.contents);
+        let added_count = count_item_with_contents(item);
+        if current_count.saturating_add(added_count) > capacity {
+            return Err("depot capacity exceeded".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn up_container_for_player(
+        &mut self,
+        player_id: PlayerId,
+        container_id: u8,
+    ) -> Result<Option<OpenContainer>, String> {
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+        let Some(container) = player.open_containers.get(&container_id).cloned() else {
+            return Ok(None);
+        };
+        let Some(parent_id) = container.parent_container_id else {
+            return Ok(None);
+        };
+        let Some(parent) = player.open_containers.get(&parent_id).cloned() else {
+            return Ok(None);
+        };
+        let mut open = parent;
+        open.container_id = container_id;
+        open.has_parent = open.parent_container_id.is_some();
+        player.open_containers.insert(container_id, open.clone());
+        Ok(Some(open))
+    }
+
+    pub fn close_container_for_player(
+        &mut self,
+        player_id: PlayerId,
+        container_id: u8,
+    ) -> Result<bool, String> {
+        self.sync_container_contents(player_id, container_id);
+        let player = self
+            .players
+            .get_mut(&player_id)
+            .ok_or_else(|| format!("unknown player {:?}", player_id))?;
+        Ok(player.close_container(container_id))
+    }
+
+    fn is_container_item(&self, item: &ItemStack) -> bool {
+        self.item_types
+            .as_ref()
+            .and_then(|types| types.get(item.type_id))
+            .map(|entry| entry.kind == ItemKind::Container)
+            .unwrap_or(false)
+    }
+
+    fn close_open_containers_for_item(&mut self, player_id: PlayerId, item: &ItemStack) {
+        let open_ids: Vec<u8> = match self.players.get(&player_id) {
+            Some(player) => player
+                .open_containers
+                .iter()
+                .filter_map(|(id, container)| {
+                    if item_contains_open_container(item, container) {
+                        Some(*id)
+                    } else {
+                        None
+                    }
+                })
+                .collect(),
+            None => Vec::new(),
+        };
+        for container_id in open_ids {
+            if self
+                .close_container_for_player(player_id, container_id)
+                .unwrap_or(false)
+            {
+                self.queue_container_close(player_id, container_id);
+            }
+        }
+    }
+
+    fn tile_ground_speed(&self, tile: &Tile) -> u16 {
+        let Some(ground) = tile.items.first() else {
+            return DEFAULT_GROUND_SPEED;
+        };
+        let Some(types) = self.object_types.as_ref() else {
+            return DEFAULT_GROUND_SPEED;
+        };
+        types
+            .get(ground.type_id)
+            .and_then(|object_type| object_type.ground_speed())
+            .unwrap_or(DEFAULT_GROUND_SPEED)
+    }
+
+    fn movement_cooldown_ticks(
+        &self,
+        tile_speed: u16,
+        creature_speed: u16,
+        direction: Direction,
+        clock: &GameClock,
+    ) -> u64 {
+        let mut tile_speed = u64::from(tile_speed.max(1));
+        if direction.is_diagonal() {
+            tile_speed = tile_speed.saturating_mul(3);
+        }
+        let creature_speed = u64::from(creature_speed.max(1));
+        let raw_ms = tile_speed.saturating_mul(1000) / creature_speed;
+        let duration = Duration::from_millis(raw_ms.max(1));
+        let ticks = clock.ticks_from_duration_round_up(duration);
+        ticks.max(1)
+    }
+
+    pub fn teleport_player_admin(
+        &mut self,
+        id: PlayerId,
+        position: Position,
+    ) -> Result<(), String> {
+        if !self.position_in_bounds(position) {
+            return Err("target out of bounds".to_string());
+        }
+        if self
+            .players
+            .get(&id)
+            .map(|player| player.is_test_god)
+            .unwrap_or(false)
+        {
+            let player = self
+                .players
+                .get_mut(&id)
+                .ok_or_else(|| format!("unknown player {:?}", id))?;
+            player.move_to(position, player.direction);
+            return Ok(());
+        }
+        self.teleport_player(id, position)
+    }
+
+    pub fn run_moveuse_audit_for_player(&mut self, user_id: PlayerId) -> Result<String, String> {
+        let Some(player) = self.players.get(&user_id) else {
+            return Err(format!("unknown player {:?}", user_id));
+        };
+        if !player.is_test_god {
+            return Err("moveuse audit requires test_god privileges".to_string());
+        }
+        let base = player.position;
+        let Some(moveuse) = self.moveuse.clone() else {
             return Err("moveuse.dat is not loaded".to_string());
         };
         let rules = collect_moveuse_rules(&moveuse);
@@ -5046,6 +7581,16 @@ impl WorldState {
         let mut results = Vec::new();
         for (id, player) in self.players.iter_mut() {
             let ticks = player.tick_conditions(now);
+            for tick in &ticks {
+                Self::record_condition_damage(
+                    player,
+                    tick.kind,
+                    tick.damage_type,
+                    tick.applied_damage,
+                    now,
+                );
+            }
+            Self::forget_ended_condition_sources(player);
             if !ticks.is_empty() {
                 results.push((*id, ticks));
             }
@@ -5179,6 +7724,7 @@ impl WorldState {
         let mut outcome = SkillTimerOutcome::default();
         let ticks_per_skill = Self::skill_timer_ticks_per_second(clock);
         let now = clock.now();
+        self.sync_combat_clock(clock);
         let steps = match self.skill_tick_last {
             Some(last) => {
                 let elapsed = now.0.saturating_sub(last.0);
@@ -5200,7 +7746,6 @@ impl WorldState {
             self.sync_skill_effects(now, ticks_per_skill, &mut outcome);
             return outcome;
         }
-
         for _ in 0..steps {
             for (id, player) in self.players.iter_mut() {
                 if player.raw_skills.is_empty() {
@@ -5271,6 +7816,13 @@ impl WorldState {
                         if damage > 0 && !dead {
                             let (applied, absorbed) =
                                 player.apply_damage_with_magic_shield(DamageType::Earth, damage);
+                            Self::record_condition_damage(
+                                player,
+                                ConditionKind::Poison,
+                                DamageType::Earth,
+                                applied,
+                                now,
+                            );
                             if applied > 0 {
                                 outcome.health_updates.push(*id);
                             }
@@ -5290,6 +7842,13 @@ impl WorldState {
                         if !dead {
                             let (applied, absorbed) =
                                 player.apply_damage_with_magic_shield(DamageType::Fire, 10);
+                            Self::record_condition_damage(
+                                player,
+                                ConditionKind::Fire,
+                                DamageType::Fire,
+                                applied,
+                                now,
+                            );
                             if applied > 0 {
                                 outcome.health_updates.push(*id);
                             }
@@ -5309,6 +7868,13 @@ impl WorldState {
                         if !dead {
                             let (applied, absorbed) =
                                 player.apply_damage_with_magic_shield(DamageType::Energy, 25);
+                            Self::record_condition_damage(
+                                player,
+                                ConditionKind::Energy,
+                                DamageType::Energy,
+                                applied,
+                                now,
+                            );
                             if applied > 0 {
                                 outcome.health_updates.push(*id);
                             }
@@ -5603,6 +8169,49 @@ impl WorldState {
         row.values[SKILL_FIELD_MIN] != i32::MIN
     }
 
+    /// Credits condition damage to whoever inflicted the condition, or to the
+    /// environment when nobody is known.
+    fn record_condition_damage(
+        player: &mut PlayerState,
+        kind: ConditionKind,
+        damage_type: DamageType,
+        damage: u32,
+        now: GameTick,
+    ) {
+        let (source, name) = match player.damage_ledger.condition_source(kind) {
+            Some((source, name)) => (source, name.to_string()),
+            None => {
+                let name = match kind {
+                    ConditionKind::Poison => "poison",
+                    ConditionKind::Fire => "fire",
+                    ConditionKind::Energy => "energy",
+                    ConditionKind::Drown => "drowning",
+                    ConditionKind::Freeze => "freezing",
+                    ConditionKind::Curse => "a curse",
+                };
+                (DamageSource::Environment, name.to_string())
+            }
+        };
+        player
+            .damage_ledger
+            .record(source, &name, damage_type, damage, now);
+    }
+
+    /// Drops condition sources once neither a condition nor its skill timer
+    /// is still running.
+    fn forget_ended_condition_sources(player: &mut PlayerState) {
+        let conditions = &player.conditions;
+        let raw_skills = &player.raw_skills;
+        player.damage_ledger.retain_conditions(|kind| {
+            conditions.iter().any(|condition| condition.kind == kind)
+                || Self::condition_skill_id(kind).is_some_and(|skill_id| {
+                    raw_skills
+                        .iter()
+                        .any(|row| row.skill_id == skill_id && Self::row_active(row))
+                })
+        });
+    }
+
     fn condition_skill_id(kind: ConditionKind) -> Option<u32> {
         match kind {
             ConditionKind::Poison => Some(SKILL_POISON),
@@ -5871,11 +8480,21 @@ impl WorldState {
     }
 
     pub fn tick_monsters(&mut self, clock: &GameClock) -> MonsterTickOutcome {
-        let monster_ids = self.collect_active_monster_ids(MONSTER_ACTIVE_RANGE);
+        self.sync_combat_clock(clock);
+        self.update_monster_activity(clock);
+        let monster_ids = self.collect_active_monster_ids();
         let mut outcome = MonsterTickOutcome::default();
         let mut player_hits = HashSet::new();
         let mut player_hit_marks = HashSet::new();
         let mut monster_updates = HashSet::new();
+        let mut path_budget = MONSTER_PATH_BUDGET_PER_TICK;
+        let monsters = &self.monsters;
+        let npcs = &self.npcs;
+        self.creature_paths
+            .retain(|id, _| monsters.contains_key(id) || npcs.contains_key(id));
+        self.boss_states.retain(|id, _| monsters.contains_key(id));
+        self.monster_threat.retain(|id, _| monsters.contains_key(id));
+        self.monster_leashing.retain(|id| monsters.contains_key(id));
 
         for monster_id in monster_ids {
             let (
@@ -5893,6 +8512,7 @@ impl WorldState {
                 talk_ready,
                 flags,
                 summoner,
+                home_id,
             ) = match self.monsters.get(&monster_id) {
                 Some(monster) => (
                     monster.position,
@@ -5909,72 +8529,169 @@ impl WorldState {
                     monster.talk_cooldown.is_ready(clock),
                     monster.flags,
                     monster.summoner,
+                    monster.home_id,
                 ),
                 None => continue,
             };
 
-            if summoner.is_some() {
+            if let Some(master_id) = summoner {
+                let victim =
+                    self.tick_follower(monster_id, master_id, clock, &mut outcome, &mut path_budget);
+                if let Some(victim) = victim.filter(|_| combat_ready) {
+                    let hit = if self.monsters.contains_key(&victim) {
+                        self.follower_attack_monster(
+                            monster_id,
+                            victim,
+                            attack,
+                            &mut outcome,
+                            &mut monster_updates,
+                        )
+                    } else {
+                        let victim = PlayerId(victim.0);
+                        let hit = self.monster_melee_attack(
+                            monster_id,
+                            victim,
+                            attack,
+                            poison,
+                            clock,
+                            &mut player_hits,
+                            &mut player_hit_marks,
+                            &mut monster_updates,
+                        );
+                        if hit {
+                            if let Some(master) = self.players.get_mut(&master_id) {
+                                master.mark_in_combat(clock, self.combat_rules.fight_timer);
+                            }
+                            self.record_pvp_attack(master_id, victim, clock);
+                        }
+                        hit
+                    };
+                    if hit {
+                        if let Some(monster) = self.monsters.get_mut(&monster_id) {
+                            monster
+                                .combat_cooldown
+                                .reset_from_now_ticks(clock, MONSTER_COMBAT_INTERVAL_TICKS);
+                        }
+                    }
+                }
                 continue;
             }
 
-            if !self.has_visible_player_in_range(position, MONSTER_ACTIVE_RANGE, flags) {
+            let home = home_id
+                .and_then(|index| self.monster_homes.get(index))
+                .map(|home| (home.position, home.radius));
+            if self.monster_leashing.contains(&monster_id)
+                && home.is_none_or(|(home_position, radius)| {
+                    Self::monster_within_home(position, home_position, radius.max(1))
+                })
+            {
+                self.monster_leashing.remove(&monster_id);
+            }
+            let mut leashing = self.monster_leashing.contains(&monster_id);
+
+            if !leashing && !self.has_visible_player_in_range(position, MONSTER_ACTIVE_RANGE, flags)
+            {
                 continue;
             }
 
             let range = Self::monster_acquire_range(lose_target_distance);
-            let target = if range == 0 {
+            let mut target = if range == 0 || leashing {
                 None
             } else {
-                let damage_by = match self.monsters.get_mut(&monster_id) {
-                    Some(monster) => std::mem::take(&mut monster.damage_by),
-                    None => continue,
-                };
-                let target = self.select_monster_target(
+                self.select_monster_target(monster_id, position, range, strategy, flags, home)
+            };
+            if let Some((home_position, radius)) = home.filter(|_| !leashing) {
+                let strayed = !Self::monster_within_home(
                     position,
-                    current_target,
-                    range,
-                    strategy,
-                    &damage_by,
-                    flags,
+                    home_position,
+                    radius.saturating_add(MONSTER_LEASH_RANGE),
                 );
-                if let Some(monster) = self.monsters.get_mut(&monster_id) {
-                    monster.damage_by = damage_by;
+                let lost_target = current_target.is_some()
+                    && target.is_none()
+                    && !Self::monster_within_home(position, home_position, radius.max(1));
+                if strayed || lost_target {
+                    target = None;
+                    leashing = true;
+                    self.monster_threat.remove(&monster_id);
+                    self.monster_leashing.insert(monster_id);
+                    self.creature_paths.remove(&monster_id);
                 }
-                target
-            };
+            }
             let target_position = target.and_then(|id| self.players.get(&id).map(|p| p.position));
+            let leash_goal = home
+                .filter(|_| leashing)
+                .map(|(home_position, _)| home_position);
 
             if let Some(monster) = self.monsters.get_mut(&monster_id) {
                 monster.target = target;
             }
+            self.tick_boss(monster_id, target.is_some(), clock, &mut outcome);
 
-            if move_ready {
-                let distance_flee = flags.distance_fighting
-                    && target_position
-                        .map(|pos| Self::monster_tile_distance(position, pos) <= MONSTER_MELEE_RANGE)
-                        .unwrap_or(false);
-                let flee = (flee_threshold > 0 && health <= flee_threshold) || distance_flee;
-                let preferred = target_position.and_then(|target_pos| {
-                    let mut preferred_target = target_pos;
-                    if !flee {
-                        let dx = i32::from(target_pos.x) - i32::from(position.x);
-                        let dy = i32::from(target_pos.y) - i32::from(position.y);
-                        if dx.abs() <= 1 && dy.abs() <= 1 {
-                            let roll = self.monster_rng.roll_range(0, 4);
-                            let delta = match roll {
-                                0 => PositionDelta { dx: 1, dy: 0, dz: 0 },
-                                1 => PositionDelta { dx: -1, dy: 0, dz: 0 },
-                                2 => PositionDelta { dx: 0, dy: 1, dz: 0 },
-                                3 => PositionDelta { dx: 0, dy: -1, dz: 0 },
-                                _ => PositionDelta { dx: 0, dy: 0, dz: 0 },
-                            };
-                            if let Some(jittered) = target_pos.offset(delta) {
-                                preferred_target = jittered;
+            // Distance fighters back off when too close, close in when too
+            // far or out of sight, and otherwise hold their ground.
+            let keep_range = if flags.distance_fighting {
+                MONSTER_DISTANCE_RANGE
+            } else {
+                MONSTER_MELEE_RANGE
+            };
+            let target_distance =
+                target_position.map(|pos| Self::monster_tile_distance(position, pos));
+            let distance_flee =
+                flags.distance_fighting && target_distance.is_some_and(|distance| distance < keep_range);
+            let flee = (flee_threshold > 0 && health <= flee_threshold) || distance_flee;
+            let holding = flags.distance_fighting
+                && !flee
+                && target_distance == Some(keep_range)
+                && target_position.is_some_and(|pos| self.has_line_of_sight(position, pos));
+            let approach = !flee
+                && !holding
+                && target_distance.is_some_and(|distance| {
+                    distance > keep_range || (flags.distance_fighting && distance == keep_range)
+                });
+
+            if move_ready && !holding {
+                let path_goal = leash_goal.or(target_position.filter(|_| approach));
+                let path_step = match path_goal {
+                    Some(goal) => self.creature_path_step(
+                        monster_id,
+                        position,
+                        goal,
+                        true,
+                        PathWalker::Monster,
+                        &mut path_budget,
+                    ),
+                    None => {
+                        self.creature_paths.remove(&monster_id);
+                        None
+                    }
+                };
+                let preferred = match (path_step, leash_goal) {
+                    (Some(direction), _) => Some(direction),
+                    (None, Some(home_position)) => {
+                        Self::monster_direction(position, home_position, false)
+                    }
+                    (None, None) => target_position.and_then(|target_pos| {
+                        let mut preferred_target = target_pos;
+                        if !flee {
+                            let dx = i32::from(target_pos.x) - i32::from(position.x);
+                            let dy = i32::from(target_pos.y) - i32::from(position.y);
+                            if dx.abs() <= 1 && dy.abs() <= 1 {
+                                let roll = self.monster_rng.roll_range(0, 4);
+                                let delta = match roll {
+                                    0 => PositionDelta { dx: 1, dy: 0, dz: 0 },
+                                    1 => PositionDelta { dx: -1, dy: 0, dz: 0 },
+                                    2 => PositionDelta { dx: 0, dy: 1, dz: 0 },
+                                    3 => PositionDelta { dx: 0, dy: -1, dz: 0 },
+                                    _ => PositionDelta { dx: 0, dy: 0, dz: 0 },
+                                };
+                                if let Some(jittered) = target_pos.offset(delta) {
+                                    preferred_target = jittered;
+                                }
                             }
                         }
-                    }
-                    Self::monster_direction(position, preferred_target, flee)
-                });
+                        Self::monster_direction(position, preferred_target, flee)
+                    }),
+                };
                 let mut directions = Vec::with_capacity(MONSTER_MOVE_ATTEMPTS);
                 if let Some(direction) = preferred {
                     directions.push(direction);
@@ -5999,6 +8716,9 @@ impl WorldState {
                         break;
                     }
                 }
+                if path_step.is_some() && moved.map(|(_, direction)| direction) != path_step {
+                    self.creature_paths.remove(&monster_id);
+                }
                 let cooldown_ticks = if let Some((destination, direction)) = moved {
                     outcome.moves.push(CreatureStep {
                         id: monster_id,
@@ -6088,64 +8808,509 @@ impl WorldState {
                 }
             }
 
-            if talk_ready {
-                let (talk_lines, monster_name) = match self.monsters.get_mut(&monster_id) {
-                    Some(monster) => {
-                        (std::mem::take(&mut monster.talk_lines), monster.name.clone())
-                    }
-                    None => continue,
-                };
-                if !talk_lines.is_empty() {
-                    let delay_ticks = self.monster_talk_delay_ticks();
-                    if let Some(monster) = self.monsters.get_mut(&monster_id) {
-                        monster
-                            .talk_cooldown
-                            .reset_from_now_ticks(clock, delay_ticks);
-                    }
-                    if let Some(talk) =
-                        Self::monster_pick_talk_line(&mut self.monster_rng, &talk_lines)
-                    {
-                        let (talk_type, message) = Self::monster_parse_talk_line(&talk);
-                        outcome.talks.push(MonsterTalk {
-                            monster_id,
-                            name: monster_name,
-                            position,
-                            talk_type,
-                            message,
-                        });
-                    }
-                }
-                if let Some(monster) = self.monsters.get_mut(&monster_id) {
-                    monster.talk_lines = talk_lines;
+            if talk_ready {
+                let (talk_lines, monster_name) = match self.monsters.get_mut(&monster_id) {
+                    Some(monster) => {
+                        (std::mem::take(&mut monster.talk_lines), monster.name.clone())
+                    }
+                    None => continue,
+                };
+                if !talk_lines.is_empty() {
+                    let delay_ticks = self.monster_talk_delay_ticks();
+                    if let Some(monster) = self.monsters.get_mut(&monster_id) {
+                        monster
+                            .talk_cooldown
+                            .reset_from_now_ticks(clock, delay_ticks);
+                    }
+                    if let Some(talk) =
+                        Self::monster_pick_talk_line(&mut self.monster_rng, &talk_lines)
+                    {
+                        let (talk_type, message) = Self::monster_parse_talk_line(&talk);
+                        outcome.talks.push(MonsterTalk {
+                            monster_id,
+                            name: monster_name,
+                            position,
+                            talk_type,
+                            message,
+                        });
+                    }
+                }
+                if let Some(monster) = self.monsters.get_mut(&monster_id) {
+                    monster.talk_lines = talk_lines;
+                }
+            }
+        }
+
+        outcome.player_hits = player_hits.into_iter().collect();
+        outcome.player_hit_marks = player_hit_marks.into_iter().collect();
+        outcome.monster_updates = monster_updates.into_iter().collect();
+        outcome
+    }
+
+    /// Moves a player's summon: it closes in on whatever its master is
+    /// attacking and otherwise stays at the master's side, catching up by
+    /// teleport when the master changes floors or gets too far ahead.
+    /// Returns the creature it can hit this turn.
+    fn tick_follower(
+        &mut self,
+        monster_id: CreatureId,
+        master_id: PlayerId,
+        clock: &GameClock,
+        outcome: &mut MonsterTickOutcome,
+        path_budget: &mut usize,
+    ) -> Option<CreatureId> {
+        let (position, move_ready) = self
+            .monsters
+            .get(&monster_id)
+            .map(|monster| (monster.position, monster.move_cooldown.is_ready(clock)))?;
+        let (master_position, master_target) = self
+            .players
+            .get(&master_id)
+            .map(|master| (master.position, master.attack_target))?;
+        let target = master_target
+            .and_then(|target_id| {
+                self.follower_target_position(master_id, target_id, position)
+                    .map(|target_position| (target_id, target_position))
+            });
+
+        let mut position = position;
+        if move_ready {
+            if master_position.z != position.z
+                || Self::monster_tile_distance(position, master_position) > FOLLOWER_CATCH_UP_RANGE
+            {
+                if let Some(destination) = self.free_creature_field_near(master_position, false) {
+                    self.update_monster_sector_index(monster_id, position, destination);
+                    if let Some(monster) = self.monsters.get_mut(&monster_id) {
+                        monster.position = destination;
+                    }
+                    self.creature_paths.remove(&monster_id);
+                    outcome.refresh_map = true;
+                    position = destination;
+                }
+            } else {
+                let goal = match target {
+                    Some((_, target_position)) => Some(target_position),
+                    None => Some(master_position),
+                };
+                if let Some(goal) =
+                    goal.filter(|goal| Self::monster_tile_distance(position, *goal) > MONSTER_MELEE_RANGE)
+                {
+                    if let Some(destination) =
+                        self.step_follower(monster_id, position, goal, clock, outcome, path_budget)
+                    {
+                        position = destination;
+                    }
+                }
+            }
+        }
+
+        target
+            .filter(|(_, target_position)| {
+                Self::monster_tile_distance(position, *target_position) <= MONSTER_MELEE_RANGE
+            })
+            .map(|(target_id, _)| target_id)
+    }
+
+    /// A walkable, unoccupied field outside protection zones on or next to
+    /// `center`.
+    fn free_creature_field_near(&self, center: Position, include_center: bool) -> Option<Position> {
+        const DIRECTIONS: [Direction; 8] = [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+            Direction::Northeast,
+            Direction::Northwest,
+            Direction::Southeast,
+            Direction::Southwest,
+        ];
+        let neighbours = DIRECTIONS
+            .iter()
+            .filter_map(|direction| center.step(*direction));
+        include_center
+            .then_some(center)
+            .into_iter()
+            .chain(neighbours)
+            .find(|position| {
+                self.map.tile(*position).is_some_and(|tile| {
+                    !self.tile_blocks_movement(tile)
+                        && !tile.protection_zone
+                        && !self.position_occupied(*position)
+                })
+            })
+    }
+
+    /// Where a summon's master target stands, if the summon may fight it:
+    /// never the master or its other summons, and players only on worlds
+    /// that allow player fights.
+    fn follower_target_position(
+        &self,
+        master_id: PlayerId,
+        target_id: CreatureId,
+        position: Position,
+    ) -> Option<Position> {
+        let target_position = match self.monsters.get(&target_id) {
+            Some(monster) if monster.summoner == Some(master_id) => return None,
+            Some(monster) => monster.position,
+            None => {
+                let player_id = PlayerId(target_id.0);
+                if player_id == master_id || !self.combat_rules.players_can_attack() {
+                    return None;
+                }
+                self.players.get(&player_id)?.position
+            }
+        };
+        let reachable = target_position.z == position.z
+            && Self::monster_tile_distance(position, target_position) <= MONSTER_ACQUIRE_RANGE
+            && !self.is_protection_zone(target_position);
+        reachable.then_some(target_position)
+    }
+
+    fn step_follower(
+        &mut self,
+        monster_id: CreatureId,
+        position: Position,
+        goal: Position,
+        clock: &GameClock,
+        outcome: &mut MonsterTickOutcome,
+        path_budget: &mut usize,
+    ) -> Option<Position> {
+        let preferred = self
+            .creature_path_step(monster_id, position, goal, true, PathWalker::Monster, path_budget)
+            .or_else(|| Self::monster_direction(position, goal, false));
+        let mut directions = Vec::with_capacity(3);
+        if let Some(direction) = preferred {
+            directions.push(direction);
+            if let Some((first, second)) = Self::direction_components(direction) {
+                directions.push(first);
+                directions.push(second);
+            }
+        }
+        let mut moved = None;
+        for direction in directions {
+            if let Ok(destination) = self.move_monster(monster_id, direction) {
+                moved = Some((destination, direction));
+                break;
+            }
+        }
+        if moved.map(|(_, direction)| direction) != preferred {
+            self.creature_paths.remove(&monster_id);
+        }
+        let speed = self.monsters.get(&monster_id).map(|monster| monster.speed)?;
+        let cooldown_ticks = match moved {
+            Some((destination, direction)) => {
+                outcome.moved += 1;
+                outcome.moves.push(CreatureStep {
+                    id: monster_id,
+                    from: position,
+                    to: destination,
+                });
+                let tile_speed = self
+                    .map
+                    .tile(destination)
+                    .map(|tile| self.tile_ground_speed(tile))
+                    .unwrap_or(DEFAULT_GROUND_SPEED);
+                self.movement_cooldown_ticks(tile_speed, speed, direction, clock)
+            }
+            None => MONSTER_MOVE_INTERVAL_TICKS,
+        };
+        if let Some(monster) = self.monsters.get_mut(&monster_id) {
+            monster
+                .move_cooldown
+                .reset_from_now_ticks(clock, cooldown_ticks);
+        }
+        moved.map(|(destination, _)| destination)
+    }
+
+    /// Removes a player's summons when they log out or die.
+    fn despawn_player_summons(&mut self, player_id: PlayerId) {
+        let summons: Vec<(CreatureId, Position)> = self
+            .monsters
+            .values()
+            .filter(|monster| monster.summoner == Some(player_id))
+            .map(|monster| (monster.id, monster.position))
+            .collect();
+        for (monster_id, position) in summons {
+            self.monsters.remove(&monster_id);
+            self.remove_monster_from_sector_index(monster_id, position);
+            self.creature_paths.remove(&monster_id);
+            self.boss_states.remove(&monster_id);
+            self.monster_threat.remove(&monster_id);
+            self.queue_map_refresh(position);
+        }
+    }
+
+    /// Advances a boss fight: clears ended immunity windows, enters every
+    /// phase whose hit point threshold was crossed and starts the enrage
+    /// timer on the first tick the boss has a target.
+    fn tick_boss(
+        &mut self,
+        monster_id: CreatureId,
+        engaged: bool,
+        clock: &GameClock,
+        outcome: &mut MonsterTickOutcome,
+    ) {
+        let now = clock.now();
+        let Some((health, max_health)) = self
+            .monsters
+            .get(&monster_id)
+            .map(|monster| (monster.stats.health, monster.stats.max_health))
+        else {
+            return;
+        };
+        let Some(boss) = self.boss_states.get_mut(&monster_id) else {
+            return;
+        };
+        if boss.immune_until.is_some_and(|until| now >= until) {
+            boss.immune_until = None;
+        }
+        if engaged && boss.engaged_at.is_none() {
+            boss.engaged_at = Some(now);
+        }
+        let health_percent = u64::from(health) * 100 / u64::from(max_health.max(1));
+        let mut entered = Vec::new();
+        while let Some(phase) = boss.script.phases.get(boss.next_phase) {
+            if health_percent > u64::from(phase.health_percent) {
+                break;
+            }
+            entered.push(phase.clone());
+            boss.next_phase += 1;
+        }
+        let enrage = match (&boss.script.enrage, boss.engaged_at) {
+            (Some(enrage), Some(engaged_at)) if !boss.enraged => {
+                let delay = clock
+                    .ticks_from_duration_round_up(Duration::from_secs(u64::from(enrage.after_seconds)));
+                (now.0 >= engaged_at.0.saturating_add(delay)).then(|| enrage.clone())
+            }
+            _ => None,
+        };
+        if enrage.is_some() {
+            boss.enraged = true;
+        }
+        let enrage_percent = boss
+            .enraged
+            .then(|| boss.script.enrage.as_ref().map(|enrage| enrage.percent))
+            .flatten()
+            .unwrap_or(0);
+        for phase in entered {
+            self.enter_boss_phase(monster_id, &phase, enrage_percent, clock, outcome);
+        }
+        if let Some(enrage) = enrage {
+            self.enrage_boss(monster_id, &enrage, outcome);
+        }
+    }
+
+    fn enter_boss_phase(
+        &mut self,
+        monster_id: CreatureId,
+        phase: &MonsterPhase,
+        enrage_percent: u16,
+        clock: &GameClock,
+        outcome: &mut MonsterTickOutcome,
+    ) {
+        let Some(monster) = self.monsters.get_mut(&monster_id) else {
+            return;
+        };
+        let position = monster.position;
+        if let Some(spells) = phase.spells.as_ref() {
+            monster.spells = spells.clone();
+        }
+        if let Some(outfit) = phase.outfit {
+            match monster.outfit_effect.as_mut() {
+                Some(effect) => effect.original = outfit,
+                None if monster.outfit != outfit => {
+                    monster.outfit = outfit;
+                    outcome.outfit_updates.push(CreatureOutfitUpdate {
+                        id: monster_id.0,
+                        outfit,
+                    });
                 }
+                None => {}
+            }
+        }
+        if let Some(speed) = phase.speed {
+            let speed = scale_by_percent(u32::from(speed), enrage_percent).min(u32::from(u16::MAX))
+                as u16;
+            Self::set_monster_base_speed(monster, speed, &mut outcome.speed_updates);
+        }
+        if let Some(yell) = phase.yell.as_ref() {
+            outcome.talks.push(MonsterTalk {
+                monster_id,
+                name: monster.name.clone(),
+                position,
+                talk_type: TALK_TYPE_MONSTER_YELL,
+                message: yell.clone(),
+            });
+        }
+        if phase.immunity_seconds > 0 {
+            let ticks = clock
+                .ticks_from_duration_round_up(Duration::from_secs(u64::from(phase.immunity_seconds)));
+            if let Some(boss) = self.boss_states.get_mut(&monster_id) {
+                boss.immune_until = Some(GameTick(clock.now().0.saturating_add(ticks)));
             }
         }
+        for (race_number, count) in &phase.summons {
+            self.monster_spawn_summons(position, *race_number, *count);
+        }
+    }
 
-        outcome.player_hits = player_hits.into_iter().collect();
-        outcome.player_hit_marks = player_hit_marks.into_iter().collect();
-        outcome.monster_updates = monster_updates.into_iter().collect();
-        outcome
+    fn enrage_boss(
+        &mut self,
+        monster_id: CreatureId,
+        enrage: &MonsterEnrage,
+        outcome: &mut MonsterTickOutcome,
+    ) {
+        let Some(monster) = self.monsters.get_mut(&monster_id) else {
+            return;
+        };
+        monster.attack = scale_by_percent(monster.attack, enrage.percent);
+        let base_speed = monster
+            .speed_effect
+            .map(|effect| effect.original_speed)
+            .unwrap_or(monster.speed);
+        let speed =
+            scale_by_percent(u32::from(base_speed), enrage.percent).min(u32::from(u16::MAX)) as u16;
+        Self::set_monster_base_speed(monster, speed, &mut outcome.speed_updates);
+        if let Some(yell) = enrage.yell.as_ref() {
+            outcome.talks.push(MonsterTalk {
+                monster_id,
+                name: monster.name.clone(),
+                position: monster.position,
+                talk_type: TALK_TYPE_MONSTER_YELL,
+                message: yell.clone(),
+            });
+        }
+    }
+
+    /// Changes a monster's own speed; a running speed effect keeps its
+    /// modified speed and restores the new one when it ends.
+    fn set_monster_base_speed(
+        monster: &mut MonsterInstance,
+        speed: u16,
+        updates: &mut Vec<CreatureSpeedUpdate>,
+    ) {
+        if let Some(effect) = monster.speed_effect.as_mut() {
+            effect.original_speed = speed;
+            return;
+        }
+        if monster.speed != speed {
+            monster.speed = speed;
+            updates.push(CreatureSpeedUpdate {
+                id: monster.id.0,
+                speed,
+            });
+        }
     }
 
-    fn collect_active_monster_ids(&mut self, radius: u16) -> Vec<CreatureId> {
+    fn collect_active_monster_ids(&mut self) -> Vec<CreatureId> {
         self.ensure_monster_sector_index();
-        if self.players.is_empty() || self.monsters.is_empty() {
+        if self.monsters.is_empty() {
             return Vec::new();
         }
-        let mut active = Vec::new();
-        for player in self.players.values() {
-            let sectors = self.sectors_in_range(player.position, radius);
-            for sector in sectors {
-                if let Some(ids) = self.monster_sector_index.get(&sector) {
-                    active.extend(ids.iter().copied());
-                }
-            }
-        }
+        let mut active: Vec<CreatureId> = self
+            .awake_sectors
+            .keys()
+            .filter_map(|sector| self.monster_sector_index.get(sector))
+            .flatten()
+            .copied()
+            .collect();
         active.sort_unstable_by_key(|id| id.0);
         active.dedup();
         active
     }
 
+    /// Wakes the sectors around every player and sends the monsters of
+    /// sectors nobody has been near for `MONSTER_DORMANT_AFTER` to sleep.
+    fn update_monster_activity(&mut self, clock: &GameClock) {
+        self.ensure_monster_sector_index();
+        let now = clock.now();
+        let nearby: Vec<SectorCoord> = self
+            .players
+            .values()
+            .flat_map(|player| self.sectors_in_range(player.position, MONSTER_ACTIVE_RANGE))
+            .collect();
+        for sector in nearby {
+            self.awake_sectors.insert(sector, now);
+        }
+        let grace = clock.ticks_from_duration_round_up(MONSTER_DORMANT_AFTER);
+        let expired: Vec<SectorCoord> = self
+            .awake_sectors
+            .iter()
+            .filter(|(_, seen)| now.0.saturating_sub(seen.0) > grace)
+            .map(|(sector, _)| *sector)
+            .collect();
+        for sector in expired {
+            self.awake_sectors.remove(&sector);
+            let sleepers = self
+                .monster_sector_index
+                .get(&sector)
+                .cloned()
+                .unwrap_or_default();
+            for monster_id in sleepers {
+                self.put_monster_to_sleep(monster_id);
+            }
+        }
+    }
+
+    /// Ends whatever a monster was doing when its sector went dormant: it
+    /// forgets its fight, heals up and goes back to its spawn home unless that
+    /// lies in an awake sector. Summons stay with their master and bosses keep
+    /// their fight state.
+    fn put_monster_to_sleep(&mut self, monster_id: CreatureId) {
+        if self.boss_states.contains_key(&monster_id) {
+            return;
+        }
+        let Some(monster) = self.monsters.get(&monster_id) else {
+            return;
+        };
+        if monster.summoner.is_some() {
+            return;
+        }
+        let position = monster.position;
+        let destination = monster
+            .home_id
+            .and_then(|index| self.monster_homes.get(index))
+            .map(|home| home.position)
+            .filter(|home| !Self::monster_within_home(position, *home, 1))
+            .and_then(|home| self.free_creature_field_near(home, true))
+            .filter(|destination| {
+                // Someone is watching the home, so a silent move would leave
+                // their client showing the monster in the wrong place.
+                !self
+                    .awake_sectors
+                    .contains_key(&self.map.sector_for_position(*destination))
+            });
+        let Some(monster) = self.monsters.get_mut(&monster_id) else {
+            return;
+        };
+        monster.stats.health = monster.stats.max_health;
+        monster.target = None;
+        monster.damage_ledger.clear();
+        if let Some(destination) = destination {
+            monster.position = destination;
+            self.update_monster_sector_index(monster_id, position, destination);
+        }
+        self.monster_threat.remove(&monster_id);
+        self.monster_leashing.remove(&monster_id);
+        self.creature_paths.remove(&monster_id);
+    }
+
+    pub fn monster_activity(&self) -> MonsterActivity {
+        let active = self
+            .monsters
+            .values()
+            .filter(|monster| {
+                self.awake_sectors
+                    .contains_key(&self.map.sector_for_position(monster.position))
+            })
+            .count();
+        MonsterActivity {
+            active,
+            dormant: self.monsters.len() - active,
+            awake_sectors: self.awake_sectors.len(),
+        }
+    }
+
     fn sectors_in_range(&self, position: Position, radius: u16) -> Vec<SectorCoord> {
         let min_x = position.x.saturating_sub(radius);
         let max_x = position.x.saturating_add(radius);
@@ -6489,6 +9654,9 @@ impl WorldState {
             }
             processed = processed.saturating_add(self.process_cron_system());
         }
+        self.prune_field_owners();
+        let unix_now = unix_time_now();
+        self.corpse_owners.retain(|_, owner| owner.expires_at > unix_now);
 
         processed
     }
@@ -7529,6 +10697,7 @@ impl WorldState {
     pub fn tick_npcs(&mut self, clock: &GameClock) -> Vec<CreatureStep> {
         let npc_ids: Vec<CreatureId> = self.npcs.keys().copied().collect();
         let mut moves = Vec::new();
+        let mut path_budget = NPC_PATH_BUDGET_PER_TICK;
         for npc_id in npc_ids {
             let (position, home, radius, ready, focused, focus_expires_at) =
                 match self.npcs.get(&npc_id) {
@@ -7566,6 +10735,22 @@ impl WorldState {
             if focused.is_some() && !clear_focus {
                 continue;
             }
+            let walk_goal = self.npc_walk_targets.get(&npc_id).copied().or_else(|| {
+                (radius > 0 && !npc_within_wander_radius(home, radius, position)).then_some(home)
+            });
+            if let Some(goal) = walk_goal {
+                if !ready {
+                    continue;
+                }
+                if let Some(step) = self.npc_walk_step(npc_id, position, goal, &mut path_budget) {
+                    moves.push(step);
+                }
+                if let Some(npc) = self.npcs.get_mut(&npc_id) {
+                    npc.move_cooldown
+                        .reset_from_now_ticks(clock, NPC_MOVE_INTERVAL_TICKS);
+                }
+                continue;
+            }
             if radius == 0 || !ready {
                 continue;
             }
@@ -7602,6 +10787,39 @@ impl WorldState {
         moves
     }
 
+    /// Takes one step of an NPC's walk to `goal`, ignoring its wander radius,
+    /// and forgets the goal once the NPC stands on it.
+    fn npc_walk_step(
+        &mut self,
+        npc_id: CreatureId,
+        position: Position,
+        goal: Position,
+        budget: &mut usize,
+    ) -> Option<CreatureStep> {
+        if position == goal {
+            self.npc_walk_targets.remove(&npc_id);
+            self.creature_paths.remove(&npc_id);
+            return None;
+        }
+        let direction =
+            self.creature_path_step(npc_id, position, goal, false, PathWalker::Npc, budget)?;
+        let Ok(destination) = self.resolve_movement_destination(position, direction) else {
+            self.creature_paths.remove(&npc_id);
+            return None;
+        };
+        let npc = self.npcs.get_mut(&npc_id)?;
+        npc.position = destination;
+        npc.direction = direction;
+        if destination == goal {
+            self.npc_walk_targets.remove(&npc_id);
+        }
+        Some(CreatureStep {
+            id: npc_id,
+            from: position,
+            to: destination,
+        })
+    }
+
     fn monster_acquire_range(lose_target_distance: u16) -> u16 {
         if lose_target_distance == 0 {
             MONSTER_ACQUIRE_RANGE
@@ -7636,61 +10854,78 @@ impl WorldState {
         player.current_outfit.look_type == 0 && player.current_outfit.look_item == 0
     }
 
+    /// Picks a monster's target. Players with threat win, with the current
+    /// target kept until someone out-threatens it by a clear margin; without
+    /// any threat the script strategy chooses. Players outside the leash
+    /// area around the monster's home are never picked.
     fn select_monster_target(
         &mut self,
+        monster_id: CreatureId,
         position: Position,
-        current_target: Option<PlayerId>,
         range: u16,
         strategy: [u8; 4],
-        damage_by: &HashMap<PlayerId, u32>,
         flags: MonsterFlags,
+        home: Option<(Position, u16)>,
     ) -> Option<PlayerId> {
-        if let Some(target_id) = current_target {
-            if let Some(player) = self.players.get(&target_id) {
-                if !Self::player_visible_to_monster(player, flags) {
-                    return None;
-                }
-                if self.is_protection_zone(player.position) {
-                    return None;
-                }
-                if player.position.z == position.z
+        let current_target = self
+            .monsters
+            .get(&monster_id)
+            .and_then(|monster| monster.target);
+        let mut threat = self.monster_threat.remove(&monster_id).unwrap_or_default();
+        threat.retain(|player_id| {
+            self.players.get(&player_id).is_some_and(|player| {
+                player.position.z == position.z
                     && Self::monster_tile_distance(position, player.position) <= range
-                {
-                    return Some(target_id);
-                }
-            }
+                    && !self.is_protection_zone(player.position)
+                    && Self::monster_in_leash_area(home, player.position)
+            })
+        });
+
+        let mut candidates: Vec<PlayerId> = self
+            .players
+            .iter()
+            .filter(|(id, player)| {
+                player.position.z == position.z
+                    && Self::player_visible_to_monster(player, flags)
+                    && !self.is_protection_zone(player.position)
+                    && Self::monster_tile_distance(position, player.position) <= range
+                    && Self::monster_in_leash_area(home, player.position)
+                    && (current_target == Some(**id)
+                        || self.has_line_of_sight(position, player.position))
+            })
+            .map(|(id, _)| *id)
+            .collect();
+        candidates.sort_unstable_by_key(|id| id.0);
+
+        let target = threat
+            .choose(current_target, &candidates, TARGET_SWITCH_PERCENT)
+            .or_else(|| current_target.filter(|id| candidates.contains(id)))
+            .or_else(|| self.monster_strategy_target(position, strategy, &candidates, &threat));
+        if !threat.is_empty() {
+            self.monster_threat.insert(monster_id, threat);
         }
+        target
+    }
 
+    fn monster_strategy_target(
+        &mut self,
+        position: Position,
+        strategy: [u8; 4],
+        candidates: &[PlayerId],
+        threat: &ThreatTable,
+    ) -> Option<PlayerId> {
         let strategy = Self::monster_strategy_choice(&mut self.monster_rng, strategy);
         let mut best: Option<(PlayerId, i32, u32)> = None;
-        for (id, player) in &self.players {
-            if player.position.z != position.z {
-                continue;
-            }
-            if !Self::player_visible_to_monster(player, flags) {
-                continue;
-            }
-            if self.is_protection_zone(player.position) {
-                continue;
-            }
-            let distance = Self::monster_tile_distance(position, player.position);
-            if distance > range {
+        for id in candidates {
+            let Some(player) = self.players.get(id) else {
                 continue;
-            }
-            let dx = if position.x >= player.position.x {
-                position.x - player.position.x
-            } else {
-                player.position.x - position.x
-            };
-            let dy = if position.y >= player.position.y {
-                position.y - player.position.y
-            } else {
-                player.position.y - position.y
             };
+            let dx = position.x.abs_diff(player.position.x);
+            let dy = position.y.abs_diff(player.position.y);
             let goodness = match strategy {
                 0 => -i32::from(dx + dy),
                 1 => -(player.stats.mana.min(i32::MAX as u32) as i32),
-                2 => damage_by.get(id).copied().unwrap_or(0) as i32,
+                2 => threat.threat(*id).min(i32::MAX as u32) as i32,
                 _ => 0,
             };
             let tie_breaker = self.monster_rng.roll_range(0, 99);
@@ -7708,6 +10943,47 @@ impl WorldState {
         best.map(|(id, _, _)| id)
     }
 
+    fn monster_within_home(position: Position, home: Position, radius: u16) -> bool {
+        position.z == home.z && Self::monster_tile_distance(position, home) <= radius
+    }
+
+    fn monster_in_leash_area(home: Option<(Position, u16)>, position: Position) -> bool {
+        home.is_none_or(|(home_position, radius)| {
+            Self::monster_within_home(
+                position,
+                home_position,
+                radius.saturating_add(MONSTER_LEASH_RANGE),
+            )
+        })
+    }
+
+    /// Healing a player makes every monster fighting them angry at the
+    /// healer.
+    fn add_healing_threat(&mut self, healer_id: PlayerId, healed_id: PlayerId, amount: u32) {
+        let threat = healing_threat(amount);
+        if threat == 0 {
+            return;
+        }
+        let engaged: Vec<CreatureId> = self
+            .monsters
+            .values()
+            .filter(|monster| {
+                monster.target == Some(healed_id)
+                    || self
+                        .monster_threat
+                        .get(&monster.id)
+                        .is_some_and(|table| table.contains(healed_id))
+            })
+            .map(|monster| monster.id)
+            .collect();
+        for monster_id in engaged {
+            self.monster_threat
+                .entry(monster_id)
+                .or_default()
+                .add(healer_id, threat);
+        }
+    }
+
     fn monster_strategy_choice(rng: &mut MonsterRng, strategy: [u8; 4]) -> u8 {
         let mut selection = rng.roll_range(0, 99) as i32;
         for (index, weight) in strategy.iter().take(3).enumerate() {
@@ -7925,18 +11201,9 @@ impl WorldState {
         }
     }
 
-    fn protection_mask_for_damage(damage_type: DamageType) -> u16 {
-        match damage_type {
-            DamageType::Physical => 1,
-            DamageType::Earth => 2,
-            DamageType::Fire => 4,
-            DamageType::Energy => 8,
-            DamageType::LifeDrain => 0x100,
-            DamageType::ManaDrain => 0x200,
-            _ => 0,
-        }
-    }
-
+    /// Reduces damage by the player's own resistances and then by each worn
+    /// protection item in turn. Items with `TotalUses` lose a charge per
+    /// absorbed hit and wear out into `WearoutTarget` (or vanish) when empty.
     fn apply_player_protection_reduction(
         &mut self,
         target_id: PlayerId,
@@ -7946,12 +11213,17 @@ impl WorldState {
         if amount == 0 {
             return 0;
         }
-        let mask = Self::protection_mask_for_damage(damage_type);
-        if mask == 0 {
+        let Some(mut reduced) = self
+            .players
+            .get(&target_id)
+            .map(|player| player.stats.resistances.apply(damage_type, amount))
+        else {
             return amount;
-        }
-        let mut reduced = amount;
+        };
         for slot in INVENTORY_SLOTS {
+            if reduced == 0 {
+                break;
+            }
             let item = match self
                 .players
                 .get(&target_id)
@@ -7960,9 +11232,9 @@ impl WorldState {
                 Some(item) => item,
                 None => continue,
             };
-            let (body_position, protection_mask, reduction, total_uses, wearout_target) = {
+            let (body_position, absorbs, charges, wearout_target) = {
                 let Some(object_types) = self.object_types.as_ref() else {
-                    return amount;
+                    return reduced;
                 };
                 let Some(object_type) = object_types.get(item.type_id) else {
                     continue;
@@ -7973,72 +11245,46 @@ impl WorldState {
                 let Some(body_position) = object_type.body_position() else {
                     continue;
                 };
-                let protection_mask = object_type.attribute_u16("ProtectionDamageTypes").unwrap_or(0);
-                let reduction = object_type.attribute_u16("DamageReduction").unwrap_or(0);
-                let total_uses = match object_type.attribute_u16("TotalUses") {
-                    Some(value) => value,
-                    None => continue,
-                };
                 let wearout_target = object_type.attribute_u16("WearoutTarget").unwrap_or(0);
-                (body_position, protection_mask, reduction, total_uses, wearout_target)
+                (
+                    body_position,
+                    object_type.protection_absorbs(),
+                    object_type.remaining_charges(item.remaining_uses()),
+                    wearout_target,
+                )
             };
             let expected = match u8::try_from(slot.index().saturating_add(1)) {
                 Ok(value) => value,
                 Err(_) => continue,
             };
-            if body_position != expected {
-                continue;
-            }
-            if protection_mask & mask == 0 {
-                continue;
-            }
-            if reduction == 0 {
+            if body_position != expected || absorbs.percent(damage_type) == 0 {
                 continue;
             }
-            let capped = reduction.min(100) as u32;
-            reduced = (reduced * (100 - capped)) / 100;
+            reduced = absorbs.apply(damage_type, reduced);
 
-            let remaining = if item.count <= 1 && total_uses > 1 {
-                total_uses
-            } else {
-                item.count
+            let Some(remaining) = charges else {
+                continue;
             };
             let next_remaining = remaining.saturating_sub(1);
-            if next_remaining == 0 {
-                if wearout_target > 0 {
-                    let mut updated = item.clone();
-                    let updated = if self
-                        .change_itemstack_type(&mut updated, ItemTypeId(wearout_target), 0)
-                        .is_ok()
-                    {
-                        Some(updated)
-                    } else {
-                        None
-                    };
-                    if let Some(player) = self.players.get_mut(&target_id) {
-                        player.inventory.set_slot(slot, updated);
-                    }
-                } else {
-                    if let Some(player) = self.players.get_mut(&target_id) {
-                        player.inventory.set_slot(slot, None);
-                    }
-                }
+            let updated = if next_remaining > 0 {
+                let mut updated = item.clone();
+                let _ = set_itemstack_attribute_u16(
+                    &mut updated,
+                    "RemainingUses",
+                    i32::from(next_remaining),
+                    ItemAttribute::RemainingUses,
+                );
+                Some(updated)
+            } else if wearout_target > 0 {
+                let mut updated = item.clone();
+                self.change_itemstack_type(&mut updated, ItemTypeId(wearout_target), 0)
+                    .ok()
+                    .map(|_| updated)
             } else {
-                if let Some(player) = self.players.get_mut(&target_id) {
-                    player.inventory.set_slot(
-                        slot,
-                        Some(ItemStack { id: crate::entities::item::ItemId::next(),
-                            type_id: item.type_id,
-                            count: next_remaining,
-                            attributes: item.attributes.clone(),
-                            contents: item.contents.clone(),
-                        }),
-                    );
-                }
-            }
-
-            if reduced == 0 {
-                break;
+                None
+            };
+            if let Some(player) = self.players.get_mut(&target_id) {
+                player.inventory.set_slot(slot, updated);
             }
         }
 
@@ -8047,17 +11293,18 @@ impl WorldState {
 
     fn player_attack_values(&self, player: &PlayerState) -> AttackSelection {
         let fallback_attack = u32::from(player.skills.fist.level).max(1);
-        let mut selection = AttackSelection {
+        let selection = AttackSelection {
             attack: fallback_attack,
             range: PLAYER_MELEE_RANGE,
             skill: SkillType::Fist,
             damage_type: DamageType::Physical,
+            ranged: None,
         };
         let Some(object_types) = self.object_types.as_ref() else {
             return selection;
         };
 
-        let selection_for_item = |item: &ItemStack| -> Option<AttackSelection> {
+        let selection_for_item = |slot: InventorySlot, item: &ItemStack| -> Option<AttackSelection> {
             let object_type = object_types.get(item.type_id)?;
             if let Some(value) = object_type.attribute_u16("WeaponAttackValue") {
                 let skill = object_type
@@ -8069,14 +11316,32 @@ impl WorldState {
                     range: PLAYER_MELEE_RANGE,
                     skill,
                     damage_type: DamageType::Physical,
+                    ranged: None,
                 });
             }
+            if object_type.has_flag("Bow") {
+                return Some(Self::bow_attack_values(player, object_types, object_type));
+            }
             if let Some(value) = object_type.attribute_u16("ThrowAttackValue") {
                 return Some(AttackSelection {
                     attack: u32::from(value),
-                    range: PLAYER_THROW_RANGE,
+                    range: object_type
+                        .attribute_u16("ThrowRange")
+                        .unwrap_or(PLAYER_THROW_RANGE),
                     skill: SkillType::Distance,
                     damage_type: DamageType::Physical,
+                    ranged: Some(RangedAttack {
+                        slot,
+                        missile_id: object_type
+                            .attribute_u16("ThrowMissile")
+                            .and_then(|value| u8::try_from(value).ok())
+                            .unwrap_or(0),
+                        fires_ammo: false,
+                        fragility: u32::from(
+                            object_type.attribute_u16("ThrowFragility").unwrap_or(100),
+                        )
+                        .min(100),
+                    }),
                 });
             }
             if let Some(value) = object_type.attribute_u16("WandAttackStrength") {
@@ -8085,35 +11350,129 @@ impl WorldState {
                     range: PLAYER_DISTANCE_RANGE,
                     skill: SkillType::Magic,
                     damage_type: DamageType::Energy,
+                    ranged: None,
                 });
             }
             None
         };
 
-        if let Some(item) = player.inventory.slot(InventorySlot::RightHand) {
-            if let Some(found) = selection_for_item(item) {
-                return found;
+        for slot in [InventorySlot::RightHand, InventorySlot::LeftHand] {
+            if let Some(item) = player.inventory.slot(slot) {
+                if let Some(found) = selection_for_item(slot, item) {
+                    return found;
+                }
             }
         }
-        if let Some(item) = player.inventory.slot(InventorySlot::LeftHand) {
-            if let Some(found) = selection_for_item(item) {
-                return found;
+
+        selection
+    }
+
+    /// Bows and crossbows shoot the ammunition in the ammo slot when its
+    /// `AmmoType` matches the launcher's `BowAmmoType`; without fitting
+    /// ammunition they cannot attack at all.
+    fn bow_attack_values(
+        player: &PlayerState,
+        object_types: &ObjectTypeIndex,
+        bow: &ObjectType,
+    ) -> AttackSelection {
+        let range = bow.attribute_u16("BowRange").unwrap_or(PLAYER_DISTANCE_RANGE);
+        let ammo = player
+            .inventory
+            .slot(InventorySlot::Ammo)
+            .and_then(|item| object_types.get(item.type_id))
+            .filter(|ammo| {
+                match (bow.attribute_u16("BowAmmoType"), ammo.attribute_u16("AmmoType")) {
+                    (Some(wanted), Some(loaded)) => wanted == loaded,
+                    (None, Some(_)) => true,
+                    _ => false,
+                }
+            });
+        let Some(ammo) = ammo else {
+            return AttackSelection {
+                attack: 0,
+                range,
+                skill: SkillType::Distance,
+                damage_type: DamageType::Physical,
+                ranged: None,
+            };
+        };
+        AttackSelection {
+            attack: u32::from(ammo.attribute_u16("AmmoAttackValue").unwrap_or(0)),
+            range,
+            skill: SkillType::Distance,
+            damage_type: DamageType::Physical,
+            ranged: Some(RangedAttack {
+                slot: InventorySlot::Ammo,
+                missile_id: ammo
+                    .attribute_u16("AmmoMissile")
+                    .and_then(|value| u8::try_from(value).ok())
+                    .unwrap_or(0),
+                fires_ammo: true,
+                fragility: 100,
+            }),
+        }
+    }
+
+    /// Uses up the item a distance attack fires, sends its missile to every
+    /// viewer and rolls the hit chance. Thrown weapons that survive land on
+    /// the target's tile. Returns `None` when the target is out of sight or
+    /// nothing is left to fire.
+    fn fire_ranged_attack(
+        &mut self,
+        player_id: PlayerId,
+        selection: AttackSelection,
+        from: Position,
+        to: Position,
+        outcome: &mut PlayerCombatOutcome,
+    ) -> Option<bool> {
+        if selection.range > PLAYER_MELEE_RANGE && !self.has_line_of_sight(from, to) {
+            return None;
+        }
+        let Some(ranged) = selection.ranged else {
+            return Some(true);
+        };
+        let (shot, skill) = {
+            let player = self.players.get_mut(&player_id)?;
+            let item = player.inventory.slot(ranged.slot)?.clone();
+            if item.count > 1 {
+                let mut rest = item.clone();
+                rest.count -= 1;
+                player.inventory.set_slot(ranged.slot, Some(rest));
+            } else {
+                player.inventory.set_slot(ranged.slot, None);
             }
+            let shot = ItemStack {
+                id: ItemId::next(),
+                type_id: item.type_id,
+                count: 1,
+                attributes: item.attributes.clone(),
+                contents: Vec::new(),
+            };
+            (shot, player.skills.get(SkillType::Distance).level)
+        };
+        outcome.inventory_updates.push(ranged.slot);
+        if ranged.missile_id != 0 {
+            self.queue_missile_effect(from, to, ranged.missile_id);
         }
-        if let Some(item) = player.inventory.slot(InventorySlot::Ammo) {
-            if let Some(object_type) = object_types.get(item.type_id) {
-                if let Some(value) = object_type.attribute_u16("AmmoAttackValue") {
-                    selection = AttackSelection {
-                        attack: u32::from(value),
-                        range: PLAYER_DISTANCE_RANGE,
-                        skill: SkillType::Distance,
-                        damage_type: DamageType::Physical,
-                    };
-                }
+        if !self.moveuse_rng.roll_percent(ranged.fragility) {
+            let stackable = self
+                .item_types
+                .as_ref()
+                .and_then(|index| index.get(shot.type_id))
+                .map(|entry| entry.stackable)
+                .unwrap_or(false);
+            let movable = self.item_is_movable(&shot);
+            let placed = match self.map.tile_mut(to) {
+                Some(tile) => place_on_tile_with_dustbin(tile, shot, stackable, movable).is_ok(),
+                None => false,
+            };
+            if placed {
+                self.queue_map_refresh(to);
             }
         }
-
-        selection
+        let distance = Self::monster_tile_distance(from, to);
+        let chance = distance_hit_chance(skill, distance, ranged.fires_ammo);
+        Some(self.moveuse_rng.roll_percent(chance))
     }
 
     fn roll_player_attack_damage(
@@ -8187,7 +11546,7 @@ impl WorldState {
         spells: &[MonsterSpell],
         clock: &GameClock,
         effects: &mut Vec<MonsterVisualEffect>,
-        missiles: &mut Vec<MonsterMissileEffect>,
+        missiles: &mut Vec<MissileEffect>,
         player_hits: &mut HashSet<PlayerId>,
         player_hit_marks: &mut HashSet<PlayerHitMarker>,
         monster_updates: &mut HashSet<CreatureId>,
@@ -8218,7 +11577,7 @@ impl WorldState {
                 MonsterSpellTargetMeta::Victim { missile_id, .. }
                 | MonsterSpellTargetMeta::Destination { missile_id, .. } => {
                     if missile_id != 0 {
-                        missiles.push(MonsterMissileEffect {
+                        missiles.push(MissileEffect {
                             from: monster_pos,
                             to: target_pos,
                             missile_id,
@@ -8289,7 +11648,14 @@ impl WorldState {
                                 attacker_id: monster_id,
                             });
                         }
-                        let _ = applied_damage;
+                        if applied_damage > 0 {
+                            self.record_player_damage(
+                                target_id,
+                                DamageSource::Monster(monster_id),
+                                *damage_type,
+                                applied_damage,
+                            );
+                        }
                     }
                 }
                 MonsterSpellEffect::Healing { args } => {
@@ -8419,7 +11785,7 @@ impl WorldState {
                     );
                     self.schedule_cron_for_item_tree(&stack);
                     let movable = self.item_is_movable(&stack);
-                    let mut placed_any = false;
+                    let mut placed = Vec::new();
                     for position in &positions {
                         if self.is_protection_zone(*position) {
                             continue;
@@ -8433,11 +11799,12 @@ impl WorldState {
                         if place_on_tile_with_dustbin(tile, stack.clone(), stackable, movable)
                             .is_ok()
                         {
-                            placed_any = true;
+                            placed.push(*position);
                         }
                     }
-                    if placed_any {
+                    if !placed.is_empty() {
                         *refresh_map = true;
+                        self.note_field_owner(stack.id, DamageSource::Monster(monster_id), placed);
                     }
                     if let Some((kind, _)) = Self::field_condition_kind(field_kind) {
                         for target_id in self.monster_spell_targets(&positions, flags) {
@@ -8448,6 +11815,11 @@ impl WorldState {
                                     MONSTER_FIELD_TICK_DAMAGE,
                                 );
                             }
+                            self.note_player_condition_source(
+                                target_id,
+                                kind,
+                                DamageSource::Monster(monster_id),
+                            );
                         }
                     }
                 }
@@ -8533,35 +11905,8 @@ impl WorldState {
         player_hit_marks: &mut HashSet<PlayerHitMarker>,
         _monster_updates: &mut HashSet<CreatureId>,
     ) -> bool {
-        let (skills, flags) = match self.monsters.get(&monster_id) {
-            Some(monster) => (monster.skills, monster.flags),
-            None => return false,
-        };
-        let base_damage = if attack == 0 {
-            0
-        } else {
-            self.monster_rng.roll_range(1, attack) as i32
-        };
-        let attempted_damage = if base_damage == 0 {
-            0
-        } else {
-            let skill_a = skills.melee_skill(flags);
-            let mut skill_b = skills.level;
-            let skill_a = if skill_a == 0 && skill_b == 0 {
-                skill_b = 0;
-                50
-            } else {
-                skill_a
-            };
-            let scaled = compute_damage(
-                base_damage,
-                0,
-                skill_a,
-                skill_b,
-                DamageScaleFlags::NONE,
-                0,
-            );
-            scaled.max(0) as u32
+        let Some(attempted_damage) = self.roll_monster_melee_damage(monster_id, attack) else {
+            return false;
         };
         let (defend_selection, attack_mode, defend_ready) = match self.players.get(&target_id) {
             Some(target) => (
@@ -8617,6 +11962,14 @@ impl WorldState {
                 attacker_id: monster_id,
             });
         }
+        if applied_damage > 0 {
+            self.record_player_damage(
+                target_id,
+                DamageSource::Monster(monster_id),
+                DamageType::Physical,
+                applied_damage,
+            );
+        }
         if attempted_damage > 0 && defend_ready {
             self.apply_defend_wear(target_id, defend_selection);
             let defend_ticks = clock.ticks_from_duration_round_up(Duration::from_millis(
@@ -8636,12 +11989,101 @@ impl WorldState {
                 if let Some(target) = self.players.get_mut(&target_id) {
                     Self::apply_condition_skill_timer(target, ConditionKind::Poison, poison_roll);
                 }
+                self.note_player_condition_source(
+                    target_id,
+                    ConditionKind::Poison,
+                    DamageSource::Monster(monster_id),
+                );
             }
         }
 
         true
     }
 
+    fn roll_monster_melee_damage(&mut self, monster_id: CreatureId, attack: u32) -> Option<u32> {
+        let (skills, flags) = match self.monsters.get(&monster_id) {
+            Some(monster) => (monster.skills, monster.flags),
+            None => return None,
+        };
+        let base_damage = if attack == 0 {
+            0
+        } else {
+            self.monster_rng.roll_range(1, attack) as i32
+        };
+        if base_damage == 0 {
+            return Some(0);
+        }
+        let skill_a = skills.melee_skill(flags);
+        let mut skill_b = skills.level;
+        let skill_a = if skill_a == 0 && skill_b == 0 {
+            skill_b = 0;
+            50
+        } else {
+            skill_a
+        };
+        let scaled = compute_damage(
+            base_damage,
+            0,
+            skill_a,
+            skill_b,
+            DamageScaleFlags::NONE,
+            0,
+        );
+        Some(scaled.max(0) as u32)
+    }
+
+    /// A summon's melee hit on another monster; the damage is credited to
+    /// the summon's master.
+    fn follower_attack_monster(
+        &mut self,
+        follower_id: CreatureId,
+        target_id: CreatureId,
+        attack: u32,
+        outcome: &mut MonsterTickOutcome,
+        monster_updates: &mut HashSet<CreatureId>,
+    ) -> bool {
+        let Some(target_position) = self.monsters.get(&target_id).map(|monster| monster.position)
+        else {
+            return false;
+        };
+        let Some(damage) = self.roll_monster_melee_damage(follower_id, attack) else {
+            return false;
+        };
+        let before = self
+            .monsters
+            .get(&target_id)
+            .map(|monster| monster.stats.health)
+            .unwrap_or(0);
+        if self
+            .apply_damage_to_monster(
+                target_id,
+                DamageType::Physical,
+                damage,
+                DamageSource::Monster(follower_id),
+            )
+            .is_err()
+        {
+            return false;
+        }
+        match self.monsters.get(&target_id) {
+            Some(target) => {
+                if damage > 0 {
+                    outcome.effects.push(MonsterVisualEffect {
+                        position: target_position,
+                        effect_id: if target.stats.health < before {
+                            PLAYER_ATTACK_EFFECT_BLOOD_ID
+                        } else {
+                            PLAYER_ATTACK_EFFECT_BLOCK_ID
+                        },
+                    });
+                }
+                monster_updates.insert(target_id);
+            }
+            None => outcome.refresh_map = true,
+        }
+        true
+    }
+
     fn monster_spell_targets(
         &self,
         positions: &[Position],
@@ -8690,9 +12132,6 @@ impl WorldState {
     }
 
     fn monster_parse_talk_line(line: &str) -> (u8, String) {
-        const TALK_TYPE_MONSTER_SAY: u8 = 0x10;
-        const TALK_TYPE_MONSTER_YELL: u8 = 0x11;
-
         let trimmed = line.trim();
         if let Some(stripped) = trimmed.strip_prefix("#Y") {
             return (TALK_TYPE_MONSTER_YELL, stripped.trim_start().to_string());
@@ -8713,7 +12152,9 @@ impl WorldState {
         match meta {
             MonsterSpellTargetMeta::Actor { .. } => Some(vec![monster_pos]),
             MonsterSpellTargetMeta::Victim { range, .. } => {
-                if Self::monster_tile_distance(monster_pos, target_pos) > range {
+                if Self::monster_tile_distance(monster_pos, target_pos) > range
+                    || !self.has_line_of_sight(monster_pos, target_pos)
+                {
                     return None;
                 }
                 Some(vec![target_pos])
@@ -8722,7 +12163,9 @@ impl WorldState {
                 Some(circle_positions(self.circles.as_ref(), monster_pos, radius))
             }
             MonsterSpellTargetMeta::Destination { range, radius, .. } => {
-                if Self::monster_tile_distance(monster_pos, target_pos) > range {
+                if Self::monster_tile_distance(monster_pos, target_pos) > range
+                    || !self.has_line_of_sight(monster_pos, target_pos)
+                {
                     return None;
                 }
                 Some(circle_positions(self.circles.as_ref(), target_pos, radius))
@@ -8946,7 +12389,7 @@ impl WorldState {
                 Ok(id) => {
                     if let Some(monster) = self.monsters.get_mut(&id) {
                         monster.target = None;
-                        monster.damage_by.clear();
+                        monster.damage_ledger.clear();
                         monster.talk_lines.clear();
                     }
                     Some(id)
@@ -9029,7 +12472,7 @@ impl WorldState {
             ) {
                 if let Some(monster) = self.monsters.get_mut(&id) {
                     monster.target = None;
-                    monster.damage_by.clear();
+                    monster.damage_ledger.clear();
                     monster.talk_lines.clear();
                 }
                 spawned = spawned.saturating_add(1);
@@ -9919,6 +13362,11 @@ impl WorldState {
             if stack.type_id != expected_rune {
                 return Err("rune cast failed: wrong rune".to_string());
             }
+            if let Some(target) = target_position {
+                if target != player.position && !self.has_line_of_sight(player.position, target) {
+                    return Err("rune cast failed: target out of sight".to_string());
+                }
+            }
         }
 
         let report = self.cast_spell_inner(
@@ -9950,6 +13398,7 @@ impl WorldState {
         cost_mode: SpellCostMode,
         spell_args: Option<Vec<String>>,
     ) -> Result<SpellCastReport, String> {
+        self.sync_combat_clock(clock);
         let caster_position = self
             .players
             .get(&caster_id)
@@ -10128,13 +13577,18 @@ impl WorldState {
                 player_targets.remove(&caster_id);
             }
 
-            if offensive && !self.combat_rules.pvp_enabled {
-                if player_targets
-                    .iter()
-                    .any(|target_id| *target_id != caster_id)
-                {
+            if offensive && !self.combat_rules.players_can_attack() {
+                let aimed_at_player = spell.target == SpellTarget::Creature
+                    && target_position.is_some_and(|position| {
+                        self.players
+                            .values()
+                            .any(|player| player.id != caster_id && player.position == position)
+                    });
+                if aimed_at_player {
                     return Err("spell cast failed: pvp disabled".to_string());
                 }
+                // Area spells still hit monsters but pass over other players.
+                player_targets.retain(|target_id| *target_id == caster_id);
             }
         }
 
@@ -10316,6 +13770,14 @@ impl WorldState {
                             );
                             applied
                         };
+                        if applied_damage > 0 && *target_id != caster_id {
+                            self.record_player_damage(
+                                *target_id,
+                                DamageSource::Player(caster_id),
+                                effect.damage_type,
+                                applied_damage,
+                            );
+                        }
                         hits.push(SpellHit {
                             target: SpellTargetId::Player(*target_id),
                             attempted_damage,
@@ -10332,7 +13794,7 @@ impl WorldState {
                             *monster_id,
                             effect.damage_type,
                             attempted_damage,
-                            Some(caster_id),
+                            DamageSource::Player(caster_id),
                         );
                         let after = self
                             .monsters
@@ -10359,6 +13821,7 @@ impl WorldState {
                             (applied_heal, position)
                         };
                         if applied_heal > 0 {
+                            self.add_healing_threat(caster_id, *target_id, applied_heal);
                             text_effects.push(SpellTextEffect {
                                 position,
                                 color: HEAL_TEXT_COLOR,
@@ -10452,7 +13915,7 @@ impl WorldState {
                 );
                 self.schedule_cron_for_item_tree(&stack);
                 let movable = self.item_is_movable(&stack);
-                let mut placed_any = false;
+                let mut placed = Vec::new();
                 for position in &target_positions {
                     if self.is_protection_zone(*position) {
                         continue;
@@ -10466,11 +13929,12 @@ impl WorldState {
                     if place_on_tile_with_dustbin(tile, stack.clone(), stackable, movable)
                         .is_ok()
                     {
-                        placed_any = true;
+                        placed.push(*position);
                     }
                 }
-                if placed_any {
+                if !placed.is_empty() {
                     refresh_map = true;
+                    self.note_field_owner(stack.id, DamageSource::Player(caster_id), placed);
                 }
             }
         }
@@ -10610,14 +14074,8 @@ impl WorldState {
 
         if offensive && !hits.is_empty() {
             let fight_timer = self.combat_rules.fight_timer;
-            let skull_timer = self.combat_rules.white_skull_timer;
             if let Some(caster) = self.players.get_mut(&caster_id) {
                 caster.mark_in_combat(clock, fight_timer);
-                if hits.iter().any(|hit| {
-                    matches!(hit.target, SpellTargetId::Player(target_id) if target_id != caster_id)
-                }) {
-                    caster.mark_white_skull(clock, skull_timer);
-                }
             }
             for hit in &hits {
                 if let SpellTargetId::Player(target_id) = hit.target {
@@ -10627,6 +14085,7 @@ impl WorldState {
                     if let Some(target) = self.players.get_mut(&target_id) {
                         target.mark_in_combat(clock, fight_timer);
                     }
+                    self.record_pvp_attack(caster_id, target_id, clock);
                 }
             }
         }
@@ -10774,12 +14233,22 @@ impl WorldState {
             let Some(monster_id) = target_monster_id else {
                 return Err("spell cast failed: target creature missing".to_string());
             };
+            let mut home_id = None;
             if let Some(monster) = self.monsters.get_mut(&monster_id) {
                 monster.summoner = Some(caster_id);
                 monster.target = None;
-                monster.damage_by.clear();
+                monster.damage_ledger.clear();
                 monster.talk_lines.clear();
+                home_id = monster.home_id.take();
             }
+            // A convinced creature leaves its spawn for good, so the home
+            // may respawn it.
+            if let Some(home_id) = home_id {
+                self.notify_monster_home_death(home_id);
+            }
+            self.monster_threat.remove(&monster_id);
+            self.monster_leashing.remove(&monster_id);
+            self.creature_paths.remove(&monster_id);
         } else {
             let spawned = self.player_spawn_summons(caster_id, caster_position, race_number, count);
             if spawned > 0 {
@@ -10919,6 +14388,7 @@ impl WorldState {
         type_id: ItemTypeId,
         count: u16,
     ) -> Result<InventorySlot, String> {
+        self.ensure_corpse_take_allowed(id, position, type_id, None)?;
         self.ensure_player_in_range(id, position)?;
         self.ensure_house_item_access(id, position)?;
         let stackable = self
@@ -10955,6 +14425,7 @@ impl WorldState {
         count: u16,
         slot: InventorySlot,
     ) -> Result<Vec<ContainerUpdate>, String> {
+        self.ensure_corpse_take_allowed(id, position, type_id, None)?;
         self.ensure_player_in_range(id, position)?;
         self.ensure_house_item_access(id, position)?;
         let stackable = self
@@ -11288,6 +14759,7 @@ impl WorldState {
         type_id: ItemTypeId,
         count: u16,
     ) -> Result<(), String> {
+        self.ensure_corpse_take_allowed(id, from, type_id, None)?;
         self.ensure_player_in_range(id, from)?;
         self.ensure_player_can_throw_to(id, from, to)?;
         self.ensure_house_item_access(id, from)?;
@@ -11337,6 +14809,7 @@ impl WorldState {
         item_type: ItemTypeId,
         to_slot: InventorySlot,
     ) -> Result<Vec<ContainerUpdate>, String> {
+        self.ensure_container_loot_access(id, container_id)?;
         let stackable = self.stackable_for(item_type);
         let use_container_slot = self.slot_container_capacity(id, to_slot).is_some();
         if !use_container_slot {
@@ -11500,6 +14973,7 @@ impl WorldState {
         item_type: ItemTypeId,
         position: Position,
     ) -> Result<Vec<ContainerUpdate>, String> {
+        self.ensure_container_loot_access(id, container_id)?;
         let origin = self
             .players
             .get(&id)
@@ -11555,6 +15029,7 @@ impl WorldState {
         container_id: u8,
         to_slot: u8,
     ) -> Result<Vec<ContainerUpdate>, String> {
+        self.ensure_corpse_take_allowed(id, position, item_type, Some(from_stack))?;
         self.ensure_player_in_range(id, position)?;
         self.ensure_house_item_access(id, position)?;
         let mut map_item_snapshot = None;
@@ -11665,6 +15140,7 @@ impl WorldState {
         to_container: u8,
         to_slot: u8,
     ) -> Result<Vec<ContainerUpdate>, String> {
+        self.ensure_container_loot_access(id, from_container)?;
         if from_container == to_container {
             return self.move_container_item_within_container(
                 id,
@@ -11793,6 +15269,7 @@ impl WorldState {
         item_type: ItemTypeId,
         to_slot: u8,
     ) -> Result<Vec<ContainerUpdate>, String> {
+        self.ensure_container_loot_access(id, container_id)?;
         let stackable = self.stackable_for(item_type);
         let moved_item = {
             let player = self
@@ -12135,7 +15612,7 @@ impl WorldState {
         id: PlayerId,
         position: Position,
         type_id: ItemTypeId,
-    ) -> Result<MoveUseOutcome, String> {
+    ) -> Result<MoveUseOutcome, UseObjectError> {
         self.use_object_with_clock(id, position, type_id, None)
     }
 
@@ -12145,7 +15622,7 @@ impl WorldState {
         position: Position,
         type_id: ItemTypeId,
         clock: Option<&GameClock>,
-    ) -> Result<MoveUseOutcome, String> {
+    ) -> Result<MoveUseOutcome, UseObjectError> {
         let player = self
             .players
             .get(&id)
@@ -12162,11 +15639,11 @@ impl WorldState {
             && dx.unsigned_abs() <= 1
             && dy.unsigned_abs() <= 1;
         if !in_range && !player.is_test_god {
-            return Err("object is out of reach".to_string());
+            return Err(UseObjectError::OutOfReach);
         }
 
         if !tile.items.iter().any(|item| item.type_id == type_id) {
-            return Err("object not found on tile".to_string());
+            return Err(UseObjectError::Failed("object not found on tile".to_string()));
         }
 
         let ctx = MoveUseContext {
@@ -12179,6 +15656,27 @@ impl WorldState {
             object2_position: None,
             object2_type_id: None,
         };
+        let item = ScriptItem {
+            type_id: type_id.0,
+            position,
+        };
+        if self.run_script_hook(id, ScriptHook::Use(type_id.0), Some(item)) {
+            return Ok(MoveUseOutcome {
+                matched_rule: None,
+                ignored_actions: Vec::new(),
+                effects: Vec::new(),
+                texts: Vec::new(),
+                edit_texts: Vec::new(),
+                edit_lists: Vec::new(),
+                messages: Vec::new(),
+                damages: Vec::new(),
+                quest_updates: Vec::new(),
+                logout_users: Vec::new(),
+                refresh_positions: Vec::new(),
+                inventory_updates: Vec::new(),
+                container_updates: Vec::new(),
+            });
+        }
         let mut outcome = self.run_moveuse_event(ctx, clock)?;
         if outcome.matched_rule.is_none() {
             if let Some(edit) = self.try_open_list_edit(id, position, type_id) {
@@ -12207,6 +15705,9 @@ impl WorldState {
         item_type: ItemTypeId,
         clock: &GameClock,
     ) -> Result<Option<MoveUseOutcome>, String> {
+        if let UseObjectSource::Container { container_id, .. } = source {
+            self.ensure_container_loot_access(id, container_id)?;
+        }
         let Some(object_types) = self.object_types.as_ref() else {
             return Ok(None);
         };
@@ -12421,7 +15922,7 @@ impl WorldState {
             && dx.unsigned_abs() <= 1
             && dy.unsigned_abs() <= 1;
         if !in_range {
-            return Err("object is out of reach".to_string());
+            return Err(OBJECT_OUT_OF_REACH.to_string());
         }
         Ok(())
     }
@@ -12632,7 +16133,7 @@ impl WorldState {
             return Ok(());
         }
         if !crate::net::game::position_in_viewport(player.position, position) {
-            return Err("object is out of reach".to_string());
+            return Err(OBJECT_OUT_OF_REACH.to_string());
         }
         if !self.throw_possible(origin, position, 1) {
             return Err("cannot throw there".to_string());
@@ -12649,6 +16150,9 @@ impl WorldState {
         target_type: ItemTypeId,
         clock: Option<&GameClock>,
     ) -> Result<MoveUseOutcome, String> {
+        if let UseObjectSource::Container { container_id, .. } = object_source {
+            self.ensure_container_loot_access(id, container_id)?;
+        }
         let player = self
             .players
             .get(&id)
@@ -12743,7 +16247,7 @@ impl WorldState {
             && dx.unsigned_abs() <= 1
             && dy.unsigned_abs() <= 1;
         if !in_range && !player.is_test_god {
-            return Err("object is out of reach".to_string());
+            return Err(OBJECT_OUT_OF_REACH.to_string());
         }
 
         let index = usize::from(stack_pos);
@@ -13533,6 +17037,10 @@ impl WorldState {
             );
             self.queue_moveuse_outcomes(player_id, separation);
             self.queue_moveuse_outcomes(player_id, collision);
+            self.publish_event(WorldEvent::StepOn {
+                player_id,
+                position: destination,
+            });
             return Ok(true);
         }
 
@@ -13605,6 +17113,207 @@ impl WorldState {
         })
     }
 
+    fn tile_blocks_projectile(&self, tile: &Tile) -> bool {
+        let Some(object_types) = self.object_types.as_ref() else {
+            return false;
+        };
+        tile.items.iter().any(|item| {
+            object_types
+                .get(item.type_id)
+                .is_some_and(|object_type| object_type.blocks_projectile())
+        })
+    }
+
+    /// Whether missiles and spells can reach `to` from `from` without
+    /// passing a tile that blocks projectiles.
+    pub fn has_line_of_sight(&self, from: Position, to: Position) -> bool {
+        has_line_of_sight(from, to, |position| {
+            self.map
+                .tile(position)
+                .is_some_and(|tile| self.tile_blocks_projectile(tile))
+        })
+    }
+
+    /// Cost of walking onto `position` in a path search, from its ground
+    /// speed, or `None` when the tile is missing, blocked, changes floor or
+    /// holds a creature.
+    fn path_step_cost(
+        &self,
+        position: Position,
+        occupied: &HashSet<Position>,
+        walker: PathWalker,
+    ) -> Option<u32> {
+        if occupied.contains(&position) || !self.position_in_bounds(position) {
+            return None;
+        }
+        let Some(tile) = self.map.tile(position) else {
+            return self
+                .map
+                .tiles
+                .is_empty()
+                .then_some(u32::from(DEFAULT_GROUND_SPEED));
+        };
+        if self.tile_blocks_movement(tile)
+            || self.tile_floor_change(tile).is_some()
+            || self.tile_moveuse_floor_change(tile)
+        {
+            return None;
+        }
+        if walker == PathWalker::Monster
+            && (self.is_protection_zone(position) || self.tile_has_avoid_unmove(tile))
+        {
+            return None;
+        }
+        Some(u32::from(self.tile_ground_speed(tile)))
+    }
+
+    /// Plans a walk from `from` to `goal`, or to any free tile next to it
+    /// when `adjacent` is set, around walls and other creatures.
+    fn find_creature_path(
+        &self,
+        from: Position,
+        goal: Position,
+        adjacent: bool,
+        walker: PathWalker,
+        max_nodes: usize,
+        max_radius: u16,
+    ) -> PathSearch {
+        let in_reach = |position: Position| {
+            position.z == from.z
+                && position.x.abs_diff(from.x) <= max_radius.saturating_add(1)
+                && position.y.abs_diff(from.y) <= max_radius.saturating_add(1)
+        };
+        let occupied: HashSet<Position> = self
+            .players
+            .values()
+            .map(|player| player.position)
+            .chain(self.monsters.values().map(|monster| monster.position))
+            .chain(self.npcs.values().map(|npc| npc.position))
+            .filter(|position| *position != from && in_reach(*position))
+            .collect();
+        let limits = PathLimits {
+            max_nodes,
+            max_radius,
+            min_step_cost: PATH_MIN_STEP_COST,
+        };
+        let is_goal = |position: Position| {
+            if adjacent {
+                position != goal && Self::monster_tile_distance(position, goal) <= 1
+            } else {
+                position == goal
+            }
+        };
+        find_path(from, goal, limits, is_goal, |position| {
+            self.path_step_cost(position, &occupied, walker)
+        })
+    }
+
+    /// Next step along a creature's cached path to `goal`, planning a new
+    /// path when the goal moved or the cache ran out. `budget` caps the
+    /// search and is charged for the tiles it expanded.
+    fn creature_path_step(
+        &mut self,
+        creature_id: CreatureId,
+        from: Position,
+        goal: Position,
+        adjacent: bool,
+        walker: PathWalker,
+        budget: &mut usize,
+    ) -> Option<Direction> {
+        if let Some(cached) = self.creature_paths.get_mut(&creature_id) {
+            if cached.goal == goal {
+                if let Some(direction) = cached.steps.pop_front() {
+                    return Some(direction);
+                }
+            }
+        }
+        self.creature_paths.remove(&creature_id);
+        if *budget == 0 {
+            return None;
+        }
+        let (max_nodes, max_radius) = match walker {
+            PathWalker::Monster => (MONSTER_PATH_MAX_NODES, MONSTER_PATH_RADIUS),
+            PathWalker::Npc => (NPC_PATH_MAX_NODES, NPC_PATH_RADIUS),
+            PathWalker::Player => (PLAYER_PATH_MAX_NODES, PLAYER_PATH_RADIUS),
+        };
+        let search = self.find_creature_path(
+            from,
+            goal,
+            adjacent,
+            walker,
+            max_nodes.min(*budget),
+            max_radius,
+        );
+        *budget = budget.saturating_sub(search.expanded);
+        let mut steps: VecDeque<Direction> = search.steps?.into();
+        let first = steps.pop_front()?;
+        if !steps.is_empty() {
+            self.creature_paths
+                .insert(creature_id, CachedPath { goal, steps });
+        }
+        Some(first)
+    }
+
+    /// Server-side "walk to" for map clicks: plans a path to `destination`,
+    /// or next to it when `adjacent` is set, and hands it to auto-walk.
+    pub fn walk_player_to(
+        &mut self,
+        id: PlayerId,
+        destination: Position,
+        adjacent: bool,
+    ) -> Result<usize, String> {
+        let from = self
+            .players
+            .get(&id)
+            .ok_or_else(|| format!("unknown player {:?}", id))?
+            .position;
+        let search = self.find_creature_path(
+            from,
+            destination,
+            adjacent,
+            PathWalker::Player,
+            PLAYER_PATH_MAX_NODES,
+            PLAYER_PATH_RADIUS,
+        );
+        let Some(steps) = search.steps else {
+            self.queue_player_message(id, MESSAGE_STATUS_SMALL, PLAYER_NO_WAY_MESSAGE.to_string());
+            return Err(PLAYER_NO_WAY_MESSAGE.to_string());
+        };
+        let count = steps.len();
+        self.set_player_autowalk(id, steps);
+        Ok(count)
+    }
+
+    /// Walks the player next to an out-of-reach map object and uses it on
+    /// arrival, like a client double-click from afar.
+    pub fn walk_player_to_use(
+        &mut self,
+        id: PlayerId,
+        position: Position,
+        type_id: ItemTypeId,
+    ) -> Result<usize, String> {
+        let count = self.walk_player_to(id, position, true)?;
+        self.walk_to_uses.insert(id, (position, type_id));
+        Ok(count)
+    }
+
+    /// Sends an NPC walking to `target`, or back to wandering with `None`.
+    pub fn set_npc_walk_target(
+        &mut self,
+        id: CreatureId,
+        target: Option<Position>,
+    ) -> Result<(), String> {
+        if !self.npcs.contains_key(&id) {
+            return Err(format!("unknown npc {:?}", id));
+        }
+        self.creature_paths.remove(&id);
+        match target {
+            Some(target) => self.npc_walk_targets.insert(id, target),
+            None => self.npc_walk_targets.remove(&id),
+        };
+        Ok(())
+    }
+
     fn tile_has_avoid_unmove(&self, tile: &Tile) -> bool {
         let Some(object_types) = self.object_types.as_ref() else {
             return false;
@@ -13827,6 +17536,7 @@ impl WorldState {
     }
 
     fn apply_moveuse_outcome_damage(&mut self, outcome: &mut MoveUseOutcome) -> Result<(), String> {
+        let mut pending = Vec::new();
         for damage in &mut outcome.damages {
             if let MoveUseActor::User(player_id) = damage.target {
                 let reduced_damage = self.apply_player_protection_reduction(
@@ -13841,8 +17551,22 @@ impl WorldState {
                 let (applied, _) =
                     player.apply_damage_with_magic_shield(damage.damage_type, reduced_damage);
                 damage.amount = applied;
+                pending.push((player_id, damage.source, damage.damage_type, applied));
             }
         }
+        for (player_id, actor, damage_type, applied) in pending {
+            let source = match actor {
+                MoveUseActor::User(source_id) if source_id != player_id => {
+                    DamageSource::Player(source_id)
+                }
+                MoveUseActor::Object(position) => self
+                    .field_owner_at(position)
+                    .filter(|owner| *owner != DamageSource::Player(player_id))
+                    .unwrap_or(DamageSource::Environment),
+                _ => DamageSource::Environment,
+            };
+            self.record_player_damage(player_id, source, damage_type, applied);
+        }
         Ok(())
     }
 
@@ -15525,6 +19249,59 @@ impl WorldState {
         true
     }
 
+    /// Refuses taking a corpse off `position` while it is still reserved for
+    /// someone else's side. Only the item being moved is checked: the one at
+    /// `stack_pos`, or the first of `type_id` like `take_from_tile` picks.
+    fn ensure_corpse_take_allowed(
+        &mut self,
+        player_id: PlayerId,
+        position: Position,
+        type_id: ItemTypeId,
+        stack_pos: Option<u8>,
+    ) -> Result<(), String> {
+        let item_id = self.map.tile(position).and_then(|tile| {
+            let item = match stack_pos {
+                Some(stack_pos) => tile.items.get(usize::from(stack_pos)),
+                None => tile.items.iter().find(|item| item.type_id == type_id),
+            }?;
+            (item.type_id == type_id).then_some(item.id)
+        });
+        let protected =
+            item_id.is_some_and(|item_id| !self.corpse_open_allowed(player_id, item_id));
+        if protected {
+            return self.refuse_loot(player_id);
+        }
+        Ok(())
+    }
+
+    /// Refuses moving or using anything inside an open container that lies
+    /// in a corpse still reserved for someone else's side.
+    fn ensure_container_loot_access(&mut self, player_id: PlayerId, container_id: u8) -> Result<(), String> {
+        let corpse_id = self
+            .players
+            .get(&player_id)
+            .and_then(|player| container_root_map_source(player, container_id))
+            .and_then(|(position, stack_pos)| {
+                let tile = self.map.tile(position)?;
+                tile.items.get(stack_pos as usize).map(|item| item.id)
+            });
+        match corpse_id {
+            Some(corpse_id) if !self.corpse_open_allowed(player_id, corpse_id) => {
+                self.refuse_loot(player_id)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn refuse_loot(&mut self, player_id: PlayerId) -> Result<(), String> {
+        self.queue_player_message(
+            player_id,
+            MESSAGE_STATUS_SMALL,
+            LOOT_NOT_OWNER_MESSAGE.to_string(),
+        );
+        Err(LOOT_NOT_OWNER_MESSAGE.to_string())
+    }
+
     fn ensure_house_item_access(&self, player_id: PlayerId, position: Position) -> Result<(), String> {
         let Some(house) = self.house_for_position(position) else {
             return Ok(());
@@ -15567,7 +19344,7 @@ impl WorldState {
 
     fn send_mail_from_tile(
         &mut self,
-        _user_id: PlayerId,
+        user_id: PlayerId,
         mailbox_position: Position,
     ) -> Result<usize, String> {
         let tile = self
@@ -15603,13 +19380,27 @@ impl WorldState {
         let mut delivered_indices = Vec::new();
         let mut delivered_count = 0usize;
         let mut notified = HashSet::new();
+        let sender = self.players.get(&user_id).map(|player| player.name.clone());
         for (idx, item, address) in candidates {
             let Some(address) = address else {
                 continue;
             };
             let mut stamped = item;
             stamped.type_id = Self::stamped_mail_type(stamped.type_id);
-            match self.deliver_mail_item(&address, stamped) {
+            let audited = stamped.clone();
+            let delivery = self.deliver_mail_item(&address, stamped);
+            if delivery != MailDelivery::Failed {
+                let recipient = Some(address.name.clone());
+                record_item_transfer(
+                    TransferKind::Mail,
+                    &sender,
+                    &recipient,
+                    &audited,
+                    audited.count,
+                    None,
+                );
+            }
+            match delivery {
                 MailDelivery::DeliveredOnline(recipient_id) => {
                     delivered_indices.push(idx);
                     delivered_count = delivered_count.saturating_add(1);
@@ -16057,6 +19848,10 @@ impl WorldState {
             .get_mut(&id)
             .ok_or_else(|| format!("unknown player {:?}", id))?;
         player.move_to(position, player.direction);
+        self.publish_event(WorldEvent::StepOn {
+            player_id: id,
+            position,
+        });
         Ok(())
     }
 
@@ -16072,32 +19867,24 @@ impl WorldState {
         CreatureId(id)
     }
 
-    fn ensure_raid_schedules(&mut self, now: GameTick, clock: &GameClock) {
-        if !self.raid_schedules.is_empty() {
+    fn ensure_raid_schedules(&mut self, unix_now: u64) {
+        if self.raid_schedules_built {
             return;
         }
         let Some(index) = self.monster_index.as_ref() else {
             return;
         };
+        self.raid_schedules_built = true;
+        let offset = self.raid_calendar.utc_offset_minutes;
         for (name, raid) in &index.raids {
-            let Some(interval) = raid.interval else {
-                continue;
-            };
-            if interval <= 0 {
-                continue;
-            }
-            let ticks = clock.ticks_from_duration_round_up(Duration::from_secs(interval as u64));
-            if ticks == 0 {
+            let rules = self.raid_calendar.rules_for(name, raid.interval);
+            if rules.is_empty() {
                 continue;
             }
-            let next_at = GameTick(now.0.saturating_add(ticks));
-            self.raid_schedules.insert(
-                name.clone(),
-                RaidSchedule {
-                    interval_ticks: ticks,
-                    next_at,
-                },
-            );
+            let last_run = self.raid_history.last_run(name);
+            let next_at = next_raid_start(name, &rules, unix_now, last_run, offset);
+            self.raid_schedules
+                .insert(name.clone(), RaidSchedule { rules, next_at });
         }
     }
 }
@@ -16123,6 +19910,35 @@ fn index_item_tree(
     }
 }
 
+fn report_unknown_calendar_raids(calendar: &RaidCalendarConfig, index: &MonsterIndex) {
+    for raid in calendar.unknown_raids(|raid| index.raids.contains_key(raid)) {
+        eprintln!("tibia: raids.yaml names unknown raid {}", raid);
+    }
+}
+
+/// Writes an audit record for `item` and one for every item nested in it, so
+/// a search by item id also finds things moved inside a container.
+fn record_item_transfer(
+    kind: TransferKind,
+    from: &Option<String>,
+    to: &Option<String>,
+    item: &ItemStack,
+    count: u16,
+    position: Option<[u16; 3]>,
+) {
+    audit::record(AuditEvent::ItemTransfer {
+        kind,
+        from: from.clone(),
+        to: to.clone(),
+        item_type: item.type_id.0,
+        count,
+        position,
+    });
+    for nested in &item.contents {
+        record_item_transfer(kind, from, to, nested, nested.count, position);
+    }
+}
+
 fn raid_seed(name: &str, now: GameTick) -> u64 {
     let mut hash = 0u64;
     for byte in name.as_bytes() {
@@ -16131,6 +19947,36 @@ fn raid_seed(name: &str, now: GameTick) -> u64 {
     hash ^ now.0
 }
 
+/// `value` raised by `percent` percent.
+fn scale_by_percent(value: u32, percent: u16) -> u32 {
+    let scaled = u64::from(value) * (100 + u64::from(percent)) / 100;
+    scaled.min(u64::from(u32::MAX)) as u32
+}
+
+/// Earliest start over a raid's rules, with the delay into each rule's
+/// window drawn from the raid name and current time.
+fn next_raid_start(
+    name: &str,
+    rules: &[RaidRule],
+    unix_now: u64,
+    last_run: Option<u64>,
+    utc_offset_minutes: i32,
+) -> u64 {
+    let mut seed = raid_seed(name, GameTick(unix_now));
+    rules
+        .iter()
+        .map(|rule| {
+            rule.next_start(unix_now, last_run, utc_offset_minutes, |window| {
+                seed = seed
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                (seed >> 33) % window.saturating_add(1)
+            })
+        })
+        .min()
+        .unwrap_or(u64::MAX)
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MoveUseOutcome {
     pub matched_rule: Option<usize>,
@@ -16509,6 +20355,64 @@ fn matches_moveuse_rule(
     Ok(true)
 }
 
+/// Conditions `evaluate_moveuse_condition` understands; anything else never
+/// matches at runtime.
+const MOVEUSE_CONDITIONS: &[&str] = &[
+    "IsType",
+    "IsPosition",
+    "IsObjectThere",
+    "IsProtectionZone",
+    "Random",
+    "IsHouse",
+    "IsHouseOwner",
+    "IsPlayer",
+    "IsCreature",
+    "IsPlayerThere",
+    "IsObjectInInventory",
+    "CountObjects",
+    "CountObjectsOnMap",
+    "HasInstanceAttribute",
+    "HasFlag",
+    "IsDressed",
+    "IsPeaceful",
+    "HasQuestValue",
+    "HasLevel",
+    "HasProfession",
+    "TestSkill",
+    "HasRight",
+    "MayLogout",
+];
+
+/// Checks a parsed `moveuse.dat` for rules that would silently never fire:
+/// unknown events, unknown conditions and `IsType` checks without a valid
+/// item type.
+fn validate_moveuse(moveuse: &MoveUseDatabase) -> Vec<String> {
+    let mut errors = Vec::new();
+    for rule in collect_moveuse_rules(moveuse) {
+        if parse_moveuse_event_name(&rule.event.name).is_none() {
+            errors.push(format!(
+                "line {}: unknown event {}",
+                rule.line_no,
+                rule.event.name.trim()
+            ));
+        }
+        for condition in &rule.conditions {
+            let name = condition.name.trim();
+            let name = name.strip_prefix('!').unwrap_or(name);
+            if !MOVEUSE_CONDITIONS.contains(&name) {
+                errors.push(format!("line {}: unknown condition {}", rule.line_no, name));
+            } else if name == "IsType" {
+                let valid = condition.args.len() >= 2
+                    && parse_item_type_id(&condition.args[1]).is_ok();
+                if !valid {
+                    errors.push(format!("line {}: IsType expects an item type", rule.line_no));
+                }
+            }
+        }
+    }
+    errors
+}
+
 fn evaluate_moveuse_condition(
     condition: &MoveUseExpr,
     ctx: &MoveUseContext,
@@ -16963,7 +20867,7 @@ fn parse_item_type_id(raw: &str) -> Result<ItemTypeId, String> {
         .map_err(|_| format!("invalid item type id '{}'", raw))
 }
 
-fn parse_position_arg(raw: &str) -> Result<Position, String> {
+pub(crate) fn parse_position_arg(raw: &str) -> Result<Position, String> {
     let raw = raw.trim();
     if !raw.starts_with('[') || !raw.ends_with(']') {
         return Err(format!("invalid position '{}'", raw));
@@ -18556,6 +22460,7 @@ enum NpcPlannedAction {
     CreateMoney(u32),
     DeleteMoney(u32),
     Teleport(Position),
+    WalkTo(Position),
     EffectOpp(u16),
     EffectMe(u16),
     QueuePlayer,
@@ -18925,6 +22830,20 @@ fn npc_rule_action_plan(
                     plan.actions.push(NpcPlannedAction::Teleport(Position { x, y, z }));
                 }
             }
+            NpcAction::Call { name, args } if name.eq_ignore_ascii_case("Walk") => {
+                if args.len() < 3 {
+                    continue;
+                }
+                let x = npc_eval_expr(&args[0], ctx, vars)
+                    .and_then(|value| u16::try_from(value).ok());
+                let y = npc_eval_expr(&args[1], ctx, vars)
+                    .and_then(|value| u16::try_from(value).ok());
+                let z = npc_eval_expr(&args[2], ctx, vars)
+                    .and_then(|value| u8::try_from(value).ok());
+                if let (Some(x), Some(y), Some(z)) = (x, y, z) {
+                    plan.actions.push(NpcPlannedAction::WalkTo(Position { x, y, z }));
+                }
+            }
             NpcAction::Call { name, args } if name.eq_ignore_ascii_case("EffectOpp") => {
                 if let Some(effect) = args.get(0).and_then(|value| npc_eval_expr(value, ctx, vars)) {
                     if let Ok(effect) = u16::try_from(effect) {
@@ -19495,6 +23414,20 @@ fn container_root_position(player: &PlayerState, container_id: u8) -> Option<Pos
     }
 }
 
+/// The map position and stack position of the item the container chain
+/// starting at `container_id` was opened from, if it lies on the map.
+fn container_root_map_source(player: &PlayerState, container_id: u8) -> Option<(Position, u8)> {
+    let mut current_id = container_id;
+    for _ in 0..=32 {
+        let container = player.open_containers.get(&current_id)?;
+        if let Some(position) = container.source_position {
+            return Some((position, container.source_stack_pos?));
+        }
+        current_id = container.parent_container_id?;
+    }
+    None
+}
+
 fn container_active_depot(player: &PlayerState, container_id: u8) -> Option<ActiveDepot> {
     let active = player.active_depot?;
     let root = container_root_position(player, container_id)?;
@@ -19611,24 +23544,6 @@ fn ensure_capacity_for_weight(
     Ok(())
 }
 
-fn money_value_for_type(
-    object_types: &ObjectTypeIndex,
-    type_id: ItemTypeId,
-) -> Option<u32> {
-    let object = object_types.get(type_id)?;
-    money_value_from_object(object)
-}
-
-fn money_value_from_object(object: &ObjectType) -> Option<u32> {
-    let meaning = object.attribute_u16("Meaning")?;
-    match meaning {
-        1 => Some(1),
-        2 => Some(100),
-        3 => Some(10_000),
-        _ => None,
-    }
-}
-
 fn calculate_money_change(
     amount: i32,
     gold: i32,
@@ -19870,6 +23785,9 @@ fn apply_npc_plans(
                 NpcPlannedAction::Teleport(position) => {
                     let _ = world.teleport_player(player_id, position);
                 }
+                NpcPlannedAction::WalkTo(position) => {
+                    let _ = world.set_npc_walk_target(plan.npc_id, Some(position));
+                }
                 NpcPlannedAction::EffectOpp(effect_id) => {
                     if let Some(player) = world.players.get(&player_id) {
                         effects.push(MoveUseEffect {
@@ -20067,103 +23985,236 @@ mod tests {
     use std::time::Duration;
 
     fn test_world() -> WorldState {
-        WorldState {
-            root: None,
-            map: Map {
-                name: "test".to_string(),
-                sector_bounds: None,
-                sectors: Vec::new(),
-                tiles: HashMap::new(),
-            },
-            map_original: None,
-            map_dat: None,
-            mem_dat: None,
-            circles: None,
-            npc_index: None,
-            monster_index: None,
-            monster_homes: Vec::new(),
-            npcs: HashMap::new(),
-            monsters: HashMap::new(),
-            raid_events: Vec::new(),
-            raid_schedules: HashMap::new(),
-            house_areas: None,
-            houses: None,
-            house_owners: None,
-            house_position_index: None,
-            next_house_rent_check: None,
-            moveuse: None,
-            object_types: None,
-            item_types: None,
-            cron: crate::world::cron::CronSystem::new(),
-            players: HashMap::new(),
-            offline_players: HashMap::new(),
-            spellbook: SpellBook::default(),
-            combat_rules: CombatRules::default(),
-            pending_messages: Vec::new(),
-            pending_skill_updates: Vec::new(),
-            pending_data_updates: Vec::new(),
-            pending_turn_updates: Vec::new(),
-            pending_outfit_updates: Vec::new(),
-            pending_map_refreshes: Vec::new(),
-            pending_buddy_updates: Vec::new(),
-            pending_party_updates: Vec::new(),
-            pending_trade_updates: Vec::new(),
-            pending_container_closes: HashMap::new(),
-            pending_container_refresh: HashSet::new(),
-            shop_sessions: HashMap::new(),
-            request_queue: Vec::new(),
-            request_queue_players: HashSet::new(),
-            private_channels: HashMap::new(),
-            private_channel_owners: HashMap::new(),
-            next_private_channel_id: PRIVATE_CHANNEL_ID_START,
-            parties: HashMap::new(),
-            next_party_id: 1,
-            pending_moveuse_outcomes: HashMap::new(),
-            trade_sessions: HashMap::new(),
-            trade_by_player: HashMap::new(),
-            next_trade_id: 1,
-            next_text_edit_id: 1,
-            text_edit_sessions: HashMap::new(),
-            next_list_edit_id: 1,
-            list_edit_sessions: HashMap::new(),
-            moveuse_rng: MoveUseRng::default(),
-            loot_rng: LootRng::default(),
-            monster_rng: MonsterRng::default(),
-            npc_rng: NpcRng::default(),
-            next_npc_id: NPC_ID_BASE,
-            next_monster_id: 1,
-            refresh_state: None,
-            skill_tick_last: None,
-            monster_home_tick_last: None,
-            cron_tick_last: None,
-            cron_tick_accum: 0,
-            cron_round: 0,
-            item_index: HashMap::new(),
-            item_index_dirty: true,
+        WorldState::empty()
+    }
+
+fn make_tile(position: Position, protection_zone: bool) -> Tile {
+        Tile {
+            position,
+            items: Vec::new(),
+            item_details: Vec::new(),
+            refresh: false,
+            protection_zone,
+            no_logout: false,
+            annotations: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    fn make_spell(target: SpellTarget, effect: SpellEffect) -> Spell {
+        Spell {
+            id: crate::entities::spells::SpellId(1),
+            name: "Test Spell".to_string(),
+            words: "test".to_string(),
+            kind: SpellKind::Instant,
+            rune_type_id: None,
+            target,
+            group: None,
+            mana_cost: 0,
+            soul_cost: 0,
+            level_required: 1,
+            magic_level_required: 0,
+            cooldown_ms: 0,
+            group_cooldown_ms: 0,
+            damage_scale_flags: crate::combat::damage::DamageScaleFlags::NONE,
+            effect: Some(effect),
+            summon: None,
+            haste: None,
+            light: None,
+            dispel: None,
+            field: None,
+            magic_shield: None,
+            outfit: None,
+            challenge: None,
+            levitate: None,
+            raise_dead: None,
+            conjure: None,
+            antidote: None,
+            magic_rope: None,
+            find_person: None,
+            enchant_staff: None,
+        }
+    }
+
+    #[test]
+    fn spell_words_skip_the_spam_throttle_and_npc_dialog_gets_a_looser_one() {
+        let mut world = test_world();
+        let player_id = PlayerId(1);
+        let origin = Position { x: 200, y: 200, z: 7 };
+        world
+            .players
+            .insert(player_id, PlayerState::new(player_id, "Talker".to_string(), origin));
+        let effect = SpellEffect {
+            shape: SpellShape::Area { radius: 0 },
+            kind: crate::entities::spells::SpellEffectKind::Healing,
+            damage_type: DamageType::Physical,
+            min_damage: 1,
+            max_damage: 1,
+            include_caster: true,
+            base_damage: None,
+            variance: None,
+        };
+        let spell = make_spell(SpellTarget::SelfOnly, effect);
+        let spell_id = spell.id;
+        world.add_spell(spell).expect("spell");
+        world.word_filter = WordFilter::from_words(["test", "scam"]);
+        let clock = GameClock::new(Duration::from_millis(100));
+
+        assert_eq!(world.talk_kind(player_id, "hi", true), TalkKind::Chat);
+        assert_eq!(world.talk_kind(player_id, "test", true), TalkKind::Chat);
+        world.teach_spell(player_id, spell_id).expect("teach");
+        assert_eq!(world.talk_kind(player_id, "test", true), TalkKind::Spell);
+        assert_eq!(world.talk_kind(player_id, "test", false), TalkKind::Chat);
+
+        for _ in 0..10 {
+            assert_eq!(
+                world
+                    .moderate_talk(player_id, "test", TalkKind::Spell, &clock)
+                    .expect("moderate"),
+                TalkModeration::Allowed("test".to_string())
+            );
+        }
+        assert_eq!(
+            world
+                .moderate_talk(player_id, "a scam offer", TalkKind::NpcDialog, &clock)
+                .expect("moderate"),
+            TalkModeration::Allowed("a **** offer".to_string())
+        );
+        for _ in 0..9 {
+            let outcome = world
+                .moderate_talk(player_id, "hi", TalkKind::NpcDialog, &clock)
+                .expect("moderate");
+            assert_eq!(outcome, TalkModeration::Allowed("hi".to_string()));
         }
+        let outcome = world
+            .moderate_talk(player_id, "hi", TalkKind::NpcDialog, &clock)
+            .expect("moderate");
+        assert!(matches!(outcome, TalkModeration::Blocked(_)));
+        let outcome = world
+            .moderate_talk(player_id, "test", TalkKind::Spell, &clock)
+            .expect("moderate");
+        assert!(matches!(outcome, TalkModeration::Blocked(_)));
+
+        let chatter_id = PlayerId(2);
+        world
+            .players
+            .insert(chatter_id, PlayerState::new(chatter_id, "Chatter".to_string(), origin));
+        for _ in 0..5 {
+            let outcome = world
+                .moderate_talk(chatter_id, "hi", TalkKind::Chat, &clock)
+                .expect("moderate");
+            assert_eq!(outcome, TalkModeration::Allowed("hi".to_string()));
+        }
+        let outcome = world
+            .moderate_talk(chatter_id, "hi", TalkKind::Chat, &clock)
+            .expect("moderate");
+        assert!(matches!(outcome, TalkModeration::Blocked(_)));
+    }
+
+    #[test]
+    fn cast_spell_words_requires_known_spell() {
+        let mut world = test_world();
+        let caster_id = PlayerId(1);
+        let caster_pos = Position { x: 200, y: 200, z: 7 };
+
+        world
+            .players
+            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+
+        let effect = SpellEffect {
+            shape: SpellShape::Area { radius: 0 },
+            kind: crate::entities::spells::SpellEffectKind::Damage,
+            damage_type: DamageType::Physical,
+            min_damage: 0,
+            max_damage: 0,
+            include_caster: true,
+            base_damage: None,
+            variance: None,
+            base_damage: None,
+            variance: None,
+        };
+        let spell = make_spell(SpellTarget::SelfOnly, effect);
+        world.add_spell(spell).expect("spell insert");
+
+        let clock = GameClock::new(Duration::from_millis(100));
+        let err = world
+            .cast_spell_words(caster_id, "test", None, None, &clock)
+            .expect_err("cast should require known spell");
+        assert_eq!(err, "spell cast failed: spell not known");
+    }
+
+    #[test]
+    fn cast_spell_words_casts_for_known_spell() {
+        let mut world = test_world();
+        let caster_id = PlayerId(1);
+        let caster_pos = Position { x: 210, y: 210, z: 7 };
+
+        world
+            .players
+            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+
+        let effect = SpellEffect {
+            shape: SpellShape::Area { radius: 0 },
+            kind: crate::entities::spells::SpellEffectKind::Damage,
+            damage_type: DamageType::Physical,
+            min_damage: 0,
+            max_damage: 0,
+            include_caster: true,
+            base_damage: None,
+            variance: None,
+            base_damage: None,
+            variance: None,
+        };
+        let spell = make_spell(SpellTarget::SelfOnly, effect);
+        let spell_id = spell.id;
+        world.add_spell(spell).expect("spell insert");
+        world
+            .teach_spell(caster_id, spell_id)
+            .expect("teach spell");
+
+        let clock = GameClock::new(Duration::from_millis(100));
+        let report = world
+            .cast_spell_words(caster_id, "test", None, None, &clock)
+            .expect("cast should succeed");
+        assert_eq!(report.hits.len(), 1);
+        assert_eq!(report.hits[0].target, caster_id);
     }
 
-fn make_tile(position: Position, protection_zone: bool) -> Tile {
-        Tile {
-            position,
-            items: Vec::new(),
-            item_details: Vec::new(),
-            refresh: false,
-            protection_zone,
-            no_logout: false,
-            annotations: Vec::new(),
-            tags: Vec::new(),
+    #[test]
+    fn cast_spell_words_resolves_named_target() {
+        let mut world = test_world();
+        let caster_id = PlayerId(1);
+        let target_id = PlayerId(2);
+        let caster_pos = Position { x: 210, y: 210, z: 7 };
+        let target_pos = Position { x: 211, y: 210, z: 7 };
+
+        world
+            .players
+            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+        world
+            .players
+            .insert(target_id, PlayerState::new(target_id, "Target".to_string(), target_pos));
+        if let Some(target) = world.players.get_mut(&target_id) {
+            target.stats.health = 50;
         }
-    }
 
-    fn make_spell(target: SpellTarget, effect: SpellEffect) -> Spell {
-        Spell {
-            id: crate::entities::spells::SpellId(1),
-            name: "Test Spell".to_string(),
-            words: "test".to_string(),
+        let effect = SpellEffect {
+            shape: SpellShape::Area { radius: 0 },
+            kind: crate::entities::spells::SpellEffectKind::Healing,
+            damage_type: DamageType::Holy,
+            min_damage: 10,
+            max_damage: 10,
+            include_caster: true,
+            base_damage: None,
+            variance: None,
+        };
+        let spell = Spell {
+            id: crate::entities::spells::SpellId(2),
+            name: "Heal Friend".to_string(),
+            words: "exura sio \"name\"".to_string(),
             kind: SpellKind::Instant,
             rune_type_id: None,
-            target,
+            target: SpellTarget::Creature,
             group: None,
             mana_cost: 0,
             soul_cost: 0,
@@ -20188,15 +24239,219 @@ fn make_tile(position: Position, protection_zone: bool) -> Tile {
             magic_rope: None,
             find_person: None,
             enchant_staff: None,
-        }
+        };
+        world.add_spell(spell).expect("spell insert");
+        world
+            .teach_spell(caster_id, crate::entities::spells::SpellId(2))
+            .expect("teach spell");
+
+        let clock = GameClock::new(Duration::from_millis(100));
+        let report = world
+            .cast_spell_words(caster_id, "exura sio Target", None, None, &clock)
+            .expect("cast should succeed");
+
+        assert_eq!(report.hits.len(), 1);
+        let target = world.players.get(&target_id).unwrap();
+        assert_eq!(target.stats.health, 60);
+    }
+
+    #[test]
+    fn cast_spell_applies_damage_to_target() {
+        let mut world = test_world();
+        let caster_id = PlayerId(1);
+        let target_id = PlayerId(2);
+        let caster_pos = Position { x: 100, y: 100, z: 7 };
+        let target_pos = Position { x: 101, y: 100, z: 7 };
+
+        world
+            .players
+            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+        world
+            .players
+            .insert(target_id, PlayerState::new(target_id, "Target".to_string(), target_pos));
+
+        let effect = SpellEffect {
+            shape: SpellShape::Area { radius: 0 },
+            kind: crate::entities::spells::SpellEffectKind::Damage,
+            damage_type: DamageType::Physical,
+            min_damage: 12,
+            max_damage: 12,
+            include_caster: false,
+            base_damage: None,
+            variance: None,
+        };
+        let spell = make_spell(SpellTarget::Position, effect);
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        let report = world
+            .cast_spell(caster_id, &spell, Some(target_pos), None, &clock)
+            .expect("cast should succeed");
+
+        assert_eq!(report.hits.len(), 1);
+        let target = world.players.get(&target_id).unwrap();
+        assert_eq!(target.stats.health, 88);
+        let caster = world.players.get(&caster_id).unwrap();
+        assert_eq!(caster.stats.health, 100);
+        clock.advance(1);
+    }
+
+    #[test]
+    fn offensive_spell_blocked_by_protection_zone() {
+        let mut world = test_world();
+        let caster_id = PlayerId(1);
+        let target_id = PlayerId(2);
+        let caster_pos = Position { x: 10, y: 10, z: 7 };
+        let target_pos = Position { x: 11, y: 10, z: 7 };
+
+        world.map.tiles.insert(caster_pos, make_tile(caster_pos, false));
+        world.map.tiles.insert(target_pos, make_tile(target_pos, true));
+
+        world
+            .players
+            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+        world
+            .players
+            .insert(target_id, PlayerState::new(target_id, "Target".to_string(), target_pos));
+
+        let effect = SpellEffect {
+            shape: SpellShape::Area { radius: 0 },
+            kind: crate::entities::spells::SpellEffectKind::Damage,
+            damage_type: DamageType::Physical,
+            min_damage: 5,
+            max_damage: 5,
+            include_caster: false,
+            base_damage: None,
+            variance: None,
+        };
+        let spell = make_spell(SpellTarget::Position, effect);
+        let clock = GameClock::new(Duration::from_millis(100));
+        let err = world
+            .cast_spell(caster_id, &spell, Some(target_pos), None, &clock)
+            .expect_err("cast should be blocked");
+        assert_eq!(err, "spell cast failed: protection zone");
+    }
+
+    #[test]
+    fn line_spell_requires_direction() {
+        let mut world = test_world();
+        let caster_id = PlayerId(1);
+        let caster_pos = Position { x: 50, y: 50, z: 7 };
+
+        world
+            .players
+            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+
+        let effect = SpellEffect {
+            shape: SpellShape::Line { length: 3 },
+            kind: crate::entities::spells::SpellEffectKind::Damage,
+            damage_type: DamageType::Energy,
+            min_damage: 3,
+            max_damage: 3,
+            include_caster: true,
+            base_damage: None,
+            variance: None,
+        };
+        let spell = make_spell(SpellTarget::SelfOnly, effect);
+        let clock = GameClock::new(Duration::from_millis(100));
+        let err = world
+            .cast_spell(caster_id, &spell, None, None, &clock)
+            .expect_err("line spell should require a direction");
+        assert_eq!(
+            err,
+            "spell cast failed: direction required for line spell"
+        );
+    }
+
+    #[test]
+    fn spell_cast_consumes_costs_and_respects_cooldown() {
+        let mut world = test_world();
+        let caster_id = PlayerId(1);
+        let caster_pos = Position { x: 300, y: 300, z: 7 };
+        let mut player = PlayerState::new(caster_id, "Caster".to_string(), caster_pos);
+        player.stats.mana = 50;
+        player.stats.soul = 5;
+        world.players.insert(caster_id, player);
+
+        let effect = SpellEffect {
+            shape: SpellShape::Area { radius: 0 },
+            kind: crate::entities::spells::SpellEffectKind::Damage,
+            damage_type: DamageType::Physical,
+            min_damage: 1,
+            max_damage: 1,
+            include_caster: true,
+            base_damage: None,
+            variance: None,
+        };
+        let mut spell = make_spell(SpellTarget::SelfOnly, effect);
+        spell.mana_cost = 10;
+        spell.soul_cost = 2;
+        spell.cooldown_ms = 1_000;
+
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        world
+            .cast_spell(caster_id, &spell, None, None, &clock)
+            .expect("first cast");
+
+        let player = world.players.get(&caster_id).expect("player");
+        assert_eq!(player.stats.mana, 40);
+        assert_eq!(player.stats.soul, 3);
+
+        let err = world
+            .cast_spell(caster_id, &spell, None, None, &clock)
+            .expect_err("cooldown should block");
+        assert_eq!(err, "spell cast failed: spell cooldown");
+
+        clock.advance(10);
+        world
+            .cast_spell(caster_id, &spell, None, None, &clock)
+            .expect("cooldown expired");
+        let player = world.players.get(&caster_id).expect("player");
+        assert_eq!(player.stats.mana, 30);
+        assert_eq!(player.stats.soul, 1);
+    }
+
+    #[test]
+    fn spell_cast_blocks_insufficient_mana_and_soul() {
+        let mut world = test_world();
+        let caster_id = PlayerId(1);
+        let caster_pos = Position { x: 320, y: 320, z: 7 };
+        let mut player = PlayerState::new(caster_id, "Caster".to_string(), caster_pos);
+        player.stats.mana = 5;
+        player.stats.soul = 1;
+        world.players.insert(caster_id, player);
+
+        let effect = SpellEffect {
+            shape: SpellShape::Area { radius: 0 },
+            kind: crate::entities::spells::SpellEffectKind::Damage,
+            damage_type: DamageType::Physical,
+            min_damage: 1,
+            max_damage: 1,
+            include_caster: true,
+            base_damage: None,
+            variance: None,
+        };
+        let mut spell = make_spell(SpellTarget::SelfOnly, effect);
+        spell.mana_cost = 10;
+        spell.soul_cost = 2;
+
+        let clock = GameClock::new(Duration::from_millis(100));
+        let err = world
+            .cast_spell(caster_id, &spell, None, None, &clock)
+            .expect_err("insufficient mana");
+        assert_eq!(err, "spell cast failed: insufficient mana");
+
+        let player = world.players.get_mut(&caster_id).expect("player");
+        player.stats.mana = 50;
+        let err = world
+            .cast_spell(caster_id, &spell, None, None, &clock)
+            .expect_err("insufficient soul");
+        assert_eq!(err, "spell cast failed: insufficient soul");
     }
 
     #[test]
-    fn cast_spell_words_requires_known_spell() {
+    fn spell_group_cooldown_blocks_other_spells() {
         let mut world = test_world();
         let caster_id = PlayerId(1);
-        let caster_pos = Position { x: 200, y: 200, z: 7 };
-
+        let caster_pos = Position { x: 340, y: 340, z: 7 };
         world
             .players
             .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
@@ -20205,546 +24460,810 @@ fn make_tile(position: Position, protection_zone: bool) -> Tile {
             shape: SpellShape::Area { radius: 0 },
             kind: crate::entities::spells::SpellEffectKind::Damage,
             damage_type: DamageType::Physical,
-            min_damage: 0,
-            max_damage: 0,
+            min_damage: 1,
+            max_damage: 1,
             include_caster: true,
             base_damage: None,
             variance: None,
-            base_damage: None,
-            variance: None,
         };
-        let spell = make_spell(SpellTarget::SelfOnly, effect);
-        world.add_spell(spell).expect("spell insert");
+        let group = Some(SpellGroupId(1));
+        let mut spell_a = make_spell(SpellTarget::SelfOnly, effect);
+        spell_a.id = SpellId(10);
+        spell_a.group = group;
+        spell_a.group_cooldown_ms = 1_000;
+        let mut spell_b = make_spell(SpellTarget::SelfOnly, effect);
+        spell_b.id = SpellId(11);
+        spell_b.group = group;
+        spell_b.group_cooldown_ms = 1_000;
 
         let clock = GameClock::new(Duration::from_millis(100));
+        world
+            .cast_spell(caster_id, &spell_a, None, None, &clock)
+            .expect("cast spell a");
         let err = world
-            .cast_spell_words(caster_id, "test", None, None, &clock)
-            .expect_err("cast should require known spell");
-        assert_eq!(err, "spell cast failed: spell not known");
+            .cast_spell(caster_id, &spell_b, None, None, &clock)
+            .expect_err("group cooldown should block");
+        assert_eq!(err, "spell cast failed: group cooldown");
     }
 
     #[test]
-    fn cast_spell_words_casts_for_known_spell() {
+    fn area_spell_hits_targets_in_radius() {
         let mut world = test_world();
         let caster_id = PlayerId(1);
-        let caster_pos = Position { x: 210, y: 210, z: 7 };
+        let target_a = PlayerId(2);
+        let target_b = PlayerId(3);
+        let caster_pos = Position { x: 400, y: 400, z: 7 };
+        let target_a_pos = Position { x: 401, y: 400, z: 7 };
+        let target_b_pos = Position { x: 400, y: 401, z: 7 };
 
         world
             .players
             .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+        world.players.insert(
+            target_a,
+            PlayerState::new(target_a, "TargetA".to_string(), target_a_pos),
+        );
+        world.players.insert(
+            target_b,
+            PlayerState::new(target_b, "TargetB".to_string(), target_b_pos),
+        );
 
         let effect = SpellEffect {
-            shape: SpellShape::Area { radius: 0 },
+            shape: SpellShape::Area { radius: 1 },
             kind: crate::entities::spells::SpellEffectKind::Damage,
             damage_type: DamageType::Physical,
-            min_damage: 0,
-            max_damage: 0,
-            include_caster: true,
-            base_damage: None,
-            variance: None,
+            min_damage: 1,
+            max_damage: 1,
+            include_caster: false,
             base_damage: None,
             variance: None,
         };
-        let spell = make_spell(SpellTarget::SelfOnly, effect);
-        let spell_id = spell.id;
-        world.add_spell(spell).expect("spell insert");
-        world
-            .teach_spell(caster_id, spell_id)
-            .expect("teach spell");
-
+        let spell = make_spell(SpellTarget::Area, effect);
         let clock = GameClock::new(Duration::from_millis(100));
         let report = world
-            .cast_spell_words(caster_id, "test", None, None, &clock)
-            .expect("cast should succeed");
-        assert_eq!(report.hits.len(), 1);
-        assert_eq!(report.hits[0].target, caster_id);
+            .cast_spell(caster_id, &spell, Some(caster_pos), None, &clock)
+            .expect("cast");
+        let hit_targets: HashSet<PlayerId> = report
+            .hits
+            .iter()
+            .filter_map(|hit| match hit.target {
+                SpellTargetId::Player(target_id) => Some(target_id),
+                SpellTargetId::Monster(_) => None,
+            })
+            .collect();
+        assert!(hit_targets.contains(&target_a));
+        assert!(hit_targets.contains(&target_b));
+        assert!(!hit_targets.contains(&caster_id));
     }
 
     #[test]
-    fn cast_spell_words_resolves_named_target() {
+    fn move_player_blocks_missing_tile() {
         let mut world = test_world();
-        let caster_id = PlayerId(1);
-        let target_id = PlayerId(2);
-        let caster_pos = Position { x: 210, y: 210, z: 7 };
-        let target_pos = Position { x: 211, y: 210, z: 7 };
-
-        world
-            .players
-            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+        let player_id = PlayerId(1);
+        let origin = Position { x: 10, y: 10, z: 7 };
+        world.map.tiles.insert(origin, make_tile(origin, false));
         world
             .players
-            .insert(target_id, PlayerState::new(target_id, "Target".to_string(), target_pos));
-        if let Some(target) = world.players.get_mut(&target_id) {
-            target.stats.health = 50;
-        }
-
-        let effect = SpellEffect {
-            shape: SpellShape::Area { radius: 0 },
-            kind: crate::entities::spells::SpellEffectKind::Healing,
-            damage_type: DamageType::Holy,
-            min_damage: 10,
-            max_damage: 10,
-            include_caster: true,
-            base_damage: None,
-            variance: None,
-        };
-        let spell = Spell {
-            id: crate::entities::spells::SpellId(2),
-            name: "Heal Friend".to_string(),
-            words: "exura sio \"name\"".to_string(),
-            kind: SpellKind::Instant,
-            rune_type_id: None,
-            target: SpellTarget::Creature,
-            group: None,
-            mana_cost: 0,
-            soul_cost: 0,
-            level_required: 1,
-            magic_level_required: 0,
-            cooldown_ms: 0,
-            group_cooldown_ms: 0,
-            damage_scale_flags: crate::combat::damage::DamageScaleFlags::NONE,
-            effect: Some(effect),
-            summon: None,
-            haste: None,
-            light: None,
-            dispel: None,
-            field: None,
-            magic_shield: None,
-            outfit: None,
-            challenge: None,
-            levitate: None,
-            raise_dead: None,
-            conjure: None,
-            antidote: None,
-            magic_rope: None,
-            find_person: None,
-            enchant_staff: None,
-        };
-        world.add_spell(spell).expect("spell insert");
-        world
-            .teach_spell(caster_id, crate::entities::spells::SpellId(2))
-            .expect("teach spell");
-
+            .insert(player_id, PlayerState::new(player_id, "Mover".to_string(), origin));
         let clock = GameClock::new(Duration::from_millis(100));
-        let report = world
-            .cast_spell_words(caster_id, "exura sio Target", None, None, &clock)
-            .expect("cast should succeed");
 
-        assert_eq!(report.hits.len(), 1);
-        let target = world.players.get(&target_id).unwrap();
-        assert_eq!(target.stats.health, 60);
+        let err = world
+            .move_player(player_id, Direction::East, &clock)
+            .expect_err("movement should fail");
+        assert_eq!(err, "movement blocked: missing tile");
     }
 
     #[test]
-    fn cast_spell_applies_damage_to_target() {
+    fn move_player_blocks_occupied_tile() {
         let mut world = test_world();
-        let caster_id = PlayerId(1);
-        let target_id = PlayerId(2);
-        let caster_pos = Position { x: 100, y: 100, z: 7 };
-        let target_pos = Position { x: 101, y: 100, z: 7 };
-
+        let mover_id = PlayerId(10);
+        let blocker_id = PlayerId(11);
+        let origin = Position { x: 20, y: 20, z: 7 };
+        let destination = Position { x: 21, y: 20, z: 7 };
+        world.map.tiles.insert(origin, make_tile(origin, false));
         world
-            .players
-            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+            .map
+            .tiles
+            .insert(destination, make_tile(destination, false));
         world
             .players
-            .insert(target_id, PlayerState::new(target_id, "Target".to_string(), target_pos));
-
-        let effect = SpellEffect {
-            shape: SpellShape::Area { radius: 0 },
-            kind: crate::entities::spells::SpellEffectKind::Damage,
-            damage_type: DamageType::Physical,
-            min_damage: 12,
-            max_damage: 12,
-            include_caster: false,
-            base_damage: None,
-            variance: None,
-        };
-        let spell = make_spell(SpellTarget::Position, effect);
-        let mut clock = GameClock::new(Duration::from_millis(100));
-        let report = world
-            .cast_spell(caster_id, &spell, Some(target_pos), None, &clock)
-            .expect("cast should succeed");
+            .insert(mover_id, PlayerState::new(mover_id, "Mover".to_string(), origin));
+        world.players.insert(
+            blocker_id,
+            PlayerState::new(blocker_id, "Blocker".to_string(), destination),
+        );
+        let clock = GameClock::new(Duration::from_millis(100));
 
-        assert_eq!(report.hits.len(), 1);
-        let target = world.players.get(&target_id).unwrap();
-        assert_eq!(target.stats.health, 88);
-        let caster = world.players.get(&caster_id).unwrap();
-        assert_eq!(caster.stats.health, 100);
-        clock.advance(1);
+        let err = world
+            .move_player(mover_id, Direction::East, &clock)
+            .expect_err("movement should fail");
+        assert_eq!(err, "movement blocked: creature");
     }
 
     #[test]
-    fn offensive_spell_blocked_by_protection_zone() {
+    fn move_player_blocks_out_of_bounds_sector() {
         let mut world = test_world();
-        let caster_id = PlayerId(1);
-        let target_id = PlayerId(2);
-        let caster_pos = Position { x: 10, y: 10, z: 7 };
-        let target_pos = Position { x: 11, y: 10, z: 7 };
+        world.map.sector_bounds = Some(SectorBounds {
+            min: SectorCoord { x: 0, y: 0, z: 7 },
+            max: SectorCoord { x: 0, y: 0, z: 7 },
+        });
+        let player_id = PlayerId(2);
+        let origin = Position { x: 31, y: 0, z: 7 };
+        world
+            .players
+            .insert(player_id, PlayerState::new(player_id, "Mover".to_string(), origin));
+        let clock = GameClock::new(Duration::from_millis(100));
 
-        world.map.tiles.insert(caster_pos, make_tile(caster_pos, false));
-        world.map.tiles.insert(target_pos, make_tile(target_pos, true));
+        let err = world
+            .move_player(player_id, Direction::East, &clock)
+            .expect_err("movement should fail");
+        assert_eq!(err, "movement blocked: out of bounds");
+    }
 
+    #[test]
+    fn move_player_succeeds_when_tiles_unbounded() {
+        let mut world = test_world();
+        let player_id = PlayerId(3);
+        let origin = Position { x: 100, y: 100, z: 7 };
         world
             .players
-            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+            .insert(player_id, PlayerState::new(player_id, "Mover".to_string(), origin));
+        let clock = GameClock::new(Duration::from_millis(100));
+
+        let destination = world
+            .move_player(player_id, Direction::North, &clock)
+            .expect("movement should succeed");
+        assert_eq!(destination, Position { x: 100, y: 99, z: 7 });
+        let player = world.players.get(&player_id).expect("player exists");
+        assert_eq!(player.position, destination);
+    }
+
+    #[test]
+    fn move_player_respects_cooldown() {
+        let mut world = test_world();
+        let player_id = PlayerId(4);
+        let origin = Position { x: 200, y: 200, z: 7 };
         world
             .players
-            .insert(target_id, PlayerState::new(target_id, "Target".to_string(), target_pos));
+            .insert(player_id, PlayerState::new(player_id, "Mover".to_string(), origin));
+        let mut clock = GameClock::new(Duration::from_millis(100));
 
-        let effect = SpellEffect {
-            shape: SpellShape::Area { radius: 0 },
-            kind: crate::entities::spells::SpellEffectKind::Damage,
-            damage_type: DamageType::Physical,
-            min_damage: 5,
-            max_damage: 5,
-            include_caster: false,
-            base_damage: None,
-            variance: None,
-        };
-        let spell = make_spell(SpellTarget::Position, effect);
-        let clock = GameClock::new(Duration::from_millis(100));
+        world
+            .move_player(player_id, Direction::North, &clock)
+            .expect("first move should succeed");
         let err = world
-            .cast_spell(caster_id, &spell, Some(target_pos), None, &clock)
-            .expect_err("cast should be blocked");
-        assert_eq!(err, "spell cast failed: protection zone");
+            .move_player(player_id, Direction::North, &clock)
+            .expect_err("movement should respect cooldown");
+        assert_eq!(err, "movement blocked: cooldown");
+        clock.advance(20);
+        world
+            .move_player(player_id, Direction::North, &clock)
+            .expect("move should succeed after cooldown");
     }
 
     #[test]
-    fn line_spell_requires_direction() {
+    fn tick_monsters_moves_when_ready() {
         let mut world = test_world();
-        let caster_id = PlayerId(1);
-        let caster_pos = Position { x: 50, y: 50, z: 7 };
-
+        let origin = Position { x: 50, y: 50, z: 7 };
+        world.map.tiles.insert(origin, make_tile(origin, false));
+        for direction in [
+            Direction::North,
+            Direction::East,
+            Direction::South,
+            Direction::West,
+            Direction::Northeast,
+            Direction::Northwest,
+            Direction::Southeast,
+            Direction::Southwest,
+        ] {
+            let neighbor = origin.step(direction).expect("step");
+            world.map.tiles.insert(neighbor, make_tile(neighbor, false));
+        }
+        let player_id = PlayerId(1);
+        let player_pos = Position { x: 51, y: 50, z: 7 };
         world
             .players
-            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+            .insert(player_id, PlayerState::new(player_id, "Nearby".to_string(), player_pos));
+        let monster_id = CreatureId(1);
+        world.monsters.insert(
+            monster_id,
+            MonsterInstance {
+                id: monster_id,
+                race_number: 1,
+                summoner: None,
+                summoned: false,
+                home_id: None,
+                name: "TestMonster".to_string(),
+                position: origin,
+                direction: Direction::South,
+                outfit: DEFAULT_OUTFIT,
+                stats: Stats::default(),
+                experience: 0,
+                loot: MonsterLootTable::default(),
+                inventory: Inventory::default(),
+                inventory_containers: HashMap::new(),
+                corpse_ids: Vec::new(),
+                flags: MonsterFlags::default(),
+                skills: MonsterSkills::default(),
+                attack: 0,
+                defend: 0,
+                armor: 0,
+                poison: 0,
+                spells: Vec::new(),
+                strategy: [100, 0, 0, 0],
+                flee_threshold: 0,
+                lose_target_distance: 0,
+                target: None,
+                damage_ledger: DamageLedger::default(),
+                speed: 220,
+                outfit_effect: None,
+                speed_effect: None,
+                strength_effect: None,
+                move_cooldown: Cooldown::new(GameTick(0)),
+                combat_cooldown: Cooldown::new(GameTick(0)),
+                talk_lines: Vec::new(),
+                talk_cooldown: Cooldown::new(GameTick(0)),
+            },
+        );
 
-        let effect = SpellEffect {
-            shape: SpellShape::Line { length: 3 },
-            kind: crate::entities::spells::SpellEffectKind::Damage,
-            damage_type: DamageType::Energy,
-            min_damage: 3,
-            max_damage: 3,
-            include_caster: true,
-            base_damage: None,
-            variance: None,
-        };
-        let spell = make_spell(SpellTarget::SelfOnly, effect);
         let clock = GameClock::new(Duration::from_millis(100));
-        let err = world
-            .cast_spell(caster_id, &spell, None, None, &clock)
-            .expect_err("line spell should require a direction");
-        assert_eq!(
-            err,
-            "spell cast failed: direction required for line spell"
-        );
+        let moved = world.tick_monsters(&clock);
+        assert_eq!(moved.moved, 1);
+        let monster = world.monsters.get(&monster_id).expect("monster exists");
+        assert_ne!(monster.position, origin);
     }
 
     #[test]
-    fn spell_cast_consumes_costs_and_respects_cooldown() {
+    fn tick_monsters_moves_towards_target() {
         let mut world = test_world();
-        let caster_id = PlayerId(1);
-        let caster_pos = Position { x: 300, y: 300, z: 7 };
-        let mut player = PlayerState::new(caster_id, "Caster".to_string(), caster_pos);
-        player.stats.mana = 50;
-        player.stats.soul = 5;
-        world.players.insert(caster_id, player);
-
-        let effect = SpellEffect {
-            shape: SpellShape::Area { radius: 0 },
-            kind: crate::entities::spells::SpellEffectKind::Damage,
-            damage_type: DamageType::Physical,
-            min_damage: 1,
-            max_damage: 1,
-            include_caster: true,
-            base_damage: None,
-            variance: None,
-        };
-        let mut spell = make_spell(SpellTarget::SelfOnly, effect);
-        spell.mana_cost = 10;
-        spell.soul_cost = 2;
-        spell.cooldown_ms = 1_000;
-
-        let mut clock = GameClock::new(Duration::from_millis(100));
+        let origin = Position { x: 50, y: 50, z: 7 };
+        let step = Position { x: 51, y: 50, z: 7 };
+        let target_pos = Position { x: 52, y: 50, z: 7 };
+        for pos in [origin, step, target_pos] {
+            world.map.tiles.insert(pos, make_tile(pos, false));
+        }
+        let player_id = PlayerId(1);
         world
-            .cast_spell(caster_id, &spell, None, None, &clock)
-            .expect("first cast");
-
-        let player = world.players.get(&caster_id).expect("player");
-        assert_eq!(player.stats.mana, 40);
-        assert_eq!(player.stats.soul, 3);
+            .players
+            .insert(player_id, PlayerState::new(player_id, "Target".to_string(), target_pos));
+        let monster_id = CreatureId(2);
+        world.monsters.insert(
+            monster_id,
+            MonsterInstance {
+                id: monster_id,
+                race_number: 1,
+                summoner: None,
+                summoned: false,
+                home_id: None,
+                name: "Hunter".to_string(),
+                position: origin,
+                direction: Direction::South,
+                outfit: DEFAULT_OUTFIT,
+                stats: Stats::default(),
+                experience: 0,
+                loot: MonsterLootTable::default(),
+                inventory: Inventory::default(),
+                inventory_containers: HashMap::new(),
+                corpse_ids: Vec::new(),
+                flags: MonsterFlags::default(),
+                skills: MonsterSkills::default(),
+                attack: 0,
+                defend: 0,
+                armor: 0,
+                poison: 0,
+                spells: Vec::new(),
+                strategy: [100, 0, 0, 0],
+                flee_threshold: 0,
+                lose_target_distance: 8,
+                target: None,
+                damage_ledger: DamageLedger::default(),
+                speed: 220,
+                outfit_effect: None,
+                speed_effect: None,
+                strength_effect: None,
+                move_cooldown: Cooldown::new(GameTick(0)),
+                combat_cooldown: Cooldown::new(GameTick(0)),
+                talk_lines: Vec::new(),
+                talk_cooldown: Cooldown::new(GameTick(0)),
+            },
+        );
 
-        let err = world
-            .cast_spell(caster_id, &spell, None, None, &clock)
-            .expect_err("cooldown should block");
-        assert_eq!(err, "spell cast failed: spell cooldown");
+        let clock = GameClock::new(Duration::from_millis(100));
+        world.tick_monsters(&clock);
 
-        clock.advance(10);
-        world
-            .cast_spell(caster_id, &spell, None, None, &clock)
-            .expect("cooldown expired");
-        let player = world.players.get(&caster_id).expect("player");
-        assert_eq!(player.stats.mana, 30);
-        assert_eq!(player.stats.soul, 1);
+        let monster = world.monsters.get(&monster_id).expect("monster exists");
+        assert_eq!(monster.position, step);
     }
 
     #[test]
-    fn spell_cast_blocks_insufficient_mana_and_soul() {
+    fn tick_monsters_moves_away_when_fleeing() {
         let mut world = test_world();
-        let caster_id = PlayerId(1);
-        let caster_pos = Position { x: 320, y: 320, z: 7 };
-        let mut player = PlayerState::new(caster_id, "Caster".to_string(), caster_pos);
-        player.stats.mana = 5;
-        player.stats.soul = 1;
-        world.players.insert(caster_id, player);
-
-        let effect = SpellEffect {
-            shape: SpellShape::Area { radius: 0 },
-            kind: crate::entities::spells::SpellEffectKind::Damage,
-            damage_type: DamageType::Physical,
-            min_damage: 1,
-            max_damage: 1,
-            include_caster: true,
-            base_damage: None,
-            variance: None,
-        };
-        let mut spell = make_spell(SpellTarget::SelfOnly, effect);
-        spell.mana_cost = 10;
-        spell.soul_cost = 2;
+        let origin = Position { x: 51, y: 50, z: 7 };
+        let step = Position { x: 50, y: 50, z: 7 };
+        let target_pos = Position { x: 52, y: 50, z: 7 };
+        for pos in [origin, step, target_pos] {
+            world.map.tiles.insert(pos, make_tile(pos, false));
+        }
+        let player_id = PlayerId(1);
+        world
+            .players
+            .insert(player_id, PlayerState::new(player_id, "Target".to_string(), target_pos));
+        let monster_id = CreatureId(2);
+        let mut stats = Stats::default();
+        stats.health = 5;
+        stats.max_health = 5;
+        world.monsters.insert(
+            monster_id,
+            MonsterInstance {
+                id: monster_id,
+                race_number: 1,
+                summoner: None,
+                summoned: false,
+                home_id: None,
+                name: "Runner".to_string(),
+                position: origin,
+                direction: Direction::South,
+                outfit: DEFAULT_OUTFIT,
+                stats,
+                experience: 0,
+                loot: MonsterLootTable::default(),
+                inventory: Inventory::default(),
+                inventory_containers: HashMap::new(),
+                corpse_ids: Vec::new(),
+                flags: MonsterFlags::default(),
+                skills: MonsterSkills::default(),
+                attack: 0,
+                defend: 0,
+                armor: 0,
+                poison: 0,
+                spells: Vec::new(),
+                strategy: [100, 0, 0, 0],
+                flee_threshold: 10,
+                lose_target_distance: 8,
+                target: None,
+                damage_ledger: DamageLedger::default(),
+                speed: 220,
+                outfit_effect: None,
+                speed_effect: None,
+                strength_effect: None,
+                move_cooldown: Cooldown::new(GameTick(0)),
+                combat_cooldown: Cooldown::new(GameTick(0)),
+                talk_lines: Vec::new(),
+                talk_cooldown: Cooldown::new(GameTick(0)),
+            },
+        );
 
         let clock = GameClock::new(Duration::from_millis(100));
-        let err = world
-            .cast_spell(caster_id, &spell, None, None, &clock)
-            .expect_err("insufficient mana");
-        assert_eq!(err, "spell cast failed: insufficient mana");
+        world.tick_monsters(&clock);
 
-        let player = world.players.get_mut(&caster_id).expect("player");
-        player.stats.mana = 50;
-        let err = world
-            .cast_spell(caster_id, &spell, None, None, &clock)
-            .expect_err("insufficient soul");
-        assert_eq!(err, "spell cast failed: insufficient soul");
+        let monster = world.monsters.get(&monster_id).expect("monster exists");
+        assert_eq!(monster.position, step);
     }
 
     #[test]
-    fn spell_group_cooldown_blocks_other_spells() {
+    fn monster_target_ignores_players_in_protection_zone() {
         let mut world = test_world();
-        let caster_id = PlayerId(1);
-        let caster_pos = Position { x: 340, y: 340, z: 7 };
+        let monster_pos = Position { x: 70, y: 70, z: 7 };
+        let player_pos = Position { x: 71, y: 70, z: 7 };
+        world.map.tiles.insert(monster_pos, make_tile(monster_pos, false));
+        world.map.tiles.insert(player_pos, make_tile(player_pos, true));
+        let player_id = PlayerId(10);
         world
             .players
-            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
+            .insert(player_id, PlayerState::new(player_id, "Safe".to_string(), player_pos));
 
-        let effect = SpellEffect {
-            shape: SpellShape::Area { radius: 0 },
-            kind: crate::entities::spells::SpellEffectKind::Damage,
-            damage_type: DamageType::Physical,
-            min_damage: 1,
-            max_damage: 1,
-            include_caster: true,
-            base_damage: None,
-            variance: None,
-        };
-        let group = Some(SpellGroupId(1));
-        let mut spell_a = make_spell(SpellTarget::SelfOnly, effect);
-        spell_a.id = SpellId(10);
-        spell_a.group = group;
-        spell_a.group_cooldown_ms = 1_000;
-        let mut spell_b = make_spell(SpellTarget::SelfOnly, effect);
-        spell_b.id = SpellId(11);
-        spell_b.group = group;
-        spell_b.group_cooldown_ms = 1_000;
+        let target = world.select_monster_target(
+            CreatureId(1),
+            monster_pos,
+            8,
+            [100, 0, 0, 0],
+            MonsterFlags::default(),
+            None,
+        );
+        assert!(target.is_none());
+    }
+
+    #[test]
+    fn monster_movement_blocks_entering_protection_zone() {
+        let mut world = test_world();
+        let origin = Position { x: 80, y: 80, z: 7 };
+        let destination = Position { x: 81, y: 80, z: 7 };
+        world.map.tiles.insert(origin, make_tile(origin, false));
+        world.map.tiles.insert(destination, make_tile(destination, true));
+        let monster_id = CreatureId(9);
+        world.monsters.insert(
+            monster_id,
+            MonsterInstance {
+                id: monster_id,
+                race_number: 1,
+                summoner: None,
+                summoned: false,
+                home_id: None,
+                name: "PZTester".to_string(),
+                position: origin,
+                direction: Direction::South,
+                outfit: DEFAULT_OUTFIT,
+                stats: Stats::default(),
+                experience: 0,
+                loot: MonsterLootTable::default(),
+                inventory: Inventory::default(),
+                inventory_containers: HashMap::new(),
+                corpse_ids: Vec::new(),
+                flags: MonsterFlags::default(),
+                skills: MonsterSkills::default(),
+                attack: 0,
+                defend: 0,
+                armor: 0,
+                poison: 0,
+                spells: Vec::new(),
+                strategy: [100, 0, 0, 0],
+                flee_threshold: 0,
+                lose_target_distance: 0,
+                target: None,
+                damage_ledger: DamageLedger::default(),
+                speed: 220,
+                outfit_effect: None,
+                speed_effect: None,
+                strength_effect: None,
+                move_cooldown: Cooldown::new(GameTick(0)),
+                combat_cooldown: Cooldown::new(GameTick(0)),
+                talk_lines: Vec::new(),
+                talk_cooldown: Cooldown::new(GameTick(0)),
+            },
+        );
 
-        let clock = GameClock::new(Duration::from_millis(100));
-        world
-            .cast_spell(caster_id, &spell_a, None, None, &clock)
-            .expect("cast spell a");
         let err = world
-            .cast_spell(caster_id, &spell_b, None, None, &clock)
-            .expect_err("group cooldown should block");
-        assert_eq!(err, "spell cast failed: group cooldown");
+            .move_monster(monster_id, Direction::East)
+            .expect_err("movement should be blocked");
+        assert_eq!(err, "movement blocked: protection zone");
     }
 
     #[test]
-    fn area_spell_hits_targets_in_radius() {
+    fn monster_kicks_blocking_box() {
         let mut world = test_world();
-        let caster_id = PlayerId(1);
-        let target_a = PlayerId(2);
-        let target_b = PlayerId(3);
-        let caster_pos = Position { x: 400, y: 400, z: 7 };
-        let target_a_pos = Position { x: 401, y: 400, z: 7 };
-        let target_b_pos = Position { x: 400, y: 401, z: 7 };
-
-        world
-            .players
-            .insert(caster_id, PlayerState::new(caster_id, "Caster".to_string(), caster_pos));
-        world.players.insert(
-            target_a,
-            PlayerState::new(target_a, "TargetA".to_string(), target_a_pos),
-        );
-        world.players.insert(
-            target_b,
-            PlayerState::new(target_b, "TargetB".to_string(), target_b_pos),
+        let origin = Position { x: 40, y: 40, z: 7 };
+        let destination = Position { x: 41, y: 40, z: 7 };
+        let pushed = Position { x: 42, y: 40, z: 7 };
+        world.map.tiles.insert(origin, make_tile(origin, false));
+        world.map.tiles.insert(destination, make_tile(destination, false));
+        world.map.tiles.insert(pushed, make_tile(pushed, false));
+        let mut object_types = ObjectTypeIndex::default();
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(100),
+                name: "Box".to_string(),
+                flags: vec!["Unpass".to_string(), "Take".to_string()],
+                attributes: Vec::new(),
+            })
+            .expect("insert box type");
+        world.object_types = Some(object_types);
+        let dest_tile = world.map.tile_mut(destination).expect("dest tile");
+        place_on_tile(
+            dest_tile,
+            ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: ItemTypeId(100),
+                count: 1,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            },
+            false,
+        )
+        .expect("place box");
+        let monster_id = CreatureId(2);
+        world.monsters.insert(
+            monster_id,
+            MonsterInstance {
+                id: monster_id,
+                race_number: 1,
+                summoner: None,
+                summoned: false,
+                home_id: None,
+                name: "Kicker".to_string(),
+                position: origin,
+                direction: Direction::South,
+                outfit: DEFAULT_OUTFIT,
+                stats: Stats::default(),
+                experience: 0,
+                loot: MonsterLootTable::default(),
+                inventory: Inventory::default(),
+                inventory_containers: HashMap::new(),
+                corpse_ids: Vec::new(),
+                flags: MonsterFlags {
+                    kick_boxes: true,
+                    ..MonsterFlags::default()
+                },
+                skills: MonsterSkills::default(),
+                attack: 0,
+                defend: 0,
+                armor: 0,
+                poison: 0,
+                spells: Vec::new(),
+                strategy: [100, 0, 0, 0],
+                flee_threshold: 0,
+                lose_target_distance: 0,
+                target: None,
+                damage_ledger: DamageLedger::default(),
+                speed: 220,
+                outfit_effect: None,
+                speed_effect: None,
+                strength_effect: None,
+                move_cooldown: Cooldown::new(GameTick(0)),
+                combat_cooldown: Cooldown::new(GameTick(0)),
+                talk_lines: Vec::new(),
+                talk_cooldown: Cooldown::new(GameTick(0)),
+            },
         );
 
-        let effect = SpellEffect {
-            shape: SpellShape::Area { radius: 1 },
-            kind: crate::entities::spells::SpellEffectKind::Damage,
-            damage_type: DamageType::Physical,
-            min_damage: 1,
-            max_damage: 1,
-            include_caster: false,
-            base_damage: None,
-            variance: None,
-        };
-        let spell = make_spell(SpellTarget::Area, effect);
-        let clock = GameClock::new(Duration::from_millis(100));
-        let report = world
-            .cast_spell(caster_id, &spell, Some(caster_pos), None, &clock)
-            .expect("cast");
-        let hit_targets: HashSet<PlayerId> = report
-            .hits
-            .iter()
-            .filter_map(|hit| match hit.target {
-                SpellTargetId::Player(target_id) => Some(target_id),
-                SpellTargetId::Monster(_) => None,
-            })
-            .collect();
-        assert!(hit_targets.contains(&target_a));
-        assert!(hit_targets.contains(&target_b));
-        assert!(!hit_targets.contains(&caster_id));
+        let moved = world
+            .move_monster(monster_id, Direction::East)
+            .expect("movement should succeed");
+        assert_eq!(moved, destination);
+        let monster = world.monsters.get(&monster_id).expect("monster exists");
+        assert_eq!(monster.position, destination);
+        let dest_tile = world.map.tile(destination).expect("dest tile");
+        assert!(dest_tile.items.is_empty());
+        let pushed_tile = world.map.tile(pushed).expect("pushed tile");
+        assert_eq!(pushed_tile.items.len(), 1);
+        assert_eq!(pushed_tile.items[0].type_id, ItemTypeId(100));
     }
 
     #[test]
-    fn move_player_blocks_missing_tile() {
+    fn monster_kicks_blocking_creature() {
         let mut world = test_world();
-        let player_id = PlayerId(1);
-        let origin = Position { x: 10, y: 10, z: 7 };
-        world.map.tiles.insert(origin, make_tile(origin, false));
-        world
-            .players
-            .insert(player_id, PlayerState::new(player_id, "Mover".to_string(), origin));
-        let clock = GameClock::new(Duration::from_millis(100));
+        let origin = Position { x: 45, y: 40, z: 7 };
+        let destination = Position { x: 46, y: 40, z: 7 };
+        let pushed = Position { x: 47, y: 40, z: 7 };
+        for pos in [origin, destination, pushed] {
+            world.map.tiles.insert(pos, make_tile(pos, false));
+        }
+        let player_id = PlayerId(5);
+        world.players.insert(
+            player_id,
+            PlayerState::new(player_id, "Blocker".to_string(), destination),
+        );
+        world.event_scripts = crate::world::events::parse_event_scripts(
+            "BEGIN \"Events\"\n\
+             Step, Position([47,40,7]) -> Message(\"Shoved aside.\")\n\
+             END\n",
+        )
+        .expect("parse events");
+        let monster_id = CreatureId(6);
+        world.monsters.insert(
+            monster_id,
+            MonsterInstance {
+                id: monster_id,
+                race_number: 1,
+                summoner: None,
+                summoned: false,
+                home_id: None,
+                name: "Kicker".to_string(),
+                position: origin,
+                direction: Direction::South,
+                outfit: DEFAULT_OUTFIT,
+                stats: Stats::default(),
+                experience: 0,
+                loot: MonsterLootTable::default(),
+                inventory: Inventory::default(),
+                inventory_containers: HashMap::new(),
+                corpse_ids: Vec::new(),
+                flags: MonsterFlags {
+                    kick_creatures: true,
+                    ..MonsterFlags::default()
+                },
+                skills: MonsterSkills::default(),
+                attack: 0,
+                defend: 0,
+                armor: 0,
+                poison: 0,
+                spells: Vec::new(),
+                strategy: [100, 0, 0, 0],
+                flee_threshold: 0,
+                lose_target_distance: 0,
+                target: None,
+                damage_ledger: DamageLedger::default(),
+                speed: 220,
+                outfit_effect: None,
+                speed_effect: None,
+                strength_effect: None,
+                move_cooldown: Cooldown::new(GameTick(0)),
+                combat_cooldown: Cooldown::new(GameTick(0)),
+                talk_lines: Vec::new(),
+                talk_cooldown: Cooldown::new(GameTick(0)),
+            },
+        );
 
-        let err = world
-            .move_player(player_id, Direction::East, &clock)
-            .expect_err("movement should fail");
-        assert_eq!(err, "movement blocked: missing tile");
+        let moved = world
+            .move_monster(monster_id, Direction::East)
+            .expect("movement should succeed");
+        assert_eq!(moved, destination);
+        let monster = world.monsters.get(&monster_id).expect("monster exists");
+        assert_eq!(monster.position, destination);
+        let player = world.players.get(&player_id).expect("player exists");
+        assert_eq!(player.position, pushed);
+        assert!(world
+            .take_pending_messages(player_id)
+            .iter()
+            .any(|message| message.message == "Shoved aside."));
     }
 
     #[test]
-    fn move_player_blocks_occupied_tile() {
+    fn monster_kick_creature_respects_unpushable() {
         let mut world = test_world();
-        let mover_id = PlayerId(10);
-        let blocker_id = PlayerId(11);
-        let origin = Position { x: 20, y: 20, z: 7 };
-        let destination = Position { x: 21, y: 20, z: 7 };
-        world.map.tiles.insert(origin, make_tile(origin, false));
-        world
-            .map
-            .tiles
-            .insert(destination, make_tile(destination, false));
-        world
-            .players
-            .insert(mover_id, PlayerState::new(mover_id, "Mover".to_string(), origin));
-        world.players.insert(
+        let origin = Position { x: 50, y: 40, z: 7 };
+        let destination = Position { x: 51, y: 40, z: 7 };
+        let pushed = Position { x: 52, y: 40, z: 7 };
+        for pos in [origin, destination, pushed] {
+            world.map.tiles.insert(pos, make_tile(pos, false));
+        }
+        let blocker_id = CreatureId(7);
+        world.monsters.insert(
             blocker_id,
-            PlayerState::new(blocker_id, "Blocker".to_string(), destination),
+            MonsterInstance {
+                id: blocker_id,
+                race_number: 1,
+                summoner: None,
+                summoned: false,
+                home_id: None,
+                name: "Blocker".to_string(),
+                position: destination,
+                direction: Direction::South,
+                outfit: DEFAULT_OUTFIT,
+                stats: Stats::default(),
+                experience: 0,
+                loot: MonsterLootTable::default(),
+                inventory: Inventory::default(),
+                inventory_containers: HashMap::new(),
+                corpse_ids: Vec::new(),
+                flags: MonsterFlags {
+                    unpushable: true,
+                    ..MonsterFlags::default()
+                },
+                skills: MonsterSkills::default(),
+                attack: 0,
+                defend: 0,
+                armor: 0,
+                poison: 0,
+                spells: Vec::new(),
+                strategy: [100, 0, 0, 0],
+                flee_threshold: 0,
+                lose_target_distance: 0,
+                target: None,
+                damage_ledger: DamageLedger::default(),
+                speed: 220,
+                outfit_effect: None,
+                speed_effect: None,
+                strength_effect: None,
+                move_cooldown: Cooldown::new(GameTick(0)),
+                combat_cooldown: Cooldown::new(GameTick(0)),
+                talk_lines: Vec::new(),
+                talk_cooldown: Cooldown::new(GameTick(0)),
+            },
+        );
+        let monster_id = CreatureId(8);
+        world.monsters.insert(
+            monster_id,
+            MonsterInstance {
+                id: monster_id,
+                race_number: 1,
+                summoner: None,
+                summoned: false,
+                home_id: None,
+                name: "Kicker".to_string(),
+                position: origin,
+                direction: Direction::South,
+                outfit: DEFAULT_OUTFIT,
+                stats: Stats::default(),
+                experience: 0,
+                loot: MonsterLootTable::default(),
+                inventory: Inventory::default(),
+                inventory_containers: HashMap::new(),
+                corpse_ids: Vec::new(),
+                flags: MonsterFlags {
+                    kick_creatures: true,
+                    ..MonsterFlags::default()
+                },
+                skills: MonsterSkills::default(),
+                attack: 0,
+                defend: 0,
+                armor: 0,
+                poison: 0,
+                spells: Vec::new(),
+                strategy: [100, 0, 0, 0],
+                flee_threshold: 0,
+                lose_target_distance: 0,
+                target: None,
+                damage_ledger: DamageLedger::default(),
+                speed: 220,
+                outfit_effect: None,
+                speed_effect: None,
+                strength_effect: None,
+                move_cooldown: Cooldown::new(GameTick(0)),
+                combat_cooldown: Cooldown::new(GameTick(0)),
+                talk_lines: Vec::new(),
+                talk_cooldown: Cooldown::new(GameTick(0)),
+            },
         );
-        let clock = GameClock::new(Duration::from_millis(100));
 
         let err = world
-            .move_player(mover_id, Direction::East, &clock)
-            .expect_err("movement should fail");
+            .move_monster(monster_id, Direction::East)
+            .expect_err("movement should be blocked");
         assert_eq!(err, "movement blocked: creature");
+        let monster = world.monsters.get(&monster_id).expect("monster exists");
+        assert_eq!(monster.position, origin);
+        let blocker = world.monsters.get(&blocker_id).expect("blocker exists");
+        assert_eq!(blocker.position, destination);
     }
 
     #[test]
-    fn move_player_blocks_out_of_bounds_sector() {
+    fn tick_monsters_respects_cooldown() {
         let mut world = test_world();
-        world.map.sector_bounds = Some(SectorBounds {
-            min: SectorCoord { x: 0, y: 0, z: 7 },
-            max: SectorCoord { x: 0, y: 0, z: 7 },
-        });
-        let player_id = PlayerId(2);
-        let origin = Position { x: 31, y: 0, z: 7 };
+        let origin = Position { x: 60, y: 60, z: 7 };
+        world.map.tiles.insert(origin, make_tile(origin, false));
+        let player_id = PlayerId(1);
         world
             .players
-            .insert(player_id, PlayerState::new(player_id, "Mover".to_string(), origin));
-        let clock = GameClock::new(Duration::from_millis(100));
-
-        let err = world
-            .move_player(player_id, Direction::East, &clock)
-            .expect_err("movement should fail");
-        assert_eq!(err, "movement blocked: out of bounds");
-    }
+            .insert(player_id, PlayerState::new(player_id, "Nearby".to_string(), origin));
+        let monster_id = CreatureId(3);
+        world.monsters.insert(
+            monster_id,
+            MonsterInstance {
+                id: monster_id,
+                race_number: 1,
+                summoner: None,
+                summoned: false,
+                home_id: None,
+                name: "Sleeper".to_string(),
+                position: origin,
+                direction: Direction::South,
+                outfit: DEFAULT_OUTFIT,
+                stats: Stats::default(),
+                experience: 0,
+                loot: MonsterLootTable::default(),
+                inventory: Inventory::default(),
+                inventory_containers: HashMap::new(),
+                corpse_ids: Vec::new(),
+                flags: MonsterFlags::default(),
+                skills: MonsterSkills::default(),
+                attack: 0,
+                defend: 0,
+                armor: 0,
+                poison: 0,
+                spells: Vec::new(),
+                strategy: [100, 0, 0, 0],
+                flee_threshold: 0,
+                lose_target_distance: 0,
+                target: None,
+                damage_ledger: DamageLedger::default(),
+                speed: 220,
+                outfit_effect: None,
+                speed_effect: None,
+                strength_effect: None,
+                move_cooldown: Cooldown::new(GameTick(5)),
+                combat_cooldown: Cooldown::new(GameTick(0)),
+                talk_lines: Vec::new(),
+                talk_cooldown: Cooldown::new(GameTick(0)),
+            },
+        );
 
-    #[test]
-    fn move_player_succeeds_when_tiles_unbounded() {
-        let mut world = test_world();
-        let player_id = PlayerId(3);
-        let origin = Position { x: 100, y: 100, z: 7 };
-        world
-            .players
-            .insert(player_id, PlayerState::new(player_id, "Mover".to_string(), origin));
         let clock = GameClock::new(Duration::from_millis(100));
-
-        let destination = world
-            .move_player(player_id, Direction::North, &clock)
-            .expect("movement should succeed");
-        assert_eq!(destination, Position { x: 100, y: 99, z: 7 });
-        let player = world.players.get(&player_id).expect("player exists");
-        assert_eq!(player.position, destination);
-    }
-
-    #[test]
-    fn move_player_respects_cooldown() {
-        let mut world = test_world();
-        let player_id = PlayerId(4);
-        let origin = Position { x: 200, y: 200, z: 7 };
-        world
-            .players
-            .insert(player_id, PlayerState::new(player_id, "Mover".to_string(), origin));
-        let mut clock = GameClock::new(Duration::from_millis(100));
-
-        world
-            .move_player(player_id, Direction::North, &clock)
-            .expect("first move should succeed");
-        let err = world
-            .move_player(player_id, Direction::North, &clock)
-            .expect_err("movement should respect cooldown");
-        assert_eq!(err, "movement blocked: cooldown");
-        clock.advance(20);
-        world
-            .move_player(player_id, Direction::North, &clock)
-            .expect("move should succeed after cooldown");
+        let moved = world.tick_monsters(&clock);
+        assert_eq!(moved.moved, 0);
+        let monster = world.monsters.get(&monster_id).expect("monster exists");
+        assert_eq!(monster.position, origin);
     }
 
     #[test]
-    fn tick_monsters_moves_when_ready() {
+    fn tick_monsters_resets_cooldown_after_tick() {
         let mut world = test_world();
-        let origin = Position { x: 50, y: 50, z: 7 };
+        let origin = Position { x: 70, y: 70, z: 7 };
         world.map.tiles.insert(origin, make_tile(origin, false));
-        for direction in [
-            Direction::North,
-            Direction::East,
-            Direction::South,
-            Direction::West,
-            Direction::Northeast,
-            Direction::Northwest,
-            Direction::Southeast,
-            Direction::Southwest,
-        ] {
-            let neighbor = origin.step(direction).expect("step");
-            world.map.tiles.insert(neighbor, make_tile(neighbor, false));
-        }
+        let neighbor = origin.step(Direction::North).expect("step");
+        world.map.tiles.insert(neighbor, make_tile(neighbor, false));
         let player_id = PlayerId(1);
-        let player_pos = Position { x: 51, y: 50, z: 7 };
         world
             .players
-            .insert(player_id, PlayerState::new(player_id, "Nearby".to_string(), player_pos));
-        let monster_id = CreatureId(1);
+            .insert(player_id, PlayerState::new(player_id, "Nearby".to_string(), origin));
+        let monster_id = CreatureId(4);
         world.monsters.insert(
             monster_id,
             MonsterInstance {
@@ -20753,7 +25272,7 @@ fn make_tile(position: Position, protection_zone: bool) -> Tile {
                 summoner: None,
                 summoned: false,
                 home_id: None,
-                name: "TestMonster".to_string(),
+                name: "Runner".to_string(),
                 position: origin,
                 direction: Direction::South,
                 outfit: DEFAULT_OUTFIT,
@@ -20774,7 +25293,7 @@ fn make_tile(position: Position, protection_zone: bool) -> Tile {
                 flee_threshold: 0,
                 lose_target_distance: 0,
                 target: None,
-                damage_by: HashMap::new(),
+                damage_ledger: DamageLedger::default(),
                 speed: 220,
                 outfit_effect: None,
                 speed_effect: None,
@@ -20786,1588 +25305,2702 @@ fn make_tile(position: Position, protection_zone: bool) -> Tile {
             },
         );
 
-        let clock = GameClock::new(Duration::from_millis(100));
-        let moved = world.tick_monsters(&clock);
-        assert_eq!(moved.moved, 1);
-        let monster = world.monsters.get(&monster_id).expect("monster exists");
-        assert_ne!(monster.position, origin);
+        let clock = GameClock::new(Duration::from_millis(100));
+        world.tick_monsters(&clock);
+        let monster = world.monsters.get(&monster_id).expect("monster exists");
+        assert_eq!(
+            monster.move_cooldown.ready_at(),
+            GameTick(MONSTER_MOVE_INTERVAL_TICKS)
+        );
+    }
+
+    #[test]
+    fn tick_npcs_respects_cooldown() {
+        let mut world = test_world();
+        let origin = Position { x: 80, y: 80, z: 7 };
+        world.map.tiles.insert(origin, make_tile(origin, false));
+        let player_id = PlayerId(1);
+        world
+            .players
+            .insert(player_id, PlayerState::new(player_id, "Nearby".to_string(), origin));
+        let npc_id = CreatureId(0x4000_0001);
+        world.npcs.insert(
+            npc_id,
+            NpcInstance {
+                id: npc_id,
+                script_key: "test".to_string(),
+                name: "Npc".to_string(),
+                position: origin,
+                direction: Direction::South,
+                home: origin,
+                outfit: DEFAULT_OUTFIT,
+                radius: 2,
+                focused: None,
+                focus_expires_at: None,
+                queue: VecDeque::new(),
+                move_cooldown: Cooldown::new(GameTick(10)),
+            },
+        );
+
+        let clock = GameClock::new(Duration::from_millis(100));
+        let moved = world.tick_npcs(&clock);
+        assert_eq!(moved.len(), 0);
+        let npc = world.npcs.get(&npc_id).expect("npc exists");
+        assert_eq!(npc.position, origin);
+    }
+
+    #[test]
+    fn tick_npcs_resets_cooldown_after_tick() {
+        let mut world = test_world();
+        let origin = Position { x: 90, y: 90, z: 7 };
+        world.map.tiles.insert(origin, make_tile(origin, false));
+        let neighbor = origin.step(Direction::North).expect("step");
+        world.map.tiles.insert(neighbor, make_tile(neighbor, false));
+        let player_id = PlayerId(1);
+        world
+            .players
+            .insert(player_id, PlayerState::new(player_id, "Nearby".to_string(), origin));
+        let npc_id = CreatureId(0x4000_0002);
+        world.npcs.insert(
+            npc_id,
+            NpcInstance {
+                id: npc_id,
+                script_key: "test".to_string(),
+                name: "Npc".to_string(),
+                position: origin,
+                direction: Direction::South,
+                home: origin,
+                outfit: DEFAULT_OUTFIT,
+                radius: 2,
+                focused: None,
+                focus_expires_at: None,
+                queue: VecDeque::new(),
+                move_cooldown: Cooldown::new(GameTick(0)),
+            },
+        );
+
+        let clock = GameClock::new(Duration::from_millis(100));
+        let _ = world.tick_npcs(&clock);
+        let npc = world.npcs.get(&npc_id).expect("npc exists");
+        assert_eq!(
+            npc.move_cooldown.ready_at(),
+            GameTick(NPC_MOVE_INTERVAL_TICKS)
+        );
+    }
+
+    #[test]
+    fn raid_schedule_spawns_monsters() {
+        let mut world = test_world();
+        let mut index = MonsterIndex::default();
+        let mut script = MonsterScript::default();
+        script.name = Some("Badger".to_string());
+        index.scripts.insert("Badger".to_string(), script);
+        index.race_index.insert(1, "Badger".to_string());
+        index.raids.insert(
+            "TestRaid".to_string(),
+            RaidScript {
+                raid_type: Some("SmallRaid".to_string()),
+                interval: None,
+                spawns: vec![RaidSpawn {
+                    delay: Some(5),
+                    position: Some(RaidPosition { x: 10, y: 10, z: 7 }),
+                    spread: None,
+                    race: Some(1),
+                    count: None,
+                    message: None,
+                    fields: Vec::new(),
+                }],
+                fields: Vec::new(),
+            },
+        );
+        world.monster_index = Some(index);
+
+        world
+            .schedule_raid("TestRaid", 1, GameTick(10))
+            .expect("schedule");
+        assert_eq!(world.raid_events.len(), 1);
+        assert_eq!(world.raid_events[0].at, GameTick(15));
+
+        let none = world.spawn_due_raids(GameTick(14));
+        assert!(none.is_empty());
+        let spawned = world.spawn_due_raids(GameTick(15));
+        assert_eq!(spawned.len(), 1);
+        let id = spawned[0];
+        let monster = world.monsters.get(&id).expect("monster spawned");
+        assert_eq!(monster.name, "Badger");
+        assert_eq!(monster.position, Position { x: 10, y: 10, z: 7 });
+    }
+
+    #[test]
+    fn raid_calendar_starts_due_raids_and_keeps_big_raids_apart() {
+        let mut world = test_world();
+        let player_id = PlayerId(26);
+        world.players.insert(
+            player_id,
+            PlayerState::new(player_id, "Watcher".to_string(), Position { x: 20, y: 20, z: 7 }),
+        );
+        let mut index = MonsterIndex::default();
+        let mut script = MonsterScript::default();
+        script.name = Some("Rat".to_string());
+        index.scripts.insert("Rat".to_string(), script);
+        index.race_index.insert(1, "Rat".to_string());
+        let announcement = |delay: i64, text: &str| RaidSpawn {
+            delay: Some(delay),
+            position: None,
+            spread: None,
+            race: None,
+            count: None,
+            message: Some(text.to_string()),
+            fields: Vec::new(),
+        };
+        index.raids.insert(
+            "rats".to_string(),
+            RaidScript {
+                raid_type: Some("SmallRaid".to_string()),
+                interval: Some(3600),
+                spawns: vec![
+                    announcement(0, "Rats are coming!"),
+                    RaidSpawn {
+                        delay: Some(5),
+                        position: Some(RaidPosition { x: 10, y: 10, z: 7 }),
+                        spread: None,
+                        race: Some(1),
+                        count: None,
+                        message: None,
+                        fields: Vec::new(),
+                    },
+                ],
+                fields: Vec::new(),
+            },
+        );
+        index.raids.insert(
+            "orcs".to_string(),
+            RaidScript {
+                raid_type: Some("BigRaid".to_string()),
+                interval: None,
+                spawns: vec![announcement(0, "Orcs are coming!")],
+                fields: Vec::new(),
+            },
+        );
+        world.monster_index = Some(index);
+        world.raid_calendar = serde_yaml::from_str(
+            "interval_window_percent: 0\nraids:\n  - raid: orcs\n    at: daily 12:30\n",
+        )
+        .expect("calendar");
+
+        // Saturday 2024-01-06 12:00 UTC.
+        let noon = 1_704_542_400;
+        assert!(world.tick_raids_at(GameTick(1), noon).is_empty());
+        assert_eq!(world.raid_schedules["rats"].next_at, noon + 3600);
+        assert_eq!(world.raid_schedules["orcs"].next_at, noon + 1800);
+
+        // The big raid waits while the rats still have spawns pending.
+        world.start_raid_at("rats", GameTick(2), noon + 1700).expect("start rats");
+        assert_eq!(world.raid_history.last_run("rats"), Some(noon + 1700));
+        assert_eq!(world.raid_schedules["rats"].next_at, noon + 1700 + 3600);
+        assert!(world.tick_raids_at(GameTick(3), noon + 1800).is_empty());
+        let messages: Vec<String> = world
+            .take_pending_messages(player_id)
+            .into_iter()
+            .map(|message| message.message)
+            .collect();
+        assert_eq!(messages, vec!["Rats are coming!".to_string()]);
+        assert_eq!(world.raid_schedules["orcs"].next_at, noon + 1800 + RAID_POSTPONE_SECS);
+
+        let spawned = world.tick_raids_at(GameTick(7), noon + 1860);
+        assert_eq!(spawned.len(), 1);
+        assert!(world.raid_events.is_empty());
+        world.tick_raids_at(GameTick(8), noon + 1920);
+        assert_eq!(world.raid_history.last_run("orcs"), Some(noon + 1920));
+        assert_eq!(world.raid_schedules["orcs"].next_at, noon + 1800 + 86_400);
+        let messages = world.take_pending_messages(player_id);
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message, "Orcs are coming!");
+        assert_eq!(messages[0].message_type, MESSAGE_WARNING);
+    }
+
+    #[test]
+    fn defeating_monster_awards_experience_and_drops() {
+        let mut world = test_world();
+        let killer_id = PlayerId(1);
+        let killer_pos = Position { x: 20, y: 20, z: 7 };
+        world
+            .players
+            .insert(killer_id, PlayerState::new(killer_id, "Hunter".to_string(), killer_pos));
+
+        let monster_pos = Position { x: 21, y: 20, z: 7 };
+        world.map.tiles.insert(monster_pos, make_tile(monster_pos, false));
+
+        let script_input = r#"
+Name = "Rat"
+RaceNumber = 1
+Experience = 25
+Skills = {(HitPoints, 10, 0, 0, 0, 0, 0)}
+Inventory = {(3031, 2, 1000)}
+Corpse = 4240
+"#;
+        let script = parse_monster_script(script_input).expect("parse script");
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(1, "Rat".to_string());
+        index.scripts.insert("Rat".to_string(), script);
+        world.monster_index = Some(index);
+
+        let monster_id = world
+            .spawn_monster_by_race(1, monster_pos)
+            .expect("spawn monster");
+        let reward = world
+            .apply_damage_to_monster(
+                monster_id,
+                DamageType::Physical,
+                10,
+                DamageSource::Player(killer_id),
+            )
+            .expect("apply damage")
+            .expect("monster reward");
+
+        assert_eq!(reward.experience, 25);
+        assert_eq!(reward.drops.len(), 1);
+        assert_eq!(reward.drops[0].type_id.0, 3031);
+        assert_eq!(reward.drops[0].count, 2);
+
+        let killer = world.players.get(&killer_id).expect("killer exists");
+        assert_eq!(killer.experience, 25);
+
+        let tile = world.map.tile(monster_pos).expect("tile exists");
+        assert_eq!(tile.items.len(), 1);
+        assert_eq!(tile.items[0].type_id.0, 4240);
+        assert_eq!(tile.items[0].count, 1);
+        assert_eq!(tile.items[0].contents.len(), 1);
+        assert_eq!(tile.items[0].contents[0].type_id.0, 3031);
+        assert_eq!(tile.items[0].contents[0].count, 2);
     }
 
     #[test]
-    fn tick_monsters_moves_towards_target() {
+    fn move_inventory_item_rejects_invalid_slot_for_body_position() {
         let mut world = test_world();
-        let origin = Position { x: 50, y: 50, z: 7 };
-        let step = Position { x: 51, y: 50, z: 7 };
-        let target_pos = Position { x: 52, y: 50, z: 7 };
-        for pos in [origin, step, target_pos] {
-            world.map.tiles.insert(pos, make_tile(pos, false));
-        }
+        let mut object_types = ObjectTypeIndex::default();
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(100),
+                name: "Helmet".to_string(),
+                flags: Vec::new(),
+                attributes: vec![ObjectAttribute {
+                    key: "BodyPosition".to_string(),
+                    value: "1".to_string(),
+                }],
+            })
+            .expect("insert object type");
+        world.object_types = Some(object_types);
+
+        let mut item_types = ItemTypeIndex::default();
+        item_types
+            .insert(ItemType {
+                id: ItemTypeId(100),
+                name: "Helmet".to_string(),
+                kind: ItemKind::Armor,
+                stackable: false,
+                has_count: false,
+                container_capacity: None,
+                takeable: true,
+                is_expiring: false,
+                expire_stop: false,
+                expire_time_secs: None,
+                expire_target: None,
+            })
+            .expect("insert item type");
+        world.item_types = Some(item_types);
+
         let player_id = PlayerId(1);
+        let player_pos = Position { x: 10, y: 10, z: 7 };
         world
             .players
-            .insert(player_id, PlayerState::new(player_id, "Target".to_string(), target_pos));
-        let monster_id = CreatureId(2);
-        world.monsters.insert(
-            monster_id,
-            MonsterInstance {
-                id: monster_id,
-                race_number: 1,
-                summoner: None,
-                summoned: false,
-                home_id: None,
-                name: "Hunter".to_string(),
-                position: origin,
-                direction: Direction::South,
-                outfit: DEFAULT_OUTFIT,
-                stats: Stats::default(),
-                experience: 0,
-                loot: MonsterLootTable::default(),
-                inventory: Inventory::default(),
-                inventory_containers: HashMap::new(),
-                corpse_ids: Vec::new(),
-                flags: MonsterFlags::default(),
-                skills: MonsterSkills::default(),
-                attack: 0,
-                defend: 0,
-                armor: 0,
-                poison: 0,
-                spells: Vec::new(),
-                strategy: [100, 0, 0, 0],
-                flee_threshold: 0,
-                lose_target_distance: 8,
-                target: None,
-                damage_by: HashMap::new(),
-                speed: 220,
-                outfit_effect: None,
-                speed_effect: None,
-                strength_effect: None,
-                move_cooldown: Cooldown::new(GameTick(0)),
-                combat_cooldown: Cooldown::new(GameTick(0)),
-                talk_lines: Vec::new(),
-                talk_cooldown: Cooldown::new(GameTick(0)),
-            },
+            .insert(player_id, PlayerState::new(player_id, "Player".to_string(), player_pos));
+        let player = world.players.get_mut(&player_id).expect("player exists");
+        player.inventory.set_slot(
+            InventorySlot::Head,
+            Some(ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: ItemTypeId(100),
+                count: 1,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            }),
         );
 
-        let clock = GameClock::new(Duration::from_millis(100));
-        world.tick_monsters(&clock);
+        let err = world
+            .move_inventory_item(player_id, InventorySlot::Head, InventorySlot::Armor, 1)
+            .expect_err("invalid slot should fail");
+        assert_eq!(err, "item cannot be equipped in slot");
+    }
 
-        let monster = world.monsters.get(&monster_id).expect("monster exists");
-        assert_eq!(monster.position, step);
+    #[test]
+    fn place_on_tile_merges_stackable_items() {
+        let position = Position { x: 1, y: 1, z: 7 };
+        let mut tile = make_tile(position, false);
+        tile.items.push(ItemStack { id: crate::entities::item::ItemId::next(),
+            type_id: ItemTypeId(200),
+            count: 4,
+            attributes: Vec::new(),
+            contents: Vec::new(),
+        });
+
+        place_on_tile(
+            &mut tile,
+            ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: ItemTypeId(200),
+                count: 6,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            },
+            true,
+        )
+        .expect("place on tile");
+
+        assert_eq!(tile.items.len(), 1);
+        assert_eq!(tile.items[0].count, 10);
     }
 
     #[test]
-    fn tick_monsters_moves_away_when_fleeing() {
+    fn cron_container_decay_moves_contents_to_parent_tile() {
         let mut world = test_world();
-        let origin = Position { x: 51, y: 50, z: 7 };
-        let step = Position { x: 50, y: 50, z: 7 };
-        let target_pos = Position { x: 52, y: 50, z: 7 };
-        for pos in [origin, step, target_pos] {
-            world.map.tiles.insert(pos, make_tile(pos, false));
-        }
-        let player_id = PlayerId(1);
-        world
-            .players
-            .insert(player_id, PlayerState::new(player_id, "Target".to_string(), target_pos));
-        let monster_id = CreatureId(2);
-        let mut stats = Stats::default();
-        stats.health = 5;
-        stats.max_health = 5;
-        world.monsters.insert(
-            monster_id,
-            MonsterInstance {
-                id: monster_id,
-                race_number: 1,
-                summoner: None,
-                summoned: false,
-                home_id: None,
-                name: "Runner".to_string(),
-                position: origin,
-                direction: Direction::South,
-                outfit: DEFAULT_OUTFIT,
-                stats,
-                experience: 0,
-                loot: MonsterLootTable::default(),
-                inventory: Inventory::default(),
-                inventory_containers: HashMap::new(),
-                corpse_ids: Vec::new(),
-                flags: MonsterFlags::default(),
-                skills: MonsterSkills::default(),
-                attack: 0,
-                defend: 0,
-                armor: 0,
-                poison: 0,
-                spells: Vec::new(),
-                strategy: [100, 0, 0, 0],
-                flee_threshold: 10,
-                lose_target_distance: 8,
-                target: None,
-                damage_by: HashMap::new(),
-                speed: 220,
-                outfit_effect: None,
-                speed_effect: None,
-                strength_effect: None,
-                move_cooldown: Cooldown::new(GameTick(0)),
-                combat_cooldown: Cooldown::new(GameTick(0)),
-                talk_lines: Vec::new(),
-                talk_cooldown: Cooldown::new(GameTick(0)),
-            },
-        );
+        let mut object_types = ObjectTypeIndex::default();
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(100),
+                name: "Bag".to_string(),
+                flags: vec!["Container".to_string(), "Expire".to_string()],
+                attributes: vec![
+                    ObjectAttribute {
+                        key: "Capacity".to_string(),
+                        value: "10".to_string(),
+                    },
+                    ObjectAttribute {
+                        key: "ExpireTarget".to_string(),
+                        value: "101".to_string(),
+                    },
+                    ObjectAttribute {
+                        key: "TotalExpireTime".to_string(),
+                        value: "1".to_string(),
+                    },
+                ],
+            })
+            .expect("insert container type");
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(101),
+                name: "Bag (decayed)".to_string(),
+                flags: Vec::new(),
+                attributes: Vec::new(),
+            })
+            .expect("insert target type");
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(200),
+                name: "Gem".to_string(),
+                flags: Vec::new(),
+                attributes: Vec::new(),
+            })
+            .expect("insert child type");
+        world.item_types = Some(crate::world::item_types::build_item_types(&object_types));
+        world.object_types = Some(object_types);
+
+        let child = ItemStack { id: crate::entities::item::ItemId::next(),
+            type_id: ItemTypeId(200),
+            count: 1,
+            attributes: Vec::new(),
+            contents: Vec::new(),
+        };
+        let container = ItemStack { id: crate::entities::item::ItemId::next(),
+            type_id: ItemTypeId(100),
+            count: 1,
+            attributes: Vec::new(),
+            contents: vec![child],
+        };
 
-        let clock = GameClock::new(Duration::from_millis(100));
-        world.tick_monsters(&clock);
+        let position = Position { x: 5, y: 5, z: 7 };
+        let mut tile = make_tile(position, false);
+        tile.items.push(container.clone());
+        world.map.tiles.insert(position, tile);
+        world.schedule_cron_for_item_tree(&container);
 
-        let monster = world.monsters.get(&monster_id).expect("monster exists");
-        assert_eq!(monster.position, step);
+        world.cron_round = 1;
+        let processed = world.process_cron_system();
+        assert_eq!(processed, 1);
+
+        let tile = world.map.tile(position).expect("tile exists");
+        assert_eq!(tile.items.len(), 2);
+        assert_eq!(tile.items[0].type_id, ItemTypeId(101));
+        assert!(tile.items[0].contents.is_empty());
+        assert_eq!(tile.items[1].type_id, ItemTypeId(200));
     }
 
     #[test]
-    fn monster_target_ignores_players_in_protection_zone() {
+    fn cron_corpse_decay_deletes_contents() {
         let mut world = test_world();
-        let monster_pos = Position { x: 70, y: 70, z: 7 };
-        let player_pos = Position { x: 71, y: 70, z: 7 };
-        world.map.tiles.insert(monster_pos, make_tile(monster_pos, false));
-        world.map.tiles.insert(player_pos, make_tile(player_pos, true));
-        let player_id = PlayerId(10);
-        world
-            .players
-            .insert(player_id, PlayerState::new(player_id, "Safe".to_string(), player_pos));
+        let mut object_types = ObjectTypeIndex::default();
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(300),
+                name: "Corpse".to_string(),
+                flags: vec![
+                    "Container".to_string(),
+                    "Corpse".to_string(),
+                    "Expire".to_string(),
+                ],
+                attributes: vec![
+                    ObjectAttribute {
+                        key: "ExpireTarget".to_string(),
+                        value: "301".to_string(),
+                    },
+                    ObjectAttribute {
+                        key: "TotalExpireTime".to_string(),
+                        value: "1".to_string(),
+                    },
+                ],
+            })
+            .expect("insert corpse type");
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(301),
+                name: "Bone".to_string(),
+                flags: Vec::new(),
+                attributes: Vec::new(),
+            })
+            .expect("insert corpse target type");
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(302),
+                name: "Loot".to_string(),
+                flags: Vec::new(),
+                attributes: Vec::new(),
+            })
+            .expect("insert loot type");
+        world.item_types = Some(crate::world::item_types::build_item_types(&object_types));
+        world.object_types = Some(object_types);
 
-        let target = world.select_monster_target(
-            monster_pos,
-            None,
-            8,
-            [100, 0, 0, 0],
-            &HashMap::new(),
-            MonsterFlags::default(),
-        );
-        assert!(target.is_none());
+        let child = ItemStack { id: crate::entities::item::ItemId::next(),
+            type_id: ItemTypeId(302),
+            count: 1,
+            attributes: Vec::new(),
+            contents: Vec::new(),
+        };
+        let corpse = ItemStack { id: crate::entities::item::ItemId::next(),
+            type_id: ItemTypeId(300),
+            count: 1,
+            attributes: Vec::new(),
+            contents: vec![child],
+        };
+
+        let position = Position { x: 6, y: 6, z: 7 };
+        let mut tile = make_tile(position, false);
+        tile.items.push(corpse.clone());
+        world.map.tiles.insert(position, tile);
+        world.schedule_cron_for_item_tree(&corpse);
+
+        world.cron_round = 1;
+        let processed = world.process_cron_system();
+        assert_eq!(processed, 1);
+
+        let tile = world.map.tile(position).expect("tile exists");
+        assert_eq!(tile.items.len(), 1);
+        assert_eq!(tile.items[0].type_id, ItemTypeId(301));
+        assert!(tile.items[0].contents.is_empty());
     }
 
     #[test]
-    fn monster_movement_blocks_entering_protection_zone() {
+    fn schedule_cron_respects_remaining_expire_time() {
         let mut world = test_world();
-        let origin = Position { x: 80, y: 80, z: 7 };
-        let destination = Position { x: 81, y: 80, z: 7 };
-        world.map.tiles.insert(origin, make_tile(origin, false));
-        world.map.tiles.insert(destination, make_tile(destination, true));
-        let monster_id = CreatureId(9);
-        world.monsters.insert(
-            monster_id,
-            MonsterInstance {
-                id: monster_id,
-                race_number: 1,
-                summoner: None,
-                summoned: false,
-                home_id: None,
-                name: "PZTester".to_string(),
-                position: origin,
-                direction: Direction::South,
-                outfit: DEFAULT_OUTFIT,
-                stats: Stats::default(),
-                experience: 0,
-                loot: MonsterLootTable::default(),
-                inventory: Inventory::default(),
-                inventory_containers: HashMap::new(),
-                corpse_ids: Vec::new(),
-                flags: MonsterFlags::default(),
-                skills: MonsterSkills::default(),
-                attack: 0,
-                defend: 0,
-                armor: 0,
-                poison: 0,
-                spells: Vec::new(),
-                strategy: [100, 0, 0, 0],
-                flee_threshold: 0,
-                lose_target_distance: 0,
-                target: None,
-                damage_by: HashMap::new(),
-                speed: 220,
-                outfit_effect: None,
-                speed_effect: None,
-                strength_effect: None,
-                move_cooldown: Cooldown::new(GameTick(0)),
-                combat_cooldown: Cooldown::new(GameTick(0)),
-                talk_lines: Vec::new(),
-                talk_cooldown: Cooldown::new(GameTick(0)),
-            },
-        );
+        let mut object_types = ObjectTypeIndex::default();
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(400),
+                name: "Timed".to_string(),
+                flags: vec!["Expire".to_string()],
+                attributes: vec![ObjectAttribute {
+                    key: "TotalExpireTime".to_string(),
+                    value: "10".to_string(),
+                }],
+            })
+            .expect("insert expiring type");
+        world.item_types = Some(crate::world::item_types::build_item_types(&object_types));
+        world.object_types = Some(object_types);
 
-        let err = world
-            .move_monster(monster_id, Direction::East)
-            .expect_err("movement should be blocked");
-        assert_eq!(err, "movement blocked: protection zone");
+        let mut item = ItemStack { id: crate::entities::item::ItemId::next(),
+            type_id: ItemTypeId(400),
+            count: 1,
+            attributes: Vec::new(),
+            contents: Vec::new(),
+        };
+        set_remaining_expire_secs(&mut item, 2);
+        world.schedule_cron_for_item_tree(&item);
+
+        assert_eq!(world.cron.get_remaining(item.id, world.cron_round), Some(2));
     }
 
     #[test]
-    fn monster_kicks_blocking_box() {
+    fn expirestop_preserves_and_restores_remaining_time() {
         let mut world = test_world();
-        let origin = Position { x: 40, y: 40, z: 7 };
-        let destination = Position { x: 41, y: 40, z: 7 };
-        let pushed = Position { x: 42, y: 40, z: 7 };
-        world.map.tiles.insert(origin, make_tile(origin, false));
-        world.map.tiles.insert(destination, make_tile(destination, false));
-        world.map.tiles.insert(pushed, make_tile(pushed, false));
         let mut object_types = ObjectTypeIndex::default();
         object_types
             .insert(ObjectType {
-                id: ItemTypeId(100),
-                name: "Box".to_string(),
-                flags: vec!["Unpass".to_string(), "Take".to_string()],
+                id: ItemTypeId(500),
+                name: "Timed".to_string(),
+                flags: vec!["Expire".to_string()],
+                attributes: vec![ObjectAttribute {
+                    key: "TotalExpireTime".to_string(),
+                    value: "10".to_string(),
+                }],
+            })
+            .expect("insert expiring type");
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(501),
+                name: "Stopped".to_string(),
+                flags: vec!["ExpireStop".to_string()],
                 attributes: Vec::new(),
             })
-            .expect("insert box type");
+            .expect("insert stop type");
+        world.item_types = Some(crate::world::item_types::build_item_types(&object_types));
         world.object_types = Some(object_types);
-        let dest_tile = world.map.tile_mut(destination).expect("dest tile");
-        place_on_tile(
-            dest_tile,
+
+        let mut item = ItemStack { id: crate::entities::item::ItemId::next(),
+            type_id: ItemTypeId(500),
+            count: 1,
+            attributes: Vec::new(),
+            contents: Vec::new(),
+        };
+        world.schedule_cron_for_item_tree(&item);
+
+        world.cron_round = 3;
+        assert_eq!(world.cron.get_remaining(item.id, world.cron_round), Some(7));
+
+        world
+            .change_itemstack_type(&mut item, ItemTypeId(501), 0)
+            .expect("change to stop");
+        assert!(item_saved_expire_secs(&item).unwrap_or(0) > 0);
+        assert_eq!(world.cron.get_remaining(item.id, world.cron_round), None);
+
+        world
+            .change_itemstack_type(&mut item, ItemTypeId(500), 0)
+            .expect("change to expiring");
+        assert_eq!(world.cron.get_remaining(item.id, world.cron_round), Some(7));
+    }
+
+    #[test]
+    fn insert_into_container_append_merges_stackable() {
+        let mut container = OpenContainer {
+            container_id: 1,
+            item_type: ItemTypeId(400),
+            name: "Bag".to_string(),
+            capacity: 10,
+            has_parent: false,
+            parent_container_id: None,
+            parent_slot: None,
+            source_slot: None,
+            source_position: None,
+            source_stack_pos: None,
+            items: vec![ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: ItemTypeId(300),
+                count: 5,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            }],
+        };
+
+        let update = insert_into_container(
+            &mut container,
+            1,
+            0xff,
             ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: ItemTypeId(100),
-                count: 1,
+                type_id: ItemTypeId(300),
+                count: 7,
                 attributes: Vec::new(),
                 contents: Vec::new(),
             },
-            false,
+            true,
         )
-        .expect("place box");
-        let monster_id = CreatureId(2);
-        world.monsters.insert(
-            monster_id,
-            MonsterInstance {
-                id: monster_id,
-                race_number: 1,
-                summoner: None,
-                summoned: false,
-                home_id: None,
-                name: "Kicker".to_string(),
-                position: origin,
-                direction: Direction::South,
-                outfit: DEFAULT_OUTFIT,
-                stats: Stats::default(),
-                experience: 0,
-                loot: MonsterLootTable::default(),
-                inventory: Inventory::default(),
-                inventory_containers: HashMap::new(),
-                corpse_ids: Vec::new(),
-                flags: MonsterFlags {
-                    kick_boxes: true,
-                    ..MonsterFlags::default()
-                },
-                skills: MonsterSkills::default(),
-                attack: 0,
-                defend: 0,
-                armor: 0,
-                poison: 0,
-                spells: Vec::new(),
-                strategy: [100, 0, 0, 0],
-                flee_threshold: 0,
-                lose_target_distance: 0,
-                target: None,
-                damage_by: HashMap::new(),
-                speed: 220,
-                outfit_effect: None,
-                speed_effect: None,
-                strength_effect: None,
-                move_cooldown: Cooldown::new(GameTick(0)),
-                combat_cooldown: Cooldown::new(GameTick(0)),
-                talk_lines: Vec::new(),
-                talk_cooldown: Cooldown::new(GameTick(0)),
-            },
-        );
+        .expect("insert into container");
 
-        let moved = world
-            .move_monster(monster_id, Direction::East)
-            .expect("movement should succeed");
-        assert_eq!(moved, destination);
-        let monster = world.monsters.get(&monster_id).expect("monster exists");
-        assert_eq!(monster.position, destination);
-        let dest_tile = world.map.tile(destination).expect("dest tile");
-        assert!(dest_tile.items.is_empty());
-        let pushed_tile = world.map.tile(pushed).expect("pushed tile");
-        assert_eq!(pushed_tile.items.len(), 1);
-        assert_eq!(pushed_tile.items[0].type_id, ItemTypeId(100));
+        let expected = ItemStack { id: crate::entities::item::ItemId::next(),
+            type_id: ItemTypeId(300),
+            count: 12,
+            attributes: Vec::new(),
+            contents: Vec::new(),
+        };
+        assert_eq!(container.items.len(), 1);
+        assert_eq!(container.items[0], expected);
+        assert_eq!(
+            update,
+            ContainerUpdate::Update {
+                container_id: 1,
+                slot: 0,
+                item: expected
+            }
+        );
     }
 
     #[test]
-    fn monster_kicks_blocking_creature() {
+    fn moveuse_use_emits_effect_text_and_damage() {
         let mut world = test_world();
-        let origin = Position { x: 45, y: 40, z: 7 };
-        let destination = Position { x: 46, y: 40, z: 7 };
-        let pushed = Position { x: 47, y: 40, z: 7 };
-        for pos in [origin, destination, pushed] {
-            world.map.tiles.insert(pos, make_tile(pos, false));
-        }
-        let player_id = PlayerId(5);
+        let player_id = PlayerId(7);
+        let player_pos = Position { x: 10, y: 10, z: 7 };
         world.players.insert(
             player_id,
-            PlayerState::new(player_id, "Blocker".to_string(), destination),
-        );
-        let monster_id = CreatureId(6);
-        world.monsters.insert(
-            monster_id,
-            MonsterInstance {
-                id: monster_id,
-                race_number: 1,
-                summoner: None,
-                summoned: false,
-                home_id: None,
-                name: "Kicker".to_string(),
-                position: origin,
-                direction: Direction::South,
-                outfit: DEFAULT_OUTFIT,
-                stats: Stats::default(),
-                experience: 0,
-                loot: MonsterLootTable::default(),
-                inventory: Inventory::default(),
-                inventory_containers: HashMap::new(),
-                corpse_ids: Vec::new(),
-                flags: MonsterFlags {
-                    kick_creatures: true,
-                    ..MonsterFlags::default()
-                },
-                skills: MonsterSkills::default(),
-                attack: 0,
-                defend: 0,
-                armor: 0,
-                poison: 0,
-                spells: Vec::new(),
-                strategy: [100, 0, 0, 0],
-                flee_threshold: 0,
-                lose_target_distance: 0,
-                target: None,
-                damage_by: HashMap::new(),
-                speed: 220,
-                outfit_effect: None,
-                speed_effect: None,
-                strength_effect: None,
-                move_cooldown: Cooldown::new(GameTick(0)),
-                combat_cooldown: Cooldown::new(GameTick(0)),
-                talk_lines: Vec::new(),
-                talk_cooldown: Cooldown::new(GameTick(0)),
-            },
+            PlayerState::new(player_id, "User".to_string(), player_pos),
         );
 
-        let moved = world
-            .move_monster(monster_id, Direction::East)
-            .expect("movement should succeed");
-        assert_eq!(moved, destination);
-        let monster = world.monsters.get(&monster_id).expect("monster exists");
-        assert_eq!(monster.position, destination);
-        let player = world.players.get(&player_id).expect("player exists");
-        assert_eq!(player.position, pushed);
-    }
-
-    #[test]
-    fn monster_kick_creature_respects_unpushable() {
-        let mut world = test_world();
-        let origin = Position { x: 50, y: 40, z: 7 };
-        let destination = Position { x: 51, y: 40, z: 7 };
-        let pushed = Position { x: 52, y: 40, z: 7 };
-        for pos in [origin, destination, pushed] {
-            world.map.tiles.insert(pos, make_tile(pos, false));
-        }
-        let blocker_id = CreatureId(7);
-        world.monsters.insert(
-            blocker_id,
-            MonsterInstance {
-                id: blocker_id,
-                race_number: 1,
-                summoner: None,
-                summoned: false,
-                home_id: None,
-                name: "Blocker".to_string(),
-                position: destination,
-                direction: Direction::South,
-                outfit: DEFAULT_OUTFIT,
-                stats: Stats::default(),
-                experience: 0,
-                loot: MonsterLootTable::default(),
-                inventory: Inventory::default(),
-                inventory_containers: HashMap::new(),
-                corpse_ids: Vec::new(),
-                flags: MonsterFlags {
-                    unpushable: true,
-                    ..MonsterFlags::default()
-                },
-                skills: MonsterSkills::default(),
-                attack: 0,
-                defend: 0,
-                armor: 0,
-                poison: 0,
-                spells: Vec::new(),
-                strategy: [100, 0, 0, 0],
-                flee_threshold: 0,
-                lose_target_distance: 0,
-                target: None,
-                damage_by: HashMap::new(),
-                speed: 220,
-                outfit_effect: None,
-                speed_effect: None,
-                strength_effect: None,
-                move_cooldown: Cooldown::new(GameTick(0)),
-                combat_cooldown: Cooldown::new(GameTick(0)),
-                talk_lines: Vec::new(),
-                talk_cooldown: Cooldown::new(GameTick(0)),
-            },
-        );
-        let monster_id = CreatureId(8);
-        world.monsters.insert(
-            monster_id,
-            MonsterInstance {
-                id: monster_id,
-                race_number: 1,
-                summoner: None,
-                summoned: false,
-                home_id: None,
-                name: "Kicker".to_string(),
-                position: origin,
-                direction: Direction::South,
-                outfit: DEFAULT_OUTFIT,
-                stats: Stats::default(),
-                experience: 0,
-                loot: MonsterLootTable::default(),
-                inventory: Inventory::default(),
-                inventory_containers: HashMap::new(),
-                corpse_ids: Vec::new(),
-                flags: MonsterFlags {
-                    kick_creatures: true,
-                    ..MonsterFlags::default()
-                },
-                skills: MonsterSkills::default(),
-                attack: 0,
-                defend: 0,
-                armor: 0,
-                poison: 0,
-                spells: Vec::new(),
-                strategy: [100, 0, 0, 0],
-                flee_threshold: 0,
-                lose_target_distance: 0,
-                target: None,
-                damage_by: HashMap::new(),
-                speed: 220,
-                outfit_effect: None,
-                speed_effect: None,
-                strength_effect: None,
-                move_cooldown: Cooldown::new(GameTick(0)),
-                combat_cooldown: Cooldown::new(GameTick(0)),
-                talk_lines: Vec::new(),
-                talk_cooldown: Cooldown::new(GameTick(0)),
+        let item_type = ItemTypeId(100);
+        let tile_pos = Position { x: 11, y: 10, z: 7 };
+        world.map.tiles.insert(tile_pos, make_tile(tile_pos, false));
+        world
+            .map
+            .tile_mut(tile_pos)
+            .expect("tile exists")
+            .items
+            .push(ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: item_type,
+                count: 1,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            });
+
+        let rule = MoveUseRule {
+            event: MoveUseExpr {
+                name: "Use".to_string(),
+                args: Vec::new(),
             },
-        );
+            conditions: vec![MoveUseExpr {
+                name: "IsType".to_string(),
+                args: vec!["Obj1".to_string(), "100".to_string()],
+            }],
+            actions: vec![
+                MoveUseExpr {
+                    name: "Effect".to_string(),
+                    args: vec!["User".to_string(), "13".to_string()],
+                },
+                MoveUseExpr {
+                    name: "Text".to_string(),
+                    args: vec![
+                        "User".to_string(),
+                        "\"Hello\"".to_string(),
+                        "20".to_string(),
+                    ],
+                },
+                MoveUseExpr {
+                    name: "Damage".to_string(),
+                    args: vec![
+                        "User".to_string(),
+                        "User".to_string(),
+                        "1".to_string(),
+                        "5".to_string(),
+                    ],
+                },
+            ],
+            line_no: 1,
+        };
 
-        let err = world
-            .move_monster(monster_id, Direction::East)
-            .expect_err("movement should be blocked");
-        assert_eq!(err, "movement blocked: creature");
-        let monster = world.monsters.get(&monster_id).expect("monster exists");
-        assert_eq!(monster.position, origin);
-        let blocker = world.monsters.get(&blocker_id).expect("blocker exists");
-        assert_eq!(blocker.position, destination);
+        world.moveuse = Some(MoveUseDatabase {
+            sections: vec![MoveUseSection {
+                name: "Root".to_string(),
+                rules: vec![rule],
+                children: Vec::new(),
+            }],
+        });
+
+        let outcome = world
+            .use_object(player_id, tile_pos, item_type)
+            .expect("use object");
+        assert_eq!(outcome.matched_rule, Some(1));
+        assert_eq!(outcome.effects.len(), 1);
+        assert_eq!(outcome.effects[0].position, player_pos);
+        assert_eq!(outcome.effects[0].effect_id, 13);
+        assert_eq!(outcome.texts.len(), 1);
+        assert_eq!(outcome.texts[0].position, player_pos);
+        assert_eq!(outcome.texts[0].message, "Hello");
+        assert_eq!(outcome.texts[0].mode, 20);
+        assert_eq!(outcome.damages.len(), 1);
+        assert_eq!(outcome.damages[0].amount, 5);
+
+        let player = world.players.get(&player_id).expect("player exists");
+        assert_eq!(player.stats.health, 95);
     }
 
     #[test]
-    fn tick_monsters_respects_cooldown() {
+    fn moveuse_delete_in_inventory_removes_item() {
         let mut world = test_world();
-        let origin = Position { x: 60, y: 60, z: 7 };
-        world.map.tiles.insert(origin, make_tile(origin, false));
-        let player_id = PlayerId(1);
+        let player_id = PlayerId(8);
+        let player_pos = Position { x: 20, y: 20, z: 7 };
+        world.players.insert(
+            player_id,
+            PlayerState::new(player_id, "User".to_string(), player_pos),
+        );
+        let player = world.players.get_mut(&player_id).expect("player exists");
+        player.inventory.set_slot(
+            InventorySlot::Backpack,
+            Some(ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: ItemTypeId(200),
+                count: 5,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            }),
+        );
+
+        let trigger_type = ItemTypeId(100);
+        let tile_pos = Position { x: 21, y: 20, z: 7 };
+        world.map.tiles.insert(tile_pos, make_tile(tile_pos, false));
         world
-            .players
-            .insert(player_id, PlayerState::new(player_id, "Nearby".to_string(), origin));
-        let monster_id = CreatureId(3);
-        world.monsters.insert(
-            monster_id,
-            MonsterInstance {
-                id: monster_id,
-                race_number: 1,
-                summoner: None,
-                summoned: false,
-                home_id: None,
-                name: "Sleeper".to_string(),
-                position: origin,
-                direction: Direction::South,
-                outfit: DEFAULT_OUTFIT,
-                stats: Stats::default(),
-                experience: 0,
-                loot: MonsterLootTable::default(),
-                inventory: Inventory::default(),
-                inventory_containers: HashMap::new(),
-                corpse_ids: Vec::new(),
-                flags: MonsterFlags::default(),
-                skills: MonsterSkills::default(),
-                attack: 0,
-                defend: 0,
-                armor: 0,
-                poison: 0,
-                spells: Vec::new(),
-                strategy: [100, 0, 0, 0],
-                flee_threshold: 0,
-                lose_target_distance: 0,
-                target: None,
-                damage_by: HashMap::new(),
-                speed: 220,
-                outfit_effect: None,
-                speed_effect: None,
-                strength_effect: None,
-                move_cooldown: Cooldown::new(GameTick(5)),
-                combat_cooldown: Cooldown::new(GameTick(0)),
-                talk_lines: Vec::new(),
-                talk_cooldown: Cooldown::new(GameTick(0)),
+            .map
+            .tile_mut(tile_pos)
+            .expect("tile exists")
+            .items
+            .push(ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: trigger_type,
+                count: 1,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            });
+
+        let rule = MoveUseRule {
+            event: MoveUseExpr {
+                name: "Use".to_string(),
+                args: Vec::new(),
             },
-        );
+            conditions: vec![MoveUseExpr {
+                name: "IsType".to_string(),
+                args: vec!["Obj1".to_string(), "100".to_string()],
+            }],
+            actions: vec![MoveUseExpr {
+                name: "DeleteInInventory".to_string(),
+                args: vec![
+                    "User".to_string(),
+                    "200".to_string(),
+                    "3".to_string(),
+                ],
+            }],
+            line_no: 1,
+        };
 
-        let clock = GameClock::new(Duration::from_millis(100));
-        let moved = world.tick_monsters(&clock);
-        assert_eq!(moved.moved, 0);
-        let monster = world.monsters.get(&monster_id).expect("monster exists");
-        assert_eq!(monster.position, origin);
+        world.moveuse = Some(MoveUseDatabase {
+            sections: vec![MoveUseSection {
+                name: "Root".to_string(),
+                rules: vec![rule],
+                children: Vec::new(),
+            }],
+        });
+
+        world
+            .use_object(player_id, tile_pos, trigger_type)
+            .expect("use object");
+        let player = world.players.get(&player_id).expect("player exists");
+        let item = player
+            .inventory
+            .slot(InventorySlot::Backpack)
+            .expect("item exists");
+        assert_eq!(item.count, 2);
     }
 
     #[test]
-    fn tick_monsters_resets_cooldown_after_tick() {
+    fn moveuse_move_top_rel_teleports_player() {
         let mut world = test_world();
-        let origin = Position { x: 70, y: 70, z: 7 };
+        let player_id = PlayerId(9);
+        let origin = Position { x: 30, y: 30, z: 7 };
+        let collision = Position { x: 31, y: 30, z: 7 };
+        let target = Position { x: 31, y: 30, z: 8 };
         world.map.tiles.insert(origin, make_tile(origin, false));
-        let neighbor = origin.step(Direction::North).expect("step");
-        world.map.tiles.insert(neighbor, make_tile(neighbor, false));
-        let player_id = PlayerId(1);
+        world.map.tiles.insert(collision, make_tile(collision, false));
+        world.map.tiles.insert(target, make_tile(target, false));
+
+        world.players.insert(
+            player_id,
+            PlayerState::new(player_id, "Mover".to_string(), origin),
+        );
         world
-            .players
-            .insert(player_id, PlayerState::new(player_id, "Nearby".to_string(), origin));
-        let monster_id = CreatureId(4);
-        world.monsters.insert(
-            monster_id,
-            MonsterInstance {
-                id: monster_id,
-                race_number: 1,
-                summoner: None,
-                summoned: false,
-                home_id: None,
-                name: "Runner".to_string(),
-                position: origin,
-                direction: Direction::South,
-                outfit: DEFAULT_OUTFIT,
-                stats: Stats::default(),
-                experience: 0,
-                loot: MonsterLootTable::default(),
-                inventory: Inventory::default(),
-                inventory_containers: HashMap::new(),
-                corpse_ids: Vec::new(),
-                flags: MonsterFlags::default(),
-                skills: MonsterSkills::default(),
-                attack: 0,
-                defend: 0,
-                armor: 0,
-                poison: 0,
-                spells: Vec::new(),
-                strategy: [100, 0, 0, 0],
-                flee_threshold: 0,
-                lose_target_distance: 0,
-                target: None,
-                damage_by: HashMap::new(),
-                speed: 220,
-                outfit_effect: None,
-                speed_effect: None,
-                strength_effect: None,
-                move_cooldown: Cooldown::new(GameTick(0)),
-                combat_cooldown: Cooldown::new(GameTick(0)),
-                talk_lines: Vec::new(),
-                talk_cooldown: Cooldown::new(GameTick(0)),
+            .map
+            .tile_mut(collision)
+            .expect("tile exists")
+            .items
+            .push(ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: ItemTypeId(300),
+                count: 1,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            });
+
+        let rule = MoveUseRule {
+            event: MoveUseExpr {
+                name: "Collision".to_string(),
+                args: Vec::new(),
             },
-        );
+            conditions: vec![MoveUseExpr {
+                name: "IsType".to_string(),
+                args: vec!["Obj1".to_string(), "300".to_string()],
+            }],
+            actions: vec![MoveUseExpr {
+                name: "MoveTopRel".to_string(),
+                args: vec!["Obj1".to_string(), "[0,0,1]".to_string()],
+            }],
+            line_no: 1,
+        };
+
+        world.moveuse = Some(MoveUseDatabase {
+            sections: vec![MoveUseSection {
+                name: "Root".to_string(),
+                rules: vec![rule],
+                children: Vec::new(),
+            }],
+        });
 
         let clock = GameClock::new(Duration::from_millis(100));
-        world.tick_monsters(&clock);
-        let monster = world.monsters.get(&monster_id).expect("monster exists");
-        assert_eq!(
-            monster.move_cooldown.ready_at(),
-            GameTick(MONSTER_MOVE_INTERVAL_TICKS)
-        );
+        world
+            .move_player(player_id, Direction::East, &clock)
+            .expect("move player");
+        let player = world.players.get(&player_id).expect("player exists");
+        assert_eq!(player.position, target);
     }
 
     #[test]
-    fn tick_npcs_respects_cooldown() {
+    fn moveuse_retrieve_ignores_immovable_top_item() {
         let mut world = test_world();
-        let origin = Position { x: 80, y: 80, z: 7 };
-        world.map.tiles.insert(origin, make_tile(origin, false));
-        let player_id = PlayerId(1);
+        let player_id = PlayerId(91);
+        let player_pos = Position { x: 70, y: 70, z: 7 };
+        let target_pos = Position { x: 71, y: 70, z: 7 };
+        let destination_pos = Position { x: 72, y: 70, z: 7 };
+        world.players.insert(
+            player_id,
+            PlayerState::new(player_id, "Roper".to_string(), player_pos),
+        );
+        let player = world.players.get_mut(&player_id).expect("player exists");
+        player.inventory.set_slot(
+            InventorySlot::Backpack,
+            Some(ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: ItemTypeId(3003),
+                count: 1,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            }),
+        );
+
+        world.map.tiles.insert(target_pos, make_tile(target_pos, false));
         world
-            .players
-            .insert(player_id, PlayerState::new(player_id, "Nearby".to_string(), origin));
-        let npc_id = CreatureId(0x4000_0001);
-        world.npcs.insert(
-            npc_id,
-            NpcInstance {
-                id: npc_id,
-                script_key: "test".to_string(),
-                name: "Npc".to_string(),
-                position: origin,
-                direction: Direction::South,
-                home: origin,
-                outfit: DEFAULT_OUTFIT,
-                radius: 2,
-                focused: None,
-                focus_expires_at: None,
-                queue: VecDeque::new(),
-                move_cooldown: Cooldown::new(GameTick(10)),
+            .map
+            .tile_mut(target_pos)
+            .expect("target tile exists")
+            .items
+            .extend([
+                ItemStack {
+                    id: crate::entities::item::ItemId::next(),
+                    type_id: ItemTypeId(501),
+                    count: 1,
+                    attributes: Vec::new(),
+                    contents: Vec::new(),
+                },
+                ItemStack {
+                    id: crate::entities::item::ItemId::next(),
+                    type_id: ItemTypeId(500),
+                    count: 1,
+                    attributes: Vec::new(),
+                    contents: Vec::new(),
+                },
+            ]);
+        world
+            .map
+            .tiles
+            .insert(destination_pos, make_tile(destination_pos, false));
+
+        let mut object_types = ObjectTypeIndex::default();
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(501),
+                name: "movable".to_string(),
+                flags: vec!["Take".to_string()],
+                attributes: Vec::new(),
+            })
+            .expect("insert movable object type");
+        object_types
+            .insert(ObjectType {
+                id: ItemTypeId(500),
+                name: "immovable".to_string(),
+                flags: vec!["Unmove".to_string()],
+                attributes: Vec::new(),
+            })
+            .expect("insert immovable object type");
+        world.object_types = Some(object_types);
+
+        let rule = MoveUseRule {
+            event: MoveUseExpr {
+                name: "MultiUse".to_string(),
+                args: Vec::new(),
             },
-        );
+            conditions: vec![
+                MoveUseExpr {
+                    name: "IsType".to_string(),
+                    args: vec!["Obj1".to_string(), "3003".to_string()],
+                },
+                MoveUseExpr {
+                    name: "IsType".to_string(),
+                    args: vec!["Obj2".to_string(), "500".to_string()],
+                },
+            ],
+            actions: vec![MoveUseExpr {
+                name: "Retrieve".to_string(),
+                args: vec![
+                    "Obj2".to_string(),
+                    "[0,0,0]".to_string(),
+                    "[1,0,0]".to_string(),
+                ],
+            }],
+            line_no: 1,
+        };
+        world.moveuse = Some(MoveUseDatabase {
+            sections: vec![MoveUseSection {
+                name: "Root".to_string(),
+                rules: vec![rule],
+                children: Vec::new(),
+            }],
+        });
 
-        let clock = GameClock::new(Duration::from_millis(100));
-        let moved = world.tick_npcs(&clock);
-        assert_eq!(moved.len(), 0);
-        let npc = world.npcs.get(&npc_id).expect("npc exists");
-        assert_eq!(npc.position, origin);
+        world
+            .use_object_on_position_with_clock(
+                player_id,
+                UseObjectSource::Inventory(InventorySlot::Backpack),
+                ItemTypeId(3003),
+                target_pos,
+                ItemTypeId(500),
+                None,
+            )
+            .expect("multi-use should succeed");
+
+        let target_tile = world.map.tile(target_pos).expect("target tile exists");
+        assert_eq!(target_tile.items.len(), 2);
+        assert_eq!(target_tile.items[0].type_id, ItemTypeId(501));
+        assert_eq!(target_tile.items[1].type_id, ItemTypeId(500));
+        let destination_tile = world
+            .map
+            .tile(destination_pos)
+            .expect("destination tile exists");
+        assert!(destination_tile.items.is_empty());
     }
 
     #[test]
-    fn tick_npcs_resets_cooldown_after_tick() {
+    fn moveuse_move_sets_player_position() {
         let mut world = test_world();
-        let origin = Position { x: 90, y: 90, z: 7 };
-        world.map.tiles.insert(origin, make_tile(origin, false));
-        let neighbor = origin.step(Direction::North).expect("step");
-        world.map.tiles.insert(neighbor, make_tile(neighbor, false));
-        let player_id = PlayerId(1);
-        world
-            .players
-            .insert(player_id, PlayerState::new(player_id, "Nearby".to_string(), origin));
-        let npc_id = CreatureId(0x4000_0002);
-        world.npcs.insert(
-            npc_id,
-            NpcInstance {
-                id: npc_id,
-                script_key: "test".to_string(),
-                name: "Npc".to_string(),
-                position: origin,
-                direction: Direction::South,
-                home: origin,
-                outfit: DEFAULT_OUTFIT,
-                radius: 2,
-                focused: None,
-                focus_expires_at: None,
-                queue: VecDeque::new(),
-                move_cooldown: Cooldown::new(GameTick(0)),
-            },
+        let player_id = PlayerId(10);
+        let player_pos = Position { x: 40, y: 40, z: 7 };
+        let target = Position { x: 41, y: 40, z: 7 };
+        world.players.insert(
+            player_id,
+            PlayerState::new(player_id, "User".to_string(), player_pos),
         );
 
-        let clock = GameClock::new(Duration::from_millis(100));
-        let _ = world.tick_npcs(&clock);
-        let npc = world.npcs.get(&npc_id).expect("npc exists");
-        assert_eq!(
-            npc.move_cooldown.ready_at(),
-            GameTick(NPC_MOVE_INTERVAL_TICKS)
-        );
-    }
+        let trigger_type = ItemTypeId(110);
+        world.map.tiles.insert(player_pos, make_tile(player_pos, false));
+        world.map.tiles.insert(target, make_tile(target, false));
+        world
+            .map
+            .tile_mut(player_pos)
+            .expect("tile exists")
+            .items
+            .push(ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: trigger_type,
+                count: 1,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            });
 
-    #[test]
-    fn raid_schedule_spawns_monsters() {
-        let mut world = test_world();
-        let mut index = MonsterIndex::default();
-        let mut script = MonsterScript::default();
-        script.name = Some("Badger".to_string());
-        index.scripts.insert("Badger".to_string(), script);
-        index.race_index.insert(1, "Badger".to_string());
-        index.raids.insert(
-            "TestRaid".to_string(),
-            RaidScript {
-                raid_type: Some("SmallRaid".to_string()),
-                interval: None,
-                spawns: vec![RaidSpawn {
-                    delay: Some(5),
-                    position: Some(RaidPosition { x: 10, y: 10, z: 7 }),
-                    spread: None,
-                    race: Some(1),
-                    count: None,
-                    message: None,
-                    fields: Vec::new(),
-                }],
-                fields: Vec::new(),
+        let rule = MoveUseRule {
+            event: MoveUseExpr {
+                name: "Use".to_string(),
+                args: Vec::new(),
             },
-        );
-        world.monster_index = Some(index);
+            conditions: vec![MoveUseExpr {
+                name: "IsType".to_string(),
+                args: vec!["Obj1".to_string(), "110".to_string()],
+            }],
+            actions: vec![MoveUseExpr {
+                name: "Move".to_string(),
+                args: vec!["User".to_string(), "[41,40,7]".to_string()],
+            }],
+            line_no: 1,
+        };
 
-        world
-            .schedule_raid("TestRaid", 1, GameTick(10))
-            .expect("schedule");
-        assert_eq!(world.raid_events.len(), 1);
-        assert_eq!(world.raid_events[0].at, GameTick(15));
+        world.moveuse = Some(MoveUseDatabase {
+            sections: vec![MoveUseSection {
+                name: "Root".to_string(),
+                rules: vec![rule],
+                children: Vec::new(),
+            }],
+        });
 
-        let none = world.spawn_due_raids(GameTick(14));
-        assert!(none.is_empty());
-        let spawned = world.spawn_due_raids(GameTick(15));
-        assert_eq!(spawned.len(), 1);
-        let id = spawned[0];
-        let monster = world.monsters.get(&id).expect("monster spawned");
-        assert_eq!(monster.name, "Badger");
-        assert_eq!(monster.position, Position { x: 10, y: 10, z: 7 });
+        world
+            .use_object(player_id, player_pos, trigger_type)
+            .expect("use object");
+        let player = world.players.get(&player_id).expect("player exists");
+        assert_eq!(player.position, target);
     }
 
     #[test]
-    fn defeating_monster_awards_experience_and_drops() {
+    fn moveuse_set_start_updates_start_position() {
         let mut world = test_world();
-        let killer_id = PlayerId(1);
-        let killer_pos = Position { x: 20, y: 20, z: 7 };
-        world
-            .players
-            .insert(killer_id, PlayerState::new(killer_id, "Hunter".to_string(), killer_pos));
-
-        let monster_pos = Position { x: 21, y: 20, z: 7 };
-        world.map.tiles.insert(monster_pos, make_tile(monster_pos, false));
-
-        let script_input = r#"
-Name = "Rat"
-RaceNumber = 1
-Experience = 25
-Skills = {(HitPoints, 10, 0, 0, 0, 0, 0)}
-Inventory = {(3031, 2, 1000)}
-Corpse = 4240
-"#;
-        let script = parse_monster_script(script_input).expect("parse script");
-        let mut index = MonsterIndex::default();
-        index.race_index.insert(1, "Rat".to_string());
-        index.scripts.insert("Rat".to_string(), script);
-        world.monster_index = Some(index);
+        let player_id = PlayerId(11);
+        let player_pos = Position { x: 50, y: 50, z: 7 };
+        let start = Position { x: 51, y: 50, z: 7 };
+        world.players.insert(
+            player_id,
+            PlayerState::new(player_id, "User".to_string(), player_pos),
+        );
 
-        let monster_id = world
-            .spawn_monster_by_race(1, monster_pos)
-            .expect("spawn monster");
-        let reward = world
-            .apply_damage_to_monster(monster_id, DamageType::Physical, 10, Some(killer_id))
-            .expect("apply damage")
-            .expect("monster reward");
+        let trigger_type = ItemTypeId(120);
+        world.map.tiles.insert(player_pos, make_tile(player_pos, false));
+        world
+            .map
+            .tile_mut(player_pos)
+            .expect("tile exists")
+            .items
+            .push(ItemStack { id: crate::entities::item::ItemId::next(),
+                type_id: trigger_type,
+                count: 1,
+                attributes: Vec::new(),
+                contents: Vec::new(),
+            });
 
-        assert_eq!(reward.experience, 25);
-        assert_eq!(reward.drops.len(), 1);
-        assert_eq!(reward.drops[0].type_id.0, 3031);
-        assert_eq!(reward.drops[0].count, 2);
+        let rule = MoveUseRule {
+            event: MoveUseExpr {
+                name: "Use".to_string(),
+                args: Vec::new(),
+            },
+            conditions: vec![MoveUseExpr {
+                name: "IsType".to_string(),
+                args: vec!["Obj1".to_string(), "120".to_string()],
+            }],
+            actions: vec![MoveUseExpr {
+                name: "SetStart".to_string(),
+                args: vec!["User".to_string(), "[51,50,7]".to_string()],
+            }],
+            line_no: 1,
+        };
 
-        let killer = world.players.get(&killer_id).expect("killer exists");
-        assert_eq!(killer.experience, 25);
+        world.moveuse = Some(MoveUseDatabase {
+            sections: vec![MoveUseSection {
+                name: "Root".to_string(),
+                rules: vec![rule],
+                children: Vec::new(),
+            }],
+        });
 
-        let tile = world.map.tile(monster_pos).expect("tile exists");
-        assert_eq!(tile.items.len(), 1);
-        assert_eq!(tile.items[0].type_id.0, 4240);
-        assert_eq!(tile.items[0].count, 1);
-        assert_eq!(tile.items[0].contents.len(), 1);
-        assert_eq!(tile.items[0].contents[0].type_id.0, 3031);
-        assert_eq!(tile.items[0].contents[0].count, 2);
+        world
+            .use_object(player_id, player_pos, trigger_type)
+            .expect("use object");
+        let player = world.players.get(&player_id).expect("player exists");
+        assert_eq!(player.start_position, start);
     }
 
     #[test]
-    fn move_inventory_item_rejects_invalid_slot_for_body_position() {
+    fn player_death_leaves_corpse_and_respawns_at_temple() {
         let mut world = test_world();
-        let mut object_types = ObjectTypeIndex::default();
-        object_types
-            .insert(ObjectType {
-                id: ItemTypeId(100),
-                name: "Helmet".to_string(),
-                flags: Vec::new(),
-                attributes: vec![ObjectAttribute {
-                    key: "BodyPosition".to_string(),
-                    value: "1".to_string(),
-                }],
-            })
-            .expect("insert object type");
-        world.object_types = Some(object_types);
-
-        let mut item_types = ItemTypeIndex::default();
-        item_types
-            .insert(ItemType {
-                id: ItemTypeId(100),
-                name: "Helmet".to_string(),
-                kind: ItemKind::Armor,
-                stackable: false,
-                has_count: false,
-                container_capacity: None,
-                takeable: true,
-                is_expiring: false,
-                expire_stop: false,
-                expire_time_secs: None,
-                expire_target: None,
-            })
-            .expect("insert item type");
-        world.item_types = Some(item_types);
-
-        let player_id = PlayerId(1);
-        let player_pos = Position { x: 10, y: 10, z: 7 };
-        world
-            .players
-            .insert(player_id, PlayerState::new(player_id, "Player".to_string(), player_pos));
-        let player = world.players.get_mut(&player_id).expect("player exists");
+        let player_id = PlayerId(12);
+        let player_pos = Position { x: 60, y: 60, z: 7 };
+        let temple = Position { x: 40, y: 40, z: 7 };
+        world.map.tiles.insert(player_pos, make_tile(player_pos, false));
+        let mut player = PlayerState::new(player_id, "Victim".to_string(), player_pos);
+        player.start_position = temple;
+        player.level = 20;
+        player.experience = 98_800;
+        player.stats.health = 0;
+        player.damage_ledger.record(
+            DamageSource::Monster(CreatureId(900)),
+            "a rat",
+            DamageType::Physical,
+            30,
+            GameTick(0),
+        );
         player.inventory.set_slot(
-            InventorySlot::Head,
-            Some(ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: ItemTypeId(100),
+            InventorySlot::Necklace,
+            Some(ItemStack {
+                id: crate::entities::item::ItemId::next(),
+                type_id: AMULET_OF_LOSS_TYPE_ID,
                 count: 1,
                 attributes: Vec::new(),
                 contents: Vec::new(),
             }),
         );
+        world.players.insert(player_id, player);
+
+        let deaths = world.tick_player_deaths();
+        assert_eq!(deaths.len(), 1);
+        assert_eq!(deaths[0].experience_lost, 9_880);
+        assert_eq!(deaths[0].new_level, 19);
+        assert_eq!(deaths[0].killers, vec!["a rat".to_string()]);
+        assert!(world.take_pending_forced_logout(player_id));
+        assert!(world
+            .take_pending_messages(player_id)
+            .iter()
+            .any(|message| message.message == "You were killed by a rat."));
 
-        let err = world
-            .move_inventory_item(player_id, InventorySlot::Head, InventorySlot::Armor, 1)
-            .expect_err("invalid slot should fail");
-        assert_eq!(err, "item cannot be equipped in slot");
-    }
-
-    #[test]
-    fn place_on_tile_merges_stackable_items() {
-        let position = Position { x: 1, y: 1, z: 7 };
-        let mut tile = make_tile(position, false);
-        tile.items.push(ItemStack { id: crate::entities::item::ItemId::next(),
-            type_id: ItemTypeId(200),
-            count: 4,
-            attributes: Vec::new(),
-            contents: Vec::new(),
-        });
-
-        place_on_tile(
-            &mut tile,
-            ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: ItemTypeId(200),
-                count: 6,
-                attributes: Vec::new(),
-                contents: Vec::new(),
-            },
-            true,
-        )
-        .expect("place on tile");
+        assert!(!world.players.contains_key(&player_id));
+        let player = world.offline_players.get(&player_id).expect("player offline");
+        assert_eq!(player.position, temple);
+        assert_eq!(player.stats.health, player.stats.max_health);
+        assert!(player.inventory.slot(InventorySlot::Necklace).is_none());
 
-        assert_eq!(tile.items.len(), 1);
-        assert_eq!(tile.items[0].count, 10);
+        let tile = world.map.tile(player_pos).expect("tile exists");
+        let corpse = tile
+            .items
+            .iter()
+            .find(|item| item.type_id == PLAYER_CORPSE_MALE_TYPE_ID)
+            .expect("corpse placed");
+        assert!(corpse.contents.is_empty());
+        assert_eq!(
+            corpse.attributes,
+            vec![ItemAttribute::String(
+                "You recognize Victim. He was killed by a rat.".to_string()
+            )]
+        );
     }
 
     #[test]
-    fn cron_container_decay_moves_contents_to_parent_tile() {
+    fn unjustified_kill_records_murder_and_red_skull() {
         let mut world = test_world();
-        let mut object_types = ObjectTypeIndex::default();
-        object_types
-            .insert(ObjectType {
-                id: ItemTypeId(100),
-                name: "Bag".to_string(),
-                flags: vec!["Container".to_string(), "Expire".to_string()],
-                attributes: vec![
-                    ObjectAttribute {
-                        key: "Capacity".to_string(),
-                        value: "10".to_string(),
-                    },
-                    ObjectAttribute {
-                        key: "ExpireTarget".to_string(),
-                        value: "101".to_string(),
-                    },
-                    ObjectAttribute {
-                        key: "TotalExpireTime".to_string(),
-                        value: "1".to_string(),
-                    },
-                ],
-            })
-            .expect("insert container type");
-        object_types
-            .insert(ObjectType {
-                id: ItemTypeId(101),
-                name: "Bag (decayed)".to_string(),
-                flags: Vec::new(),
-                attributes: Vec::new(),
-            })
-            .expect("insert target type");
-        object_types
-            .insert(ObjectType {
-                id: ItemTypeId(200),
-                name: "Gem".to_string(),
-                flags: Vec::new(),
-                attributes: Vec::new(),
-            })
-            .expect("insert child type");
-        world.item_types = Some(crate::world::item_types::build_item_types(&object_types));
-        world.object_types = Some(object_types);
+        let clock = GameClock::new(Duration::from_millis(100));
+        let killer_id = PlayerId(13);
+        let victim_id = PlayerId(14);
+        let killer_pos = Position { x: 70, y: 70, z: 7 };
+        let victim_pos = Position { x: 71, y: 70, z: 7 };
+        world.map.tiles.insert(victim_pos, make_tile(victim_pos, false));
+        let now = unix_time_now();
+        let mut killer = PlayerState::new(killer_id, "Killer".to_string(), killer_pos);
+        killer.murders = vec![now - 60, now - 120];
+        world.players.insert(killer_id, killer);
+        world.players.insert(
+            victim_id,
+            PlayerState::new(victim_id, "Victim".to_string(), victim_pos),
+        );
+
+        world.record_pvp_attack(killer_id, victim_id, &clock);
+        assert_eq!(world.skull_for_viewer(victim_id, killer_id), SkullState::White);
+        assert_eq!(world.skull_for_viewer(killer_id, victim_id), SkullState::Yellow);
+        assert_eq!(world.skull_for_viewer(victim_id, victim_id), SkullState::None);
+
+        // Hitting back is retaliation and does not mark the victim.
+        world.record_pvp_attack(victim_id, killer_id, &clock);
+        assert_eq!(
+            world.players.get(&victim_id).expect("victim").pvp.skull,
+            SkullState::None
+        );
 
-        let child = ItemStack { id: crate::entities::item::ItemId::next(),
-            type_id: ItemTypeId(200),
+        let victim = world.players.get_mut(&victim_id).expect("victim");
+        victim.damage_ledger.record(
+            DamageSource::Player(killer_id),
+            "Killer",
+            DamageType::Physical,
+            50,
+            GameTick(0),
+        );
+        victim.stats.health = 0;
+        world.tick_player_deaths();
+
+        let killer = world.players.get(&killer_id).expect("killer online");
+        assert_eq!(killer.murders.len(), 3);
+        assert!(killer.playerkiller_end > now);
+        assert_eq!(killer.pvp.skull, SkullState::Red);
+        assert!(world
+            .take_pending_messages(killer_id)
+            .iter()
+            .any(|message| message.message.contains("was not justified")));
+        assert!(world.pvp_aggressions.is_empty());
+    }
+
+    #[test]
+    fn offline_murderer_is_marked_and_banished_through_account_registry() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("tibia-murder-ban-{}", suffix));
+        std::fs::create_dir_all(root.join("save")).expect("save dir");
+        std::fs::write(
+            root.join("save").join("accounts.txt"),
+            "account = \"killer\"\npassword = \"secret\"\nplayer_id = 17\n",
+        )
+        .expect("accounts");
+        let mut world = test_world();
+        world.root = Some(root.clone());
+        let killer_id = PlayerId(17);
+        let victim_id = PlayerId(18);
+        let victim_pos = Position { x: 91, y: 90, z: 7 };
+        world.map.tiles.insert(victim_pos, make_tile(victim_pos, false));
+        let now = unix_time_now();
+        let mut killer = PlayerState::new(killer_id, "Killer".to_string(), victim_pos);
+        killer.murders = (1..=5).map(|minutes| now - minutes * 60).collect();
+        world.offline_players.insert(killer_id, killer);
+        world.players.insert(
+            victim_id,
+            PlayerState::new(victim_id, "Victim".to_string(), victim_pos),
+        );
+
+        let victim = world.players.get_mut(&victim_id).expect("victim");
+        victim.damage_ledger.record(
+            DamageSource::Player(killer_id),
+            "Killer",
+            DamageType::Physical,
+            50,
+            GameTick(0),
+        );
+        victim.stats.health = 0;
+        world.tick_player_deaths();
+
+        let killer = world.offline_players.get(&killer_id).expect("killer offline");
+        assert_eq!(killer.murders.len(), 6);
+        assert!(killer.blackskull_end > now);
+        assert_eq!(killer.pvp.skull, SkullState::Black);
+        let bans = BanList::load(&root).expect("banlist").expect("banlist exists");
+        assert!(bans.is_banned("killer", SystemTime::now()));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn ledger_credits_field_owners_and_splits_experience_by_player_damage() {
+        let mut world = test_world();
+        walled_arena(&mut world, &[]);
+        let clock = GameClock::new(Duration::from_millis(100));
+        world.sync_combat_clock(&clock);
+        let hunter_id = PlayerId(40);
+        let walker_id = PlayerId(41);
+        let field_pos = Position { x: 150, y: 151, z: 7 };
+        world.players.insert(
+            hunter_id,
+            PlayerState::new(hunter_id, "Hunter".to_string(), Position { x: 150, y: 150, z: 7 }),
+        );
+        world.players.insert(
+            walker_id,
+            PlayerState::new(walker_id, "Walker".to_string(), field_pos),
+        );
+
+        let field = ItemStack {
+            id: ItemId::next(),
+            type_id: ItemTypeId(FIRE_FIELD_TYPE_ID),
             count: 1,
             attributes: Vec::new(),
             contents: Vec::new(),
         };
-        let container = ItemStack { id: crate::entities::item::ItemId::next(),
-            type_id: ItemTypeId(100),
-            count: 1,
-            attributes: Vec::new(),
-            contents: vec![child],
+        world
+            .map
+            .tile_mut(field_pos)
+            .expect("tile")
+            .items
+            .push(field.clone());
+        world.note_field_owner(field.id, DamageSource::Player(hunter_id), vec![field_pos]);
+        let mut outcome = MoveUseOutcome {
+            matched_rule: None,
+            ignored_actions: Vec::new(),
+            effects: Vec::new(),
+            texts: Vec::new(),
+            edit_texts: Vec::new(),
+            edit_lists: Vec::new(),
+            messages: Vec::new(),
+            damages: vec![MoveUseDamage {
+                source: MoveUseActor::Object(field_pos),
+                target: MoveUseActor::User(walker_id),
+                damage_type: DamageType::Fire,
+                amount: 20,
+            }],
+            quest_updates: Vec::new(),
+            logout_users: Vec::new(),
+            refresh_positions: Vec::new(),
+            inventory_updates: Vec::new(),
+            container_updates: Vec::new(),
         };
+        world.apply_moveuse_outcome_damage(&mut outcome).expect("damage");
+        let (now, window) = world.damage_window();
+        let credits = world.players[&walker_id].damage_ledger.credits(now, window);
+        assert_eq!(credits[0].source, DamageSource::Player(hunter_id));
+        assert_eq!(credits[0].name, "Hunter");
+
+        world.map.tile_mut(field_pos).expect("tile").items.clear();
+        world.prune_field_owners();
+        assert!(world.field_owners.is_empty());
+
+        let walker = world.players.get_mut(&walker_id).expect("walker");
+        walker
+            .damage_ledger
+            .note_condition(ConditionKind::Fire, DamageSource::Player(hunter_id), "Hunter");
+        world.tick_conditions(clock.now());
+        assert!(world.players[&walker_id]
+            .damage_ledger
+            .condition_source(ConditionKind::Fire)
+            .is_none());
 
-        let position = Position { x: 5, y: 5, z: 7 };
-        let mut tile = make_tile(position, false);
-        tile.items.push(container.clone());
-        world.map.tiles.insert(position, tile);
-        world.schedule_cron_for_item_tree(&container);
+        let script_input = r#"
+Name = "Wolf"
+RaceNumber = 27
+Experience = 100
+Skills = {(HitPoints, 20, 0, 0, 0, 0, 0)}
+"#;
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(27, "Wolf".to_string());
+        index
+            .scripts
+            .insert("Wolf".to_string(), parse_monster_script(script_input).expect("parse"));
+        world.monster_index = Some(index);
+        let monster_id = world
+            .spawn_monster_by_race(27, Position { x: 152, y: 152, z: 7 })
+            .expect("spawn monster");
+        let ledger = &mut world.monsters.get_mut(&monster_id).expect("monster").damage_ledger;
+        ledger.record(
+            DamageSource::Player(hunter_id),
+            "Hunter",
+            DamageType::Physical,
+            5,
+            now,
+        );
+        ledger.record(
+            DamageSource::Monster(CreatureId(999)),
+            "a troll",
+            DamageType::Physical,
+            15,
+            now,
+        );
+        let before = world.players[&hunter_id].experience;
+        world.defeat_monster(monster_id, Some(hunter_id)).expect("defeat");
+        assert_eq!(world.players[&hunter_id].experience - before, 100);
+    }
 
-        world.cron_round = 1;
-        let processed = world.process_cron_system();
-        assert_eq!(processed, 1);
+    #[test]
+    fn enforced_world_skips_skulls_and_rewards_player_kills() {
+        let mut world = test_world();
+        world.combat_rules.world_type = crate::combat::rules::WorldType::PvpEnforced;
+        let clock = GameClock::new(Duration::from_millis(100));
+        let killer_id = PlayerId(15);
+        let victim_id = PlayerId(16);
+        let victim_pos = Position { x: 81, y: 80, z: 7 };
+        world.map.tiles.insert(victim_pos, make_tile(victim_pos, false));
+        let mut killer = PlayerState::new(killer_id, "Hunter".to_string(), victim_pos);
+        killer.level = 10;
+        world.players.insert(killer_id, killer);
+        let mut victim = PlayerState::new(victim_id, "Prey".to_string(), victim_pos);
+        victim.level = 20;
+        victim.experience = 100_000;
+        world.players.insert(victim_id, victim);
+
+        world.record_pvp_attack(killer_id, victim_id, &clock);
+        assert_eq!(world.skull_for_viewer(victim_id, killer_id), SkullState::None);
+        assert_eq!(world.skull_for_viewer(killer_id, victim_id), SkullState::None);
+
+        let helper_id = PlayerId(17);
+        let mut helper = PlayerState::new(helper_id, "Helper".to_string(), victim_pos);
+        helper.level = 10;
+        world.players.insert(helper_id, helper);
+
+        world.record_player_damage(
+            victim_id,
+            DamageSource::Player(helper_id),
+            DamageType::Physical,
+            25,
+        );
+        world.record_player_damage(
+            victim_id,
+            DamageSource::Player(killer_id),
+            DamageType::Fire,
+            75,
+        );
+        world.players.get_mut(&victim_id).expect("victim").stats.health = 0;
+        world.tick_player_deaths();
 
-        let tile = world.map.tile(position).expect("tile exists");
-        assert_eq!(tile.items.len(), 2);
-        assert_eq!(tile.items[0].type_id, ItemTypeId(101));
-        assert!(tile.items[0].contents.is_empty());
-        assert_eq!(tile.items[1].type_id, ItemTypeId(200));
+        let killer = world.players.get(&killer_id).expect("killer");
+        assert_eq!(killer.experience, 2_062);
+        assert!(killer.murders.is_empty());
+        let helper = world.players.get(&helper_id).expect("helper");
+        assert_eq!(helper.experience, 687);
     }
 
     #[test]
-    fn cron_corpse_decay_deletes_contents() {
+    fn monster_experience_is_split_by_damage_share() {
+        let mut world = test_world();
+        let hunter_id = PlayerId(18);
+        let helper_id = PlayerId(19);
+        let position = Position { x: 90, y: 90, z: 7 };
+        world.map.tiles.insert(position, make_tile(position, false));
+        world
+            .players
+            .insert(hunter_id, PlayerState::new(hunter_id, "Hunter".to_string(), position));
+        world
+            .players
+            .insert(helper_id, PlayerState::new(helper_id, "Helper".to_string(), position));
+
+        let script_input = r#"
+Name = "Wolf"
+RaceNumber = 2
+Experience = 100
+Skills = {(HitPoints, 40, 0, 0, 0, 0, 0)}
+"#;
+        let script = parse_monster_script(script_input).expect("parse script");
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(2, "Wolf".to_string());
+        index.scripts.insert("Wolf".to_string(), script);
+        world.monster_index = Some(index);
+        let monster_id = world
+            .spawn_monster_by_race(2, position)
+            .expect("spawn monster");
+
+        world
+            .apply_damage_to_monster(monster_id, DamageType::Fire, 10, DamageSource::Player(helper_id))
+            .expect("apply damage");
+        let ledger = &world.monsters.get(&monster_id).expect("monster").damage_ledger;
+        assert_eq!(ledger.entries()[0].name, "Helper");
+        assert_eq!(ledger.entries()[0].damage_type, DamageType::Fire);
+        world
+            .apply_damage_to_monster(
+                monster_id,
+                DamageType::Physical,
+                30,
+                DamageSource::Player(hunter_id),
+            )
+            .expect("apply damage")
+            .expect("monster reward");
+
+        assert_eq!(world.players.get(&hunter_id).expect("hunter").experience, 75);
+        assert_eq!(world.players.get(&helper_id).expect("helper").experience, 25);
+    }
+
+    #[test]
+    fn distance_attacks_use_ammunition_and_need_line_of_sight() {
         let mut world = test_world();
+        let mut clock = GameClock::new(Duration::from_millis(100));
         let mut object_types = ObjectTypeIndex::default();
-        object_types
-            .insert(ObjectType {
-                id: ItemTypeId(300),
-                name: "Corpse".to_string(),
-                flags: vec![
-                    "Container".to_string(),
-                    "Corpse".to_string(),
-                    "Expire".to_string(),
+        let attribute = |key: &str, value: &str| ObjectAttribute {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        for (id, name, flags, attributes) in [
+            (
+                200,
+                "bow",
+                vec!["Bow".to_string()],
+                vec![attribute("BowRange", "6"), attribute("BowAmmoType", "1")],
+            ),
+            (
+                201,
+                "arrow",
+                Vec::new(),
+                vec![
+                    attribute("AmmoType", "1"),
+                    attribute("AmmoAttackValue", "30"),
+                    attribute("AmmoMissile", "3"),
                 ],
-                attributes: vec![
-                    ObjectAttribute {
-                        key: "ExpireTarget".to_string(),
-                        value: "301".to_string(),
-                    },
-                    ObjectAttribute {
-                        key: "TotalExpireTime".to_string(),
-                        value: "1".to_string(),
-                    },
+            ),
+            (
+                202,
+                "spear",
+                Vec::new(),
+                vec![
+                    attribute("ThrowAttackValue", "25"),
+                    attribute("ThrowRange", "5"),
+                    attribute("ThrowMissile", "1"),
+                    attribute("ThrowFragility", "0"),
                 ],
-            })
-            .expect("insert corpse type");
-        object_types
-            .insert(ObjectType {
-                id: ItemTypeId(301),
-                name: "Bone".to_string(),
-                flags: Vec::new(),
-                attributes: Vec::new(),
-            })
-            .expect("insert corpse target type");
-        object_types
+            ),
+        ] {
+            object_types
+                .insert(ObjectType {
+                    id: ItemTypeId(id),
+                    name: name.to_string(),
+                    flags,
+                    attributes,
+                })
+                .expect("insert object type");
+        }
+        world.object_types = Some(object_types);
+
+        let player_id = PlayerId(22);
+        let player_pos = Position { x: 110, y: 110, z: 7 };
+        let monster_pos = Position { x: 114, y: 110, z: 7 };
+        world.map.tiles.insert(player_pos, make_tile(player_pos, false));
+        world.map.tiles.insert(monster_pos, make_tile(monster_pos, false));
+        let script_input = r#"
+Name = "Troll"
+RaceNumber = 3
+Experience = 20
+Skills = {(HitPoints, 1000, 0, 0, 0, 0, 0)}
+"#;
+        let script = parse_monster_script(script_input).expect("parse script");
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(3, "Troll".to_string());
+        index.scripts.insert("Troll".to_string(), script);
+        world.monster_index = Some(index);
+        let monster_id = world
+            .spawn_monster_by_race(3, monster_pos)
+            .expect("spawn monster");
+
+        let mut player = PlayerState::new(player_id, "Paladin".to_string(), player_pos);
+        player.attack_target = Some(monster_id);
+        player
+            .inventory
+            .set_slot(InventorySlot::RightHand, Some(ItemStack::new(ItemTypeId(200), 1)));
+        player
+            .inventory
+            .set_slot(InventorySlot::Ammo, Some(ItemStack::new(ItemTypeId(201), 10)));
+        world.players.insert(player_id, player);
+
+        let outcome = world.tick_player_attack(player_id, &clock);
+        assert_eq!(outcome.inventory_updates, vec![InventorySlot::Ammo]);
+        let player = world.players.get(&player_id).expect("player");
+        assert_eq!(player.inventory.slot(InventorySlot::Ammo).map(|item| item.count), Some(9));
+        assert_eq!(
+            world.take_pending_missile_effects(player_id),
+            vec![MissileEffect {
+                from: player_pos,
+                to: monster_pos,
+                missile_id: 3,
+            }]
+        );
+
+        let player = world.players.get_mut(&player_id).expect("player");
+        player.inventory.set_slot(InventorySlot::Ammo, None);
+        clock.advance(PLAYER_COMBAT_INTERVAL_TICKS);
+        let outcome = world.tick_player_attack(player_id, &clock);
+        assert!(outcome.inventory_updates.is_empty());
+        assert!(world.take_pending_missile_effects(player_id).is_empty());
+
+        let player = world.players.get_mut(&player_id).expect("player");
+        player
+            .inventory
+            .set_slot(InventorySlot::RightHand, Some(ItemStack::new(ItemTypeId(202), 1)));
+        let outcome = world.tick_player_attack(player_id, &clock);
+        assert_eq!(outcome.inventory_updates, vec![InventorySlot::RightHand]);
+        let player = world.players.get(&player_id).expect("player");
+        assert!(player.inventory.slot(InventorySlot::RightHand).is_none());
+        let tile = world.map.tile(monster_pos).expect("tile");
+        assert!(tile.items.iter().any(|item| item.type_id == ItemTypeId(202)));
+        assert_eq!(world.take_pending_missile_effects(player_id)[0].missile_id, 1);
+
+        let wall_pos = Position { x: 112, y: 110, z: 7 };
+        world
+            .object_types
+            .as_mut()
+            .expect("object types")
             .insert(ObjectType {
-                id: ItemTypeId(302),
-                name: "Loot".to_string(),
-                flags: Vec::new(),
+                id: ItemTypeId(203),
+                name: "wall".to_string(),
+                flags: vec!["Unpass".to_string(), "Unthrow".to_string()],
                 attributes: Vec::new(),
             })
-            .expect("insert loot type");
-        world.item_types = Some(crate::world::item_types::build_item_types(&object_types));
-        world.object_types = Some(object_types);
+            .expect("insert object type");
+        let mut wall = make_tile(wall_pos, false);
+        wall.items.push(ItemStack::new(ItemTypeId(203), 1));
+        world.map.tiles.insert(wall_pos, wall);
+        assert!(!world.has_line_of_sight(player_pos, monster_pos));
+        let player = world.players.get_mut(&player_id).expect("player");
+        player
+            .inventory
+            .set_slot(InventorySlot::RightHand, Some(ItemStack::new(ItemTypeId(202), 1)));
+        clock.advance(PLAYER_COMBAT_INTERVAL_TICKS);
+        let outcome = world.tick_player_attack(player_id, &clock);
+        assert!(outcome.inventory_updates.is_empty());
+        assert!(world.take_pending_missile_effects(player_id).is_empty());
+    }
 
-        let child = ItemStack { id: crate::entities::item::ItemId::next(),
-            type_id: ItemTypeId(302),
-            count: 1,
-            attributes: Vec::new(),
-            contents: Vec::new(),
-        };
-        let corpse = ItemStack { id: crate::entities::item::ItemId::next(),
-            type_id: ItemTypeId(300),
-            count: 1,
-            attributes: Vec::new(),
-            contents: vec![child],
+    #[test]
+    fn monster_corpse_is_reserved_for_top_damager() {
+        let mut world = test_world();
+        let hunter_id = PlayerId(20);
+        let stranger_id = PlayerId(21);
+        let position = Position { x: 95, y: 95, z: 7 };
+        world.map.tiles.insert(position, make_tile(position, false));
+        world
+            .players
+            .insert(hunter_id, PlayerState::new(hunter_id, "Hunter".to_string(), position));
+        world
+            .players
+            .insert(stranger_id, PlayerState::new(stranger_id, "Stranger".to_string(), position));
+
+        let script_input = r#"
+Name = "Rat"
+RaceNumber = 1
+Experience = 5
+Skills = {(HitPoints, 10, 0, 0, 0, 0, 0)}
+Corpse = 4240
+"#;
+        let script = parse_monster_script(script_input).expect("parse script");
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(1, "Rat".to_string());
+        index.scripts.insert("Rat".to_string(), script);
+        world.monster_index = Some(index);
+        let monster_id = world
+            .spawn_monster_by_race(1, position)
+            .expect("spawn monster");
+        world
+            .apply_damage_to_monster(
+                monster_id,
+                DamageType::Physical,
+                10,
+                DamageSource::Player(hunter_id),
+            )
+            .expect("apply damage");
+
+        let corpse = world.map.tile(position).expect("tile").items[0].clone();
+        let source = ContainerSource::Map {
+            position,
+            stack_pos: 0,
         };
+        let err = world
+            .open_container_for_player(stranger_id, corpse.type_id, source, None)
+            .expect_err("corpse protected");
+        assert_eq!(err, ContainerOpenError::NotOwner);
+        assert!(world
+            .take_pending_messages(stranger_id)
+            .iter()
+            .any(|message| message.message == LOOT_NOT_OWNER_MESSAGE));
+        let err = world
+            .move_item_between_tiles(stranger_id, position, position, corpse.type_id, 1)
+            .expect_err("corpse cannot be carried off");
+        assert_eq!(err, LOOT_NOT_OWNER_MESSAGE);
+        world
+            .open_container_for_player(hunter_id, corpse.type_id, source, None)
+            .expect("owner opens corpse");
 
-        let position = Position { x: 6, y: 6, z: 7 };
-        let mut tile = make_tile(position, false);
-        tile.items.push(corpse.clone());
-        world.map.tiles.insert(position, tile);
-        world.schedule_cron_for_item_tree(&corpse);
+        world
+            .corpse_owners
+            .get_mut(&corpse.id)
+            .expect("corpse owner")
+            .expires_at = 0;
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        world.tick_cron_system(&clock);
+        clock.advance_duration(Duration::from_secs(1));
+        world.tick_cron_system(&clock);
+        assert!(world.corpse_owners.is_empty());
+        world
+            .open_container_for_player(stranger_id, corpse.type_id, source, None)
+            .expect("protection expired");
+    }
+    #[test]
+    fn corpse_take_checks_only_the_moved_corpse() {
+        let mut world = test_world();
+        let hunter_id = PlayerId(20);
+        let stranger_id = PlayerId(21);
+        let position = Position { x: 95, y: 95, z: 7 };
+        let destination = Position { x: 96, y: 95, z: 7 };
+        world.map.tiles.insert(position, make_tile(position, false));
+        world.map.tiles.insert(destination, make_tile(destination, false));
+        world
+            .players
+            .insert(hunter_id, PlayerState::new(hunter_id, "Hunter".to_string(), position));
+        world
+            .players
+            .insert(stranger_id, PlayerState::new(stranger_id, "Stranger".to_string(), position));
 
-        world.cron_round = 1;
-        let processed = world.process_cron_system();
-        assert_eq!(processed, 1);
+        let script_input = r#"
+Name = "Rat"
+RaceNumber = 1
+Experience = 5
+Skills = {(HitPoints, 10, 0, 0, 0, 0, 0)}
+Corpse = 4240
+"#;
+        let script = parse_monster_script(script_input).expect("parse script");
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(1, "Rat".to_string());
+        index.scripts.insert("Rat".to_string(), script);
+        world.monster_index = Some(index);
+        for killer in [stranger_id, hunter_id] {
+            let monster_id = world
+                .spawn_monster_by_race(1, position)
+                .expect("spawn monster");
+            world
+                .apply_damage_to_monster(
+                    monster_id,
+                    DamageType::Physical,
+                    10,
+                    DamageSource::Player(killer),
+                )
+                .expect("apply damage");
+        }
 
-        let tile = world.map.tile(position).expect("tile exists");
-        assert_eq!(tile.items.len(), 1);
-        assert_eq!(tile.items[0].type_id, ItemTypeId(301));
-        assert!(tile.items[0].contents.is_empty());
+        let corpses = world.map.tile(position).expect("tile").items.clone();
+        assert_eq!(corpses.len(), 2);
+        assert_eq!(corpses[0].type_id, corpses[1].type_id);
+        let owner = |stack: usize| world.corpse_owners[&corpses[stack].id].player_id;
+        assert_eq!(owner(0), stranger_id);
+        assert_eq!(owner(1), hunter_id);
+        let type_id = corpses[0].type_id;
+        world
+            .ensure_corpse_take_allowed(stranger_id, position, type_id, Some(0))
+            .expect("own corpse at its stack position");
+        assert!(world
+            .ensure_corpse_take_allowed(stranger_id, position, type_id, Some(1))
+            .is_err());
+        world.take_pending_messages(stranger_id);
+
+        world
+            .move_item_between_tiles(stranger_id, position, destination, type_id, 1)
+            .expect("first corpse is the stranger's");
+        let err = world
+            .move_item_between_tiles(stranger_id, position, destination, type_id, 1)
+            .expect_err("hunter's corpse stays reserved");
+        assert_eq!(err, LOOT_NOT_OWNER_MESSAGE);
     }
 
     #[test]
-    fn schedule_cron_respects_remaining_expire_time() {
+    fn worn_protection_absorbs_damage_and_uses_up_charges() {
         let mut world = test_world();
         let mut object_types = ObjectTypeIndex::default();
-        object_types
-            .insert(ObjectType {
-                id: ItemTypeId(400),
-                name: "Timed".to_string(),
-                flags: vec!["Expire".to_string()],
-                attributes: vec![ObjectAttribute {
-                    key: "TotalExpireTime".to_string(),
-                    value: "10".to_string(),
-                }],
-            })
-            .expect("insert expiring type");
-        world.item_types = Some(crate::world::item_types::build_item_types(&object_types));
+        let attribute = |key: &str, value: &str| ObjectAttribute {
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let flags = vec!["Protection".to_string(), "Clothes".to_string()];
+        for (id, name, attributes) in [
+            (
+                300,
+                "might ring",
+                vec![
+                    attribute("BodyPosition", "9"),
+                    attribute("ProtectionDamageTypes", "1"),
+                    attribute("DamageReduction", "50"),
+                    attribute("TotalUses", "2"),
+                ],
+            ),
+            (
+                301,
+                "dragon amulet",
+                vec![
+                    attribute("BodyPosition", "2"),
+                    attribute("AbsorbFire", "20"),
+                    attribute("AbsorbIce", "-10"),
+                ],
+            ),
+        ] {
+            object_types
+                .insert(ObjectType {
+                    id: ItemTypeId(id),
+                    name: name.to_string(),
+                    flags: flags.clone(),
+                    attributes,
+                })
+                .expect("insert object type");
+        }
         world.object_types = Some(object_types);
 
-        let mut item = ItemStack { id: crate::entities::item::ItemId::next(),
-            type_id: ItemTypeId(400),
-            count: 1,
-            attributes: Vec::new(),
-            contents: Vec::new(),
-        };
-        set_remaining_expire_secs(&mut item, 2);
-        world.schedule_cron_for_item_tree(&item);
+        let player_id = PlayerId(23);
+        let position = Position { x: 120, y: 120, z: 7 };
+        let mut player = PlayerState::new(player_id, "Wearer".to_string(), position);
+        let mut resistances = [0i16; DamageType::COUNT];
+        resistances[DamageType::Fire.index().unwrap()] = 50;
+        player.stats.resistances =
+            crate::entities::stats::DamageResistances::from_array(resistances);
+        player
+            .inventory
+            .set_slot(InventorySlot::Ring, Some(ItemStack::new(ItemTypeId(300), 1)));
+        player
+            .inventory
+            .set_slot(InventorySlot::Necklace, Some(ItemStack::new(ItemTypeId(301), 1)));
+        world.players.insert(player_id, player);
 
-        assert_eq!(world.cron.get_remaining(item.id, world.cron_round), Some(2));
+        assert_eq!(
+            world.apply_player_protection_reduction(player_id, DamageType::Fire, 100),
+            40
+        );
+        assert_eq!(
+            world.apply_player_protection_reduction(player_id, DamageType::Ice, 100),
+            110
+        );
+        assert_eq!(
+            world.apply_player_protection_reduction(player_id, DamageType::Physical, 100),
+            50
+        );
+        let ring = world.players[&player_id]
+            .inventory
+            .slot(InventorySlot::Ring)
+            .cloned()
+            .expect("ring after first hit");
+        assert_eq!(ring.remaining_uses(), Some(1));
+        assert_eq!(
+            world.apply_player_protection_reduction(player_id, DamageType::Physical, 100),
+            50
+        );
+        assert!(world.players[&player_id]
+            .inventory
+            .slot(InventorySlot::Ring)
+            .is_none());
+        assert_eq!(
+            world.apply_player_protection_reduction(player_id, DamageType::Physical, 100),
+            100
+        );
     }
 
     #[test]
-    fn expirestop_preserves_and_restores_remaining_time() {
+    fn reload_scripts_validates_each_target_and_respawns_changed_npcs() {
+        let suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("time")
+            .as_nanos();
+        let root = std::env::temp_dir().join(format!("tibia-reload-test-{}", suffix));
+        for dir in ["npc", "mon", "dat"] {
+            std::fs::create_dir_all(root.join(dir)).expect("create dir");
+        }
+        let klara = |greeting: &str| {
+            format!(
+                "Name = \"Klara\"\nHome = [150,150,7]\nBehaviour = {{\n\"hi\" -> \"{}\"\n}}\n",
+                greeting
+            )
+        };
+        std::fs::write(root.join("npc").join("klara.npc"), klara("Hello.")).expect("write npc");
+        std::fs::write(
+            root.join("mon").join("wolf.mon"),
+            "Name = \"Wolf\"\nRaceNumber = 27\nExperience = 100\n",
+        )
+        .expect("write monster");
+        std::fs::write(root.join("mon").join("broken.evt"), "Interval = \"soon\"\n")
+            .expect("write raid");
+        std::fs::write(
+            root.join("dat").join("moveuse.dat"),
+            "BEGIN \"Root\"\nUse, IsType(Obj1, 100), IsMagic() -> NOP\nEND\n",
+        )
+        .expect("write moveuse");
+
         let mut world = test_world();
+        walled_arena(&mut world, &[]);
+        world.root = Some(root.clone());
+
+        let report = world
+            .reload_scripts(ScriptReloadTarget::Monsters)
+            .expect("a broken raid does not block a monster reload");
+        assert_eq!(report.monster_scripts, Some(1));
+        assert!(world.reload_scripts(ScriptReloadTarget::Raids).is_err());
+        let err = world
+            .reload_scripts(ScriptReloadTarget::MoveUse)
+            .expect_err("unknown condition");
+        assert!(err.contains("unknown condition IsMagic"), "{}", err);
+        assert!(world.moveuse.is_none());
+
+        let report = world.reload_scripts(ScriptReloadTarget::Npcs).expect("npc reload");
+        assert_eq!((report.npcs_respawned, report.npcs_removed), (1, 0));
+        let report = world.reload_scripts(ScriptReloadTarget::Npcs).expect("npc reload");
+        assert_eq!((report.npcs_respawned, report.npcs_removed), (0, 0));
+        std::fs::write(root.join("npc").join("klara.npc"), klara("Welcome!")).expect("write npc");
+        let report = world.reload_scripts(ScriptReloadTarget::Npcs).expect("npc reload");
+        assert_eq!((report.npcs_respawned, report.npcs_removed), (1, 1));
+        assert_eq!(world.npcs.len(), 1);
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    fn walled_arena(world: &mut WorldState, wall: &[Position]) {
         let mut object_types = ObjectTypeIndex::default();
         object_types
             .insert(ObjectType {
-                id: ItemTypeId(500),
-                name: "Timed".to_string(),
-                flags: vec!["Expire".to_string()],
-                attributes: vec![ObjectAttribute {
-                    key: "TotalExpireTime".to_string(),
-                    value: "10".to_string(),
-                }],
-            })
-            .expect("insert expiring type");
-        object_types
-            .insert(ObjectType {
-                id: ItemTypeId(501),
-                name: "Stopped".to_string(),
-                flags: vec!["ExpireStop".to_string()],
+                id: ItemTypeId(210),
+                name: "wall".to_string(),
+                flags: vec!["Unpass".to_string()],
                 attributes: Vec::new(),
             })
-            .expect("insert stop type");
-        world.item_types = Some(crate::world::item_types::build_item_types(&object_types));
+            .expect("insert wall");
         world.object_types = Some(object_types);
-
-        let mut item = ItemStack { id: crate::entities::item::ItemId::next(),
-            type_id: ItemTypeId(500),
-            count: 1,
-            attributes: Vec::new(),
-            contents: Vec::new(),
-        };
-        world.schedule_cron_for_item_tree(&item);
-
-        world.cron_round = 3;
-        assert_eq!(world.cron.get_remaining(item.id, world.cron_round), Some(7));
-
-        world
-            .change_itemstack_type(&mut item, ItemTypeId(501), 0)
-            .expect("change to stop");
-        assert!(item_saved_expire_secs(&item).unwrap_or(0) > 0);
-        assert_eq!(world.cron.get_remaining(item.id, world.cron_round), None);
-
-        world
-            .change_itemstack_type(&mut item, ItemTypeId(500), 0)
-            .expect("change to expiring");
-        assert_eq!(world.cron.get_remaining(item.id, world.cron_round), Some(7));
+        for x in 140..=160 {
+            for y in 140..=160 {
+                let position = Position { x, y, z: 7 };
+                let mut tile = make_tile(position, false);
+                if wall.contains(&position) {
+                    tile.items.push(ItemStack::new(ItemTypeId(210), 1));
+                }
+                world.map.tiles.insert(position, tile);
+            }
+        }
     }
 
     #[test]
-    fn insert_into_container_append_merges_stackable() {
-        let mut container = OpenContainer {
-            container_id: 1,
-            item_type: ItemTypeId(400),
-            name: "Bag".to_string(),
-            capacity: 10,
-            has_parent: false,
-            parent_container_id: None,
-            parent_slot: None,
-            source_slot: None,
-            source_position: None,
-            source_stack_pos: None,
-            items: vec![ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: ItemTypeId(300),
-                count: 5,
-                attributes: Vec::new(),
-                contents: Vec::new(),
-            }],
-        };
-
-        let update = insert_into_container(
-            &mut container,
-            1,
-            0xff,
-            ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: ItemTypeId(300),
-                count: 7,
-                attributes: Vec::new(),
-                contents: Vec::new(),
-            },
-            true,
-        )
-        .expect("insert into container");
+    fn monsters_chase_around_walls_along_cached_paths() {
+        let mut world = test_world();
+        let wall: Vec<Position> = (146..=154).map(|y| Position { x: 152, y, z: 7 }).collect();
+        walled_arena(&mut world, &wall);
+        let player_id = PlayerId(24);
+        let player_pos = Position { x: 150, y: 150, z: 7 };
+        world
+            .players
+            .insert(player_id, PlayerState::new(player_id, "Bait".to_string(), player_pos));
+        let script_input = r#"
+Name = "Wolf"
+RaceNumber = 27
+Experience = 18
+Skills = {(HitPoints, 25, 0, 0, 0, 0, 0), (GoStrength, 200, 0, 0, 0, 0, 0)}
+"#;
+        let script = parse_monster_script(script_input).expect("parse script");
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(27, "Wolf".to_string());
+        index.scripts.insert("Wolf".to_string(), script);
+        world.monster_index = Some(index);
+        let monster_id = world
+            .spawn_monster_by_race(27, Position { x: 154, y: 150, z: 7 })
+            .expect("spawn monster");
 
-        let expected = ItemStack { id: crate::entities::item::ItemId::next(),
-            type_id: ItemTypeId(300),
-            count: 12,
-            attributes: Vec::new(),
-            contents: Vec::new(),
-        };
-        assert_eq!(container.items.len(), 1);
-        assert_eq!(container.items[0], expected);
-        assert_eq!(
-            update,
-            ContainerUpdate::Update {
-                container_id: 1,
-                slot: 0,
-                item: expected
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        let mut cached = false;
+        for _ in 0..400 {
+            clock.advance(1);
+            world.tick_monsters(&clock);
+            cached |= world.creature_paths.contains_key(&monster_id);
+            let position = world.monsters[&monster_id].position;
+            assert!(!wall.contains(&position));
+            if WorldState::monster_tile_distance(position, player_pos) <= 1 {
+                break;
             }
+        }
+        assert!(cached);
+        assert!(
+            WorldState::monster_tile_distance(world.monsters[&monster_id].position, player_pos)
+                <= 1
         );
     }
 
     #[test]
-    fn moveuse_use_emits_effect_text_and_damage() {
+    fn monsters_follow_threat_with_hysteresis_and_leash_home() {
         let mut world = test_world();
-        let player_id = PlayerId(7);
-        let player_pos = Position { x: 10, y: 10, z: 7 };
+        walled_arena(&mut world, &[]);
+        let knight = PlayerId(31);
+        let druid = PlayerId(32);
         world.players.insert(
-            player_id,
-            PlayerState::new(player_id, "User".to_string(), player_pos),
+            knight,
+            PlayerState::new(knight, "Knight".to_string(), Position { x: 150, y: 152, z: 7 }),
         );
+        world.players.insert(
+            druid,
+            PlayerState::new(druid, "Druid".to_string(), Position { x: 152, y: 150, z: 7 }),
+        );
+        let script_input = r#"
+Name = "Wolf"
+RaceNumber = 27
+Skills = {(HitPoints, 1000, 0, 0, 0, 0, 0), (GoStrength, 200, 0, 0, 0, 0, 0)}
+"#;
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(27, "Wolf".to_string());
+        index
+            .scripts
+            .insert("Wolf".to_string(), parse_monster_script(script_input).expect("parse"));
+        world.monster_index = Some(index);
+        let home = Position { x: 145, y: 150, z: 7 };
+        world.monster_homes.push(MonsterHome {
+            race_number: 27,
+            position: home,
+            radius: 0,
+            amount: 1,
+            regen: 0,
+            act_monsters: 1,
+            timer: 0,
+        });
+        let monster_id = world
+            .spawn_monster_by_race(27, Position { x: 150, y: 150, z: 7 })
+            .expect("spawn monster");
+        world.monsters.get_mut(&monster_id).expect("monster").home_id = Some(0);
 
-        let item_type = ItemTypeId(100);
-        let tile_pos = Position { x: 11, y: 10, z: 7 };
-        world.map.tiles.insert(tile_pos, make_tile(tile_pos, false));
+        let mut clock = GameClock::new(Duration::from_millis(100));
         world
-            .map
-            .tile_mut(tile_pos)
-            .expect("tile exists")
-            .items
-            .push(ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: item_type,
-                count: 1,
-                attributes: Vec::new(),
-                contents: Vec::new(),
-            });
-
-        let rule = MoveUseRule {
-            event: MoveUseExpr {
-                name: "Use".to_string(),
-                args: Vec::new(),
-            },
-            conditions: vec![MoveUseExpr {
-                name: "IsType".to_string(),
-                args: vec!["Obj1".to_string(), "100".to_string()],
-            }],
-            actions: vec![
-                MoveUseExpr {
-                    name: "Effect".to_string(),
-                    args: vec!["User".to_string(), "13".to_string()],
-                },
-                MoveUseExpr {
-                    name: "Text".to_string(),
-                    args: vec![
-                        "User".to_string(),
-                        "\"Hello\"".to_string(),
-                        "20".to_string(),
-                    ],
-                },
-                MoveUseExpr {
-                    name: "Damage".to_string(),
-                    args: vec![
-                        "User".to_string(),
-                        "User".to_string(),
-                        "1".to_string(),
-                        "5".to_string(),
-                    ],
-                },
-            ],
-            line_no: 1,
-        };
+            .apply_damage_to_monster(monster_id, DamageType::Physical, 100, DamageSource::Player(knight))
+            .expect("knight hit");
+        clock.advance(1);
+        world.tick_monsters(&clock);
+        assert_eq!(world.monsters[&monster_id].target, Some(knight));
 
-        world.moveuse = Some(MoveUseDatabase {
-            sections: vec![MoveUseSection {
-                name: "Root".to_string(),
-                rules: vec![rule],
-                children: Vec::new(),
-            }],
-        });
+        world
+            .apply_damage_to_monster(monster_id, DamageType::Physical, 120, DamageSource::Player(druid))
+            .expect("druid hit");
+        clock.advance(1);
+        world.tick_monsters(&clock);
+        assert_eq!(world.monsters[&monster_id].target, Some(knight));
 
-        let outcome = world
-            .use_object(player_id, tile_pos, item_type)
-            .expect("use object");
-        assert_eq!(outcome.matched_rule, Some(1));
-        assert_eq!(outcome.effects.len(), 1);
-        assert_eq!(outcome.effects[0].position, player_pos);
-        assert_eq!(outcome.effects[0].effect_id, 13);
-        assert_eq!(outcome.texts.len(), 1);
-        assert_eq!(outcome.texts[0].position, player_pos);
-        assert_eq!(outcome.texts[0].message, "Hello");
-        assert_eq!(outcome.texts[0].mode, 20);
-        assert_eq!(outcome.damages.len(), 1);
-        assert_eq!(outcome.damages[0].amount, 5);
+        world.add_healing_threat(druid, knight, 40);
+        clock.advance(1);
+        world.tick_monsters(&clock);
+        assert_eq!(world.monsters[&monster_id].target, Some(druid));
 
-        let player = world.players.get(&player_id).expect("player exists");
-        assert_eq!(player.stats.health, 95);
+        for (player_id, position) in [
+            (knight, Position { x: 158, y: 152, z: 7 }),
+            (druid, Position { x: 158, y: 148, z: 7 }),
+        ] {
+            world.players.get_mut(&player_id).expect("player").position = position;
+        }
+        clock.advance(1);
+        world.tick_monsters(&clock);
+        assert_eq!(world.monsters[&monster_id].target, None);
+        assert!(world.monster_leashing.contains(&monster_id));
+        assert!(!world.monster_threat.contains_key(&monster_id));
+
+        for _ in 0..400 {
+            clock.advance(1);
+            world.tick_monsters(&clock);
+            assert_eq!(world.monsters[&monster_id].target, None);
+            if !world.monster_leashing.contains(&monster_id) {
+                break;
+            }
+        }
+        assert!(!world.monster_leashing.contains(&monster_id));
+        assert!(WorldState::monster_tile_distance(world.monsters[&monster_id].position, home) <= 1);
     }
 
     #[test]
-    fn moveuse_delete_in_inventory_removes_item() {
+    fn distance_fighters_back_off_to_their_preferred_range() {
         let mut world = test_world();
-        let player_id = PlayerId(8);
-        let player_pos = Position { x: 20, y: 20, z: 7 };
-        world.players.insert(
-            player_id,
-            PlayerState::new(player_id, "User".to_string(), player_pos),
-        );
-        let player = world.players.get_mut(&player_id).expect("player exists");
-        player.inventory.set_slot(
-            InventorySlot::Backpack,
-            Some(ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: ItemTypeId(200),
-                count: 5,
-                attributes: Vec::new(),
-                contents: Vec::new(),
-            }),
+        walled_arena(&mut world, &[]);
+        let player_id = PlayerId(33);
+        let player_pos = Position { x: 151, y: 150, z: 7 };
+        world
+            .players
+            .insert(player_id, PlayerState::new(player_id, "Bait".to_string(), player_pos));
+        let script_input = r#"
+Name = "Archer"
+RaceNumber = 28
+Skills = {(HitPoints, 100, 0, 0, 0, 0, 0), (GoStrength, 200, 0, 0, 0, 0, 0)}
+"#;
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(28, "Archer".to_string());
+        index
+            .scripts
+            .insert("Archer".to_string(), parse_monster_script(script_input).expect("parse"));
+        world.monster_index = Some(index);
+        let monster_id = world
+            .spawn_monster_by_race(28, Position { x: 150, y: 150, z: 7 })
+            .expect("spawn monster");
+        world.monsters.get_mut(&monster_id).expect("monster").flags.distance_fighting = true;
+
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        for _ in 0..200 {
+            clock.advance(1);
+            world.tick_monsters(&clock);
+        }
+        let position = world.monsters[&monster_id].position;
+        assert_eq!(
+            WorldState::monster_tile_distance(position, player_pos),
+            MONSTER_DISTANCE_RANGE
         );
+        for _ in 0..50 {
+            clock.advance(1);
+            world.tick_monsters(&clock);
+            assert_eq!(world.monsters[&monster_id].position, position);
+        }
+    }
 
-        let trigger_type = ItemTypeId(100);
-        let tile_pos = Position { x: 21, y: 20, z: 7 };
-        world.map.tiles.insert(tile_pos, make_tile(tile_pos, false));
-        world
-            .map
-            .tile_mut(tile_pos)
-            .expect("tile exists")
-            .items
-            .push(ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: trigger_type,
-                count: 1,
-                attributes: Vec::new(),
-                contents: Vec::new(),
-            });
+    #[test]
+    fn player_summons_fight_for_their_master_and_leave_with_them() {
+        let mut world = test_world();
+        walled_arena(&mut world, &[]);
+        let master_id = PlayerId(34);
+        world.players.insert(
+            master_id,
+            PlayerState::new(master_id, "Necro".to_string(), Position { x: 150, y: 150, z: 7 }),
+        );
+        let skeleton_input = r#"
+Name = "Skeleton"
+RaceNumber = 30
+Attack = 20
+Skills = {(HitPoints, 50, 0, 0, 0, 0, 0), (GoStrength, 200, 0, 0, 0, 0, 0)}
+"#;
+        let rat_input = r#"
+Name = "Rat"
+RaceNumber = 31
+Skills = {(HitPoints, 1000, 0, 0, 0, 0, 0)}
+"#;
+        let mut index = MonsterIndex::default();
+        for (race, name, input) in [(30, "Skeleton", skeleton_input), (31, "Rat", rat_input)] {
+            index.race_index.insert(race, name.to_string());
+            index
+                .scripts
+                .insert(name.to_string(), parse_monster_script(input).expect("parse"));
+        }
+        world.monster_index = Some(index);
+        let summon_id = world
+            .spawn_monster_by_race_with_summoner(
+                30,
+                Position { x: 149, y: 150, z: 7 },
+                Some(master_id),
+                true,
+                None,
+            )
+            .expect("spawn summon");
+        let rat_id = world
+            .spawn_monster_by_race(31, Position { x: 150, y: 156, z: 7 })
+            .expect("spawn rat");
+        world.set_player_attack_target(master_id, Some(rat_id));
 
-        let rule = MoveUseRule {
-            event: MoveUseExpr {
-                name: "Use".to_string(),
-                args: Vec::new(),
-            },
-            conditions: vec![MoveUseExpr {
-                name: "IsType".to_string(),
-                args: vec!["Obj1".to_string(), "100".to_string()],
-            }],
-            actions: vec![MoveUseExpr {
-                name: "DeleteInInventory".to_string(),
-                args: vec![
-                    "User".to_string(),
-                    "200".to_string(),
-                    "3".to_string(),
-                ],
-            }],
-            line_no: 1,
-        };
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        for _ in 0..200 {
+            clock.advance(1);
+            world.tick_monsters(&clock);
+            if world.monsters[&rat_id].stats.health < 1000 {
+                break;
+            }
+        }
+        assert!(world.monsters[&rat_id].stats.health < 1000);
+        assert!(world.monster_threat[&rat_id].contains(master_id));
 
-        world.moveuse = Some(MoveUseDatabase {
-            sections: vec![MoveUseSection {
-                name: "Root".to_string(),
-                rules: vec![rule],
-                children: Vec::new(),
-            }],
-        });
+        world.set_player_attack_target(master_id, None);
+        let master_position = Position { x: 141, y: 141, z: 7 };
+        world.players.get_mut(&master_id).expect("master").position = master_position;
+        clock.advance(1);
+        world.tick_monsters(&clock);
+        assert!(
+            WorldState::monster_tile_distance(world.monsters[&summon_id].position, master_position)
+                <= 2
+        );
 
-        world
-            .use_object(player_id, tile_pos, trigger_type)
-            .expect("use object");
-        let player = world.players.get(&player_id).expect("player exists");
-        let item = player
-            .inventory
-            .slot(InventorySlot::Backpack)
-            .expect("item exists");
-        assert_eq!(item.count, 2);
+        world.handle_disconnect(master_id);
+        assert!(!world.monsters.contains_key(&summon_id));
+        assert!(world.monsters.contains_key(&rat_id));
     }
 
     #[test]
-    fn moveuse_move_top_rel_teleports_player() {
+    fn monsters_fall_asleep_at_home_once_players_leave() {
         let mut world = test_world();
-        let player_id = PlayerId(9);
-        let origin = Position { x: 30, y: 30, z: 7 };
-        let collision = Position { x: 31, y: 30, z: 7 };
-        let target = Position { x: 31, y: 30, z: 8 };
-        world.map.tiles.insert(origin, make_tile(origin, false));
-        world.map.tiles.insert(collision, make_tile(collision, false));
-        world.map.tiles.insert(target, make_tile(target, false));
-
+        walled_arena(&mut world, &[]);
+        let player_id = PlayerId(35);
         world.players.insert(
             player_id,
-            PlayerState::new(player_id, "Mover".to_string(), origin),
+            PlayerState::new(player_id, "Scout".to_string(), Position { x: 150, y: 150, z: 7 }),
         );
+        let script_input = r#"
+Name = "Wolf"
+RaceNumber = 27
+Skills = {(HitPoints, 100, 0, 0, 0, 0, 0)}
+"#;
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(27, "Wolf".to_string());
+        index
+            .scripts
+            .insert("Wolf".to_string(), parse_monster_script(script_input).expect("parse"));
+        world.monster_index = Some(index);
+        let home = Position { x: 145, y: 150, z: 7 };
+        world.monster_homes.push(MonsterHome {
+            race_number: 27,
+            position: home,
+            radius: 0,
+            amount: 1,
+            regen: 0,
+            act_monsters: 1,
+            timer: 0,
+        });
+        let monster_id = world
+            .spawn_monster_by_race(27, Position { x: 150, y: 153, z: 7 })
+            .expect("spawn monster");
+        world.monsters.get_mut(&monster_id).expect("monster").home_id = Some(0);
         world
-            .map
-            .tile_mut(collision)
-            .expect("tile exists")
-            .items
-            .push(ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: ItemTypeId(300),
-                count: 1,
-                attributes: Vec::new(),
-                contents: Vec::new(),
-            });
+            .apply_damage_to_monster(monster_id, DamageType::Physical, 40, DamageSource::Player(player_id))
+            .expect("hit monster");
 
-        let rule = MoveUseRule {
-            event: MoveUseExpr {
-                name: "Collision".to_string(),
-                args: Vec::new(),
-            },
-            conditions: vec![MoveUseExpr {
-                name: "IsType".to_string(),
-                args: vec!["Obj1".to_string(), "300".to_string()],
-            }],
-            actions: vec![MoveUseExpr {
-                name: "MoveTopRel".to_string(),
-                args: vec!["Obj1".to_string(), "[0,0,1]".to_string()],
-            }],
-            line_no: 1,
-        };
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        clock.advance(1);
+        world.tick_monsters(&clock);
+        assert_eq!(world.monster_activity().active, 1);
+        assert_eq!(world.monsters[&monster_id].target, Some(player_id));
 
-        world.moveuse = Some(MoveUseDatabase {
-            sections: vec![MoveUseSection {
-                name: "Root".to_string(),
-                rules: vec![rule],
-                children: Vec::new(),
-            }],
-        });
+        world.players.get_mut(&player_id).expect("player").position =
+            Position { x: 400, y: 400, z: 7 };
+        for _ in 0..300 {
+            clock.advance(1);
+            world.tick_monsters(&clock);
+        }
+        assert_eq!(world.monster_activity().dormant, 0);
 
-        let clock = GameClock::new(Duration::from_millis(100));
+        clock.advance(1);
+        world.tick_monsters(&clock);
+        let monster = &world.monsters[&monster_id];
+        assert_eq!(monster.stats.health, monster.stats.max_health);
+        assert_eq!(monster.target, None);
+        assert!(WorldState::monster_tile_distance(monster.position, home) <= 1);
+        assert!(!world.monster_threat.contains_key(&monster_id));
+        let activity = world.monster_activity();
+        assert_eq!(activity.active, 0);
+        assert_eq!(activity.dormant, 1);
+
+        let away = Position { x: 150, y: 153, z: 7 };
+        world.monsters.get_mut(&monster_id).expect("monster").position = away;
         world
-            .move_player(player_id, Direction::East, &clock)
-            .expect("move player");
-        let player = world.players.get(&player_id).expect("player exists");
-        assert_eq!(player.position, target);
+            .awake_sectors
+            .insert(world.map.sector_for_position(home), clock.now());
+        world.put_monster_to_sleep(monster_id);
+        assert_eq!(world.monsters[&monster_id].position, away);
     }
 
     #[test]
-    fn moveuse_retrieve_ignores_immovable_top_item() {
+    fn player_logout_event_waits_until_the_player_leaves() {
+        fn count_logouts(world: &mut WorldState, event: &WorldEvent) {
+            if let WorldEvent::PlayerLogout { player_id, .. } = event {
+                if let Some(player) = world.players.get_mut(player_id) {
+                    *player.quest_values.entry(9).or_default() += 1;
+                }
+            }
+        }
+
         let mut world = test_world();
-        let player_id = PlayerId(91);
-        let player_pos = Position { x: 70, y: 70, z: 7 };
-        let target_pos = Position { x: 71, y: 70, z: 7 };
-        let destination_pos = Position { x: 72, y: 70, z: 7 };
+        walled_arena(&mut world, &[]);
+        let player_id = PlayerId(37);
+        let mut player =
+            PlayerState::new(player_id, "Fighter".to_string(), Position { x: 150, y: 150, z: 7 });
+        player.pvp.fight_expires_at = Some(GameTick(600));
+        world.players.insert(player_id, player);
+        world.subscribe_event(WorldEventKind::PlayerLogout, count_logouts);
+
+        world.handle_disconnect(player_id);
+        assert_eq!(world.players[&player_id].quest_values.get(&9), None);
+
+        world.players.get_mut(&player_id).expect("player").pvp.fight_expires_at = None;
+        world.handle_disconnect(player_id);
+        assert!(!world.players.contains_key(&player_id));
+        assert_eq!(world.offline_players[&player_id].quest_values.get(&9), Some(&1));
+    }
+
+    #[test]
+    fn world_events_reach_builtin_handlers_subscribers_and_scripts() {
+        fn count_spawns(world: &mut WorldState, event: &WorldEvent) {
+            if let WorldEvent::MonsterSpawned { .. } = event {
+                for player in world.players.values_mut() {
+                    *player.quest_values.entry(9).or_default() += 1;
+                }
+            }
+        }
+
+        let mut world = test_world();
+        walled_arena(&mut world, &[]);
+        let player_id = PlayerId(36);
         world.players.insert(
             player_id,
-            PlayerState::new(player_id, "Roper".to_string(), player_pos),
+            PlayerState::new(player_id, "Hunter".to_string(), Position { x: 150, y: 150, z: 7 }),
         );
-        let player = world.players.get_mut(&player_id).expect("player exists");
-        player.inventory.set_slot(
-            InventorySlot::Backpack,
-            Some(ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: ItemTypeId(3003),
-                count: 1,
-                attributes: Vec::new(),
-                contents: Vec::new(),
-            }),
+        let script_input = r#"
+Name = "Wolf"
+RaceNumber = 27
+Experience = 100
+Skills = {(HitPoints, 20, 0, 0, 0, 0, 0)}
+"#;
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(27, "Wolf".to_string());
+        index
+            .scripts
+            .insert("Wolf".to_string(), parse_monster_script(script_input).expect("parse"));
+        world.monster_index = Some(index);
+        world.monster_homes.push(MonsterHome {
+            race_number: 27,
+            position: Position { x: 145, y: 150, z: 7 },
+            radius: 0,
+            amount: 1,
+            regen: 0,
+            act_monsters: 1,
+            timer: 0,
+        });
+        world.event_scripts = crate::world::events::parse_event_scripts(
+            "BEGIN \"Events\"\n\
+             Kill, Race(27) -> Broadcast(\"%N slew a wolf.\"), SetQuestValue(7, 1)\n\
+             LevelUp, Level(2) -> Message(\"Level two already?\")\n\
+             END\n",
+        )
+        .expect("parse events");
+        world.subscribe_event(WorldEventKind::MonsterSpawned, count_spawns);
+
+        let monster_id = world
+            .spawn_monster_by_race(27, Position { x: 150, y: 152, z: 7 })
+            .expect("spawn monster");
+        world.monsters.get_mut(&monster_id).expect("monster").home_id = Some(0);
+        assert_eq!(world.players[&player_id].quest_values.get(&9), Some(&1));
+
+        world.defeat_monster(monster_id, Some(player_id)).expect("defeat");
+        assert_eq!(world.monster_homes[0].act_monsters, 0);
+        let player = &world.players[&player_id];
+        assert_eq!(player.level, 2);
+        assert_eq!(
+            (player.stats.max_health, player.stats.max_mana, player.stats.capacity),
+            (155, 5, 410)
+        );
+        assert!(world.take_pending_data_update(player_id));
+        let player = &world.players[&player_id];
+        assert_eq!(player.quest_values.get(&7), Some(&1));
+        let messages: Vec<String> = world
+            .take_pending_messages(player_id)
+            .into_iter()
+            .map(|message| message.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Hunter slew a wolf.".to_string(),
+                "You advanced from Level 1 to Level 2.".to_string(),
+                "Level two already?".to_string(),
+            ]
         );
+    }
 
-        world.map.tiles.insert(target_pos, make_tile(target_pos, false));
-        world
-            .map
-            .tile_mut(target_pos)
-            .expect("target tile exists")
-            .items
-            .extend([
-                ItemStack {
-                    id: crate::entities::item::ItemId::next(),
-                    type_id: ItemTypeId(501),
-                    count: 1,
-                    attributes: Vec::new(),
-                    contents: Vec::new(),
-                },
-                ItemStack {
-                    id: crate::entities::item::ItemId::next(),
-                    type_id: ItemTypeId(500),
-                    count: 1,
-                    attributes: Vec::new(),
-                    contents: Vec::new(),
-                },
-            ]);
+    #[test]
+    fn home_slots_are_freed_past_the_chained_event_limit() {
+        fn flood(world: &mut WorldState, event: &WorldEvent) {
+            if let WorldEvent::RaidStarted { name } = event {
+                if name == "flood" {
+                    for _ in 0..MAX_CHAINED_EVENTS {
+                        world.publish_event(WorldEvent::RaidStarted {
+                            name: "noise".to_string(),
+                        });
+                    }
+                    let monster_id = *world.monsters.keys().next().expect("monster");
+                    world.defeat_monster(monster_id, None).expect("defeat");
+                }
+            }
+        }
+
+        let mut world = test_world();
+        walled_arena(&mut world, &[]);
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(27, "Wolf".to_string());
+        index.scripts.insert(
+            "Wolf".to_string(),
+            parse_monster_script("Name = \"Wolf\"\nRaceNumber = 27\n").expect("parse"),
+        );
+        world.monster_index = Some(index);
+        world.monster_homes.push(MonsterHome {
+            race_number: 27,
+            position: Position { x: 145, y: 150, z: 7 },
+            radius: 0,
+            amount: 1,
+            regen: 0,
+            act_monsters: 1,
+            timer: 0,
+        });
+        let monster_id = world
+            .spawn_monster_by_race(27, Position { x: 150, y: 152, z: 7 })
+            .expect("spawn monster");
+        world.monsters.get_mut(&monster_id).expect("monster").home_id = Some(0);
+        world.subscribe_event(WorldEventKind::RaidStarted, flood);
+
+        world.publish_event(WorldEvent::RaidStarted {
+            name: "flood".to_string(),
+        });
+        assert!(world.monsters.is_empty());
+        assert_eq!(world.monster_homes[0].act_monsters, 0);
+    }
+
+    #[test]
+    fn content_scripts_handle_use_and_login_hooks() {
+        let mut world = test_world();
+        let player_id = PlayerId(37);
+        let player_pos = Position { x: 10, y: 10, z: 7 };
+        world.players.insert(
+            player_id,
+            PlayerState::new(player_id, "Seeker".to_string(), player_pos),
+        );
+        let chest_type = ItemTypeId(2480);
+        let chest_pos = Position { x: 11, y: 10, z: 7 };
+        world.map.tiles.insert(player_pos, make_tile(player_pos, false));
+        world.map.tiles.insert(chest_pos, make_tile(chest_pos, false));
         world
             .map
-            .tiles
-            .insert(destination_pos, make_tile(destination_pos, false));
-
+            .tile_mut(chest_pos)
+            .expect("tile exists")
+            .items
+            .push(ItemStack::new(chest_type, 1));
         let mut object_types = ObjectTypeIndex::default();
         object_types
             .insert(ObjectType {
-                id: ItemTypeId(501),
-                name: "movable".to_string(),
-                flags: vec!["Take".to_string()],
-                attributes: Vec::new(),
-            })
-            .expect("insert movable object type");
-        object_types
-            .insert(ObjectType {
-                id: ItemTypeId(500),
-                name: "immovable".to_string(),
-                flags: vec!["Unmove".to_string()],
+                id: ItemTypeId(3031),
+                name: "gold coin".to_string(),
+                flags: vec!["Cumulative".to_string()],
                 attributes: Vec::new(),
             })
-            .expect("insert immovable object type");
+            .expect("insert object type");
         world.object_types = Some(object_types);
+        let mut scripts = ScriptRuntime::new();
+        scripts
+            .add_script(
+                "chest",
+                r#"
+const USE_ITEMS = [2480];
+fn on_use(player, item) {
+    if player.quest(4001) == 0 {
+        player.give_item(3031, 10);
+        player.set_quest(4001, 1);
+    } else {
+        player.message("The chest is empty.");
+    }
+    true
+}
+fn on_login(player) {
+    if player.quest(4001) > 0 && player.item_count(3031) == 0 {
+        player.message(`Welcome back, ${player.name}. Your gold is still on the floor.`);
+    }
+}
+"#,
+            )
+            .expect("load chest");
+        world.scripts = Some(scripts);
 
-        let rule = MoveUseRule {
-            event: MoveUseExpr {
-                name: "MultiUse".to_string(),
-                args: Vec::new(),
-            },
-            conditions: vec![
-                MoveUseExpr {
-                    name: "IsType".to_string(),
-                    args: vec!["Obj1".to_string(), "3003".to_string()],
-                },
-                MoveUseExpr {
-                    name: "IsType".to_string(),
-                    args: vec!["Obj2".to_string(), "500".to_string()],
-                },
-            ],
-            actions: vec![MoveUseExpr {
-                name: "Retrieve".to_string(),
-                args: vec![
-                    "Obj2".to_string(),
-                    "[0,0,0]".to_string(),
-                    "[1,0,0]".to_string(),
-                ],
-            }],
-            line_no: 1,
-        };
-        world.moveuse = Some(MoveUseDatabase {
-            sections: vec![MoveUseSection {
-                name: "Root".to_string(),
-                rules: vec![rule],
-                children: Vec::new(),
-            }],
-        });
-
+        let outcome = world
+            .use_object(player_id, chest_pos, chest_type)
+            .expect("use chest");
+        assert_eq!(outcome.matched_rule, None);
+        assert_eq!(world.players[&player_id].quest_values.get(&4001), Some(&1));
         world
-            .use_object_on_position_with_clock(
-                player_id,
-                UseObjectSource::Inventory(InventorySlot::Backpack),
-                ItemTypeId(3003),
-                target_pos,
-                ItemTypeId(500),
-                None,
-            )
-            .expect("multi-use should succeed");
+            .use_object(player_id, chest_pos, chest_type)
+            .expect("use chest again");
+        // Without a backpack the reward lands at the player's feet.
+        let tile = world.map.tile(player_pos).expect("player tile");
+        assert_eq!(tile.items.len(), 1);
+        assert_eq!(tile.items[0].type_id, ItemTypeId(3031));
+        assert_eq!(tile.items[0].count, 10);
 
-        let target_tile = world.map.tile(target_pos).expect("target tile exists");
-        assert_eq!(target_tile.items.len(), 2);
-        assert_eq!(target_tile.items[0].type_id, ItemTypeId(501));
-        assert_eq!(target_tile.items[1].type_id, ItemTypeId(500));
-        let destination_tile = world
-            .map
-            .tile(destination_pos)
-            .expect("destination tile exists");
-        assert!(destination_tile.items.is_empty());
+        world.publish_event(WorldEvent::PlayerLogin {
+            player_id,
+            position: player_pos,
+        });
+        let messages: Vec<String> = world
+            .take_pending_messages(player_id)
+            .into_iter()
+            .map(|message| message.message)
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "The chest is empty.".to_string(),
+                "Welcome back, Seeker. Your gold is still on the floor.".to_string(),
+            ]
+        );
     }
 
     #[test]
-    fn moveuse_move_sets_player_position() {
+    fn boss_phases_swap_behaviour_and_enrage_over_time() {
         let mut world = test_world();
-        let player_id = PlayerId(10);
-        let player_pos = Position { x: 40, y: 40, z: 7 };
-        let target = Position { x: 41, y: 40, z: 7 };
+        walled_arena(&mut world, &[]);
+        let player_id = PlayerId(27);
         world.players.insert(
             player_id,
-            PlayerState::new(player_id, "User".to_string(), player_pos),
+            PlayerState::new(player_id, "Hero".to_string(), Position { x: 150, y: 153, z: 7 }),
         );
+        let boss_input = r#"
+Name = "Warlord"
+RaceNumber = 50
+Attack = 10
+Skills = {(HitPoints, 100, 0, 0, 0, 0, 0)}
+Enrage = (1, 100)
+EnrageYell = "ENOUGH!"
+Phase = 50
+PhaseOutfit = (35, 0-0-0-0)
+PhaseSpeed = 300
+PhaseSummon = {(27, 1)}
+PhaseYell = "To me, my servants!"
+PhaseImmunity = 2
+"#;
+        let wolf_input = "Name = \"Wolf\"\nRaceNumber = 27\n";
+        let mut index = MonsterIndex::default();
+        for (race, name, input) in [(50, "Warlord", boss_input), (27, "Wolf", wolf_input)] {
+            index.race_index.insert(race, name.to_string());
+            index
+                .scripts
+                .insert(name.to_string(), parse_monster_script(input).expect("parse"));
+        }
+        world.monster_index = Some(index);
+        let boss_id = world
+            .spawn_monster_by_race(50, Position { x: 150, y: 150, z: 7 })
+            .expect("spawn boss");
 
-        let trigger_type = ItemTypeId(110);
-        world.map.tiles.insert(player_pos, make_tile(player_pos, false));
-        world.map.tiles.insert(target, make_tile(target, false));
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        clock.advance(1);
+        world.tick_monsters(&clock);
+        assert_eq!(world.monsters[&boss_id].target, Some(player_id));
         world
-            .map
-            .tile_mut(player_pos)
-            .expect("tile exists")
-            .items
-            .push(ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: trigger_type,
-                count: 1,
-                attributes: Vec::new(),
-                contents: Vec::new(),
-            });
-
-        let rule = MoveUseRule {
-            event: MoveUseExpr {
-                name: "Use".to_string(),
-                args: Vec::new(),
-            },
-            conditions: vec![MoveUseExpr {
-                name: "IsType".to_string(),
-                args: vec!["Obj1".to_string(), "110".to_string()],
-            }],
-            actions: vec![MoveUseExpr {
-                name: "Move".to_string(),
-                args: vec!["User".to_string(), "[41,40,7]".to_string()],
-            }],
-            line_no: 1,
-        };
+            .apply_damage_to_monster(boss_id, DamageType::Fire, 60, DamageSource::Player(player_id))
+            .expect("hit boss");
 
-        world.moveuse = Some(MoveUseDatabase {
-            sections: vec![MoveUseSection {
-                name: "Root".to_string(),
-                rules: vec![rule],
-                children: Vec::new(),
-            }],
-        });
+        clock.advance(1);
+        let outcome = world.tick_monsters(&clock);
+        assert_eq!(world.monsters.len(), 2);
+        assert_eq!(world.monsters[&boss_id].outfit.look_type, 35);
+        assert!(outcome
+            .speed_updates
+            .contains(&CreatureSpeedUpdate { id: boss_id.0, speed: 300 }));
+        assert!(outcome.talks.iter().any(|talk| {
+            talk.message == "To me, my servants!" && talk.talk_type == TALK_TYPE_MONSTER_YELL
+        }));
+        world
+            .apply_damage_to_monster(boss_id, DamageType::Fire, 30, DamageSource::Player(player_id))
+            .expect("immune hit");
+        assert_eq!(world.monsters[&boss_id].stats.health, 40);
 
+        clock.advance(20);
+        let outcome = world.tick_monsters(&clock);
+        assert!(outcome.talks.iter().any(|talk| talk.message == "ENOUGH!"));
+        assert_eq!(world.monsters[&boss_id].attack, 20);
+        assert_eq!(world.monsters[&boss_id].speed, 600);
         world
-            .use_object(player_id, player_pos, trigger_type)
-            .expect("use object");
-        let player = world.players.get(&player_id).expect("player exists");
-        assert_eq!(player.position, target);
+            .apply_damage_to_monster(boss_id, DamageType::Fire, 30, DamageSource::Player(player_id))
+            .expect("hit boss");
+        assert_eq!(world.monsters[&boss_id].stats.health, 10);
     }
 
     #[test]
-    fn moveuse_set_start_updates_start_position() {
+    fn boss_phase_immunity_ends_without_players_around() {
         let mut world = test_world();
-        let player_id = PlayerId(11);
-        let player_pos = Position { x: 50, y: 50, z: 7 };
-        let start = Position { x: 51, y: 50, z: 7 };
+        walled_arena(&mut world, &[]);
+        let player_id = PlayerId(27);
         world.players.insert(
             player_id,
-            PlayerState::new(player_id, "User".to_string(), player_pos),
+            PlayerState::new(player_id, "Hero".to_string(), Position { x: 150, y: 153, z: 7 }),
         );
+        let boss_input = r#"
+Name = "Warlord"
+RaceNumber = 50
+Skills = {(HitPoints, 100, 0, 0, 0, 0, 0)}
+Phase = 50
+PhaseImmunity = 2
+"#;
+        let mut index = MonsterIndex::default();
+        index.race_index.insert(50, "Warlord".to_string());
+        index
+            .scripts
+            .insert("Warlord".to_string(), parse_monster_script(boss_input).expect("parse"));
+        world.monster_index = Some(index);
+        let boss_id = world
+            .spawn_monster_by_race(50, Position { x: 150, y: 150, z: 7 })
+            .expect("spawn boss");
 
-        let trigger_type = ItemTypeId(120);
-        world.map.tiles.insert(player_pos, make_tile(player_pos, false));
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        clock.advance(1);
+        world.tick_monsters(&clock);
         world
-            .map
-            .tile_mut(player_pos)
-            .expect("tile exists")
-            .items
-            .push(ItemStack { id: crate::entities::item::ItemId::next(),
-                type_id: trigger_type,
-                count: 1,
-                attributes: Vec::new(),
-                contents: Vec::new(),
-            });
+            .apply_damage_to_monster(boss_id, DamageType::Fire, 60, DamageSource::Player(player_id))
+            .expect("hit boss");
+        clock.advance(1);
+        world.tick_monsters(&clock);
+        world
+            .apply_damage_to_monster(boss_id, DamageType::Fire, 10, DamageSource::Player(player_id))
+            .expect("immune hit");
+        assert_eq!(world.monsters[&boss_id].stats.health, 40);
 
-        let rule = MoveUseRule {
-            event: MoveUseExpr {
-                name: "Use".to_string(),
-                args: Vec::new(),
+        world.players.remove(&player_id);
+        clock.advance(20);
+        world.tick_monsters(&clock);
+        assert!(world.boss_states[&boss_id].immune_until.is_some());
+        world
+            .apply_damage_to_monster(boss_id, DamageType::Fire, 10, DamageSource::Player(player_id))
+            .expect("immunity over");
+        assert_eq!(world.monsters[&boss_id].stats.health, 30);
+    }
+
+    #[test]
+    fn players_and_npcs_walk_to_points_around_walls() {
+        let mut world = test_world();
+        let wall: Vec<Position> = (146..=154).map(|y| Position { x: 152, y, z: 7 }).collect();
+        walled_arena(&mut world, &wall);
+        let player_id = PlayerId(25);
+        let start = Position { x: 150, y: 150, z: 7 };
+        let lever = Position { x: 154, y: 150, z: 7 };
+        world
+            .players
+            .insert(player_id, PlayerState::new(player_id, "Walker".to_string(), start));
+
+        let steps = world.walk_player_to(player_id, lever, true).expect("path");
+        let queued: Vec<Direction> =
+            world.players[&player_id].autowalk_steps.iter().copied().collect();
+        assert_eq!(queued.len(), steps);
+        let end = queued
+            .iter()
+            .try_fold(start, |position, direction| position.step(*direction))
+            .expect("walkable steps");
+        assert!(WorldState::monster_tile_distance(end, lever) <= 1);
+        assert!(end.x > 152);
+
+        let err = world
+            .walk_player_to(player_id, Position { x: 170, y: 150, z: 7 }, false)
+            .expect_err("no tiles there");
+        assert_eq!(err, PLAYER_NO_WAY_MESSAGE);
+
+        let npc_id = CreatureId(0x4000_0002);
+        world.npcs.insert(
+            npc_id,
+            NpcInstance {
+                id: npc_id,
+                script_key: "test".to_string(),
+                name: "Courier".to_string(),
+                position: Position { x: 150, y: 148, z: 7 },
+                direction: Direction::South,
+                home: Position { x: 150, y: 148, z: 7 },
+                outfit: DEFAULT_OUTFIT,
+                radius: 1,
+                focused: None,
+                focus_expires_at: None,
+                queue: VecDeque::new(),
+                move_cooldown: Cooldown::new(GameTick(0)),
             },
-            conditions: vec![MoveUseExpr {
-                name: "IsType".to_string(),
-                args: vec!["Obj1".to_string(), "120".to_string()],
-            }],
-            actions: vec![MoveUseExpr {
-                name: "SetStart".to_string(),
-                args: vec!["User".to_string(), "[51,50,7]".to_string()],
-            }],
-            line_no: 1,
+        );
+        let plan = NpcActionPlan {
+            npc_id,
+            actions: vec![NpcPlannedAction::WalkTo(lever)],
         };
+        apply_npc_plans(&mut world, player_id, &[plan], &HashSet::new());
+        assert_eq!(world.npc_walk_targets.get(&npc_id), Some(&lever));
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        let mut arrived = false;
+        for _ in 0..40 {
+            clock.advance(NPC_MOVE_INTERVAL_TICKS);
+            world.tick_npcs(&clock);
+            let position = world.npcs[&npc_id].position;
+            assert!(!wall.contains(&position));
+            if position == lever {
+                arrived = true;
+                break;
+            }
+        }
+        assert!(arrived);
+        assert!(!world.npc_walk_targets.contains_key(&npc_id));
+    }
 
-        world.moveuse = Some(MoveUseDatabase {
-            sections: vec![MoveUseSection {
-                name: "Root".to_string(),
-                rules: vec![rule],
-                children: Vec::new(),
-            }],
-        });
+    #[test]
+    fn out_of_reach_use_walks_there_and_uses_on_arrival() {
+        let mut world = test_world();
+        walled_arena(&mut world, &[]);
+        let lever_type = ItemTypeId(2772);
+        if let Some(object_types) = world.object_types.as_mut() {
+            object_types
+                .insert(ObjectType {
+                    id: lever_type,
+                    name: "lever".to_string(),
+                    flags: vec!["ChangeUse".to_string()],
+                    attributes: vec![ObjectAttribute {
+                        key: "ChangeTarget".to_string(),
+                        value: "2773".to_string(),
+                    }],
+                })
+                .expect("insert lever");
+            object_types
+                .insert(ObjectType {
+                    id: ItemTypeId(2773),
+                    name: "lever".to_string(),
+                    flags: Vec::new(),
+                    attributes: Vec::new(),
+                })
+                .expect("insert pulled lever");
+        }
+        let lever = Position { x: 155, y: 150, z: 7 };
+        world
+            .map
+            .tiles
+            .get_mut(&lever)
+            .expect("lever tile")
+            .items
+            .push(ItemStack::new(lever_type, 1));
+        let player_id = PlayerId(26);
+        let start = Position { x: 150, y: 150, z: 7 };
+        world
+            .players
+            .insert(player_id, PlayerState::new(player_id, "Walker".to_string(), start));
 
+        let mut clock = GameClock::new(Duration::from_millis(100));
+        let err = world
+            .use_object_with_clock(player_id, lever, lever_type, Some(&clock))
+            .expect_err("too far");
+        assert_eq!(err, UseObjectError::OutOfReach);
         world
-            .use_object(player_id, player_pos, trigger_type)
-            .expect("use object");
-        let player = world.players.get(&player_id).expect("player exists");
-        assert_eq!(player.start_position, start);
+            .walk_player_to_use(player_id, lever, lever_type)
+            .expect("path");
+        for _ in 0..200 {
+            clock.advance(1);
+            world.tick_player_autowalk(player_id, &clock);
+            if world.players[&player_id].autowalk_steps.is_empty() {
+                break;
+            }
+        }
+        assert!(WorldState::monster_tile_distance(world.players[&player_id].position, lever) <= 1);
+        let tile = world.map.tile(lever).expect("lever tile");
+        assert!(tile.items.iter().any(|item| item.type_id == ItemTypeId(2773)));
+        assert!(!world.take_pending_moveuse_outcomes(player_id).is_empty());
+        assert!(world.walk_to_uses.is_empty());
     }
 }
 